@@ -0,0 +1,6 @@
+// No commands are exposed to the webview; the app calls the plugin from Rust
+const COMMANDS: &[&str] = &[];
+
+fn main() {
+    tauri_plugin::Builder::new(COMMANDS).android_path("android").build();
+}
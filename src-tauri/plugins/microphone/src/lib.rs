@@ -0,0 +1,76 @@
+//! RECORD_AUDIO on Android: declared in the manifest of the plugin's
+//! Android library, merged into the app's, and requested at runtime, which
+//! Android never does on its own when a capture starts. Android only.
+
+#![cfg(target_os = "android")]
+
+use serde::{Deserialize, Serialize};
+use tauri::plugin::{Builder, PermissionState, TauriPlugin};
+use tauri::{Manager, Runtime};
+
+const PLUGIN_IDENTIFIER: &str = "app.poptranscribe.microphone";
+
+/// Alias of RECORD_AUDIO in `MicrophonePlugin`.
+const ALIAS: &str = "microphone";
+
+#[derive(Deserialize)]
+struct PermissionResponse {
+    microphone: PermissionState,
+}
+
+#[derive(Serialize)]
+struct RequestPermissions {
+    permissions: Vec<&'static str>,
+}
+
+/// Access to the Android plugin, managed by the app.
+pub struct Microphone<R: Runtime>(tauri::plugin::PluginHandle<R>);
+
+impl<R: Runtime> Microphone<R> {
+    /// Current state, without prompting.
+    pub fn state(&self) -> Result<PermissionState, String> {
+        self.0
+            .run_mobile_plugin::<PermissionResponse>("checkPermissions", ())
+            .map(|response| response.microphone)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Shows the system prompt and waits for the answer. Blocking.
+    pub fn request(&self) -> Result<PermissionState, String> {
+        self.0
+            .run_mobile_plugin::<PermissionResponse>(
+                "requestPermissions",
+                RequestPermissions { permissions: vec![ALIAS] },
+            )
+            .map(|response| response.microphone)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Opens the settings page of the app, where a permission the user
+    /// refused for good can be granted.
+    pub fn open_settings(&self) -> Result<(), String> {
+        self.0
+            .run_mobile_plugin::<()>("openSettings", ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+pub trait MicrophoneExt<R: Runtime> {
+    fn microphone(&self) -> &Microphone<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> MicrophoneExt<R> for T {
+    fn microphone(&self) -> &Microphone<R> {
+        self.state::<Microphone<R>>().inner()
+    }
+}
+
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("microphone")
+        .setup(|app, api| {
+            let handle = api.register_android_plugin(PLUGIN_IDENTIFIER, "MicrophonePlugin")?;
+            app.manage(Microphone(handle));
+            Ok(())
+        })
+        .build()
+}
@@ -21,15 +21,120 @@ unsafe impl Sync for SendCapturer {}
 pub struct ActiveSession {
     pub id: String,
     pub capturer: SendCapturer,
-    pub audio_samples: Arc<Mutex<Vec<i16>>>,
+    pub audio_samples: Arc<Mutex<crate::audio::buffer::SampleBuffer>>,
     pub sample_rate: u32,
     pub stop_signal: tokio::sync::watch::Sender<bool>,
+    /// Thread writing the full-quality copy of the microphone, if enabled.
+    /// Yields the path of the finished file.
+    pub native_writer: Option<std::thread::JoinHandle<Result<std::path::PathBuf, String>>>,
+    /// The full-quality copy replaces the 16kHz recording.
+    pub native_only: bool,
+    /// Periodic flush of `audio_samples` to a partial WAV.
+    pub autosave: Option<crate::audio::autosave::Autosave>,
+    /// Feeds the session loop; each (re)started capture forwards into it.
+    pub chunk_sender: tokio::sync::mpsc::Sender<Vec<i16>>,
+    /// Capture stopped and devices released until the session is resumed.
+    pub paused: bool,
+    /// Chunks transcribed while recording, when enabled. Taken at the stop.
+    pub incremental: Arc<Mutex<Option<crate::mistral::incremental::IncrementalBatch>>>,
+}
+
+/// Root directory for the database and recordings.
+pub fn data_dir() -> std::path::PathBuf {
+    data_dir_lock().read().map(|dir| dir.clone()).unwrap_or_else(|e| e.into_inner().clone())
+}
+
+/// The platform directory, or the folder chosen by the user and recorded
+/// in its `data_location` file.
+fn data_dir_lock() -> &'static std::sync::RwLock<std::path::PathBuf> {
+    static DIR: std::sync::OnceLock<std::sync::RwLock<std::path::PathBuf>> = std::sync::OnceLock::new();
+    DIR.get_or_init(|| {
+        let relocated = std::fs::read_to_string(location_file())
+            .ok()
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+            .map(std::path::PathBuf::from);
+        std::sync::RwLock::new(relocated.unwrap_or_else(platform_data_dir))
+    })
+}
+
+/// Stays in the platform directory, so the chosen folder is found again at
+/// startup before the database is opened.
+fn location_file() -> std::path::PathBuf {
+    platform_data_dir().join("data_location")
+}
+
+/// Switches to `dir` for this run and the next ones. The files must already
+/// be there (see `commands::move_data_dir`).
+pub fn set_data_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    if dir == platform_data_dir() {
+        match std::fs::remove_file(location_file()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    } else {
+        std::fs::create_dir_all(platform_data_dir())?;
+        std::fs::write(location_file(), dir.to_string_lossy().as_bytes())?;
+    }
+    *data_dir_lock().write().unwrap_or_else(|e| e.into_inner()) = dir.to_path_buf();
+    Ok(())
+}
+
+/// The directory used when the user has not chosen one.
+pub fn default_data_dir() -> std::path::PathBuf {
+    platform_data_dir()
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn platform_data_dir() -> std::path::PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("poptranscribe")
+}
+
+/// Inside the app sandbox: `~/Library/Application Support`, which iOS
+/// keeps across updates and includes in backups.
+#[cfg(target_os = "ios")]
+fn platform_data_dir() -> std::path::PathBuf {
+    std::env::var_os("HOME")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("Library/Application Support/poptranscribe")
+}
+
+/// The app's internal storage, `/data/data/<package>/files`. The database is
+/// opened before Tauri starts, so the package name is read from the process
+/// name, which Android sets to it.
+#[cfg(target_os = "android")]
+fn platform_data_dir() -> std::path::PathBuf {
+    let package = std::fs::read("/proc/self/cmdline")
+        .ok()
+        .and_then(|raw| {
+            let name = raw.split(|&b| b == 0).next()?.to_vec();
+            String::from_utf8(name).ok()
+        })
+        .filter(|name| !name.is_empty());
+    match package {
+        Some(package) => std::path::PathBuf::from("/data/data")
+            .join(package)
+            .join("files")
+            .join("poptranscribe"),
+        None => std::env::temp_dir().join("poptranscribe"),
+    }
 }
 
 pub struct AppState {
     pub db: Arc<Mutex<Database>>,
     pub api_key: Arc<Mutex<String>>,
     pub active_session: Mutex<Option<ActiveSession>>,
+    /// Whether the passcode was entered; protected sessions stay hidden
+    /// until then.
+    pub unlocked: Mutex<bool>,
+    /// Watcher of the `watch_folder` setting, replaced when it changes.
+    pub folder_watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    /// Link the app was launched with, kept until the UI is ready to show
+    /// it (`take_pending_deep_link`).
+    pub pending_link: Mutex<Option<crate::deeplink::DeepLink>>,
 }
 
 impl AppState {
@@ -38,6 +143,9 @@ impl AppState {
             db: Arc::new(Mutex::new(db)),
             api_key: Arc::new(Mutex::new(String::new())),
             active_session: Mutex::new(None),
+            unlocked: Mutex::new(false),
+            folder_watcher: Mutex::new(None),
+            pending_link: Mutex::new(None),
         }
     }
 }
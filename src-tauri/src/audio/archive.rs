@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::audio::transcode::{self, AudioFormat};
+use crate::db::Database;
+
+/// What to do with recordings older than the `archive_after_days` setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveMode {
+    /// Transcode to a compressed format next to the original.
+    Compress(AudioFormat),
+    /// Move the file, unchanged, into the archive folder.
+    Move(PathBuf),
+}
+
+/// Archival policy read from settings. `None` when archival is disabled.
+pub struct ArchivePolicy {
+    pub after_days: u32,
+    pub mode: ArchiveMode,
+}
+
+impl ArchivePolicy {
+    /// Reads `archive_after_days`, `archive_mode` (`opus`, `flac` or `move`)
+    /// and `archive_dir` from settings.
+    pub fn from_settings(db: &Database) -> Option<Self> {
+        let after_days = db
+            .get_setting("archive_after_days")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|&d| d > 0)?;
+        let mode_name = db
+            .get_setting("archive_mode")
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| "opus".to_string());
+        let mode = if mode_name == "move" {
+            let dir = match db.get_setting("archive_dir").ok().flatten() {
+                Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+                _ => crate::app_state::data_dir().join("archive"),
+            };
+            ArchiveMode::Move(dir)
+        } else {
+            ArchiveMode::Compress(AudioFormat::from_name(&mode_name)?)
+        };
+        Some(Self { after_days, mode })
+    }
+}
+
+/// Archives every WAV recording older than the configured policy and
+/// updates the session's `audio_path`. Returns the number of files archived.
+pub async fn archive_old_audio(db: &Arc<Mutex<Database>>) -> Result<usize, String> {
+    let (policy, sessions) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let policy = match ArchivePolicy::from_settings(&db) {
+            Some(policy) => policy,
+            None => return Ok(0),
+        };
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(policy.after_days as i64);
+        let sessions = db
+            .list_sessions_with_audio_before(&cutoff.to_rfc3339())
+            .map_err(|e| e.to_string())?;
+        (policy, sessions)
+    };
+
+    let mut archived = 0;
+    for session in sessions {
+        let Some(audio_path) = session.audio_path.as_deref().map(PathBuf::from) else {
+            continue;
+        };
+        let is_wav = audio_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("wav"));
+        if !is_wav || !audio_path.exists() {
+            continue;
+        }
+
+        match archive_file(&audio_path, &policy.mode).await {
+            Ok(new_path) => {
+                let db = db.lock().map_err(|e| e.to_string())?;
+                db.update_session_audio_path(&session.id, new_path.to_str().unwrap_or(""))
+                    .map_err(|e| e.to_string())?;
+                archived += 1;
+            }
+            Err(e) => {
+                eprintln!("[archive] Failed to archive {}: {}", audio_path.display(), e);
+            }
+        }
+    }
+
+    Ok(archived)
+}
+
+/// Archives a single file and returns its new location. The original is only
+/// removed once the archived copy has been written successfully.
+async fn archive_file(path: &Path, mode: &ArchiveMode) -> Result<PathBuf, String> {
+    match mode {
+        ArchiveMode::Compress(format) => {
+            let output = path.with_extension(format.extension());
+            transcode::transcode(path, &output, *format).await?;
+            std::fs::remove_file(path)
+                .map_err(|e| format!("Erreur suppression WAV: {}", e))?;
+            Ok(output)
+        }
+        ArchiveMode::Move(dir) => {
+            let file_name = path.file_name().ok_or("Chemin audio invalide")?;
+            let output = dir.join(file_name);
+            move_file(path, &output)?;
+            Ok(output)
+        }
+    }
+}
+
+/// Moves a file, falling back to copy + delete across volumes.
+pub fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    }
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to).map_err(|e| format!("Erreur copie fichier: {}", e))?;
+        std::fs::remove_file(from).map_err(|e| format!("Erreur suppression fichier: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_disabled_by_default() {
+        let db = Database::new_in_memory().unwrap();
+        assert!(ArchivePolicy::from_settings(&db).is_none());
+        db.set_setting("archive_after_days", "0").unwrap();
+        assert!(ArchivePolicy::from_settings(&db).is_none());
+    }
+
+    #[test]
+    fn test_policy_from_settings() {
+        let db = Database::new_in_memory().unwrap();
+        db.set_setting("archive_after_days", "30").unwrap();
+        let policy = ArchivePolicy::from_settings(&db).unwrap();
+        assert_eq!(policy.after_days, 30);
+        assert_eq!(policy.mode, ArchiveMode::Compress(AudioFormat::Opus));
+
+        db.set_setting("archive_mode", "move").unwrap();
+        db.set_setting("archive_dir", "/tmp/archive").unwrap();
+        let policy = ArchivePolicy::from_settings(&db).unwrap();
+        assert_eq!(policy.mode, ArchiveMode::Move(PathBuf::from("/tmp/archive")));
+    }
+
+    #[test]
+    fn test_move_file() {
+        let dir = std::env::temp_dir().join("poptranscribe_test_archive");
+        let from = dir.join("in.wav");
+        let to = dir.join("archive").join("in.wav");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&from, b"RIFF").unwrap();
+
+        move_file(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(std::fs::read(&to).unwrap(), b"RIFF");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
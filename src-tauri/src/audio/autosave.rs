@@ -0,0 +1,201 @@
+//! Periodic autosave of the recording in progress. Samples are appended to
+//! `{id}.partial.wav` every few seconds with a valid header, so a crash or
+//! power loss costs at most one interval of audio. The partial file is
+//! removed once the final WAV is saved, and recovered at the next launch
+//! otherwise.
+
+use hound::WavWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::buffer::{SampleBuffer, SPILL_SUFFIX};
+use crate::db::Database;
+
+/// Default interval between two flushes.
+pub const DEFAULT_AUTOSAVE_SECS: u64 = 30;
+
+const PARTIAL_SUFFIX: &str = ".partial.wav";
+
+/// Path of the partial recording of a session.
+pub fn partial_path(audio_dir: &Path, session_id: &str) -> PathBuf {
+    audio_dir.join(format!("{}{}", session_id, PARTIAL_SUFFIX))
+}
+
+/// Background writer of a session's partial recording. Dropping it stops
+/// the writer but keeps the file, for recovery.
+pub struct Autosave {
+    stop: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+    path: PathBuf,
+}
+
+impl Autosave {
+    /// Starts flushing the samples appended to `samples` to `path` every
+    /// `interval`.
+    pub fn spawn(
+        path: PathBuf,
+        samples: Arc<Mutex<SampleBuffer>>,
+        sample_rate: u32,
+        interval: Duration,
+    ) -> Result<Self, String> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = WavWriter::create(&path, spec).map_err(|e| e.to_string())?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_thread = Arc::clone(&stop);
+        let file_path = path.clone();
+        let thread = std::thread::spawn(move || {
+            let mut written = 0usize;
+            let mut last_flush = Instant::now();
+            while !stop_thread.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(200));
+                if last_flush.elapsed() < interval {
+                    continue;
+                }
+                last_flush = Instant::now();
+                let pending: Vec<i16> = match samples.lock().map(|mut s| s.read_from(written)) {
+                    Ok(Ok(pending)) => pending,
+                    Ok(Err(e)) => {
+                        eprintln!("[autosave] Failed to read the recording buffer: {}", e);
+                        break;
+                    }
+                    Err(_) => break,
+                };
+                let result = pending
+                    .iter()
+                    .try_for_each(|&s| writer.write_sample(s))
+                    // Updates the header sizes, so the file is readable as is
+                    .and_then(|_| writer.flush())
+                    .and_then(|_| {
+                        std::fs::File::options()
+                            .write(true)
+                            .open(&file_path)
+                            .and_then(|f| f.sync_data())
+                            .map_err(hound::Error::from)
+                    });
+                if let Err(e) = result {
+                    eprintln!("[autosave] Failed to flush {}: {}", file_path.display(), e);
+                    break;
+                }
+                written += pending.len();
+            }
+        });
+        Ok(Self {
+            stop,
+            thread: Some(thread),
+            path,
+        })
+    }
+
+    /// Stops the writer and deletes the partial file, once the final
+    /// recording is safely on disk.
+    pub fn finish(mut self) {
+        self.shutdown();
+        let _ = std::fs::remove_file(&self.path);
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Autosave {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Turns the partial recordings left by a crash into the session audio, so
+/// that they can be transcribed. Returns the ids of the recovered sessions.
+pub fn recover_partial_recordings(db: &Database, audio_dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(audio_dir) else {
+        return Vec::new();
+    };
+    let mut recovered = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        // Memory overflow of a recording that did not finish; the partial
+        // file holds the same audio
+        if name.ends_with(SPILL_SUFFIX) {
+            let _ = std::fs::remove_file(&path);
+            continue;
+        }
+        let Some(session_id) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(PARTIAL_SUFFIX))
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        match db.get_session(&session_id) {
+            // Saved normally after all; the partial file is stale
+            Ok(session) if session.audio_path.is_some() => {
+                let _ = std::fs::remove_file(&path);
+            }
+            Ok(_) => {
+                let target = audio_dir.join(format!("{}.wav", session_id));
+                let duration = match crate::audio::store::read_wav(&path) {
+                    Ok(audio) if !audio.samples.is_empty() => audio.duration_secs(),
+                    _ => {
+                        let _ = std::fs::remove_file(&path);
+                        continue;
+                    }
+                };
+                if std::fs::rename(&path, &target).is_err() {
+                    continue;
+                }
+                let _ = db.update_session_audio_path(&session_id, target.to_str().unwrap_or(""));
+                let _ = db.update_session_duration(&session_id, duration);
+                eprintln!("[autosave] Recovered {:.0}s of audio for {}", duration, session_id);
+                recovered.push(session_id);
+            }
+            Err(_) => {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+    recovered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autosave_flushes_and_recovers() {
+        let dir = std::env::temp_dir().join(format!("poptranscribe_autosave_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "presentiel").unwrap();
+
+        let mut buffer = SampleBuffer::new(dir.join("buffer.pcm"), 16000);
+        buffer.push(&[1i16; 1600]).unwrap();
+        let samples = Arc::new(Mutex::new(buffer));
+        let path = partial_path(&dir, &id);
+        let autosave =
+            Autosave::spawn(path.clone(), Arc::clone(&samples), 16000, Duration::ZERO).unwrap();
+        std::thread::sleep(Duration::from_millis(500));
+        // Simulate a crash: the writer stops, the file stays
+        drop(autosave);
+        assert!(path.exists());
+
+        assert_eq!(recover_partial_recordings(&db, &dir), vec![id.clone()]);
+        let session = db.get_session(&id).unwrap();
+        assert!(session.audio_path.is_some());
+        assert_eq!(session.duration_secs, Some(0.1));
+        assert!(!path.exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
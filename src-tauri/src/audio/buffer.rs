@@ -0,0 +1,205 @@
+//! Sample buffer of the recording in progress. Samples stay in memory up
+//! to a limit, beyond which they are spilled to a raw PCM file next to the
+//! recording, so that a marathon session does not exhaust the memory.
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::store::{WavOptions, WavStreamWriter};
+
+/// Default audio duration kept in memory before spilling to disk.
+pub const DEFAULT_MEMORY_LIMIT_SECS: u64 = 3600;
+
+pub const SPILL_SUFFIX: &str = ".spill.pcm";
+
+/// Path of the spill file of a session.
+pub fn spill_path(audio_dir: &Path, session_id: &str) -> PathBuf {
+    audio_dir.join(format!("{}{}", session_id, SPILL_SUFFIX))
+}
+
+/// Samples converted to bytes at a time when spilling.
+const SPILL_WRITE_SAMPLES: usize = 32 * 1024;
+
+/// Samples spilled so far, little-endian i16.
+struct Spill {
+    writer: BufWriter<File>,
+    samples: usize,
+}
+
+impl Spill {
+    fn write(&mut self, samples: &[i16]) -> std::io::Result<()> {
+        let mut bytes = Vec::with_capacity(samples.len().min(SPILL_WRITE_SAMPLES) * 2);
+        for chunk in samples.chunks(SPILL_WRITE_SAMPLES) {
+            bytes.clear();
+            bytes.extend(chunk.iter().flat_map(|sample| sample.to_le_bytes()));
+            self.writer.write_all(&bytes)?;
+        }
+        self.samples += samples.len();
+        Ok(())
+    }
+}
+
+pub struct SampleBuffer {
+    memory: Vec<i16>,
+    spill: Option<Spill>,
+    spill_path: PathBuf,
+    /// Samples kept in memory before they are moved to `spill_path`.
+    memory_limit: usize,
+}
+
+impl SampleBuffer {
+    pub fn new(spill_path: PathBuf, memory_limit: usize) -> Self {
+        Self {
+            memory: Vec::new(),
+            spill: None,
+            spill_path,
+            memory_limit: memory_limit.max(1),
+        }
+    }
+
+    /// Total number of samples, in memory and on disk.
+    pub fn len(&self) -> usize {
+        self.spilled() + self.memory.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether samples are being written to disk.
+    pub fn is_spilling(&self) -> bool {
+        self.spill.is_some()
+    }
+
+    fn spilled(&self) -> usize {
+        self.spill.as_ref().map(|s| s.samples).unwrap_or(0)
+    }
+
+    /// Appends samples, moving the in-memory ones to disk once they exceed
+    /// the limit; from then on, each call writes its samples straight to
+    /// disk. Returns true when this call started spilling.
+    pub fn push(&mut self, samples: &[i16]) -> std::io::Result<bool> {
+        if let Some(spill) = self.spill.as_mut() {
+            spill.write(samples)?;
+            return Ok(false);
+        }
+        self.memory.extend_from_slice(samples);
+        if self.memory.len() <= self.memory_limit {
+            return Ok(false);
+        }
+        let file = File::options()
+            .create(true)
+            .truncate(true)
+            .read(true)
+            .write(true)
+            .open(&self.spill_path)?;
+        let mut spill = Spill {
+            writer: BufWriter::new(file),
+            samples: 0,
+        };
+        spill.write(&self.memory)?;
+        self.spill = Some(spill);
+        self.memory = Vec::new();
+        Ok(true)
+    }
+
+    /// Samples from `offset` to the end.
+    pub fn read_from(&mut self, offset: usize) -> std::io::Result<Vec<i16>> {
+        let spilled = self.spilled();
+        let mut samples = Vec::with_capacity(self.len().saturating_sub(offset));
+        if offset < spilled {
+            self.read_spill(offset, spilled - offset, |chunk| samples.extend_from_slice(chunk))?;
+        }
+        samples.extend_from_slice(&self.memory[offset.saturating_sub(spilled).min(self.memory.len())..]);
+        Ok(samples)
+    }
+
+    /// Writes every sample to a 16-bit mono WAV, streaming the spilled part.
+    pub fn save_wav(&mut self, path: &Path, sample_rate: u32) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = WavStreamWriter::create(path, WavOptions::mono16(sample_rate))?;
+        let mut result = Ok(());
+        self.read_spill(0, self.spilled(), |chunk| {
+            if result.is_ok() {
+                result = writer.write_i16(chunk);
+            }
+        })?;
+        result?;
+        writer.write_i16(&self.memory)?;
+        writer.finalize()?;
+        Ok(())
+    }
+
+    /// Reads `count` spilled samples from `offset`, in chunks.
+    fn read_spill(
+        &mut self,
+        offset: usize,
+        count: usize,
+        mut on_chunk: impl FnMut(&[i16]),
+    ) -> std::io::Result<()> {
+        let Some(spill) = self.spill.as_mut() else {
+            return Ok(());
+        };
+        spill.writer.flush()?;
+        let file = spill.writer.get_mut();
+        file.seek(SeekFrom::Start(offset as u64 * 2))?;
+        let mut bytes = vec![0u8; 64 * 1024];
+        let mut remaining = count;
+        while remaining > 0 {
+            let len = (remaining * 2).min(bytes.len());
+            file.read_exact(&mut bytes[..len])?;
+            let chunk: Vec<i16> = bytes[..len]
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                .collect();
+            on_chunk(&chunk);
+            remaining -= len / 2;
+        }
+        // Later writes append
+        file.seek(SeekFrom::End(0))?;
+        Ok(())
+    }
+}
+
+impl Drop for SampleBuffer {
+    fn drop(&mut self) {
+        if self.spill.take().is_some() {
+            let _ = std::fs::remove_file(&self.spill_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_buffer_spills_to_disk() {
+        let dir = std::env::temp_dir().join(format!("poptranscribe_buffer_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = spill_path(&dir, "session");
+        let mut buffer = SampleBuffer::new(path.clone(), 4);
+
+        assert!(!buffer.push(&[1, 2, 3]).unwrap());
+        assert!(buffer.push(&[4, 5]).unwrap());
+        assert!(!buffer.push(&[6]).unwrap());
+        assert!(buffer.is_spilling());
+        // Written straight to disk once spilling
+        assert!(buffer.memory.is_empty());
+        assert_eq!(buffer.len(), 6);
+        assert_eq!(buffer.read_from(3).unwrap(), vec![4, 5, 6]);
+        assert_eq!(buffer.read_from(0).unwrap(), vec![1, 2, 3, 4, 5, 6]);
+        // Appending still works after a read
+        buffer.push(&[7, 8, 9, 10, 11]).unwrap();
+        assert_eq!(buffer.read_from(5).unwrap(), vec![6, 7, 8, 9, 10, 11]);
+
+        let wav = dir.join("session.wav");
+        buffer.save_wav(&wav, 16000).unwrap();
+        let audio = crate::audio::store::read_wav(&wav).unwrap();
+        assert_eq!(audio.samples, (1..=11).collect::<Vec<i16>>());
+
+        drop(buffer);
+        assert!(!path.exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
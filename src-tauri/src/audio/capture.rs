@@ -6,40 +6,130 @@ use std::sync::mpsc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+/// Accepted range for the software microphone gain.
+pub const MIN_GAIN: f32 = 0.1;
+pub const MAX_GAIN: f32 = 8.0;
+
+/// Level above which boosted samples are progressively compressed.
+const SOFT_CLIP_KNEE: f32 = 0.9;
+
+/// Chunks buffered between the audio callbacks and their consumer, about
+/// ten seconds at the usual 10 ms device buffers. When the consumer stalls
+/// the callbacks drop chunks rather than let memory grow without bound.
+const CHUNK_QUEUE_CAPACITY: usize = 1024;
+
+fn chunk_channel() -> (mpsc::SyncSender<Vec<i16>>, mpsc::Receiver<Vec<i16>>) {
+    mpsc::sync_channel(CHUNK_QUEUE_CAPACITY)
+}
+
 pub enum CaptureMode {
     Visio,     // System audio + mic (ScreenCaptureKit on macOS, WASAPI loopback on Windows)
     InPerson,  // mic only
 }
 
+/// Configured input devices. A per-mode device takes precedence over
+/// `default`; `None` everywhere means the system default device.
+#[derive(Debug, Clone, Default)]
+pub struct DevicePreferences {
+    pub default: Option<String>,
+    pub visio: Option<String>,
+    pub in_person: Option<String>,
+}
+
+/// Full-quality copy of the microphone signal, interleaved at the device's
+/// native rate and channel count.
+pub struct NativeStream {
+    pub receiver: mpsc::Receiver<Vec<i16>>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
 pub struct AudioCapturer {
     mode: CaptureMode,
-    device_name: Option<String>,
+    devices: DevicePreferences,
     stream: Option<cpal::Stream>,
     #[cfg(target_os = "macos")]
     sc_stream: Option<SCStream>,
     #[cfg(target_os = "windows")]
     loopback_stream: Option<cpal::Stream>,
+    /// Executable whose audio alone is captured in Visio mode (e.g.
+    /// "ms-teams.exe") instead of the whole default output.
+    #[cfg(target_os = "windows")]
+    loopback_app: Option<String>,
+    #[cfg(target_os = "windows")]
+    app_loopback_thread: Option<std::thread::JoinHandle<()>>,
     capturing: Arc<AtomicBool>,
+    /// Software gain applied to the microphone before conversion to i16.
+    gain: f32,
+    /// Whether to open the microphone in its native format and expose a
+    /// copy of it through `take_native_stream()`.
+    native_copy: bool,
+    native: Option<NativeStream>,
     /// The actual sample rate of the device stream. May differ from 16kHz if the
     /// device does not natively support it. Resampling can be added later.
     pub actual_sample_rate: u32,
 }
 
 impl AudioCapturer {
-    pub fn new(mode: CaptureMode, device_name: Option<String>) -> Self {
+    pub fn new(mode: CaptureMode, devices: DevicePreferences) -> Self {
         Self {
             mode,
-            device_name,
+            devices,
             stream: None,
             #[cfg(target_os = "macos")]
             sc_stream: None,
             #[cfg(target_os = "windows")]
             loopback_stream: None,
+            #[cfg(target_os = "windows")]
+            loopback_app: None,
+            #[cfg(target_os = "windows")]
+            app_loopback_thread: None,
             capturing: Arc::new(AtomicBool::new(false)),
+            gain: 1.0,
+            native_copy: false,
+            native: None,
             actual_sample_rate: 16000,
         }
     }
 
+    /// Sets the microphone gain multiplier, clamped to `MIN_GAIN..=MAX_GAIN`.
+    /// Must be called before `start()`.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = if gain.is_finite() {
+            gain.clamp(MIN_GAIN, MAX_GAIN)
+        } else {
+            1.0
+        };
+    }
+
+    /// Requests a full-quality copy of the microphone next to the main stream,
+    /// which is then resampled to 16kHz mono. Only supported in InPerson
+    /// mode. Must be called before `start()`.
+    pub fn set_native_copy(&mut self, enabled: bool) {
+        self.native_copy = enabled;
+    }
+
+    /// Restricts the Visio system-audio capture to one application, by
+    /// executable name. Windows only (Process Loopback API); falls back to
+    /// the whole output when the application is not running. Must be called
+    /// before `start()`.
+    pub fn set_loopback_app(&mut self, app: Option<String>) {
+        #[cfg(target_os = "windows")]
+        {
+            self.loopback_app = app;
+        }
+        #[cfg(not(target_os = "windows"))]
+        if let Some(app) = app {
+            eprintln!("[capture] Per-application capture of '{}' is only available on Windows", app);
+        }
+    }
+
+    /// Takes the native-format stream requested with `set_native_copy()`,
+    /// available once `start()` succeeded.
+    pub fn take_native_stream(&mut self) -> Option<NativeStream> {
+        self.native.take()
+    }
+
     /// Start capturing audio. Returns a receiver for audio chunks.
     /// Each chunk is a Vec<i16> of PCM samples at 16kHz mono (or the closest
     /// supported sample rate if 16kHz is not available).
@@ -47,7 +137,12 @@ impl AudioCapturer {
         &mut self,
     ) -> Result<mpsc::Receiver<Vec<i16>>, Box<dyn std::error::Error>> {
         match self.mode {
-            CaptureMode::Visio => self.start_visio_capture(),
+            CaptureMode::Visio => {
+                if self.native_copy {
+                    eprintln!("[capture] Native-format copy is not available in Visio mode");
+                }
+                self.start_visio_capture()
+            }
             CaptureMode::InPerson => self.start_mic_capture(),
         }
     }
@@ -67,12 +162,19 @@ impl AudioCapturer {
             let _ = loopback.pause();
             drop(loopback);
         }
+        // The process loopback thread exits once `capturing` is false.
+        #[cfg(target_os = "windows")]
+        if let Some(thread) = self.app_loopback_thread.take() {
+            let _ = thread.join();
+        }
         // Dropping the cpal stream stops it. We take it out of the Option so it gets dropped.
         if let Some(stream) = self.stream.take() {
             // Pause before dropping to ensure clean shutdown.
             let _ = stream.pause();
             drop(stream);
         }
+        #[cfg(target_os = "ios")]
+        crate::audio::mobile::deactivate_record_session();
     }
 
     /// Check if currently capturing.
@@ -80,10 +182,15 @@ impl AudioCapturer {
         self.capturing.load(Ordering::SeqCst)
     }
 
-    /// Resolve the input device: use the configured device name if set,
-    /// otherwise fall back to the system default.
+    /// Resolve the input device: use the device configured for the current
+    /// mode if set and present, then the generic configured device, otherwise
+    /// fall back to the system default.
     fn resolve_input_device(&self, host: &cpal::Host) -> Result<cpal::Device, Box<dyn std::error::Error>> {
-        if let Some(ref name) = self.device_name {
+        let mode_device = match self.mode {
+            CaptureMode::Visio => self.devices.visio.as_ref(),
+            CaptureMode::InPerson => self.devices.in_person.as_ref(),
+        };
+        for name in mode_device.into_iter().chain(self.devices.default.as_ref()) {
             if let Ok(devices) = host.input_devices() {
                 for device in devices {
                     if let Ok(dev_name) = device.name() {
@@ -94,7 +201,7 @@ impl AudioCapturer {
                     }
                 }
             }
-            eprintln!("[capture] Configured device '{}' not found, falling back to default", name);
+            eprintln!("[capture] Configured device '{}' not found, trying next choice", name);
         }
         host.default_input_device()
             .ok_or_else(|| "No default input device available".into())
@@ -107,7 +214,7 @@ impl AudioCapturer {
     fn start_visio_capture(
         &mut self,
     ) -> Result<mpsc::Receiver<Vec<i16>>, Box<dyn std::error::Error>> {
-        let (tx, rx) = mpsc::channel::<Vec<i16>>();
+        let (tx, rx) = chunk_channel();
 
         // --- 1. Set up ScreenCaptureKit for system audio capture ---
 
@@ -167,7 +274,7 @@ impl AudioCapturer {
                             .iter()
                             .map(|&s| f32_to_i16(s))
                             .collect();
-                        let _ = tx_sc.send(i16_samples);
+                        let _ = tx_sc.try_send(i16_samples);
                     }
                 }
             },
@@ -197,7 +304,15 @@ impl AudioCapturer {
     fn start_visio_capture(
         &mut self,
     ) -> Result<mpsc::Receiver<Vec<i16>>, Box<dyn std::error::Error>> {
-        let (tx, rx) = mpsc::channel::<Vec<i16>>();
+        let (tx, rx) = chunk_channel();
+
+        // --- 1a. Selected application only: WASAPI process loopback ---
+        if let Some(pid) = self.loopback_app.as_deref().and_then(find_application_pid) {
+            self.start_app_loopback(pid, tx.clone())?;
+            self.start_visio_mic(tx)?;
+            eprintln!("[capture] Visio mode fully started (process loopback + mic resampled to 16kHz)");
+            return Ok(rx);
+        }
 
         // --- 1. Set up WASAPI loopback for system audio capture ---
         // cpal 0.15 on Windows: calling build_input_stream() on an output device
@@ -241,7 +356,7 @@ impl AudioCapturer {
                         let i16_data: Vec<i16> = data.iter().map(|&s| f32_to_i16(s)).collect();
                         let mono = downmix_to_mono_i16(&i16_data, loopback_channels);
                         let resampled = resample_simple(&mono, loopback_rate, 16000);
-                        let _ = tx_loopback.send(resampled);
+                        let _ = tx_loopback.try_send(resampled);
                     },
                     loopback_err,
                     None,
@@ -257,7 +372,7 @@ impl AudioCapturer {
                         }
                         let mono = downmix_to_mono_i16(data, loopback_channels);
                         let resampled = resample_simple(&mono, loopback_rate, 16000);
-                        let _ = tx_loopback.send(resampled);
+                        let _ = tx_loopback.try_send(resampled);
                     },
                     loopback_err,
                     None,
@@ -284,6 +399,86 @@ impl AudioCapturer {
         Ok(rx)
     }
 
+    /// Captures the audio rendered by process `pid` and its children through
+    /// the Process Loopback API (Windows 10 2004+), on a dedicated thread
+    /// that polls the WASAPI capture client until `capturing` goes false.
+    #[cfg(target_os = "windows")]
+    fn start_app_loopback(
+        &mut self,
+        pid: u32,
+        tx: mpsc::SyncSender<Vec<i16>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use std::collections::VecDeque;
+        use wasapi::{AudioClient, Direction, SampleType, ShareMode, WaveFormat};
+
+        const RATE: u32 = 48000;
+        const CHANNELS: usize = 2;
+
+        // COM objects stay on the reader thread; the setup outcome is sent
+        // back so that activation failures still reach `start()`.
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<(), String>>();
+        // Raised before the mic leg starts so the loop below does not exit
+        // straight away
+        self.capturing.store(true, Ordering::SeqCst);
+        let capturing = Arc::clone(&self.capturing);
+        let thread = std::thread::spawn(move || {
+            let setup = || -> Result<_, Box<dyn std::error::Error>> {
+                let _ = wasapi::initialize_mta();
+                let format =
+                    WaveFormat::new(32, 32, &SampleType::Float, RATE as usize, CHANNELS, None);
+                let mut client = AudioClient::new_application_loopback_client(pid, true)?;
+                client.initialize_client(&format, 0, &Direction::Capture, &ShareMode::Shared, true)?;
+                let event = client.set_get_eventhandle()?;
+                let capture = client.get_audiocaptureclient()?;
+                client.start_stream()?;
+                Ok((client, event, capture))
+            };
+            let (client, event, capture) = match setup() {
+                Ok(parts) => {
+                    let _ = ready_tx.send(Ok(()));
+                    parts
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e.to_string()));
+                    return;
+                }
+            };
+
+            let mut bytes = VecDeque::new();
+            while capturing.load(Ordering::SeqCst) {
+                // Timeouts are expected while the application is silent
+                if event.wait_for_event(200).is_err() {
+                    continue;
+                }
+                if let Err(e) = capture.read_from_device_to_deque(&mut bytes) {
+                    eprintln!("[capture] Process loopback read error: {}", e);
+                    break;
+                }
+                let frame_bytes = 4 * CHANNELS;
+                let usable = bytes.len() - bytes.len() % frame_bytes;
+                if usable == 0 {
+                    continue;
+                }
+                let raw: Vec<u8> = bytes.drain(..usable).collect();
+                let i16_data: Vec<i16> = raw
+                    .chunks_exact(4)
+                    .map(|b| f32_to_i16(f32::from_le_bytes([b[0], b[1], b[2], b[3]])))
+                    .collect();
+                let mono = downmix_to_mono_i16(&i16_data, CHANNELS);
+                let _ = tx.try_send(resample_simple(&mono, RATE, 16000));
+            }
+            let _ = client.stop_stream();
+        });
+        ready_rx
+            .recv()
+            .map_err(|_| "Process loopback thread exited during setup".to_string())?
+            .map_err(|e| format!("Process loopback activation failed for pid {}: {}", pid, e))?;
+        self.app_loopback_thread = Some(thread);
+
+        eprintln!("[capture] Process loopback capture started for pid {}", pid);
+        Ok(())
+    }
+
     // -----------------------------------------------------------------------
     // Fallback: unsupported platform
     // -----------------------------------------------------------------------
@@ -298,7 +493,7 @@ impl AudioCapturer {
     /// Used by both macOS and Windows `start_visio_capture()`.
     fn start_visio_mic(
         &mut self,
-        tx: mpsc::Sender<Vec<i16>>,
+        tx: mpsc::SyncSender<Vec<i16>>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let host = cpal::default_host();
         let device = self.resolve_input_device(&host)?;
@@ -323,6 +518,7 @@ impl AudioCapturer {
 
         let capturing_for_mic = Arc::clone(&self.capturing);
         let tx_mic = tx;
+        let gain = self.gain;
 
         let err_callback = |err: cpal::StreamError| {
             eprintln!("[capture] Mic stream error: {}", err);
@@ -339,9 +535,13 @@ impl AudioCapturer {
                         if !capturing.load(Ordering::SeqCst) {
                             return;
                         }
-                        let mono = downmix_to_mono_i16(data, channels);
+                        let i16_data: Vec<i16> = data
+                            .iter()
+                            .map(|&s| gain_sample(s as f32 / i16::MAX as f32, gain))
+                            .collect();
+                        let mono = downmix_to_mono_i16(&i16_data, channels);
                         let resampled = resample_simple(&mono, mic_rate, 16000);
-                        let _ = tx_mic.send(resampled);
+                        let _ = tx_mic.try_send(resampled);
                     },
                     err_callback,
                     None,
@@ -355,10 +555,10 @@ impl AudioCapturer {
                         if !capturing.load(Ordering::SeqCst) {
                             return;
                         }
-                        let i16_data: Vec<i16> = data.iter().map(|&s| f32_to_i16(s)).collect();
+                        let i16_data: Vec<i16> = data.iter().map(|&s| gain_sample(s, gain)).collect();
                         let mono = downmix_to_mono_i16(&i16_data, channels);
                         let resampled = resample_simple(&mono, mic_rate, 16000);
-                        let _ = tx_mic.send(resampled);
+                        let _ = tx_mic.try_send(resampled);
                     },
                     err_callback,
                     None,
@@ -374,11 +574,11 @@ impl AudioCapturer {
                         }
                         let i16_data: Vec<i16> = data
                             .iter()
-                            .map(|&s| (s as i32 - 32768) as i16)
+                            .map(|&s| gain_sample((s as f32 - 32768.0) / 32768.0, gain))
                             .collect();
                         let mono = downmix_to_mono_i16(&i16_data, channels);
                         let resampled = resample_simple(&mono, mic_rate, 16000);
-                        let _ = tx_mic.send(resampled);
+                        let _ = tx_mic.try_send(resampled);
                     },
                     err_callback,
                     None,
@@ -404,6 +604,9 @@ impl AudioCapturer {
     fn start_mic_capture(
         &mut self,
     ) -> Result<mpsc::Receiver<Vec<i16>>, Box<dyn std::error::Error>> {
+        #[cfg(target_os = "ios")]
+        crate::audio::mobile::activate_record_session()?;
+
         let host = cpal::default_host();
         let device = self.resolve_input_device(&host)?;
 
@@ -412,19 +615,41 @@ impl AudioCapturer {
 
         // Try to find a config that supports 16kHz mono i16.
         // If not available, fall back to the default input config.
-        let (stream_config, sample_format) =
-            self.select_input_config(&device)?;
+        // A native copy needs the device's own format instead.
+        let (stream_config, sample_format) = if self.native_copy {
+            let default_config = device.default_input_config()?;
+            let fmt = default_config.sample_format();
+            let stream_config: StreamConfig = default_config.into();
+            (stream_config, fmt)
+        } else {
+            self.select_input_config(&device)?
+        };
 
         eprintln!(
             "[capture] Stream config: {} Hz, {} ch, format: {:?}",
             stream_config.sample_rate.0, stream_config.channels, sample_format
         );
 
-        self.actual_sample_rate = stream_config.sample_rate.0;
-
-        let (tx, rx) = mpsc::channel::<Vec<i16>>();
+        // With a native copy, the main stream is resampled to 16kHz
+        let device_rate = stream_config.sample_rate.0;
+        let output_rate = if self.native_copy { 16000 } else { device_rate };
+        self.actual_sample_rate = output_rate;
+
+        let (tx, rx) = chunk_channel();
+        let native_tx = if self.native_copy {
+            let (native_tx, native_rx) = chunk_channel();
+            self.native = Some(NativeStream {
+                receiver: native_rx,
+                sample_rate: device_rate,
+                channels: stream_config.channels,
+            });
+            Some(native_tx)
+        } else {
+            None
+        };
 
         let capturing = Arc::clone(&self.capturing);
+        let gain = self.gain;
 
         let err_callback = |err: cpal::StreamError| {
             eprintln!("[capture] Stream error: {}", err);
@@ -441,9 +666,12 @@ impl AudioCapturer {
                         if !capturing.load(Ordering::SeqCst) {
                             return;
                         }
-                        // Downmix to mono if needed, then send the chunk.
-                        let mono = downmix_to_mono_i16(data, channels);
-                        let _ = tx.send(mono);
+                        // Apply gain, downmix to mono if needed, then send the chunk.
+                        let i16_data: Vec<i16> = data
+                            .iter()
+                            .map(|&s| gain_sample(s as f32 / i16::MAX as f32, gain))
+                            .collect();
+                        forward_mic_chunk(i16_data, channels, device_rate, output_rate, &tx, native_tx.as_ref());
                     },
                     err_callback,
                     None,
@@ -457,13 +685,12 @@ impl AudioCapturer {
                         if !capturing.load(Ordering::SeqCst) {
                             return;
                         }
-                        // Convert f32 -> i16, then downmix to mono.
+                        // Apply gain, convert f32 -> i16, then downmix to mono.
                         let i16_data: Vec<i16> = data
                             .iter()
-                            .map(|&s| f32_to_i16(s))
+                            .map(|&s| gain_sample(s, gain))
                             .collect();
-                        let mono = downmix_to_mono_i16(&i16_data, channels);
-                        let _ = tx.send(mono);
+                        forward_mic_chunk(i16_data, channels, device_rate, output_rate, &tx, native_tx.as_ref());
                     },
                     err_callback,
                     None,
@@ -477,13 +704,12 @@ impl AudioCapturer {
                         if !capturing.load(Ordering::SeqCst) {
                             return;
                         }
-                        // Convert u16 -> i16 (center at 0), then downmix.
+                        // Convert u16 -> i16 (center at 0) with gain, then downmix.
                         let i16_data: Vec<i16> = data
                             .iter()
-                            .map(|&s| (s as i32 - 32768) as i16)
+                            .map(|&s| gain_sample((s as f32 - 32768.0) / 32768.0, gain))
                             .collect();
-                        let mono = downmix_to_mono_i16(&i16_data, channels);
-                        let _ = tx.send(mono);
+                        forward_mic_chunk(i16_data, channels, device_rate, output_rate, &tx, native_tx.as_ref());
                     },
                     err_callback,
                     None,
@@ -590,6 +816,43 @@ fn f32_to_i16(sample: f32) -> i16 {
     (clamped * i16::MAX as f32) as i16
 }
 
+/// Soft limiter: linear up to `SOFT_CLIP_KNEE`, then smoothly compressed so
+/// that boosted peaks never exceed full scale.
+fn soft_clip(sample: f32) -> f32 {
+    let level = sample.abs();
+    if level <= SOFT_CLIP_KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - SOFT_CLIP_KNEE;
+    let compressed = SOFT_CLIP_KNEE + headroom * ((level - SOFT_CLIP_KNEE) / headroom).tanh();
+    compressed.copysign(sample)
+}
+
+/// Applies `gain` to a normalized sample (-1.0 to 1.0) and converts it to i16.
+fn gain_sample(sample: f32, gain: f32) -> i16 {
+    if gain == 1.0 {
+        return f32_to_i16(sample);
+    }
+    f32_to_i16(soft_clip(sample * gain))
+}
+
+/// Sends one microphone callback: the interleaved samples to the native
+/// stream if any, and a mono copy at `output_rate` to the main stream.
+fn forward_mic_chunk(
+    interleaved: Vec<i16>,
+    channels: usize,
+    device_rate: u32,
+    output_rate: u32,
+    tx: &mpsc::SyncSender<Vec<i16>>,
+    native_tx: Option<&mpsc::SyncSender<Vec<i16>>>,
+) {
+    let mono = downmix_to_mono_i16(&interleaved, channels);
+    if let Some(native_tx) = native_tx {
+        let _ = native_tx.try_send(interleaved);
+    }
+    let _ = tx.try_send(resample_simple(&mono, device_rate, output_rate));
+}
+
 /// Fast linear-interpolation resampler for use inside audio callbacks.
 fn resample_simple(samples: &[i16], from_rate: u32, to_rate: u32) -> Vec<i16> {
     if from_rate == to_rate || samples.is_empty() {
@@ -611,3 +874,58 @@ fn resample_simple(samples: &[i16], from_rate: u32, to_rate: u32) -> Vec<i16> {
     }
     out
 }
+
+/// Checks that ScreenCaptureKit can list the displays, which fails until the
+/// app has been granted the screen-recording permission.
+#[cfg(target_os = "macos")]
+pub fn check_screen_capture() -> Result<(), String> {
+    let content = SCShareableContent::get().map_err(|e| e.to_string())?;
+    if content.displays().is_empty() {
+        return Err("No display available for ScreenCaptureKit capture".to_string());
+    }
+    Ok(())
+}
+
+/// Names of the running executables, for picking the application to
+/// capture in Visio mode. Empty outside Windows.
+pub fn running_applications() -> Vec<String> {
+    #[cfg(target_os = "windows")]
+    {
+        let system = sysinfo::System::new_all();
+        let mut names: Vec<String> = system
+            .processes()
+            .values()
+            .map(|p| p.name().to_string())
+            .collect();
+        names.sort_by_key(|n| n.to_lowercase());
+        names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        names
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Root process of the running application `name`: multi-process apps
+/// (Teams, browsers) render audio from child processes, which the process
+/// loopback includes when given the top of the tree.
+#[cfg(target_os = "windows")]
+fn find_application_pid(name: &str) -> Option<u32> {
+    let system = sysinfo::System::new_all();
+    let processes = system.processes();
+    let is_app = |pid: &sysinfo::Pid| {
+        processes
+            .get(pid)
+            .is_some_and(|p| p.name().eq_ignore_ascii_case(name))
+    };
+    let root = processes
+        .iter()
+        .filter(|(pid, _)| is_app(pid))
+        .find(|(_, p)| !p.parent().is_some_and(|parent| is_app(&parent)))
+        .map(|(pid, _)| pid.as_u32());
+    if root.is_none() {
+        eprintln!("[capture] Application '{}' is not running, capturing the whole output", name);
+    }
+    root
+}
@@ -11,10 +11,58 @@ pub fn mix_samples(a: &[i16], b: &[i16]) -> Vec<i16> {
         .collect()
 }
 
+/// Peak and RMS level of a buffer, as fractions of full scale (0.0 to 1.0).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioLevels {
+    pub peak: f64,
+    pub rms: f64,
+}
+
+impl AudioLevels {
+    /// Converts a full-scale fraction to dBFS (-inf for silence).
+    pub fn to_dbfs(level: f64) -> f64 {
+        20.0 * level.log10()
+    }
+}
+
+pub fn measure_levels(samples: &[i16]) -> AudioLevels {
+    if samples.is_empty() {
+        return AudioLevels { peak: 0.0, rms: 0.0 };
+    }
+    let full_scale = i16::MAX as f64;
+    let peak = samples
+        .iter()
+        .map(|&s| (s as i32).unsigned_abs())
+        .max()
+        .unwrap_or(0) as f64
+        / full_scale;
+    let mean_square =
+        samples.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / samples.len() as f64;
+    AudioLevels {
+        peak: peak.min(1.0),
+        rms: mean_square.sqrt() / full_scale,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_measure_levels() {
+        let levels = measure_levels(&[0, i16::MAX, 0, -i16::MAX]);
+        assert_eq!(levels.peak, 1.0);
+        assert!((levels.rms - 0.5_f64.sqrt()).abs() < 1e-9);
+        assert!((AudioLevels::to_dbfs(levels.peak)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_measure_levels_silence() {
+        let levels = measure_levels(&[0; 16]);
+        assert_eq!(levels, AudioLevels { peak: 0.0, rms: 0.0 });
+        assert_eq!(measure_levels(&[]).peak, 0.0);
+    }
+
     #[test]
     fn test_mix_two_streams() {
         let a: Vec<i16> = vec![1000, 2000, 3000];
@@ -0,0 +1,67 @@
+//! Audio session handling for iOS. cpal drives the microphone through
+//! CoreAudio (iOS) and AAudio (Android) like on desktop, but iOS only routes
+//! the microphone to apps whose `AVAudioSession` is in a recording category.
+//! Android needs no session; RECORD_AUDIO is declared and requested at
+//! runtime by the `microphone` plugin, see `crate::permissions::preflight`.
+
+use objc2::runtime::{AnyObject, Bool};
+use objc2::{class, msg_send};
+
+#[link(name = "AVFoundation", kind = "framework")]
+extern "C" {
+    static AVAudioSessionCategoryPlayAndRecord: *const AnyObject;
+}
+
+/// `AVAudioSessionCategoryOptionAllowBluetooth | DefaultToSpeaker`: accept
+/// headset microphones and keep playback on the speaker, not the earpiece.
+const SESSION_OPTIONS: usize = 0x4 | 0x8;
+
+fn shared_session() -> *mut AnyObject {
+    unsafe { msg_send![class!(AVAudioSession), sharedInstance] }
+}
+
+fn error_description(error: *mut AnyObject) -> String {
+    if error.is_null() {
+        return "unknown error".to_string();
+    }
+    let description: *mut AnyObject = unsafe { msg_send![error, localizedDescription] };
+    let utf8: *const std::os::raw::c_char = unsafe { msg_send![description, UTF8String] };
+    if utf8.is_null() {
+        return "unknown error".to_string();
+    }
+    unsafe { std::ffi::CStr::from_ptr(utf8) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Switches the audio session to play-and-record and activates it. Call
+/// before opening the input stream.
+pub fn activate_record_session() -> Result<(), String> {
+    let session = shared_session();
+    let mut error: *mut AnyObject = std::ptr::null_mut();
+    let ok: Bool = unsafe {
+        msg_send![
+            session,
+            setCategory: AVAudioSessionCategoryPlayAndRecord,
+            withOptions: SESSION_OPTIONS,
+            error: &mut error
+        ]
+    };
+    if !ok.as_bool() {
+        return Err(format!("AVAudioSession category failed: {}", error_description(error)));
+    }
+    let ok: Bool = unsafe { msg_send![session, setActive: Bool::YES, error: &mut error] };
+    if !ok.as_bool() {
+        return Err(format!("AVAudioSession activation failed: {}", error_description(error)));
+    }
+    Ok(())
+}
+
+/// Releases the audio session so other apps resume their playback.
+pub fn deactivate_record_session() {
+    let mut error: *mut AnyObject = std::ptr::null_mut();
+    let ok: Bool = unsafe { msg_send![shared_session(), setActive: Bool::NO, error: &mut error] };
+    if !ok.as_bool() {
+        eprintln!("[capture] AVAudioSession deactivation failed: {}", error_description(error));
+    }
+}
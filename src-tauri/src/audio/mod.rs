@@ -1,3 +1,11 @@
+pub mod archive;
+pub mod autosave;
+pub mod buffer;
 pub mod capture;
 pub mod mixer;
+#[cfg(target_os = "ios")]
+pub mod mobile;
+pub mod playback;
+pub mod silence;
 pub mod store;
+pub mod transcode;
@@ -0,0 +1,92 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{SampleFormat, StreamConfig};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Plays mono i16 samples on the default output device and blocks until
+/// playback is over. Used to let users hear a microphone test.
+pub fn play_blocking(samples: &[i16], sample_rate: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let host = cpal::default_host();
+    let device = host
+        .default_output_device()
+        .ok_or("No default output device available")?;
+    let supported = device.default_output_config()?;
+    let sample_format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+    let channels = config.channels as usize;
+
+    let data = Arc::new(crate::mistral::realtime::resample(
+        samples,
+        sample_rate,
+        config.sample_rate.0,
+    ));
+    let position = Arc::new(AtomicUsize::new(0));
+
+    let err_callback = |err: cpal::StreamError| {
+        eprintln!("[playback] Stream error: {}", err);
+    };
+
+    let stream = match sample_format {
+        SampleFormat::F32 => {
+            let (data, position) = (Arc::clone(&data), Arc::clone(&position));
+            device.build_output_stream(
+                &config,
+                move |out: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                    fill_frames(out, channels, &data, &position, |s| s as f32 / i16::MAX as f32);
+                },
+                err_callback,
+                None,
+            )?
+        }
+        SampleFormat::I16 => {
+            let (data, position) = (Arc::clone(&data), Arc::clone(&position));
+            device.build_output_stream(
+                &config,
+                move |out: &mut [i16], _: &cpal::OutputCallbackInfo| {
+                    fill_frames(out, channels, &data, &position, |s| s);
+                },
+                err_callback,
+                None,
+            )?
+        }
+        SampleFormat::U16 => {
+            let (data, position) = (Arc::clone(&data), Arc::clone(&position));
+            device.build_output_stream(
+                &config,
+                move |out: &mut [u16], _: &cpal::OutputCallbackInfo| {
+                    fill_frames(out, channels, &data, &position, |s| (s as i32 + 32768) as u16);
+                },
+                err_callback,
+                None,
+            )?
+        }
+        _ => {
+            return Err(format!("Unsupported output sample format: {:?}", sample_format).into());
+        }
+    };
+
+    stream.play()?;
+    let duration = samples.len() as f64 / sample_rate.max(1) as f64;
+    // A little slack so that the device buffer drains before the stream is dropped
+    std::thread::sleep(std::time::Duration::from_secs_f64(duration + 0.3));
+    drop(stream);
+    Ok(())
+}
+
+/// Writes the next mono samples to every channel of `out`, then silence
+/// once `data` is exhausted.
+fn fill_frames<T: Copy>(
+    out: &mut [T],
+    channels: usize,
+    data: &[i16],
+    position: &AtomicUsize,
+    convert: impl Fn(i16) -> T,
+) {
+    let mut pos = position.load(Ordering::Relaxed);
+    for frame in out.chunks_mut(channels.max(1)) {
+        let sample = convert(data.get(pos).copied().unwrap_or(0));
+        frame.fill(sample);
+        pos += 1;
+    }
+    position.store(pos, Ordering::Relaxed);
+}
@@ -0,0 +1,103 @@
+/// Samples whose magnitude stays at or below this value count as silence
+/// (about -54 dBFS, above the noise floor of a hardware-muted microphone).
+pub const SILENCE_PEAK: i16 = 64;
+
+/// Looser threshold (about -35 dBFS) for a room where nobody speaks any
+/// more: background noise stays below it, voices do not.
+pub const QUIET_PEAK: i16 = 600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SilenceChange {
+    /// The signal has been silent for the configured duration.
+    Started,
+    /// Sound came back after a reported silence.
+    Ended,
+}
+
+/// Tracks how long the incoming signal has stayed near zero. Time is
+/// derived from sample counts, so it follows the audio rather than the clock.
+pub struct SilenceDetector {
+    sample_rate: u32,
+    peak_threshold: i16,
+    threshold_samples: u64,
+    silent_samples: u64,
+    reported: bool,
+}
+
+impl SilenceDetector {
+    pub fn new(sample_rate: u32, threshold_secs: u64) -> Self {
+        Self::with_peak(sample_rate, threshold_secs, SILENCE_PEAK)
+    }
+
+    /// Detector counting chunks whose peak stays at or below `peak_threshold`.
+    pub fn with_peak(sample_rate: u32, threshold_secs: u64, peak_threshold: i16) -> Self {
+        Self {
+            sample_rate,
+            peak_threshold,
+            threshold_samples: sample_rate as u64 * threshold_secs,
+            silent_samples: 0,
+            reported: false,
+        }
+    }
+
+    /// Seconds of uninterrupted silence so far.
+    pub fn silent_secs(&self) -> u64 {
+        self.silent_samples / self.sample_rate.max(1) as u64
+    }
+
+    /// Feeds one chunk and returns a change when the silence crosses the
+    /// threshold or ends after having been reported.
+    pub fn feed(&mut self, chunk: &[i16]) -> Option<SilenceChange> {
+        if chunk.is_empty() {
+            return None;
+        }
+        let silent = chunk.iter().all(|&s| (s as i32).abs() <= self.peak_threshold as i32);
+        if silent {
+            self.silent_samples += chunk.len() as u64;
+            if !self.reported && self.silent_samples >= self.threshold_samples {
+                self.reported = true;
+                return Some(SilenceChange::Started);
+            }
+            None
+        } else {
+            self.silent_samples = 0;
+            if self.reported {
+                self.reported = false;
+                return Some(SilenceChange::Ended);
+            }
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_silence_once_after_threshold() {
+        let mut detector = SilenceDetector::new(100, 2);
+        assert_eq!(detector.feed(&[0; 100]), None);
+        assert_eq!(detector.feed(&[3; 100]), Some(SilenceChange::Started));
+        assert_eq!(detector.feed(&[0; 100]), None);
+        assert_eq!(detector.silent_secs(), 3);
+    }
+
+    #[test]
+    fn test_custom_peak_threshold() {
+        let mut detector = SilenceDetector::with_peak(100, 1, QUIET_PEAK);
+        assert_eq!(detector.feed(&[500; 100]), Some(SilenceChange::Started));
+        assert_eq!(detector.feed(&[700; 10]), Some(SilenceChange::Ended));
+    }
+
+    #[test]
+    fn test_sound_resets_and_ends_silence() {
+        let mut detector = SilenceDetector::new(100, 1);
+        assert_eq!(detector.feed(&[0; 50]), None);
+        assert_eq!(detector.feed(&[0, 2000]), None);
+        assert_eq!(detector.feed(&[0; 50]), None);
+        assert_eq!(detector.feed(&[0; 60]), Some(SilenceChange::Started));
+        assert_eq!(detector.feed(&[0, -5000]), Some(SilenceChange::Ended));
+        assert_eq!(detector.silent_secs(), 0);
+    }
+}
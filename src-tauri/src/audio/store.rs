@@ -1,20 +1,260 @@
 use hound::{WavSpec, WavWriter, SampleFormat};
-use std::path::Path;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Sample encoding of a WAV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavFormat {
+    Int16,
+    Int24,
+    Float32,
+}
+
+/// Layout of a WAV file. Samples are interleaved when `channels` > 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavOptions {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub format: WavFormat,
+}
+
+impl WavOptions {
+    /// 16-bit mono, the format used for transcription.
+    pub fn mono16(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            channels: 1,
+            format: WavFormat::Int16,
+        }
+    }
+
+    fn spec(&self) -> WavSpec {
+        let (bits_per_sample, sample_format) = match self.format {
+            WavFormat::Int16 => (16, SampleFormat::Int),
+            WavFormat::Int24 => (24, SampleFormat::Int),
+            WavFormat::Float32 => (32, SampleFormat::Float),
+        };
+        WavSpec {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+}
+
+/// Sibling file that a file is written to before being renamed into place.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Moves a fully written `tmp` file to `path` durably: the data is synced to
+/// disk before the atomic rename, and the directory after it, so a crash
+/// leaves either the previous file or the complete new one, never a
+/// truncated one.
+fn commit_temp_file(tmp: &Path, path: &Path) -> std::io::Result<()> {
+    File::options().write(true).open(tmp)?.sync_all()?;
+    std::fs::rename(tmp, path)?;
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Incremental WAV writer: samples are converted to the target format as
+/// they are written, so long recordings never need to fit in memory. The
+/// file only appears at its path once `finalize()` succeeded.
+pub struct WavStreamWriter {
+    writer: WavWriter<BufWriter<File>>,
+    options: WavOptions,
+    samples_written: u64,
+    path: PathBuf,
+    tmp_path: PathBuf,
+}
+
+impl WavStreamWriter {
+    pub fn create(path: &Path, options: WavOptions) -> Result<Self, Box<dyn std::error::Error>> {
+        if options.channels == 0 {
+            return Err("A WAV file needs at least one channel".into());
+        }
+        let tmp_path = temp_path(path);
+        Ok(Self {
+            writer: WavWriter::create(&tmp_path, options.spec())?,
+            options,
+            samples_written: 0,
+            path: path.to_path_buf(),
+            tmp_path,
+        })
+    }
+
+    pub fn write_i16(&mut self, samples: &[i16]) -> Result<(), hound::Error> {
+        for &sample in samples {
+            match self.options.format {
+                WavFormat::Int16 => self.writer.write_sample(sample)?,
+                WavFormat::Int24 => self.writer.write_sample((sample as i32) << 8)?,
+                WavFormat::Float32 => self.writer.write_sample(sample as f32 / 32768.0)?,
+            }
+        }
+        self.samples_written += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Writes samples in -1.0..=1.0; values outside are clamped for the
+    /// integer formats.
+    pub fn write_f32(&mut self, samples: &[f32]) -> Result<(), hound::Error> {
+        for &sample in samples {
+            match self.options.format {
+                WavFormat::Int16 => self
+                    .writer
+                    .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?,
+                WavFormat::Int24 => self
+                    .writer
+                    .write_sample((sample.clamp(-1.0, 1.0) * 8_388_607.0) as i32)?,
+                WavFormat::Float32 => self.writer.write_sample(sample)?,
+            }
+        }
+        self.samples_written += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Number of complete frames (one sample per channel) written so far.
+    pub fn frames_written(&self) -> u64 {
+        self.samples_written / self.options.channels as u64
+    }
+
+    /// Writes the final header sizes and moves the file into place. Returns
+    /// the number of frames.
+    pub fn finalize(self) -> Result<u64, hound::Error> {
+        let frames = self.frames_written();
+        self.writer.finalize()?;
+        commit_temp_file(&self.tmp_path, &self.path)?;
+        Ok(frames)
+    }
+}
 
 /// Save PCM i16 samples to a WAV file (mono, specified sample rate)
 pub fn save_wav(path: &Path, samples: &[i16], sample_rate: u32) -> Result<(), Box<dyn std::error::Error>> {
-    let spec = WavSpec {
-        channels: 1,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: SampleFormat::Int,
+    save_wav_with(path, samples, WavOptions::mono16(sample_rate))
+}
+
+/// Save interleaved PCM i16 samples to a WAV file with the given layout.
+pub fn save_wav_with(
+    path: &Path,
+    samples: &[i16],
+    options: WavOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = WavStreamWriter::create(path, options)?;
+    writer.write_i16(samples)?;
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Write interleaved i16 chunks to a WAV file as they arrive, until the
+/// sender is dropped. Returns the number of frames written.
+pub fn write_wav_stream(
+    path: &Path,
+    receiver: std::sync::mpsc::Receiver<Vec<i16>>,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let options = WavOptions {
+        channels,
+        ..WavOptions::mono16(sample_rate)
+    };
+    let mut writer = WavStreamWriter::create(path, options)?;
+    for chunk in receiver {
+        writer.write_i16(&chunk)?;
+    }
+    Ok(writer.finalize()?)
+}
+
+/// Decoded WAV file: interleaved samples converted to i16.
+#[derive(Debug, Clone)]
+pub struct WavAudio {
+    pub samples: Vec<i16>,
+    pub options: WavOptions,
+}
+
+impl WavAudio {
+    pub fn duration_secs(&self) -> f64 {
+        self.samples.len() as f64 / self.options.channels as f64 / self.options.sample_rate as f64
+    }
+}
+
+/// Read a WAV file of any integer depth up to 32 bits or 32-bit float.
+/// Formats other than 16-bit, 24-bit and float are reported as `Int16`.
+pub fn read_wav(path: &Path) -> Result<WavAudio, Box<dyn std::error::Error>> {
+    let reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    let options = WavOptions {
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+        format: match (spec.sample_format, spec.bits_per_sample) {
+            (SampleFormat::Float, _) => WavFormat::Float32,
+            (SampleFormat::Int, 24) => WavFormat::Int24,
+            _ => WavFormat::Int16,
+        },
+    };
+    let samples = match spec.sample_format {
+        SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .map(|s| s.map(|v| (v.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
+            .collect::<Result<Vec<_>, _>>()?,
+        SampleFormat::Int if spec.bits_per_sample <= 16 => {
+            let shift = 16 - spec.bits_per_sample as u32;
+            reader
+                .into_samples::<i16>()
+                .map(|s| s.map(|v| v << shift))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        SampleFormat::Int => {
+            let shift = spec.bits_per_sample as u32 - 16;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|v| (v >> shift) as i16))
+                .collect::<Result<Vec<_>, _>>()?
+        }
     };
-    let mut writer = WavWriter::create(path, spec)?;
-    for &sample in samples {
-        writer.write_sample(sample)?;
+    Ok(WavAudio { samples, options })
+}
+
+/// Whether `trim_wav_start` can cut this file: a 16-bit PCM WAV, not a
+/// native-format or compressed recording.
+pub fn can_trim(path: &Path) -> bool {
+    hound::WavReader::open(path)
+        .map(|reader| {
+            let spec = reader.spec();
+            spec.sample_format == SampleFormat::Int && spec.bits_per_sample == 16
+        })
+        .unwrap_or(false)
+}
+
+/// Remove the first `seconds` of a 16-bit WAV file in place. Returns the new
+/// duration in seconds.
+pub fn trim_wav_start(path: &Path, seconds: f64) -> Result<f64, Box<dyn std::error::Error>> {
+    let reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    if spec.sample_format != SampleFormat::Int || spec.bits_per_sample != 16 {
+        return Err("Only 16-bit PCM WAV files can be trimmed".into());
+    }
+    let skip = (seconds.max(0.0) * spec.sample_rate as f64) as usize * spec.channels as usize;
+
+    // Write next to the original, then swap it in
+    let tmp_path = temp_path(path);
+    let mut writer = WavWriter::create(&tmp_path, spec)?;
+    let mut written = 0usize;
+    for sample in reader.into_samples::<i16>().skip(skip) {
+        writer.write_sample(sample?)?;
+        written += 1;
     }
     writer.finalize()?;
-    Ok(())
+    commit_temp_file(&tmp_path, path)?;
+
+    Ok(written as f64 / spec.channels as f64 / spec.sample_rate as f64)
 }
 
 #[cfg(test)]
@@ -48,6 +288,114 @@ mod tests {
         std::fs::remove_file(&path).ok();
     }
 
+    #[test]
+    fn test_wav_formats_round_trip() {
+        let samples: Vec<i16> = vec![0, 1000, -1000, i16::MAX, i16::MIN, 42];
+        for (format, name) in [
+            (WavFormat::Int16, "int16"),
+            (WavFormat::Int24, "int24"),
+            (WavFormat::Float32, "float32"),
+        ] {
+            let path = env::temp_dir().join(format!("poptranscribe_test_{}.wav", name));
+            let options = WavOptions { sample_rate: 48000, channels: 2, format };
+            save_wav_with(&path, &samples, options).unwrap();
+
+            let audio = read_wav(&path).unwrap();
+            assert_eq!(audio.options, options);
+            assert_eq!(audio.duration_secs(), 3.0 / 48000.0);
+            for (read, original) in audio.samples.iter().zip(&samples) {
+                assert!((*read as i32 - *original as i32).abs() <= 1, "{}: {} vs {}", name, read, original);
+            }
+
+            std::fs::remove_file(&path).ok();
+        }
+    }
+
+    #[test]
+    fn test_stream_writer_f32_input() {
+        let path = env::temp_dir().join("poptranscribe_test_stream_f32.wav");
+        let mut writer = WavStreamWriter::create(&path, WavOptions::mono16(16000)).unwrap();
+        writer.write_f32(&[0.0, 1.0, -2.0]).unwrap();
+        writer.write_i16(&[7]).unwrap();
+        assert_eq!(writer.finalize().unwrap(), 4);
+
+        let audio = read_wav(&path).unwrap();
+        assert_eq!(audio.samples, vec![0, i16::MAX, -i16::MAX, 7]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_stream_writer_is_atomic() {
+        let path = env::temp_dir().join("poptranscribe_test_atomic.wav");
+        std::fs::remove_file(&path).ok();
+        let mut writer = WavStreamWriter::create(&path, WavOptions::mono16(16000)).unwrap();
+        writer.write_i16(&[1, 2, 3]).unwrap();
+        // Nothing at the final path until finalized
+        assert!(!path.exists());
+        writer.finalize().unwrap();
+        assert!(path.exists());
+        assert!(!temp_path(&path).exists());
+        assert_eq!(read_wav(&path).unwrap().samples, vec![1, 2, 3]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_write_wav_stream() {
+        let path = env::temp_dir().join("poptranscribe_test_stream.wav");
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(vec![1i16, -1, 2, -2]).unwrap();
+        tx.send(vec![3i16, -3]).unwrap();
+        drop(tx);
+
+        let frames = write_wav_stream(&path, rx, 48000, 2).unwrap();
+        assert_eq!(frames, 3);
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 48000);
+        let read: Vec<i16> = reader.into_samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, vec![1, -1, 2, -2, 3, -3]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_trim_wav_start() {
+        let samples: Vec<i16> = (0..32000).map(|i| (i % 1000) as i16).collect();
+        let path = env::temp_dir().join("poptranscribe_test_trim.wav");
+        save_wav(&path, &samples, 16000).unwrap();
+
+        let duration = trim_wav_start(&path, 0.5).unwrap();
+        assert_eq!(duration, 1.5);
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        let read_samples: Vec<i16> = reader.into_samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(read_samples.len(), 24000);
+        assert_eq!(read_samples[0], samples[8000]);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_can_trim() {
+        let samples: Vec<i16> = vec![0; 160];
+        let path = env::temp_dir().join("poptranscribe_test_can_trim.wav");
+        save_wav(&path, &samples, 16000).unwrap();
+        assert!(can_trim(&path));
+
+        let options = WavOptions { sample_rate: 48000, channels: 2, format: WavFormat::Float32 };
+        save_wav_with(&path, &samples, options).unwrap();
+        assert!(!can_trim(&path));
+
+        std::fs::write(&path, b"not a wav").unwrap();
+        assert!(!can_trim(&path));
+        assert!(!can_trim(&env::temp_dir().join("poptranscribe_test_missing.wav")));
+
+        std::fs::remove_file(&path).ok();
+    }
+
     #[test]
     fn test_save_empty_wav() {
         let samples: Vec<i16> = vec![];
@@ -0,0 +1,108 @@
+use std::path::Path;
+
+/// Compressed formats the stored WAV recordings can be converted to.
+///
+/// Conversion shells out to `ffmpeg`, which must be available on the `PATH`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    Opus,
+    Flac,
+    Mp3,
+    M4a,
+}
+
+impl AudioFormat {
+    /// Parse a format name as used in settings and command arguments.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "opus" => Some(Self::Opus),
+            "flac" => Some(Self::Flac),
+            "mp3" => Some(Self::Mp3),
+            "m4a" | "aac" => Some(Self::M4a),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Opus => "opus",
+            Self::Flac => "flac",
+            Self::Mp3 => "mp3",
+            Self::M4a => "m4a",
+        }
+    }
+
+    /// Encoder arguments, tuned for speech rather than music.
+    fn codec_args(&self) -> &'static [&'static str] {
+        match self {
+            Self::Opus => &["-c:a", "libopus", "-b:a", "32k", "-application", "voip"],
+            Self::Flac => &["-c:a", "flac"],
+            Self::Mp3 => &["-c:a", "libmp3lame", "-b:a", "64k"],
+            Self::M4a => &["-c:a", "aac", "-b:a", "64k"],
+        }
+    }
+}
+
+/// MIME type of an audio file, guessed from its extension (WAV by default).
+pub fn mime_for_path(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("opus") | Some("ogg") => "audio/ogg",
+        Some("flac") => "audio/flac",
+        Some("mp3") => "audio/mpeg",
+        Some("m4a") | Some("aac") => "audio/mp4",
+        _ => "audio/wav",
+    }
+}
+
+/// Transcode `input` into `output` using ffmpeg. `output` is overwritten.
+pub async fn transcode(input: &Path, output: &Path, format: AudioFormat) -> Result<(), String> {
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    }
+
+    let result = tokio::process::Command::new("ffmpeg")
+        .args(["-y", "-hide_banner", "-loglevel", "error", "-i"])
+        .arg(input)
+        .args(format.codec_args())
+        .arg(output)
+        .output()
+        .await
+        .map_err(|e| format!("ffmpeg introuvable ou impossible a lancer: {}", e))?;
+
+    if !result.status.success() {
+        let _ = std::fs::remove_file(output);
+        return Err(format!(
+            "Erreur conversion audio: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_name() {
+        assert_eq!(AudioFormat::from_name("opus"), Some(AudioFormat::Opus));
+        assert_eq!(AudioFormat::from_name("FLAC"), Some(AudioFormat::Flac));
+        assert_eq!(AudioFormat::from_name("aac"), Some(AudioFormat::M4a));
+        assert_eq!(AudioFormat::from_name("ogg"), None);
+        assert_eq!(AudioFormat::M4a.extension(), "m4a");
+    }
+
+    #[test]
+    fn test_mime_for_path() {
+        assert_eq!(mime_for_path(Path::new("/tmp/a.wav")), "audio/wav");
+        assert_eq!(mime_for_path(Path::new("/tmp/a.FLAC")), "audio/flac");
+        assert_eq!(mime_for_path(Path::new("/tmp/a.opus")), "audio/ogg");
+        assert_eq!(mime_for_path(Path::new("/tmp/noext")), "audio/wav");
+    }
+}
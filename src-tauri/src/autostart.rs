@@ -0,0 +1,37 @@
+//! Launch at login, through the autostart plugin. The `launch_at_login`
+//! setting registers the app with the OS; `start_hidden` keeps the main
+//! window in the tray when the app was started that way.
+
+use tauri::Manager;
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+
+use crate::app_state::AppState;
+
+pub const LAUNCH_AT_LOGIN_SETTING: &str = "launch_at_login";
+pub const START_HIDDEN_SETTING: &str = "start_hidden";
+
+/// Argument passed by the OS launcher, to tell a login start from a manual one.
+const AUTOSTART_ARG: &str = "--autostart";
+
+pub fn plugin() -> tauri::plugin::TauriPlugin<tauri::Wry> {
+    tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, Some(vec![AUTOSTART_ARG]))
+}
+
+/// Registers or unregisters the app as a login item.
+pub fn apply(app: &tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let launcher = app.autolaunch();
+    let result = if enabled { launcher.enable() } else { launcher.disable() };
+    result.map_err(|e| format!("Impossible de modifier le lancement au demarrage: {}", e))
+}
+
+/// Whether this start was made at login with `start_hidden` enabled.
+pub fn should_start_hidden(app: &tauri::AppHandle) -> bool {
+    if !std::env::args().any(|arg| arg == AUTOSTART_ARG) {
+        return false;
+    }
+    app.state::<AppState>()
+        .db
+        .lock()
+        .map(|db| db.get_setting(START_HIDDEN_SETTING).ok().flatten().as_deref() == Some("true"))
+        .unwrap_or(false)
+}
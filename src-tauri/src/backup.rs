@@ -0,0 +1,402 @@
+//! End-to-end encrypted backups of the database and the recordings, sent to
+//! the upload target or the S3 bucket as set by `backup_target` ("upload"
+//! or "s3"), every `backup_interval_days` when set.
+//!
+//! Archives are encrypted on this device with XChaCha20-Poly1305 and a key
+//! kept next to the database (`backup.key`) but never inside a backup.
+//! Restoring on another device takes a copy of that key.
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+
+pub const BACKUP_TARGET_SETTING: &str = "backup_target";
+pub const BACKUP_INTERVAL_SETTING: &str = "backup_interval_days";
+pub const LAST_BACKUP_SETTING: &str = "last_backup_at";
+
+pub(crate) const KEY_FILE: &str = "backup.key";
+const DB_ENTRY: &str = "poptranscribe.db";
+const MANIFEST_ENTRY: &str = "backup.json";
+
+const MAGIC: &[u8; 8] = b"PTBACKU1";
+/// Plaintext bytes per encrypted chunk. Each chunk is authenticated on its
+/// own, so that large recordings are never held in memory at once.
+const CHUNK_SIZE: usize = 1 << 20;
+const TAG_SIZE: usize = 16;
+const NONCE_PREFIX_SIZE: usize = 16;
+
+pub type BackupKey = [u8; 32];
+
+/// Describes the archive, read back on restore.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    created_at: String,
+    /// Data directory of the saved device, to relocate file paths.
+    data_dir: String,
+}
+
+/// The key of this device, created on first use.
+pub fn load_or_create_key(data_dir: &Path) -> Result<BackupKey, String> {
+    if has_key(data_dir) {
+        return load_key(data_dir);
+    }
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    save_key(data_dir, &key)?;
+    Ok(key)
+}
+
+/// The key of this device, an error when it has none yet.
+pub fn load_key(data_dir: &Path) -> Result<BackupKey, String> {
+    let path = data_dir.join(KEY_FILE);
+    if !path.exists() {
+        return Err("Aucune cle de sauvegarde sur cet appareil: saisissez celle de l'appareil sauvegarde".to_string());
+    }
+    let encoded = std::fs::read_to_string(&path).map_err(|e| format!("Lecture de la cle impossible: {}", e))?;
+    decode_key(&encoded)
+}
+
+pub fn has_key(data_dir: &Path) -> bool {
+    data_dir.join(KEY_FILE).exists()
+}
+
+pub fn save_key(data_dir: &Path, key: &BackupKey) -> Result<(), String> {
+    std::fs::write(data_dir.join(KEY_FILE), encode_key(key))
+        .map_err(|e| format!("Enregistrement de la cle impossible: {}", e))
+}
+
+pub fn encode_key(key: &BackupKey) -> String {
+    base64::engine::general_purpose::STANDARD.encode(key)
+}
+
+pub fn decode_key(encoded: &str) -> Result<BackupKey, String> {
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| BackupKey::try_from(bytes).ok())
+        .ok_or_else(|| "Cle de sauvegarde invalide".to_string())
+}
+
+/// Whether a scheduled backup is due.
+pub fn is_due(db: &Database) -> bool {
+    let setting = |key: &str| db.get_setting(key).ok().flatten();
+    let Some(days) = setting(BACKUP_INTERVAL_SETTING)
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|&d| d > 0)
+    else {
+        return false;
+    };
+    setting(LAST_BACKUP_SETTING)
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok())
+        .map(|last| chrono::Utc::now().signed_duration_since(last).num_days() >= days)
+        .unwrap_or(true)
+}
+
+/// Fills `buf` from `reader` up to its length; shorter only at the end.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_SIZE], counter: u64) -> XNonce {
+    let mut nonce = [0u8; 24];
+    nonce[..NONCE_PREFIX_SIZE].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_SIZE..].copy_from_slice(&counter.to_be_bytes());
+    *XNonce::from_slice(&nonce)
+}
+
+/// Encrypts `reader` into `writer`: a header, then length-prefixed chunks.
+/// The last chunk, shorter than the others (possibly empty), is marked in
+/// its associated data so that a truncated file does not decrypt.
+pub fn encrypt(key: &BackupKey, mut reader: impl Read, mut writer: impl Write) -> Result<(), String> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let mut prefix = [0u8; NONCE_PREFIX_SIZE];
+    OsRng.fill_bytes(&mut prefix);
+    let io_err = |e: std::io::Error| format!("Erreur chiffrement: {}", e);
+    writer.write_all(MAGIC).map_err(io_err)?;
+    writer.write_all(&prefix).map_err(io_err)?;
+
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    let mut counter = 0u64;
+    loop {
+        let len = read_full(&mut reader, &mut buf).map_err(io_err)?;
+        let last = len < CHUNK_SIZE;
+        let sealed = cipher
+            .encrypt(
+                &chunk_nonce(&prefix, counter),
+                Payload {
+                    msg: &buf[..len],
+                    aad: &[last as u8],
+                },
+            )
+            .map_err(|_| "Erreur chiffrement".to_string())?;
+        writer.write_all(&(sealed.len() as u32).to_be_bytes()).map_err(io_err)?;
+        writer.write_all(&sealed).map_err(io_err)?;
+        if last {
+            break;
+        }
+        counter += 1;
+    }
+    writer.flush().map_err(io_err)
+}
+
+/// Reverses `encrypt`. Fails on a wrong key, or a changed or truncated file.
+pub fn decrypt(key: &BackupKey, mut reader: impl Read, mut writer: impl Write) -> Result<(), String> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let io_err = |e: std::io::Error| format!("Erreur dechiffrement: {}", e);
+    let mut header = [0u8; 8 + NONCE_PREFIX_SIZE];
+    if read_full(&mut reader, &mut header).map_err(io_err)? < header.len() || &header[..8] != MAGIC {
+        return Err("Ce fichier n'est pas une sauvegarde PopTranscribe".to_string());
+    }
+    let mut prefix = [0u8; NONCE_PREFIX_SIZE];
+    prefix.copy_from_slice(&header[8..]);
+
+    let mut counter = 0u64;
+    loop {
+        let mut len = [0u8; 4];
+        if read_full(&mut reader, &mut len).map_err(io_err)? < len.len() {
+            return Err("Sauvegarde incomplete".to_string());
+        }
+        let len = u32::from_be_bytes(len) as usize;
+        if !(TAG_SIZE..=CHUNK_SIZE + TAG_SIZE).contains(&len) {
+            return Err("Sauvegarde corrompue".to_string());
+        }
+        let mut sealed = vec![0u8; len];
+        if read_full(&mut reader, &mut sealed).map_err(io_err)? < len {
+            return Err("Sauvegarde incomplete".to_string());
+        }
+        let last = len - TAG_SIZE < CHUNK_SIZE;
+        let plain = cipher
+            .decrypt(
+                &chunk_nonce(&prefix, counter),
+                Payload {
+                    msg: &sealed,
+                    aad: &[last as u8],
+                },
+            )
+            .map_err(|_| "Dechiffrement impossible: mauvaise cle ou sauvegarde corrompue".to_string())?;
+        writer.write_all(&plain).map_err(io_err)?;
+        if last {
+            break;
+        }
+        counter += 1;
+    }
+    if reader.read(&mut [0u8; 1]).map_err(io_err)? != 0 {
+        return Err("Sauvegarde corrompue".to_string());
+    }
+    writer.flush().map_err(io_err)
+}
+
+/// Adds the files under `dir` to the archive, as `<prefix>/<relative path>`.
+fn zip_tree<W: Write + std::io::Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    dir: &Path,
+    prefix: &str,
+) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Erreur lecture dossier: {}", e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let name = format!(
+            "{}/{}",
+            prefix,
+            path.file_name().and_then(|n| n.to_str()).ok_or("Nom de fichier invalide")?
+        );
+        if path.is_dir() {
+            zip_tree(zip, &path, &name)?;
+        } else if path.is_file() {
+            // Recordings are compressed already
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored)
+                .large_file(true);
+            zip.start_file(name, options)
+                .map_err(|e| format!("Erreur archive zip: {}", e))?;
+            let mut src = std::fs::File::open(&path).map_err(|e| format!("Erreur lecture fichier: {}", e))?;
+            std::io::copy(&mut src, zip).map_err(|e| format!("Erreur archive zip: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the encrypted backup of the database and the data directory to
+/// `output`. The database is snapshotted first, so that it can be used
+/// meanwhile.
+pub fn create(db: &std::sync::Mutex<Database>, data_dir: &Path, key: &BackupKey, output: &Path) -> Result<(), String> {
+    let staging = output.with_extension("staging");
+    std::fs::create_dir_all(&staging).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    let zip_path = staging.join("backup.zip");
+    let result = (|| {
+        let snapshot = staging.join(DB_ENTRY);
+        db.lock()
+            .map_err(|e| e.to_string())?
+            .copy_to(&snapshot)
+            .map_err(|e| format!("Copie de la base impossible: {}", e))?;
+
+        let file = std::fs::File::create(&zip_path).map_err(|e| format!("Erreur creation archive: {}", e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default().large_file(true);
+        let manifest = Manifest {
+            created_at: chrono::Utc::now().to_rfc3339(),
+            data_dir: data_dir.to_string_lossy().into_owned(),
+        };
+        zip.start_file(MANIFEST_ENTRY, options)
+            .map_err(|e| format!("Erreur archive zip: {}", e))?;
+        zip.write_all(&serde_json::to_vec(&manifest).map_err(|e| e.to_string())?)
+            .map_err(|e| format!("Erreur archive zip: {}", e))?;
+        zip.start_file(DB_ENTRY, options)
+            .map_err(|e| format!("Erreur archive zip: {}", e))?;
+        let mut src = std::fs::File::open(&snapshot).map_err(|e| format!("Erreur lecture fichier: {}", e))?;
+        std::io::copy(&mut src, &mut zip).map_err(|e| format!("Erreur archive zip: {}", e))?;
+        for sub in crate::commands::DATA_SUBDIRS {
+            let dir = data_dir.join(sub);
+            if dir.is_dir() {
+                zip_tree(&mut zip, &dir, sub)?;
+            }
+        }
+        zip.finish().map_err(|e| format!("Erreur archive zip: {}", e))?;
+
+        let plain = std::fs::File::open(&zip_path).map_err(|e| format!("Erreur lecture archive: {}", e))?;
+        let sealed = std::fs::File::create(output).map_err(|e| format!("Erreur creation sauvegarde: {}", e))?;
+        encrypt(key, std::io::BufReader::new(plain), std::io::BufWriter::new(sealed))
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    if result.is_err() {
+        let _ = std::fs::remove_file(output);
+    }
+    result
+}
+
+/// Decrypts and extracts `backup` into `data_dir`, replacing the database
+/// and adding the saved files (existing ones with the same name are
+/// overwritten). The current database is kept as `poptranscribe.db.bak`.
+/// The database in `db` is reopened on the restored file.
+pub fn restore(db: &mut Database, backup: &Path, key: &BackupKey, data_dir: &Path) -> Result<(), String> {
+    let staging = data_dir.join("restore-staging");
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    let result = (|| {
+        let zip_path = staging.join("backup.zip");
+        let sealed = std::fs::File::open(backup).map_err(|e| format!("Sauvegarde introuvable: {}", e))?;
+        let plain = std::fs::File::create(&zip_path).map_err(|e| format!("Erreur creation archive: {}", e))?;
+        decrypt(key, std::io::BufReader::new(sealed), std::io::BufWriter::new(plain))?;
+
+        let extracted = staging.join("files");
+        let file = std::fs::File::open(&zip_path).map_err(|e| format!("Erreur lecture archive: {}", e))?;
+        zip::ZipArchive::new(file)
+            .and_then(|mut archive| archive.extract(&extracted))
+            .map_err(|e| format!("Archive de sauvegarde invalide: {}", e))?;
+        let manifest: Manifest = std::fs::read(extracted.join(MANIFEST_ENTRY))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or("Archive de sauvegarde invalide")?;
+
+        // Opening migrates it, and checks that it is a database
+        let restored_path = extracted.join(DB_ENTRY);
+        Database::new(&restored_path)
+            .and_then(|restored| {
+                restored.relocate_files(&manifest.data_dir, &data_dir.to_string_lossy())?;
+                Ok(())
+            })
+            .map_err(|e| format!("Base de la sauvegarde illisible: {}", e))?;
+
+        for sub in crate::commands::DATA_SUBDIRS {
+            let from = extracted.join(sub);
+            if from.is_dir() {
+                move_tree(&from, &data_dir.join(sub)).map_err(|e| format!("Restauration de {} impossible: {}", sub, e))?;
+            }
+        }
+
+        let db_path = data_dir.join(DB_ENTRY);
+        // Closes the current file before it is replaced
+        *db = Database::new_in_memory().map_err(|e| e.to_string())?;
+        let _ = std::fs::copy(&db_path, data_dir.join("poptranscribe.db.bak"));
+        for name in ["poptranscribe.db-wal", "poptranscribe.db-shm"] {
+            std::fs::remove_file(data_dir.join(name)).ok();
+        }
+        let copied = std::fs::copy(&restored_path, &db_path).map(|_| ());
+        *db = Database::new(&db_path).map_err(|e| format!("Ouverture de la base impossible: {}", e))?;
+        copied.map_err(|e| format!("Remplacement de la base impossible: {}", e))
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+/// Moves the files under `from` into `to`, replacing those with the same
+/// path.
+fn move_tree(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let dest: PathBuf = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            move_tree(&entry.path(), &dest)?;
+        } else if std::fs::rename(entry.path(), &dest).is_err() {
+            std::fs::copy(entry.path(), &dest)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = [7u8; 32];
+        // Two full chunks and a partial one, then an exact multiple
+        for size in [CHUNK_SIZE * 2 + 123, CHUNK_SIZE] {
+            let data: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+            let mut sealed = Vec::new();
+            encrypt(&key, data.as_slice(), &mut sealed).unwrap();
+            let mut plain = Vec::new();
+            decrypt(&key, sealed.as_slice(), &mut plain).unwrap();
+            assert_eq!(plain, data);
+        }
+    }
+
+    #[test]
+    fn test_decrypt_rejects_tampering() {
+        let key = [7u8; 32];
+        let data = vec![1u8; CHUNK_SIZE + 10];
+        let mut sealed = Vec::new();
+        encrypt(&key, data.as_slice(), &mut sealed).unwrap();
+
+        assert!(decrypt(&[8u8; 32], sealed.as_slice(), Vec::new()).is_err());
+        let mut changed = sealed.clone();
+        changed[40] ^= 1;
+        assert!(decrypt(&key, changed.as_slice(), Vec::new()).is_err());
+        // Cut after the first chunk
+        let truncated = &sealed[..8 + NONCE_PREFIX_SIZE + 4 + CHUNK_SIZE + TAG_SIZE];
+        assert_eq!(decrypt(&key, truncated, Vec::new()).unwrap_err(), "Sauvegarde incomplete");
+    }
+
+    #[test]
+    fn test_key_encoding() {
+        let key = [42u8; 32];
+        assert_eq!(decode_key(&format!("{}\n", encode_key(&key))).unwrap(), key);
+        assert!(decode_key("abc").is_err());
+    }
+
+    #[test]
+    fn test_is_due() {
+        let db = Database::new_in_memory().unwrap();
+        assert!(!is_due(&db));
+        db.set_setting(BACKUP_INTERVAL_SETTING, "7").unwrap();
+        assert!(is_due(&db));
+        db.set_setting(LAST_BACKUP_SETTING, &chrono::Utc::now().to_rfc3339()).unwrap();
+        assert!(!is_due(&db));
+    }
+}
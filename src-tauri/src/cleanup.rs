@@ -0,0 +1,115 @@
+//! Automatic cleanup of incoming segment text, before it is stored: the
+//! user replacement dictionary, then the profanity filter.
+
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::db::{Database, Replacement};
+use crate::profanity::ProfanityFilter;
+
+#[derive(Clone, Default)]
+pub struct SegmentCleanup {
+    /// Matches every pattern, longest first.
+    pattern: Option<Regex>,
+    /// Lowercased pattern -> replacement.
+    replacements: HashMap<String, String>,
+    profanity: Option<ProfanityFilter>,
+}
+
+impl SegmentCleanup {
+    pub fn new(replacements: &[Replacement], profanity: Option<ProfanityFilter>) -> Self {
+        let mut entries: Vec<&Replacement> = replacements.iter().filter(|r| !r.pattern.is_empty()).collect();
+        entries.sort_by_key(|r| std::cmp::Reverse(r.pattern.chars().count()));
+        // Whole words, except at pattern ends that are not word characters
+        // ("C++")
+        let alternatives: Vec<String> = entries
+            .iter()
+            .map(|r| {
+                let starts_word = r.pattern.chars().next().is_some_and(|c| c.is_alphanumeric() || c == '_');
+                let ends_word = r.pattern.chars().last().is_some_and(|c| c.is_alphanumeric() || c == '_');
+                format!(
+                    "{}{}{}",
+                    if starts_word { r"\b" } else { "" },
+                    regex::escape(&r.pattern),
+                    if ends_word { r"\b" } else { "" }
+                )
+            })
+            .collect();
+        let pattern = (!alternatives.is_empty())
+            .then(|| Regex::new(&format!("(?i)(?:{})", alternatives.join("|"))).ok())
+            .flatten();
+        Self {
+            pattern,
+            replacements: entries
+                .iter()
+                .map(|r| (r.pattern.to_lowercase(), r.replacement.clone()))
+                .collect(),
+            profanity,
+        }
+    }
+
+    /// Cleanup configured in the database.
+    pub fn from_db(db: &Database) -> Self {
+        let replacements = db.list_replacements().unwrap_or_default();
+        Self::new(&replacements, ProfanityFilter::from_settings(db))
+    }
+
+    /// Applies the replacement dictionary only.
+    pub fn replace(&self, text: &str) -> String {
+        match self.pattern {
+            Some(ref pattern) => pattern
+                .replace_all(text, |caps: &regex::Captures| {
+                    let found = &caps[0];
+                    self.replacements
+                        .get(&found.to_lowercase())
+                        .cloned()
+                        .unwrap_or_else(|| found.to_string())
+                })
+                .into_owned(),
+            None => text.to_string(),
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let text = self.replace(text);
+        match self.profanity {
+            Some(filter) => filter.apply(&text),
+            None => text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replacement(pattern: &str, replacement: &str) -> Replacement {
+        Replacement {
+            id: 0,
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_segment_cleanup() {
+        let cleanup = SegmentCleanup::new(
+            &[
+                replacement("K8S", "Kubernetes"),
+                replacement("pop scribe", "PopTranscribe"),
+                replacement("pop", "Pop"),
+                replacement("C++", "C++ 20"),
+            ],
+            Some(ProfanityFilter::Mask),
+        );
+        assert_eq!(
+            cleanup.apply("Pop Scribe tourne sur k8s, merde, en c++ !"),
+            "PopTranscribe tourne sur Kubernetes, m****, en C++ 20 !"
+        );
+        // Whole words only
+        assert_eq!(cleanup.replace("popular k8ss"), "popular k8ss");
+        assert_eq!(SegmentCleanup::default().apply("k8s"), "k8s");
+    }
+}
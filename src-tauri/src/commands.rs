@@ -1,10 +1,16 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use cpal::traits::{DeviceTrait, HostTrait};
-use tauri::{Emitter, State};
-use crate::app_state::{ActiveSession, AppState, SendCapturer};
-use crate::audio::capture::{AudioCapturer, CaptureMode};
-use crate::db::{Session, Segment};
-use crate::mistral::chat::Summary;
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use crate::app_state::{data_dir, ActiveSession, AppState, SendCapturer};
+use crate::audio::capture::{AudioCapturer, CaptureMode, DevicePreferences};
+use crate::db::{
+    Attachment, EmailTemplate, NewSegment, Operation, Replacement, Session, Segment, SegmentRevision, SpeakerProfile,
+    Statistics, TopicCount, TranscriptVersion, TranslatedSegment, VersionSegment,
+};
+use crate::export::SessionExport;
+use crate::mistral::chat::{ChatParams, ChatTask, Digest, Summary};
 
 /// Detail view for a session, including its segments and optional summary.
 #[derive(Debug, Clone, serde::Serialize)]
@@ -13,13 +19,125 @@ pub struct SessionDetail {
     pub session: Session,
     pub segments: Vec<Segment>,
     pub summary: Option<Summary>,
+    pub attachments: Vec<Attachment>,
+    /// Final realtime transcript, shown when batch processing failed.
+    pub live_transcript: Option<String>,
 }
 
 // ── Session management ───────────────────────────────────────────────
 
+/// Default title template, matching the historical "Reunion dd/mm HH:MM".
+const DEFAULT_TITLE_TEMPLATE: &str = "Reunion {date} {time}";
+
+/// Expands the placeholders of a session title template:
+/// `{date}` (dd/mm), `{time}` (HH:MM), `{year}`, `{mode}` and `{event}`
+/// (calendar event name, empty when unknown).
+fn render_title_template(
+    template: &str,
+    now: &chrono::DateTime<chrono::Local>,
+    mode: &str,
+    event_name: Option<&str>,
+) -> String {
+    let mode_label = match mode {
+        "visio" => "Visio",
+        _ => "Presentiel",
+    };
+    let title = template
+        .replace("{date}", &now.format("%d/%m").to_string())
+        .replace("{time}", &now.format("%H:%M").to_string())
+        .replace("{year}", &now.format("%Y").to_string())
+        .replace("{mode}", mode_label)
+        .replace("{event}", event_name.unwrap_or(""));
+    // Collapse the double spaces left by an empty placeholder
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        format!("Reunion {}", now.format("%d/%m %H:%M"))
+    } else {
+        title
+    }
+}
+
+/// Default recording time limit, protecting against sessions left running.
+const DEFAULT_MAX_DURATION_MINUTES: u64 = 180;
+
+/// How long before the time limit the `recording-limit-warning` event fires.
+const LIMIT_WARNING_SECS: u64 = 5 * 60;
+
+/// Below this amount of free space a recording is refused (or stopped).
+const MIN_FREE_SPACE_BYTES: u64 = 200 * 1024 * 1024;
+
+/// How often free disk space is re-checked while recording.
+const DISK_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Interval between checkpoints of the in-progress realtime text.
+const LIVE_CHECKPOINT_SECS: u64 = 10;
+
+/// How long a live segment waits for its AI punctuation before it is
+/// saved with the rule-based one.
+const PUNCTUATION_TIMEOUT_SECS: u64 = 5;
+
+/// Setting holding the interval, in minutes, between two `live-summary`
+/// events; unset or "0" disables the live summary.
+const LIVE_SUMMARY_MINUTES_SETTING: &str = "live_summary_minutes";
+
+/// Setting enabling the detection of action items while recording
+/// ("true"), emitted as `action-item-detected` events.
+const LIVE_ACTION_ITEMS_SETTING: &str = "live_action_items";
+
+/// Interval between two action item detection passes.
+const ACTION_ITEM_INTERVAL_SECS: u64 = 60;
+
+/// New live text, in characters, below which a detection pass waits for more.
+const ACTION_ITEM_MIN_PASSAGE_CHARS: usize = 200;
+
+/// Default duration of near-zero input before `mic-silent` fires.
+const DEFAULT_MIC_SILENCE_SECS: u64 = 30;
+
+/// Recording durations, in minutes, announced by `recording-milestone`
+/// unless the `duration_milestones` setting lists others ("" disables them).
+const DEFAULT_DURATION_MILESTONES: &str = "30,60";
+
+/// How long before a silence auto-stop the `silence-stop-warning` event fires.
+const SILENCE_STOP_WARNING_SECS: u64 = 60;
+
+/// Free space available on the volume holding the data directory.
+fn available_space() -> Result<u64, String> {
+    let dir = data_dir();
+    std::fs::create_dir_all(&dir).ok();
+    fs2::available_space(&dir).map_err(|e| format!("Impossible de lire l'espace disque: {}", e))
+}
+
+/// Normalizes an ISO 639-1 language code ("fr", "en"...).
+fn parse_language_code(language: &str) -> Result<String, String> {
+    let code = language.trim().to_lowercase();
+    if code.len() != 2 || !code.chars().all(|c| c.is_ascii_lowercase()) {
+        return Err(format!("Code de langue invalide: {} (ex: fr, en)", code));
+    }
+    Ok(code)
+}
+
+/// Emits `session-auto-stopped` and finishes the session in the background.
+pub(crate) fn spawn_auto_stop(app: &tauri::AppHandle, session_id: &str, reason: &str) {
+    eprintln!("[session] Auto-stopping {} ({})", session_id, reason);
+    let _ = app.emit(
+        "session-auto-stopped",
+        serde_json::json!({ "session_id": session_id, "reason": reason }),
+    );
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = finish_session(app.clone(), session_id).await {
+            let _ = app.emit("session-error", e);
+        }
+    });
+}
+
 #[tauri::command]
 pub async fn start_session(
     mode: String,
+    event_name: Option<String>,
+    language: Option<String>,
+    gain: Option<f32>,
     app: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<String, String> {
@@ -31,11 +149,43 @@ pub async fn start_session(
         }
     }
 
+    // Optional language lock, as an ISO 639-1 code ("fr", "en"...)
+    let language = language
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| parse_language_code(&l))
+        .transpose()?;
+
+    crate::permissions::preflight(&app, &mode).await?;
+
+    // Pre-flight disk space check: refuse below the hard minimum, warn if
+    // the estimated WAV size for the whole time limit does not fit
+    let available = available_space()?;
+    if available < MIN_FREE_SPACE_BYTES {
+        return Err(format!(
+            "Espace disque insuffisant ({} Mo libres). Liberez de l'espace avant d'enregistrer.",
+            available / (1024 * 1024)
+        ));
+    }
+
     // Create session in DB
     let session_id = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
-        let title = format!("Reunion {}", chrono::Local::now().format("%d/%m %H:%M"));
-        db.create_session(&title, &mode).map_err(|e| e.to_string())?
+        let template = db
+            .get_setting("title_template")
+            .ok()
+            .flatten()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_TITLE_TEMPLATE.to_string());
+        let title = render_title_template(
+            &template,
+            &chrono::Local::now(),
+            &mode,
+            event_name.as_deref(),
+        );
+        let id = db.create_session(&title, &mode).map_err(|e| e.to_string())?;
+        db.set_session_language(&id, language.as_deref())
+            .map_err(|e| e.to_string())?;
+        id
     };
 
     // Check API key
@@ -43,14 +193,22 @@ pub async fn start_session(
         let key = state.api_key.lock().map_err(|e| e.to_string())?;
         key.clone()
     };
-    if api_key.is_empty() {
+    let demo = uses_demo_provider(&state.db);
+    if api_key.is_empty() && !demo {
         return Err("Cle API Mistral non configuree. Allez dans Parametres.".to_string());
     }
 
-    // Read configured input device from settings
-    let device_name = {
+    // Read configured input devices and gain from settings; `gain` overrides
+    // the configured gain for this session only
+    let (devices, gain) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
-        db.get_setting("input_device").ok().flatten()
+        let devices = input_device_preferences(&db);
+        let configured_gain = db
+            .get_setting("input_gain")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<f32>().ok());
+        (devices, gain.or(configured_gain).unwrap_or(1.0))
     };
 
     // Start audio capture
@@ -59,31 +217,311 @@ pub async fn start_session(
         _ => CaptureMode::InPerson,
     };
 
-    let mut capturer = AudioCapturer::new(capture_mode, device_name);
+    // "audio_quality": "standard" keeps the 16kHz mono recording only,
+    // "both" adds a copy at the device's native rate/channels, "native"
+    // keeps only that copy. The API is fed at 16kHz in every case.
+    // "loopback_app": executable whose audio alone is captured in visio
+    // mode (Windows), instead of everything played on the default output
+    let (audio_quality, loopback_app) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let quality = db.get_setting("audio_quality").ok().flatten().unwrap_or_default();
+        let app = db
+            .get_setting("loopback_app")
+            .ok()
+            .flatten()
+            .filter(|v| !v.trim().is_empty());
+        (quality, app)
+    };
+
+    let mut capturer = AudioCapturer::new(capture_mode, devices);
+    capturer.set_gain(gain);
+    if mode == "visio" {
+        capturer.set_loopback_app(loopback_app);
+    }
+    capturer.set_native_copy(matches!(audio_quality.as_str(), "both" | "native"));
     let receiver = capturer.start().map_err(|e| e.to_string())?;
     let actual_sample_rate = capturer.actual_sample_rate;
 
-    let audio_samples = Arc::new(std::sync::Mutex::new(Vec::<i16>::new()));
+    let native_writer = capturer.take_native_stream().map(|native| {
+        let audio_dir = data_dir().join("audio");
+        std::fs::create_dir_all(&audio_dir).ok();
+        let path = if audio_quality == "native" {
+            audio_dir.join(format!("{}.wav", session_id))
+        } else {
+            audio_dir.join(format!("{}_native.wav", session_id))
+        };
+        std::thread::spawn(move || {
+            crate::audio::store::write_wav_stream(&path, native.receiver, native.sample_rate, native.channels)
+                .map(|_| path)
+                .map_err(|e| e.to_string())
+        })
+    });
+    // Without a native stream (Visio mode) the 16kHz recording is kept
+    let native_only = audio_quality == "native" && native_writer.is_some();
+
+    // "on_sleep" = "ignore" keeps recording across a system sleep; by default
+    // the session is stopped and saved when the machine wakes up, since the
+    // audio stream does not survive a suspend reliably
+    let stop_on_sleep = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting("on_sleep").ok().flatten().as_deref() != Some("ignore")
+    };
+
+    // Durations to announce while recording, in seconds, ascending
+    let milestones: Vec<u64> = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let value = db
+            .get_setting("duration_milestones")
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| DEFAULT_DURATION_MILESTONES.to_string());
+        let mut minutes: Vec<u64> = value
+            .split(',')
+            .filter_map(|m| m.trim().parse::<u64>().ok())
+            .filter(|&m| m > 0)
+            .collect();
+        minutes.sort_unstable();
+        minutes.dedup();
+        minutes.into_iter().map(|m| m * 60).collect()
+    };
+
+    // Recording time limit ("0" disables it)
+    let max_duration_secs = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting("max_duration_minutes")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_MAX_DURATION_MINUTES)
+            * 60
+    };
+
+    // Audio duration per realtime message, in milliseconds, and whether to
+    // try raw binary frames instead of base64 JSON
+    let (realtime_batch_ms, realtime_binary) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let batch_ms = db
+            .get_setting("realtime_batch_ms")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(crate::mistral::realtime::DEFAULT_BATCH_MS);
+        let binary = db.get_setting("realtime_binary_audio").ok().flatten().as_deref() == Some("true");
+        (batch_ms, binary)
+    };
+
+    // Silence warning delay ("0" disables it), and minutes of quiet room
+    // after which the recording stops itself (disabled by default)
+    let (mic_silence_secs, silence_stop_secs) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let setting = |key: &str| {
+            db.get_setting(key)
+                .ok()
+                .flatten()
+                .and_then(|v| v.parse::<u64>().ok())
+        };
+        (
+            setting("mic_silence_seconds").unwrap_or(DEFAULT_MIC_SILENCE_SECS),
+            setting("silence_stop_minutes").unwrap_or(0) * 60,
+        )
+    };
+
+    // 16-bit mono WAV; one hour is assumed when there is no time limit
+    let estimated_bytes = actual_sample_rate as u64
+        * 2
+        * if max_duration_secs > 0 { max_duration_secs } else { 3600 };
+    if available < estimated_bytes + MIN_FREE_SPACE_BYTES {
+        let _ = app.emit(
+            "low-disk-space",
+            serde_json::json!({
+                "available_bytes": available,
+                "estimated_bytes": estimated_bytes,
+            }),
+        );
+    }
+
+    // Audio kept in memory, in minutes ("memory_buffer_minutes"), before
+    // the recording continues in a file on disk
+    let memory_limit_secs = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting("memory_buffer_minutes")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|&m| m > 0)
+            .map(|m| m * 60)
+            .unwrap_or(crate::audio::buffer::DEFAULT_MEMORY_LIMIT_SECS)
+    };
+    let audio_samples = {
+        let audio_dir = data_dir().join("audio");
+        std::fs::create_dir_all(&audio_dir).ok();
+        Arc::new(std::sync::Mutex::new(crate::audio::buffer::SampleBuffer::new(
+            crate::audio::buffer::spill_path(&audio_dir, &session_id),
+            (memory_limit_secs * actual_sample_rate as u64) as usize,
+        )))
+    };
     let (stop_tx, stop_rx) = tokio::sync::watch::channel(false);
 
+    // Flush the recording to a partial WAV every "autosave_seconds" ("0"
+    // disables it), so that a crash does not lose the whole meeting
+    let autosave_secs = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting("autosave_seconds")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(crate::audio::autosave::DEFAULT_AUTOSAVE_SECS)
+    };
+    let autosave = if autosave_secs > 0 {
+        let audio_dir = data_dir().join("audio");
+        std::fs::create_dir_all(&audio_dir).ok();
+        match crate::audio::autosave::Autosave::spawn(
+            crate::audio::autosave::partial_path(&audio_dir, &session_id),
+            Arc::clone(&audio_samples),
+            actual_sample_rate,
+            std::time::Duration::from_secs(autosave_secs),
+        ) {
+            Ok(autosave) => Some(autosave),
+            Err(e) => {
+                eprintln!("[session] Autosave unavailable: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Batch transcription of completed chunks while recording, every
+    // "incremental_batch_minutes" minutes of audio
+    let incremental = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let chunk_minutes = db
+            .get_setting(crate::mistral::incremental::CHUNK_MINUTES_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+        let batch = (chunk_minutes > 0 && !demo).then(|| {
+            crate::mistral::incremental::IncrementalBatch::new(
+                &api_key,
+                language.as_deref(),
+                actual_sample_rate,
+                chunk_minutes * 60,
+                &data_dir().join("audio"),
+                &session_id,
+            )
+        });
+        Arc::new(std::sync::Mutex::new(batch))
+    };
+
+    // Near-live speakers, re-diarizing the last "rolling_diarization_minutes"
+    // minutes of audio every few minutes
+    let rolling_minutes = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting(crate::mistral::rolling::WINDOW_MINUTES_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    if rolling_minutes > 0 && !demo {
+        let rolling = crate::mistral::rolling::RollingDiarization::new(
+            &api_key,
+            language.as_deref(),
+            actual_sample_rate,
+            rolling_minutes * 60,
+            &data_dir().join("audio"),
+            &session_id,
+        );
+        spawn_rolling_diarization(
+            app.clone(),
+            Arc::clone(&state.db),
+            session_id.clone(),
+            rolling,
+            audio_samples.clone(),
+            stop_rx.clone(),
+        );
+    }
+
+    // Brief summary of the meeting so far, every "live_summary_minutes"
+    let live_summary_minutes = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting(LIVE_SUMMARY_MINUTES_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    if live_summary_minutes > 0 {
+        spawn_live_summary(
+            app.clone(),
+            Arc::clone(&state.db),
+            session_id.clone(),
+            if demo { None } else { Some(api_key.clone()) },
+            live_summary_minutes * 60,
+            stop_rx.clone(),
+        );
+    }
+
+    // Action items handed out during the meeting, reconciled with the final
+    // summary once it is generated
+    let live_action_items = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting(LIVE_ACTION_ITEMS_SETTING).ok().flatten().as_deref() == Some("true")
+    };
+    if live_action_items && !demo {
+        spawn_action_item_detection(
+            app.clone(),
+            Arc::clone(&state.db),
+            session_id.clone(),
+            api_key.clone(),
+            stop_rx.clone(),
+        );
+    }
+
+    // Chunks of every capture of the session, the first one and those
+    // started again after a pause
+    let (chunk_sender, chunks) = tokio::sync::mpsc::channel(64);
+    forward_chunks(receiver, chunk_sender.clone());
+
+    let (cleanup, punctuation) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let punctuation =
+            crate::punctuation::PunctuationMode::from_settings(&db).map(|mode| match mode {
+                crate::punctuation::PunctuationMode::Ai if demo => {
+                    crate::punctuation::PunctuationMode::Rules
+                }
+                mode => mode,
+            });
+        (crate::cleanup::SegmentCleanup::from_db(&db), punctuation)
+    };
+
     // Clone handles for the background task
     let session_id_clone = session_id.clone();
     let audio_samples_clone = audio_samples.clone();
+    let incremental_clone = Arc::clone(&incremental);
     let app_clone = app.clone();
     let db_clone = Arc::clone(&state.db);
 
     // Background task: real-time transcription via WebSocket
     tokio::spawn(async move {
         let sample_rate = actual_sample_rate;
-        let stop_rx = stop_rx;
+        let mut stop_rx = stop_rx;
 
         // Connect to Mistral real-time WebSocket
-        let (rt_handle, mut rt_events) = match crate::mistral::realtime::connect_realtime(
-            &api_key,
-            sample_rate,
-        )
-        .await
-        {
+        let connection = if demo {
+            Ok(crate::mistral::demo::connect_realtime(sample_rate))
+        } else {
+            crate::mistral::realtime::connect_realtime(
+                &api_key,
+                sample_rate,
+                language.as_deref(),
+                realtime_batch_ms,
+                realtime_binary,
+            )
+            .await
+        };
+        let (rt_handle, mut rt_events) = match connection {
             Ok(conn) => conn,
             Err(e) => {
                 eprintln!("[session] Failed to connect realtime transcription: {}", e);
@@ -101,10 +539,101 @@ pub async fn start_session(
         let app_events = app_clone.clone();
         let sid_events = session_id_clone.clone();
         let db_events = Arc::clone(&db_clone);
+        let api_key_events = api_key.clone();
+        let mut stop_events = stop_rx.clone();
         tokio::spawn(async move {
-            while let Some(event) = rt_events.recv().await {
+            use futures_util::StreamExt;
+
+            // Language reported by the provider, used when a segment is too
+            // short to be detected on its own
+            let locked_language = language;
+            let mut current_language: Option<String> = None;
+            // Realtime text so far, checkpointed to the DB so that a crash
+            // does not lose captions that only existed in the UI
+            let mut live_text = String::new();
+            let mut live_dirty = false;
+            // Stored text goes through the replacements and the profanity
+            // filter; the deltas shown as they arrive do not
+            let filtered = |text: &str| cleanup.apply(text);
+            // Segments waiting for their AI punctuation, restored
+            // concurrently but saved in arrival order. `waiting` keeps their
+            // raw text, punctuated by the rules when the session stops first
+            let mut restoring = futures_util::stream::FuturesOrdered::new();
+            let mut waiting = std::collections::VecDeque::new();
+            let mut stopped = false;
+            let save_with_rules = |(text, start, end, language): (String, f64, f64, Option<String>)| {
+                save_live_segment(
+                    &app_events,
+                    &db_events,
+                    &sid_events,
+                    &filtered(&crate::punctuation::restore(&text)),
+                    start,
+                    end,
+                    language.as_deref(),
+                );
+            };
+            let mut checkpoint =
+                tokio::time::interval(std::time::Duration::from_secs(LIVE_CHECKPOINT_SECS));
+            loop {
+                let event = tokio::select! {
+                    event = rt_events.recv() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    restored = restoring.next(), if !restoring.is_empty() => {
+                        if let (Some(restored), Some((text, start, end, language))) =
+                            (restored, waiting.pop_front())
+                        {
+                            let text = match restored {
+                                Ok(Ok(restored)) if crate::punctuation::same_words(&restored, &text) => {
+                                    restored
+                                }
+                                _ => crate::punctuation::restore(&text),
+                            };
+                            save_live_segment(
+                                &app_events,
+                                &db_events,
+                                &sid_events,
+                                &filtered(&text),
+                                start,
+                                end,
+                                language.as_deref(),
+                            );
+                        }
+                        continue;
+                    }
+                    changed = stop_events.changed(), if !stopped => {
+                        if changed.is_err() || *stop_events.borrow() {
+                            // Nothing waits on the AI past the stop, so no
+                            // live segment lands after the batch transcript
+                            stopped = true;
+                            restoring = futures_util::stream::FuturesOrdered::new();
+                            waiting.drain(..).for_each(save_with_rules);
+                        }
+                        continue;
+                    }
+                    _ = checkpoint.tick() => {
+                        if live_dirty {
+                            if let Ok(db) = db_events.lock() {
+                                let _ = db.save_live_transcript(&sid_events, &filtered(&live_text));
+                            }
+                            live_dirty = false;
+                        }
+                        continue;
+                    }
+                };
                 match event {
+                    crate::mistral::realtime::TranscriptionEvent::Language { audio_language } => {
+                        if current_language.as_deref() != Some(audio_language.as_str()) {
+                            if let Ok(db) = db_events.lock() {
+                                let _ = db.set_session_detected_language(&sid_events, &audio_language);
+                            }
+                        }
+                        current_language = Some(audio_language);
+                    }
                     crate::mistral::realtime::TranscriptionEvent::TextDelta { text } => {
+                        live_text.push_str(&text);
+                        live_dirty = true;
                         let _ = app_events.emit("transcription-delta", &text);
                     }
                     crate::mistral::realtime::TranscriptionEvent::Segment {
@@ -112,27 +641,47 @@ pub async fn start_session(
                         start,
                         end,
                     } => {
-                        let segment_id = {
-                            if let Ok(db) = db_events.lock() {
-                                db.save_segment(
-                                    &sid_events, &text, start, end, None, false,
-                                )
-                                .ok()
-                            } else {
-                                None
-                            }
-                        };
-
-                        let segment = serde_json::json!({
-                            "id": segment_id.unwrap_or(0),
-                            "session_id": sid_events,
-                            "text": text,
-                            "start_time": start,
-                            "end_time": end,
-                            "speaker": null,
-                            "is_diarized": false
+                        let language = locked_language.clone().or_else(|| {
+                            crate::language::detect_language(&text)
+                                .map(str::to_string)
+                                .or_else(|| current_language.clone())
                         });
-                        let _ = app_events.emit("transcription-segment", segment);
+                        // Punctuation is restored on the transcribed words,
+                        // before the replacements
+                        match punctuation {
+                            Some(crate::punctuation::PunctuationMode::Ai) if !stopped => {
+                                let api_key = api_key_events.clone();
+                                let raw = text.clone();
+                                restoring.push_back(async move {
+                                    tokio::time::timeout(
+                                        std::time::Duration::from_secs(PUNCTUATION_TIMEOUT_SECS),
+                                        crate::mistral::chat::restore_punctuation(&api_key, &raw),
+                                    )
+                                    .await
+                                });
+                                waiting.push_back((text, start, end, language));
+                            }
+                            Some(_) => save_with_rules((text, start, end, language)),
+                            None => {
+                                let text = filtered(&text);
+                                save_live_segment(
+                                    &app_events,
+                                    &db_events,
+                                    &sid_events,
+                                    &text,
+                                    start,
+                                    end,
+                                    language.as_deref(),
+                                );
+                            }
+                        }
+                    }
+                    crate::mistral::realtime::TranscriptionEvent::Done { text } => {
+                        // The final text supersedes the checkpoints
+                        if let Ok(db) = db_events.lock() {
+                            let _ = db.save_live_transcript(&sid_events, &filtered(&text));
+                        }
+                        live_dirty = false;
                     }
                     crate::mistral::realtime::TranscriptionEvent::Error { message } => {
                         eprintln!("[session] Realtime error: {}", message);
@@ -142,44 +691,236 @@ pub async fn start_session(
                     _ => {}
                 }
             }
+            waiting.drain(..).for_each(save_with_rules);
+            if live_dirty {
+                if let Ok(db) = db_events.lock() {
+                    let _ = db.save_live_transcript(&sid_events, &filtered(&live_text));
+                }
+            }
         });
 
-        // Main audio loop: read chunks, accumulate for WAV, send to WebSocket
+        // Main audio loop: read chunks, accumulate for WAV, send to WebSocket.
+        // Chunks are awaited as they arrive; the time-based checks run once
+        // per second in between
+        let mut chunks = chunks;
+        let mut housekeeping = tokio::time::interval(std::time::Duration::from_secs(1));
+        housekeeping.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut limit_warned = false;
+        let mut next_milestone = 0;
+        let mut sleep_detector = crate::power::SleepDetector::new();
+        let mut auto_stopped = false;
+        let mut lagging = false;
+        let mut last_disk_check = std::time::Instant::now();
+        let mut silence = (mic_silence_secs > 0)
+            .then(|| crate::audio::silence::SilenceDetector::new(sample_rate, mic_silence_secs));
+        let mut quiet_warning = (silence_stop_secs > SILENCE_STOP_WARNING_SECS).then(|| {
+            crate::audio::silence::SilenceDetector::with_peak(
+                sample_rate,
+                silence_stop_secs - SILENCE_STOP_WARNING_SECS,
+                crate::audio::silence::QUIET_PEAK,
+            )
+        });
+        let mut quiet_stop = (silence_stop_secs > 0).then(|| {
+            crate::audio::silence::SilenceDetector::with_peak(
+                sample_rate,
+                silence_stop_secs,
+                crate::audio::silence::QUIET_PEAK,
+            )
+        });
         loop {
-            if *stop_rx.borrow() {
-                break;
-            }
+            let chunk = tokio::select! {
+                chunk = chunks.recv() => match chunk {
+                    Some(chunk) => Some(chunk),
+                    None => break,
+                },
+                changed = stop_rx.changed() => {
+                    if changed.is_err() || *stop_rx.borrow() {
+                        break;
+                    }
+                    continue;
+                }
+                _ = housekeeping.tick() => None,
+            };
+
+            let Some(chunk) = chunk else {
+                // Send the chunk just completed, if any
+                if let Ok(mut incremental) = incremental_clone.lock() {
+                    if let Some(batch) = incremental.as_mut() {
+                        batch.poll(&audio_samples_clone);
+                    }
+                }
+
+                // System sleep: save what was recorded before the suspend
+                if let Some(slept) = sleep_detector.check() {
+                    eprintln!("[session] System slept for {}s", slept.as_secs());
+                    if stop_on_sleep && !auto_stopped {
+                        auto_stopped = true;
+                        spawn_auto_stop(&app_clone, &session_id_clone, "system_sleep");
+                        let _ = app_clone
+                            .notification()
+                            .builder()
+                            .title("Enregistrement arrete")
+                            .body("La session a ete sauvegardee suite a la mise en veille")
+                            .show();
+                    }
+                }
+
+                // Time actually recorded, pauses excluded
+                let recorded_secs = audio_samples_clone
+                    .lock()
+                    .map(|s| s.len() as u64 / sample_rate.max(1) as u64)
+                    .unwrap_or(0);
+
+                // Remind that the recording is still running
+                if let Some(&milestone) = milestones.get(next_milestone) {
+                    if recorded_secs >= milestone {
+                        next_milestone += 1;
+                        let minutes = milestone / 60;
+                        let _ = app_clone.emit(
+                            "recording-milestone",
+                            serde_json::json!({ "session_id": session_id_clone, "minutes": minutes }),
+                        );
+                        let running_for = if minutes >= 60 {
+                            format!("{}h{:02}", minutes / 60, minutes % 60)
+                        } else {
+                            format!("{} min", minutes)
+                        };
+                        let _ = app_clone
+                            .notification()
+                            .builder()
+                            .title("Enregistrement en cours")
+                            .body(format!("La session enregistre depuis {}", running_for))
+                            .show();
+                    }
+                }
+
+                // Enforce the maximum recording duration
+                if max_duration_secs > 0 && !auto_stopped {
+                    if !limit_warned && recorded_secs + LIMIT_WARNING_SECS >= max_duration_secs {
+                        limit_warned = true;
+                        let _ = app_clone.emit(
+                            "recording-limit-warning",
+                            max_duration_secs.saturating_sub(recorded_secs),
+                        );
+                    }
+                    if recorded_secs >= max_duration_secs {
+                        auto_stopped = true;
+                        spawn_auto_stop(&app_clone, &session_id_clone, "max_duration");
+                    }
+                }
+
+                // Stop gracefully while the WAV can still be written
+                if !auto_stopped && last_disk_check.elapsed().as_secs() >= DISK_CHECK_INTERVAL_SECS {
+                    last_disk_check = std::time::Instant::now();
+                    if let Ok(available) = available_space() {
+                        let pending_bytes = audio_samples_clone
+                            .lock()
+                            .map(|s| s.len() as u64 * 2)
+                            .unwrap_or(0);
+                        if available < pending_bytes + MIN_FREE_SPACE_BYTES {
+                            let _ = app_clone.emit(
+                                "low-disk-space",
+                                serde_json::json!({
+                                    "available_bytes": available,
+                                    "estimated_bytes": pending_bytes,
+                                }),
+                            );
+                            auto_stopped = true;
+                            spawn_auto_stop(&app_clone, &session_id_clone, "low_disk_space");
+                        }
+                    }
+                }
+                continue;
+            };
 
-            match receiver.try_recv() {
-                Ok(chunk) => {
-                    if !chunk.is_empty() {
-                        // Audio level for UI
-                        let rms = (chunk.iter()
-                            .map(|&s| (s as f64).powi(2))
-                            .sum::<f64>()
-                            / chunk.len() as f64)
-                            .sqrt();
-                        let level = ((rms / i16::MAX as f64) * 100.0).min(100.0);
-                        let _ = app_clone.emit("audio-level", level as u32);
-
-                        // Accumulate for WAV save
-                        if let Ok(mut samples) = audio_samples_clone.lock() {
-                            samples.extend_from_slice(&chunk);
+            if !chunk.is_empty() {
+                // Audio level for UI
+                let rms = (chunk.iter()
+                    .map(|&s| (s as f64).powi(2))
+                    .sum::<f64>()
+                    / chunk.len() as f64)
+                    .sqrt();
+                let level = ((rms / i16::MAX as f64) * 100.0).min(100.0);
+                let _ = app_clone.emit("audio-level", level as u32);
+
+                // Warn about a muted or disconnected microphone
+                if let Some(detector) = silence.as_mut() {
+                    use crate::audio::silence::SilenceChange;
+                    match detector.feed(&chunk) {
+                        Some(SilenceChange::Started) => {
+                            let _ = app_clone.emit(
+                                "mic-silent",
+                                serde_json::json!({
+                                    "silent": true,
+                                    "seconds": detector.silent_secs(),
+                                }),
+                            );
                         }
+                        Some(SilenceChange::Ended) => {
+                            let _ = app_clone.emit(
+                                "mic-silent",
+                                serde_json::json!({ "silent": false, "seconds": 0 }),
+                            );
+                        }
+                        None => {}
+                    }
+                }
 
-                        // Send to WebSocket for real-time transcription
-                        rt_handle.send_audio(chunk);
+                // Stop a recorder left running after the meeting
+                if let Some(detector) = quiet_warning.as_mut() {
+                    if detector.feed(&chunk) == Some(crate::audio::silence::SilenceChange::Started) {
+                        let _ = app_clone.emit("silence-stop-warning", SILENCE_STOP_WARNING_SECS);
+                    }
+                }
+                if let Some(detector) = quiet_stop.as_mut() {
+                    if detector.feed(&chunk) == Some(crate::audio::silence::SilenceChange::Started)
+                        && !auto_stopped
+                    {
+                        auto_stopped = true;
+                        spawn_auto_stop(&app_clone, &session_id_clone, "silence");
+                    }
+                }
+
+                // Accumulate for WAV save
+                let pushed = audio_samples_clone.lock().map(|mut samples| samples.push(&chunk));
+                match pushed {
+                    Ok(Ok(true)) => {
+                        eprintln!("[session] Memory buffer full, recording continues on disk");
+                        let _ = app_clone.emit(
+                            "recording-buffer-on-disk",
+                            serde_json::json!({
+                                "session_id": session_id_clone,
+                                "buffered_secs": memory_limit_secs,
+                            }),
+                        );
+                        let _ = app_clone
+                            .notification()
+                            .builder()
+                            .title("Enregistrement long")
+                            .body("L'audio est desormais conserve sur le disque pour limiter la memoire utilisee")
+                            .show();
+                    }
+                    Ok(Err(e)) => {
+                        eprintln!("[session] Failed to write the recording buffer: {}", e);
                     }
+                    _ => {}
                 }
-                Err(std::sync::mpsc::TryRecvError::Empty) => {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                // Send to WebSocket for real-time transcription; a
+                // full queue means the network cannot keep up
+                let sent = rt_handle.send_audio(chunk);
+                if sent == lagging {
+                    lagging = !sent;
+                    let _ = app_clone.emit(
+                        "transcription-lagging",
+                        serde_json::json!({ "session_id": session_id_clone, "lagging": lagging }),
+                    );
                 }
-                Err(std::sync::mpsc::TryRecvError::Disconnected) => break,
             }
         }
 
         // Signal end of audio to WebSocket
-        rt_handle.end_audio();
+        rt_handle.end_audio().await;
     });
 
     // Store active session in state (wrap capturer for Send safety)
@@ -190,8 +931,16 @@ pub async fn start_session(
         audio_samples,
         sample_rate: actual_sample_rate,
         stop_signal: stop_tx,
+        native_writer,
+        native_only,
+        autosave,
+        chunk_sender,
+        paused: false,
+        incremental,
     });
+    drop(active);
 
+    crate::refresh_tray(&app);
     Ok(session_id)
 }
 
@@ -199,449 +948,3825 @@ pub async fn start_session(
 pub async fn stop_session(
     session_id: String,
     app: tauri::AppHandle,
-    state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let (samples, sample_rate) = {
-        let mut active = state.active_session.lock().map_err(|e| e.to_string())?;
-
-        if let Some(mut session) = active.take() {
-            if session.id != session_id {
-                // Put it back
-                let id = session.id.clone();
-                *active = Some(session);
-                return Err(format!(
-                    "La session active ({}) ne correspond pas a celle demandee ({})",
-                    id, session_id
-                ));
-            }
-
-            // Signal the background task to stop
-            let _ = session.stop_signal.send(true);
-            // Stop audio capture hardware
-            session.capturer.0.stop();
+    finish_session(app, session_id).await
+}
 
-            // Extract accumulated audio
-            let samples = session
-                .audio_samples
-                .lock()
-                .map(|s| s.clone())
-                .unwrap_or_default();
-            let sr = session.sample_rate;
+/// Label of the compact recorder window.
+pub const MINI_WINDOW_LABEL: &str = "mini";
 
-            (samples, sr)
-        } else {
-            return Err("Aucune session active".to_string());
-        }
+/// Opens a small always-on-top window with the timer, level meter and stop
+/// button of the active session, and hides the main window.
+#[tauri::command]
+pub async fn open_mini_recorder(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let (session_id, elapsed_secs) = {
+        let active = state.active_session.lock().map_err(|e| e.to_string())?;
+        let session = active.as_ref().ok_or("Aucune session active")?;
+        let samples = session.audio_samples.lock().map(|s| s.len()).unwrap_or(0);
+        (session.id.clone(), samples as u64 / session.sample_rate.max(1) as u64)
     };
 
-    // Save full WAV file
-    let audio_dir = dirs::data_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("poptranscribe")
-        .join("audio");
-    std::fs::create_dir_all(&audio_dir).ok();
-    let audio_path = audio_dir.join(format!("{}.wav", session_id));
-
-    let duration = samples.len() as f64 / sample_rate as f64;
-
-    crate::audio::store::save_wav(&audio_path, &samples, sample_rate)
-        .map_err(|e| format!("Erreur sauvegarde audio: {}", e))?;
-
-    // Update session in DB with audio path and duration
+    #[cfg(desktop)]
     {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        db.update_session_audio_path(
-            &session_id,
-            audio_path.to_str().unwrap_or(""),
-        )
-        .map_err(|e| e.to_string())?;
-        db.update_session_duration(&session_id, duration)
-            .map_err(|e| e.to_string())?;
+        if let Some(window) = app.get_webview_window(MINI_WINDOW_LABEL) {
+            let _ = window.show();
+            return window.set_focus().map_err(|e| e.to_string());
+        }
+        let url = format!("index.html?mini={}&elapsed={}", session_id, elapsed_secs);
+        tauri::WebviewWindowBuilder::new(&app, MINI_WINDOW_LABEL, tauri::WebviewUrl::App(url.into()))
+            .title("PopTranscribe")
+            .inner_size(300.0, 56.0)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .build()
+            .map_err(|e| format!("Impossible d'ouvrir le mini enregistreur: {}", e))?;
+        if let Some(main) = app.get_webview_window("main") {
+            let _ = main.hide();
+        }
+        Ok(())
     }
+    #[cfg(not(desktop))]
+    {
+        let _ = (app, session_id, elapsed_secs);
+        Err("Mini enregistreur indisponible sur cette plateforme".to_string())
+    }
+}
 
-    // Clone what we need for the background batch reprocessing + summary task
-    let api_key = {
-        let key = state.api_key.lock().map_err(|e| e.to_string())?;
-        key.clone()
-    };
-    let db_clone = Arc::clone(&state.db);
-
-    // Background task: batch transcription with diarization, then summary
+/// Runs the rolling diarization of a session until it stops, and emits
+/// `live-speakers` with the live segments whose speaker changed.
+fn spawn_rolling_diarization(
+    app: tauri::AppHandle,
+    db: Arc<std::sync::Mutex<crate::db::Database>>,
+    session_id: String,
+    mut rolling: crate::mistral::rolling::RollingDiarization,
+    buffer: Arc<std::sync::Mutex<crate::audio::buffer::SampleBuffer>>,
+    mut stop_rx: tokio::sync::watch::Receiver<bool>,
+) {
     tokio::spawn(async move {
-        match crate::mistral::batch::transcribe_batch(&api_key, &audio_path, true, Some("fr"))
-            .await
-        {
-            Ok(response) => {
-                // Clear old live (non-diarized) segments and save diarized ones
-                if let Ok(db) = db_clone.lock() {
-                    // Remove the live streaming segments so they are replaced by
-                    // higher-quality diarized ones
-                    let _ = db.clear_live_segments(&session_id);
-
-                    for seg in &response.segments {
-                        let _ = db.save_segment(
-                            &session_id,
-                            &seg.text,
-                            seg.start,
-                            seg.end,
-                            seg.speaker_id.as_deref(),
-                            true,
-                        );
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(crate::mistral::rolling::INTERVAL_SECS));
+        // The first tick is immediate
+        interval.tick().await;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                changed = stop_rx.changed() => {
+                    if changed.is_err() || *stop_rx.borrow() {
+                        break;
                     }
+                    continue;
                 }
-
-                // Build transcript text for summary
-                let transcript_text: String = response
-                    .segments
+            }
+            let turns = match rolling.run_window(&buffer).await {
+                Ok(Some(turns)) => turns,
+                Ok(None) => continue,
+                Err(e) => {
+                    eprintln!("[rolling] Diarization window failed for {}: {}", session_id, e);
+                    continue;
+                }
+            };
+            // The final pass replaces the live segments
+            if *stop_rx.borrow() {
+                break;
+            }
+            let changes = {
+                let Ok(db) = db.lock() else {
+                    break;
+                };
+                let changes = match db.get_segments(&session_id) {
+                    Ok(segments) => crate::mistral::rolling::attribute(&segments, turns),
+                    Err(_) => continue,
+                };
+                if let Err(e) = db.set_live_speakers(&session_id, &changes) {
+                    eprintln!("[rolling] Failed to save speakers for {}: {}", session_id, e);
+                    continue;
+                }
+                changes
+            };
+            if !changes.is_empty() {
+                let segments: Vec<serde_json::Value> = changes
                     .iter()
-                    .map(|s| {
-                        if let Some(ref speaker) = s.speaker_id {
-                            format!("{}: {}", speaker, s.text)
-                        } else {
-                            s.text.clone()
-                        }
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
-
-                // Generate AI title + summary
-                if !transcript_text.is_empty() {
-                    // Title generation (fast, runs first)
-                    match crate::mistral::chat::generate_title(&api_key, &transcript_text).await
-                    {
-                        Ok(title) => {
-                            if let Ok(db) = db_clone.lock() {
-                                let _ = db.update_session_title(&session_id, &title);
-                            }
-                        }
+                    .map(|(id, speaker)| serde_json::json!({ "id": id, "speaker": speaker }))
+                    .collect();
+                let _ = app.emit(
+                    "live-speakers",
+                    serde_json::json!({ "session_id": session_id, "segments": segments }),
+                );
+            }
+        }
+    });
+}
+
+/// Saves a live segment right away, since the UI needs its id, and emits it
+/// as `transcription-segment`.
+fn save_live_segment(
+    app: &tauri::AppHandle,
+    db: &std::sync::Mutex<crate::db::Database>,
+    session_id: &str,
+    text: &str,
+    start: f64,
+    end: f64,
+    language: Option<&str>,
+) {
+    let segment_id = db.lock().ok().and_then(|db| {
+        let segment = NewSegment {
+            text,
+            start,
+            end,
+            speaker: None,
+            is_diarized: false,
+            language,
+        };
+        db.save_segments_batch(session_id, &[segment])
+            .ok()
+            .and_then(|ids| ids.first().copied())
+    });
+
+    let segment = serde_json::json!({
+        "id": segment_id.unwrap_or(0),
+        "session_id": session_id,
+        "text": text,
+        "start_time": start,
+        "end_time": end,
+        "speaker": null,
+        "is_diarized": false,
+        "language": language
+    });
+    let _ = app.emit("transcription-segment", segment);
+}
+
+/// Regenerates a brief summary of the live transcript every `interval_secs`
+/// until the session stops, and emits `live-summary`. Without an API key
+/// (demo provider) the summary is a canned one.
+fn spawn_live_summary(
+    app: tauri::AppHandle,
+    db: Arc<std::sync::Mutex<crate::db::Database>>,
+    session_id: String,
+    api_key: Option<String>,
+    interval_secs: u64,
+    mut stop_rx: tokio::sync::watch::Receiver<bool>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+        // The first tick is immediate
+        interval.tick().await;
+        let mut last_transcript: Option<Arc<crate::db::FullTranscript>> = None;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                changed = stop_rx.changed() => {
+                    if changed.is_err() || *stop_rx.borrow() {
+                        break;
+                    }
+                    continue;
+                }
+            }
+            let transcript = match db.lock() {
+                Ok(db) => match db.get_full_transcript(&session_id) {
+                    Ok(transcript) => transcript,
+                    Err(_) => continue,
+                },
+                Err(_) => break,
+            };
+            // Nothing new was said since the last summary
+            if transcript.with_speakers.trim().is_empty()
+                || last_transcript.as_ref().is_some_and(|last| Arc::ptr_eq(last, &transcript))
+            {
+                continue;
+            }
+            let summary = match api_key {
+                Some(ref key) => {
+                    match crate::mistral::chat::generate_live_summary(key, &transcript.with_speakers).await {
+                        Ok(summary) => summary,
                         Err(e) => {
-                            eprintln!(
-                                "[session] Erreur generation titre pour {}: {}",
-                                session_id, e
-                            );
+                            eprintln!("[session] Live summary failed for {}: {}", session_id, e);
+                            continue;
                         }
                     }
+                }
+                None => crate::mistral::demo::live_summary(),
+            };
+            last_transcript = Some(transcript);
+            if *stop_rx.borrow() {
+                break;
+            }
+            let _ = app.emit(
+                "live-summary",
+                serde_json::json!({
+                    "session_id": session_id,
+                    "summary": summary,
+                    "generated_at": chrono::Local::now().to_rfc3339(),
+                }),
+            );
+        }
+    });
+}
 
-                    // Summary generation
-                    match crate::mistral::chat::generate_summary(&api_key, &transcript_text).await
-                    {
-                        Ok(summary) => {
-                            if let Ok(summary_json) = serde_json::to_string(&summary) {
-                                if let Ok(db) = db_clone.lock() {
-                                    let _ = db.save_summary(&session_id, &summary_json);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!(
-                                "[session] Erreur generation resume pour {}: {}",
-                                session_id, e
-                            );
-                        }
+/// Runs a detection pass over the live segments received since the last one,
+/// every `ACTION_ITEM_INTERVAL_SECS` until the session stops. Each item
+/// found is stored and emitted as `action-item-detected`.
+fn spawn_action_item_detection(
+    app: tauri::AppHandle,
+    db: Arc<std::sync::Mutex<crate::db::Database>>,
+    session_id: String,
+    api_key: String,
+    mut stop_rx: tokio::sync::watch::Receiver<bool>,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(ACTION_ITEM_INTERVAL_SECS));
+        // The first tick is immediate
+        interval.tick().await;
+        let mut last_segment_id = 0;
+        let mut known: Vec<String> = Vec::new();
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                changed = stop_rx.changed() => {
+                    if changed.is_err() || *stop_rx.borrow() {
+                        break;
                     }
+                    continue;
                 }
+            }
+            let segments = match db.lock() {
+                Ok(db) => db.get_segments(&session_id).unwrap_or_default(),
+                Err(_) => break,
+            };
+            let new_segments: Vec<&Segment> = segments
+                .iter()
+                .filter(|s| !s.is_diarized && s.id > last_segment_id)
+                .collect();
+            let passage = new_segments
+                .iter()
+                .map(|s| match s.speaker {
+                    Some(ref speaker) => format!("{}: {}", speaker, s.text),
+                    None => s.text.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            if passage.chars().count() < ACTION_ITEM_MIN_PASSAGE_CHARS {
+                continue;
+            }
+            let passage_end = new_segments.iter().map(|s| s.id).max();
 
-                let _ = app.emit("session-complete", &session_id);
+            let items = match crate::mistral::chat::detect_action_items(&api_key, &passage, &known).await {
+                Ok(items) => items,
+                Err(e) => {
+                    eprintln!("[session] Action item detection failed for {}: {}", session_id, e);
+                    continue;
+                }
+            };
+            last_segment_id = passage_end.unwrap_or(last_segment_id);
+            for item in items {
+                let saved = db.lock().ok().and_then(|db| {
+                    db.add_live_action_item(&session_id, &item.description, item.assignee.as_deref(), passage_end)
+                        .ok()
+                });
+                known.push(item.description);
+                if let Some(saved) = saved {
+                    let _ = app.emit("action-item-detected", &saved);
+                }
             }
-            Err(e) => {
-                eprintln!(
-                    "[session] Erreur transcription batch pour {}: {}",
-                    session_id, e
-                );
-                let _ = app.emit(
-                    "session-error",
-                    format!("Erreur de transcription: {}", e),
-                );
+        }
+    });
+}
+
+/// Moves chunks from the capture's std channel to the session's tokio
+/// channel on a dedicated thread, so that the session loop awaits them
+/// instead of polling. The thread ends with the capture or when the loop is
+/// gone.
+fn forward_chunks(
+    receiver: std::sync::mpsc::Receiver<Vec<i16>>,
+    tx: tokio::sync::mpsc::Sender<Vec<i16>>,
+) {
+    std::thread::spawn(move || {
+        while let Ok(chunk) = receiver.recv() {
+            if tx.blocking_send(chunk).is_err() {
+                break;
             }
         }
     });
+}
+
+/// Pauses the active session. The capture streams are dropped, not just
+/// muted, so that the OS stops showing the microphone as in use and other
+/// applications can open the devices in exclusive mode.
+#[tauri::command]
+pub async fn pause_session(
+    session_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    {
+        let mut active = state.active_session.lock().map_err(|e| e.to_string())?;
+        let session = active
+            .as_mut()
+            .filter(|s| s.id == session_id)
+            .ok_or("Aucune session active")?;
+        if session.paused {
+            return Ok(());
+        }
+        // The full-quality file is the recording itself and ends with the
+        // first capture
+        if session.native_only {
+            return Err("La pause n'est pas disponible en qualite native".to_string());
+        }
+        session.capturer.0.stop();
+        session.paused = true;
+    }
+    let _ = app.emit(
+        "session-paused",
+        serde_json::json!({ "session_id": session_id, "paused": true }),
+    );
+    Ok(())
+}
+
+/// Reopens the devices of a paused session. The full-quality copy, if any,
+/// stops at the first pause.
+#[tauri::command]
+pub async fn resume_session(
+    session_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    {
+        let mut active = state.active_session.lock().map_err(|e| e.to_string())?;
+        let session = active
+            .as_mut()
+            .filter(|s| s.id == session_id)
+            .ok_or("Aucune session active")?;
+        if !session.paused {
+            return Ok(());
+        }
+        let capturer = &mut session.capturer.0;
+        capturer.set_native_copy(false);
+        let receiver = capturer
+            .start()
+            .map_err(|e| format!("Impossible de reprendre la capture: {}", e))?;
+        // The recording and the realtime stream keep a single rate
+        if capturer.actual_sample_rate != session.sample_rate {
+            capturer.stop();
+            return Err(format!(
+                "Le peripherique audio a change de frequence ({} Hz au lieu de {} Hz)",
+                capturer.actual_sample_rate, session.sample_rate
+            ));
+        }
+        forward_chunks(receiver, session.chunk_sender.clone());
+        session.paused = false;
+    }
+    let _ = app.emit(
+        "session-paused",
+        serde_json::json!({ "session_id": session_id, "paused": false }),
+    );
+    Ok(())
+}
+
+/// Stops the active session, saves its audio and launches post-processing.
+///
+/// Shared by the `stop_session` command and the automatic stop paths
+/// (duration limit, ...), which only have an `AppHandle` at hand.
+pub(crate) async fn finish_session(app: tauri::AppHandle, session_id: String) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let (samples, sample_rate, native_writer, native_only, autosave, incremental) = {
+        let mut active = state.active_session.lock().map_err(|e| e.to_string())?;
+
+        if let Some(mut session) = active.take() {
+            if session.id != session_id {
+                // Put it back
+                let id = session.id.clone();
+                *active = Some(session);
+                return Err(format!(
+                    "La session active ({}) ne correspond pas a celle demandee ({})",
+                    id, session_id
+                ));
+            }
+
+            // Signal the background task to stop
+            let _ = session.stop_signal.send(true);
+            // Stop audio capture hardware
+            session.capturer.0.stop();
+
+            // Accumulated audio, possibly partly on disk; saved below
+            let samples = Arc::clone(&session.audio_samples);
+            let sr = session.sample_rate;
+
+            (
+                samples,
+                sr,
+                session.native_writer.take(),
+                session.native_only,
+                session.autosave.take(),
+                session.incremental.lock().ok().and_then(|mut batch| batch.take()),
+            )
+        } else {
+            return Err("Aucune session active".to_string());
+        }
+    };
+    // The tray offers to record again
+    crate::refresh_tray(&app);
+    let _ = app.emit("session-stopped", &session_id);
+    if let Some(window) = app.get_webview_window(MINI_WINDOW_LABEL) {
+        let _ = window.close();
+    }
+
+    // Save full WAV file
+    let audio_dir = data_dir().join("audio");
+    std::fs::create_dir_all(&audio_dir).ok();
+    let audio_path = audio_dir.join(format!("{}.wav", session_id));
+
+    let duration = samples.lock().map(|s| s.len()).unwrap_or(0) as f64 / sample_rate as f64;
+
+    // Wait for the full-quality copy to be finalized (its sender was dropped
+    // with the capture stream)
+    let native_saved = match native_writer {
+        Some(handle) => match tokio::task::spawn_blocking(move || handle.join()).await {
+            Ok(Ok(Ok(path))) => {
+                eprintln!("[session] Native-format audio saved to {}", path.display());
+                true
+            }
+            Ok(Ok(Err(e))) => {
+                eprintln!("[session] Native-format audio failed: {}", e);
+                false
+            }
+            _ => {
+                eprintln!("[session] Native-format audio writer panicked");
+                false
+            }
+        },
+        None => false,
+    };
+
+    // In "native" mode the copy already sits at `audio_path`; the 16kHz
+    // samples are only written if it could not be saved. Either file is
+    // synced and renamed into place before `audio_path` is recorded
+    if !(native_only && native_saved) {
+        samples
+            .lock()
+            .map_err(|e| e.to_string())?
+            .save_wav(&audio_path, sample_rate)
+            .map_err(|e| format!("Erreur sauvegarde audio: {}", e))?;
+    }
+    // Only the audio after the chunks already sent remains to transcribe,
+    // with the overlap that matches its speakers
+    let incremental = match incremental {
+        Some(batch) => {
+            let tail = samples
+                .lock()
+                .map_err(|e| e.to_string())?
+                .read_from(batch.tail_start())
+                .map_err(|e| format!("Erreur lecture audio: {}", e))?;
+            Some((batch, tail))
+        }
+        None => None,
+    };
+    drop(samples);
+
+    // Update session in DB with audio path and duration
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.update_session_audio_path(
+            &session_id,
+            audio_path.to_str().unwrap_or(""),
+        )
+        .map_err(|e| e.to_string())?;
+        db.update_session_duration(&session_id, duration)
+            .map_err(|e| e.to_string())?;
+    }
+    // The partial recording is only needed until now
+    if let Some(autosave) = autosave {
+        autosave.finish();
+    }
+
+    // Background task: batch transcription with diarization, then summary
+    tokio::spawn(process_recording_with(app, session_id, audio_path, incremental));
 
     Ok(())
 }
 
-// ── Data retrieval ───────────────────────────────────────────────────
+/// Post-processing of a saved recording: batch transcription with
+/// diarization replacing the live segments, then AI title and summary.
+/// Emits `session-complete`, or `session-error` when transcription fails.
+pub(crate) async fn process_recording(
+    app: tauri::AppHandle,
+    session_id: String,
+    audio_path: std::path::PathBuf,
+) -> Result<(), String> {
+    process_recording_with(app, session_id, audio_path, None).await
+}
+
+/// `process_recording`, completing the chunks transcribed during the
+/// recording with the remaining audio instead of sending the whole file.
+async fn process_recording_with(
+    app: tauri::AppHandle,
+    session_id: String,
+    audio_path: std::path::PathBuf,
+    incremental: Option<(crate::mistral::incremental::IncrementalBatch, Vec<i16>)>,
+) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let api_key = {
+        let key = state.api_key.lock().map_err(|e| e.to_string())?;
+        key.clone()
+    };
+    let db_clone = Arc::clone(&state.db);
+
+    // "auto_title" = "false" keeps the user-defined title instead of letting
+    // the AI overwrite it
+    let (auto_title, locked_language, recorded_duration, demo) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let auto_title = db.get_setting("auto_title").ok().flatten().as_deref() != Some("false");
+        let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        let demo = crate::mistral::demo::is_enabled(&db);
+        (auto_title, session.language, session.duration_secs, demo)
+    };
+
+    // The title and summary are first generated from the live transcript
+    // while the batch transcription runs, so that they are ready about as
+    // soon as the diarized segments
+    let live_text = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_live_transcript(&session_id)
+            .ok()
+            .flatten()
+            .filter(|t| !t.trim().is_empty())
+    };
+    let early_results = async {
+        match live_text {
+            Some(ref text) => {
+                generate_session_results(&api_key, &db_clone, &session_id, text, auto_title, true).await
+            }
+            None => GeneratedResults::default(),
+        }
+    };
+
+    // No language hint unless pinned: meetings may switch language
+    // mid-sentence
+    let batch = async {
+        if demo {
+            return Ok(crate::mistral::demo::transcribe_batch(recorded_duration.unwrap_or(60.0)));
+        }
+        if let Some((chunks, tail)) = incremental {
+            match chunks.finish(tail).await {
+                Ok(response) => return Ok(response),
+                Err(e) => eprintln!(
+                    "[session] Incremental transcription failed for {}, sending the whole recording: {}",
+                    session_id, e
+                ),
+            }
+        }
+        crate::mistral::batch::transcribe_batch(&api_key, &audio_path, true, locked_language.as_deref()).await
+    };
+    let (batch_result, early) = tokio::join!(batch, early_results);
+    if early.summary {
+        let _ = app.emit("session-summary-ready", &session_id);
+    }
+
+    match batch_result {
+        Ok(mut response) => {
+            let cleanup = db_clone
+                .lock()
+                .map(|db| crate::cleanup::SegmentCleanup::from_db(&db))
+                .unwrap_or_default();
+            for seg in &mut response.segments {
+                seg.text = cleanup.apply(&seg.text);
+            }
+
+            // Clear old live (non-diarized) segments and save diarized ones
+            if let Ok(db) = db_clone.lock() {
+                // Remove the live streaming segments so they are replaced by
+                // higher-quality diarized ones
+                let _ = db.clear_live_segments(&session_id);
+
+                let segments: Vec<NewSegment> = response
+                    .segments
+                    .iter()
+                    .map(|seg| NewSegment {
+                        text: &seg.text,
+                        start: seg.start,
+                        end: seg.end,
+                        speaker: seg.speaker_id.as_deref(),
+                        is_diarized: true,
+                        language: locked_language
+                            .as_deref()
+                            .or(seg.language.as_deref())
+                            .or_else(|| crate::language::detect_language(&seg.text)),
+                    })
+                    .collect();
+                if let Err(e) = db.save_segments_batch(&session_id, &segments) {
+                    eprintln!("[session] Failed to save diarized segments for {}: {}", session_id, e);
+                }
+
+                let speakers: std::collections::HashSet<&str> = response
+                    .segments
+                    .iter()
+                    .filter_map(|s| s.speaker_id.as_deref())
+                    .collect();
+                if !speakers.is_empty() {
+                    let _ = db.set_session_speaker_count(&session_id, speakers.len() as i64);
+                }
+                // The batch pass sees the whole file, unlike the live one
+                if let Some(ref detected) = response.language {
+                    let _ = db.set_session_detected_language(&session_id, detected);
+                }
+
+                // Imported files have no recorded duration
+                if recorded_duration.is_none() {
+                    let end = response.segments.iter().map(|s| s.end).fold(0.0, f64::max);
+                    let _ = db.update_session_duration(&session_id, end);
+                }
+            }
+
+            // Build transcript text for summary
+            let transcript_text: String = response
+                .segments
+                .iter()
+                .map(|s| {
+                    if let Some(ref speaker) = s.speaker_id {
+                        format!("{}: {}", speaker, s.text)
+                    } else {
+                        s.text.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            // Title and summary from the diarized transcript, for what the
+            // live transcript could not provide
+            if !transcript_text.is_empty() {
+                generate_session_results(
+                    &api_key,
+                    &db_clone,
+                    &session_id,
+                    &transcript_text,
+                    auto_title && !early.title,
+                    !early.summary,
+                )
+                .await;
+            }
+
+            match auto_export_session(&db_clone, &session_id) {
+                Ok(Some(path)) => {
+                    let _ = app.emit("session-auto-exported", path.to_string_lossy().to_string());
+                    if let Err(e) = sync_export_to_git(&db_clone, &session_id, path).await {
+                        eprintln!("[git] Sync failed for {}: {}", session_id, e);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("[export] Auto-export failed for {}: {}", session_id, e),
+            }
+
+            crate::refresh_tray(&app);
+            let _ = app.emit("session-complete", &session_id);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!(
+                "[session] Erreur transcription batch pour {}: {}",
+                session_id, e
+            );
+            let message = format!("Erreur de transcription: {}", e);
+            let _ = app.emit("session-error", &message);
+            Err(message)
+        }
+    }
+}
+
+/// Model retried when a summary fails, from the `fallback_model` setting.
+fn fallback_model(db: &crate::db::Database) -> Option<String> {
+    match db.get_setting("fallback_model").ok().flatten() {
+        None => Some(crate::mistral::chat::DEFAULT_FALLBACK_MODEL.to_string()),
+        Some(model) if model.is_empty() => None,
+        Some(model) => Some(model),
+    }
+}
+
+/// Whether the `provider` setting selects the offline demo provider.
+fn uses_demo_provider(db: &std::sync::Mutex<crate::db::Database>) -> bool {
+    db.lock().map(|db| crate::mistral::demo::is_enabled(&db)).unwrap_or(false)
+}
+
+/// Which of the AI results `generate_session_results` saved.
+#[derive(Debug, Default, Clone, Copy)]
+struct GeneratedResults {
+    title: bool,
+    summary: bool,
+}
+
+/// Generates the title (if `with_title`) and the summary (if `with_summary`)
+/// of `transcript` concurrently and saves them to the session.
+async fn generate_session_results(
+    api_key: &str,
+    db: &Arc<std::sync::Mutex<crate::db::Database>>,
+    session_id: &str,
+    transcript: &str,
+    with_title: bool,
+    with_summary: bool,
+) -> GeneratedResults {
+    let participants: Vec<String> = db
+        .lock()
+        .ok()
+        .and_then(|db| db.get_session_participants(session_id).ok())
+        .unwrap_or_default()
+        .iter()
+        .map(|p| p.describe())
+        .collect();
+    let demo = uses_demo_provider(db);
+    let (title_params, summary_params, fallback, without_disfluencies) = match db.lock() {
+        Ok(db) => (
+            ChatParams::from_settings(&db, ChatTask::Title),
+            ChatParams::from_settings(&db, ChatTask::Summary),
+            fallback_model(&db),
+            crate::disfluency::enabled(&db),
+        ),
+        Err(_) => (
+            ChatTask::Title.default_params(),
+            ChatTask::Summary.default_params(),
+            None,
+            false,
+        ),
+    };
+    let summary_transcript = if without_disfluencies {
+        crate::disfluency::remove_disfluencies(transcript)
+    } else {
+        transcript.to_string()
+    };
+
+    let title = async {
+        if !with_title {
+            return false;
+        }
+        let title = if demo {
+            Ok(crate::mistral::demo::title())
+        } else {
+            crate::mistral::chat::generate_title(api_key, transcript, &title_params).await
+        };
+        match title {
+            Ok(title) => db
+                .lock()
+                .map(|db| db.update_session_title(session_id, &title).is_ok())
+                .unwrap_or(false),
+            Err(e) => {
+                eprintln!("[session] Erreur generation titre pour {}: {}", session_id, e);
+                false
+            }
+        }
+    };
+    let summary = async {
+        if !with_summary {
+            return false;
+        }
+        let summary = if demo {
+            Ok((crate::mistral::demo::summary(), "demo".to_string()))
+        } else {
+            crate::mistral::chat::generate_summary_with_fallback(
+                api_key,
+                &summary_transcript,
+                &participants,
+                &summary_params,
+                fallback.as_deref(),
+            )
+            .await
+        };
+        match summary {
+            Ok((mut summary, model)) => {
+                // Items caught during the meeting that the summary missed
+                let detected: Vec<crate::mistral::chat::ActionItem> = db
+                    .lock()
+                    .ok()
+                    .and_then(|db| db.get_live_action_items(session_id).ok())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|item| crate::mistral::chat::ActionItem {
+                        description: item.description,
+                        assignee: item.assignee,
+                    })
+                    .collect();
+                crate::mistral::chat::merge_detected_action_items(&mut summary, detected);
+                match serde_json::to_string(&summary) {
+                    Ok(summary_json) => db
+                        .lock()
+                        .map(|db| {
+                            let saved = db.save_summary(session_id, &summary_json, Some(&model)).is_ok();
+                            if let Err(e) = db.set_session_topics(session_id, &summary.topics) {
+                                eprintln!("[session] Erreur indexation des themes pour {}: {}", session_id, e);
+                            }
+                            saved
+                        })
+                        .unwrap_or(false),
+                    Err(_) => false,
+                }
+            }
+            Err(e) => {
+                eprintln!("[session] Erreur generation resume pour {}: {}", session_id, e);
+                false
+            }
+        }
+    };
+
+    let (title, summary) = tokio::join!(title, summary);
+    GeneratedResults { title, summary }
+}
+
+// ── Data retrieval ───────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_sessions(state: State<'_, AppState>) -> Result<Vec<Session>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut sessions = db.list_sessions().map_err(|e| e.to_string())?;
+    // Titles stay listed, the content of protected sessions does not
+    if !unlocked {
+        for session in sessions.iter_mut().filter(|s| s.protected) {
+            session.summary_json = None;
+        }
+    }
+    Ok(sessions)
+}
+
+/// Sessions whose summary lists `topic`, most recent first. Protected
+/// sessions are left out while locked.
+#[tauri::command]
+pub async fn list_sessions_by_topic(
+    topic: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<Session>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let sessions = db.list_sessions_by_topic(&topic).map_err(|e| e.to_string())?;
+    Ok(sessions.into_iter().filter(|s| unlocked || !s.protected).collect())
+}
+
+#[tauri::command]
+pub async fn list_topics(state: State<'_, AppState>) -> Result<Vec<TopicCount>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.list_topics(unlocked).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_session_detail(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<SessionDetail, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    let segments = db.get_segments(&session_id).map_err(|e| e.to_string())?;
+    let summary: Option<Summary> = session
+        .summary_json
+        .as_ref()
+        .and_then(|json| serde_json::from_str(json).ok());
+    let attachments = db.get_attachments(&session_id).map_err(|e| e.to_string())?;
+    let live_transcript = db.get_live_transcript(&session_id).map_err(|e| e.to_string())?;
+    Ok(SessionDetail {
+        session,
+        segments,
+        summary,
+        attachments,
+        live_transcript,
+    })
+}
+
+#[tauri::command]
+pub async fn get_statistics(state: State<'_, AppState>) -> Result<Statistics, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_statistics(5, unlocked).map_err(|e| e.to_string())
+}
+
+/// Transcript of a finished session with speaker prefixes: its segments,
+/// or the saved realtime text when none were stored (batch processing
+/// failed).
+fn session_transcript(db: &crate::db::Database, session_id: &str) -> Result<String, String> {
+    db.get_full_transcript(session_id)
+        .map(|t| t.with_speakers.clone())
+        .map_err(|e| e.to_string())
+}
+
+/// Full transcript of a session, one segment per line, with speaker
+/// prefixes unless `with_speakers` is false.
+#[tauri::command]
+pub async fn get_full_transcript(
+    session_id: String,
+    with_speakers: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    let transcript = db.get_full_transcript(&session_id).map_err(|e| e.to_string())?;
+    Ok(if with_speakers.unwrap_or(true) {
+        transcript.with_speakers.clone()
+    } else {
+        transcript.plain.clone()
+    })
+}
+
+// ── Translations ─────────────────────────────────────────────────────
+
+/// Segments sent per translation call.
+const TRANSLATION_BATCH_SEGMENTS: usize = 40;
+
+/// Translates the segments of a session into `language` (ISO 639-1) and
+/// stores the translations. Segments already translated are skipped.
+#[tauri::command]
+pub async fn translate_session(
+    session_id: String,
+    language: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<TranslatedSegment>, String> {
+    let language = parse_language_code(&language)?;
+    let unlocked = is_unlocked(&state)?;
+    let pending: Vec<(i64, String)> = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        ensure_accessible(&session, unlocked)?;
+        db.get_translated_segments(&session_id, &language)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|s| s.translation.is_none() && !s.segment.text.trim().is_empty())
+            .map(|s| (s.segment.id, s.segment.text))
+            .collect()
+    };
+    let api_key = {
+        let key = state.api_key.lock().map_err(|e| e.to_string())?;
+        key.clone()
+    };
+    let demo = uses_demo_provider(&state.db);
+
+    for batch in pending.chunks(TRANSLATION_BATCH_SEGMENTS) {
+        let translations = if demo {
+            batch
+                .iter()
+                .map(|(id, text)| (*id, crate::mistral::demo::translate(text, &language)))
+                .collect()
+        } else {
+            crate::mistral::chat::translate_segments(&api_key, batch, &language)
+                .await
+                .map_err(|e| format!("Erreur de traduction: {}", e))?
+        };
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.save_segment_translations(&language, &translations)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_translated_segments(&session_id, &language)
+        .map_err(|e| e.to_string())
+}
+
+/// Segments of a session with their stored translation into `language`;
+/// untranslated segments have a null `translation`.
+#[tauri::command]
+pub async fn get_translated_transcript(
+    session_id: String,
+    language: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<TranslatedSegment>, String> {
+    let language = parse_language_code(&language)?;
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    db.get_translated_segments(&session_id, &language)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_translation_languages(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    db.list_translation_languages(&session_id)
+        .map_err(|e| e.to_string())
+}
+
+// ── Search ───────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn search_text(
+    query: String,
+    session_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Segment>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut results = db
+        .search_text(&query, session_id.as_deref())
+        .map_err(|e| e.to_string())?;
+    if !unlocked {
+        let protected = db.protected_session_ids().map_err(|e| e.to_string())?;
+        results.retain(|seg| !protected.contains(&seg.session_id));
+    }
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn search_llm(
+    query: String,
+    session_id: String,
+    live_text: Option<String>,
+    refresh: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let unlocked = is_unlocked(&state)?;
+    let mut transcript = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        ensure_accessible(&session, unlocked)?;
+        session_transcript(&db, &session_id)?
+    };
+    let api_key = {
+        let key = state.api_key.lock().map_err(|e| e.to_string())?;
+        key.clone()
+    };
+
+    // Append live (in-progress) text from real-time transcription
+    if let Some(ref lt) = live_text {
+        if !lt.is_empty() {
+            if !transcript.is_empty() {
+                transcript.push('\n');
+            }
+            transcript.push_str(lt);
+        }
+    }
+
+    if transcript.is_empty() {
+        return Err("Aucune transcription disponible pour cette session.".to_string());
+    }
+
+    // The same question on the same transcript gets the cached answer,
+    // unless `refresh` asks for a new one
+    let transcript_hash = {
+        use sha2::Digest;
+        format!("{:x}", sha2::Sha256::digest(transcript.as_bytes()))
+    };
+    let cache_key = query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    if !refresh.unwrap_or(false) {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        if let Ok(Some(answer)) = db.get_cached_answer(&session_id, &transcript_hash, &cache_key) {
+            return Ok(answer);
+        }
+    }
+
+    let answer = if uses_demo_provider(&state.db) {
+        crate::mistral::demo::answer(&query)
+    } else {
+        let params = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            ChatParams::from_settings(&db, ChatTask::Search)
+        };
+        crate::mistral::chat::search_transcript(&api_key, &transcript, &query, &params)
+            .await
+            .map_err(|e| e.to_string())?
+    };
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        if let Err(e) = db.cache_answer(&session_id, &transcript_hash, &cache_key, &answer) {
+            eprintln!("[search] Failed to cache answer: {}", e);
+        }
+    }
+    Ok(answer)
+}
+
+// ── Segments ─────────────────────────────────────────────────────────
+
+/// Inserts a manually written segment (missed remark, clarification...).
+/// It is stored as final so that live-segment cleanup never removes it.
+#[tauri::command]
+pub async fn create_segment(
+    session_id: String,
+    text: String,
+    start: f64,
+    end: f64,
+    speaker: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Segment, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Le texte du segment est vide".to_string());
+    }
+    if start < 0.0 || end < start {
+        return Err("Horodatage du segment invalide".to_string());
+    }
+    let speaker = speaker.as_deref().map(str::trim).filter(|s| !s.is_empty());
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    ensure_editable(&db, &session_id)?;
+    let id = db
+        .save_segment(&session_id, text, start, end, speaker, true)
+        .map_err(|e| e.to_string())?;
+    db.get_segment(id).map_err(|e| e.to_string())
+}
+
+/// Deletes a segment; it can be restored with `undo_last_operation`.
+#[tauri::command]
+pub async fn delete_segment(segment_id: i64, state: State<'_, AppState>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let segment = db.get_segment(segment_id).map_err(|e| e.to_string())?;
+    ensure_editable(&db, &segment.session_id)?;
+    db.delete_segment(segment_id).map_err(|e| e.to_string())
+}
+
+/// Edits the text of a segment. The previous text is kept as a revision,
+/// see `get_segment_revisions`.
+#[tauri::command]
+pub async fn update_segment_text(segment_id: i64, text: String, state: State<'_, AppState>) -> Result<(), String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Le texte du segment est vide: supprimez le segment a la place.".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let segment = db.get_segment(segment_id).map_err(|e| e.to_string())?;
+    ensure_editable(&db, &segment.session_id)?;
+    db.update_segment_text(segment_id, text).map_err(|e| e.to_string())
+}
+
+/// Previous texts of a segment, most recent first.
+#[tauri::command]
+pub async fn get_segment_revisions(
+    segment_id: i64,
+    state: State<'_, AppState>,
+) -> Result<Vec<SegmentRevision>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let segment = db.get_segment(segment_id).map_err(|e| e.to_string())?;
+    let session = db.get_session(&segment.session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    db.get_segment_revisions(segment_id).map_err(|e| e.to_string())
+}
+
+/// Puts a previous text of a segment back and returns the segment.
+#[tauri::command]
+pub async fn revert_segment_revision(revision_id: i64, state: State<'_, AppState>) -> Result<Segment, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let revision = db.get_segment_revision(revision_id).map_err(|e| e.to_string())?;
+    let segment = db
+        .get_segment(revision.segment_id)
+        .map_err(|_| "Le segment de cette revision a ete supprime.".to_string())?;
+    ensure_editable(&db, &segment.session_id)?;
+    db.revert_segment_revision(revision_id).map_err(|e| e.to_string())?;
+    db.get_segment(revision.segment_id).map_err(|e| e.to_string())
+}
+
+/// Reverts the latest speaker rename or segment deletion, of the given
+/// session or of any session. Returns the undone operation, if any.
+#[tauri::command]
+pub async fn undo_last_operation(
+    session_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Option<Operation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let Some(operation) = db
+        .last_operation(session_id.as_deref())
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    ensure_editable(&db, &operation.session_id)?;
+    db.undo_last_operation(Some(&operation.session_id))
+        .map_err(|e| e.to_string())
+}
+
+// ── Timestamps ───────────────────────────────────────────────────────
+
+/// Shifts all segment timestamps of a session by `delta_secs`. With
+/// `trim_audio` and a negative delta, the same amount is cut from the start
+/// of the recording so that audio and transcript stay aligned; nothing is
+/// shifted when the recording cannot be cut.
+#[tauri::command]
+pub async fn shift_timestamps(
+    session_id: String,
+    delta_secs: f64,
+    trim_audio: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let session = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        ensure_editable(&db, &session_id)?
+    };
+
+    if trim_audio.unwrap_or(false) && delta_secs < 0.0 {
+        let audio_path = session
+            .audio_path
+            .filter(|p| !p.is_empty())
+            .map(std::path::PathBuf::from)
+            .ok_or("Aucun fichier audio pour cette session")?;
+        // The whole file is rewritten, without holding the database
+        let duration = tokio::task::spawn_blocking(move || {
+            if !crate::audio::store::can_trim(&audio_path) {
+                return Err(
+                    "Seuls les enregistrements WAV 16 bits peuvent etre decoupes: decalez les timestamps sans decouper l'audio.".to_string(),
+                );
+            }
+            crate::audio::store::trim_wav_start(&audio_path, -delta_secs)
+                .map_err(|e| format!("Erreur decoupe audio: {}", e))
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.update_session_duration(&session_id, duration)
+            .map_err(|e| e.to_string())?;
+    }
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.shift_segments(&session_id, delta_secs).map_err(|e| e.to_string())
+}
+
+// ── Speaker management ──────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn rename_speaker(
+    session_id: String,
+    old_name: String,
+    new_name: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    ensure_editable(&db, &session_id)?;
+    db.rename_speaker(&session_id, &old_name, &new_name)
+        .map_err(|e| e.to_string())
+}
+
+/// Reassigns one segment to another speaker without renaming the others.
+#[tauri::command]
+pub async fn set_segment_speaker(
+    session_id: String,
+    segment_id: i64,
+    speaker: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let speaker = speaker.trim();
+    if speaker.is_empty() {
+        return Err("Nom d'intervenant vide.".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    ensure_editable(&db, &session_id)?;
+    match db.set_segment_speaker(&session_id, segment_id, speaker) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Segment introuvable dans cette session.".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// ── Speaker colors ───────────────────────────────────────────────────
+
+/// Resolved colors for every speaker of a session (explicit or palette).
+#[tauri::command]
+pub async fn get_speaker_colors(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<HashMap<String, String>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    let segments = db.get_segments(&session_id).map_err(|e| e.to_string())?;
+    let explicit = db.get_speaker_colors(&session_id).map_err(|e| e.to_string())?;
+    Ok(crate::export::resolve_speaker_colors(&segments, &explicit))
+}
+
+/// Sets a speaker color (`#RRGGBB`) for one session, or globally for the
+/// speaker profile when `session_id` is omitted.
+#[tauri::command]
+pub async fn set_speaker_color(
+    session_id: Option<String>,
+    speaker: String,
+    color: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if crate::export::parse_hex_color(&color).is_none() {
+        return Err(format!("Couleur invalide: {} (format attendu #RRGGBB)", color));
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    match session_id {
+        Some(sid) => {
+            ensure_editable(&db, &sid)?;
+            db.set_session_speaker_color(&sid, &speaker, &color)
+        }
+        None => db.set_profile_color(&speaker, &color),
+    }
+    .map_err(|e| e.to_string())
+}
+
+// ── Replacements ─────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn list_replacements(state: State<'_, AppState>) -> Result<Vec<Replacement>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.list_replacements().map_err(|e| e.to_string())
+}
+
+/// Adds an entry to the replacement dictionary, applied to the segments of
+/// later sessions; `apply_replacements` updates an existing one.
+#[tauri::command]
+pub async fn set_replacement(
+    pattern: String,
+    replacement: String,
+    state: State<'_, AppState>,
+) -> Result<Replacement, String> {
+    if pattern.trim().is_empty() {
+        return Err("Le texte a remplacer est vide.".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_replacement(&pattern, &replacement)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_replacement(id: i64, state: State<'_, AppState>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.delete_replacement(id).map_err(|e| e.to_string())
+}
+
+/// Applies the replacement dictionary to the stored segments of a session.
+/// Returns the number of segments changed.
+#[tauri::command]
+pub async fn apply_replacements(session_id: String, state: State<'_, AppState>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    ensure_editable(&db, &session_id)?;
+    let replacements = db.list_replacements().map_err(|e| e.to_string())?;
+    let cleanup = crate::cleanup::SegmentCleanup::new(&replacements, None);
+    db.rewrite_segments(&session_id, |text| cleanup.replace(text))
+        .map_err(|e| e.to_string())
+}
+
+// ── Transcript versions ──────────────────────────────────────────────
+
+/// Setting holding the project glossary: expected spellings of domain
+/// terms, one per line or separated by commas.
+const GLOSSARY_SETTING: &str = "glossary";
+
+/// Segments sent per correction call.
+const POLISH_BATCH_SEGMENTS: usize = 40;
+
+/// Glossary terms, with the targets of the replacement dictionary.
+fn glossary_terms(db: &crate::db::Database) -> Vec<String> {
+    let configured = db.get_setting(GLOSSARY_SETTING).ok().flatten().unwrap_or_default();
+    let replacements = db.list_replacements().unwrap_or_default();
+    let mut terms: Vec<String> = Vec::new();
+    for term in configured
+        .split(['\n', ','])
+        .map(str::to_string)
+        .chain(replacements.into_iter().map(|r| r.replacement))
+    {
+        let term = term.trim();
+        if !term.is_empty() && !terms.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+            terms.push(term.to_string());
+        }
+    }
+    terms
+}
+
+/// Corrects the misrecognized domain terms of a session with the glossary.
+/// The corrected text is saved as a new transcript version, the segments
+/// are left unchanged until it is adopted with `adopt_transcript_version`.
+#[tauri::command]
+pub async fn polish_transcript(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<TranscriptVersion, String> {
+    let unlocked = is_unlocked(&state)?;
+    let (segments, glossary) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        ensure_accessible(&session, unlocked)?;
+        let segments: Vec<(i64, String)> = db
+            .get_segments(&session_id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|s| (s.id, s.text))
+            .collect();
+        (segments, glossary_terms(&db))
+    };
+    if segments.is_empty() {
+        return Err("Aucune transcription a corriger".to_string());
+    }
+    let api_key = {
+        let key = state.api_key.lock().map_err(|e| e.to_string())?;
+        key.clone()
+    };
+    let demo = uses_demo_provider(&state.db);
+
+    let mut corrected: HashMap<i64, String> = HashMap::new();
+    let pending: Vec<(i64, String)> = segments
+        .iter()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .cloned()
+        .collect();
+    for batch in pending.chunks(POLISH_BATCH_SEGMENTS) {
+        let corrections = if demo {
+            batch
+                .iter()
+                .map(|(id, text)| (*id, crate::mistral::demo::polish(text, &glossary)))
+                .collect()
+        } else {
+            crate::mistral::chat::polish_segments(&api_key, batch, &glossary)
+                .await
+                .map_err(|e| format!("Erreur de correction: {}", e))?
+        };
+        corrected.extend(corrections);
+    }
+
+    let version: Vec<VersionSegment> = segments
+        .into_iter()
+        .map(|(id, text)| VersionSegment {
+            segment_id: id,
+            text: corrected.remove(&id).unwrap_or(text),
+        })
+        .collect();
+    let model = (!demo).then_some(crate::mistral::chat::CHAT_MODEL);
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.save_transcript_version(&session_id, "polished", model, &version)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_transcript_versions(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<TranscriptVersion>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    db.list_transcript_versions(&session_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Replaces the text of the segments with that of a version. Returns the
+/// number of segments changed.
+#[tauri::command]
+pub async fn adopt_transcript_version(version_id: i64, state: State<'_, AppState>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let version = db.get_transcript_version(version_id).map_err(|e| e.to_string())?;
+    ensure_editable(&db, &version.session_id)?;
+    db.set_segment_texts(&version.session_id, &version.segments)
+        .map_err(|e| e.to_string())
+}
+
+/// Word-level diff between the realtime transcript of a session and its
+/// diarized batch transcript, with where each change is in the latter.
+#[tauri::command]
+pub async fn compare_transcript_versions(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<crate::compare::TranscriptComparison, String> {
+    let unlocked = is_unlocked(&state)?;
+    let (live, segments) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        ensure_accessible(&session, unlocked)?;
+        let live = db
+            .get_live_transcript(&session_id)
+            .map_err(|e| e.to_string())?
+            .filter(|t| !t.trim().is_empty())
+            .ok_or("Aucune transcription en direct pour cette session")?;
+        let segments: Vec<Segment> = db
+            .get_segments(&session_id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|s| s.is_diarized)
+            .collect();
+        (live, segments)
+    };
+    if segments.is_empty() {
+        return Err("La transcription diarisee n'est pas encore disponible".to_string());
+    }
+    tokio::task::spawn_blocking(move || crate::compare::compare(&live, &segments))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ── Speaker profiles ─────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn list_speaker_profiles(
+    state: State<'_, AppState>,
+) -> Result<Vec<SpeakerProfile>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.list_speaker_profiles().map_err(|e| e.to_string())
+}
+
+/// Participants directory names matching what is being typed in the rename
+/// field, best match and most used first.
+#[tauri::command]
+pub async fn suggest_speakers(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SpeakerProfile>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.suggest_speakers(&query, limit.unwrap_or(8)).map_err(|e| e.to_string())
+}
+
+/// Speakers of a session with their profile metadata, for export headers
+/// and the participants panel.
+#[tauri::command]
+pub async fn get_session_participants(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SpeakerProfile>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    db.get_session_participants(&session_id).map_err(|e| e.to_string())
+}
+
+/// Sets the role / company / email of a speaker profile (its color is
+/// managed by `set_speaker_color`).
+#[tauri::command]
+pub async fn update_speaker_profile(
+    name: String,
+    role: Option<String>,
+    company: Option<String>,
+    email: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Le nom du participant est vide".to_string());
+    }
+    let clean = |v: Option<String>| v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    let profile = SpeakerProfile {
+        name: name.to_string(),
+        color: None,
+        role: clean(role),
+        company: clean(company),
+        email: clean(email),
+    };
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.upsert_speaker_profile(&profile).map_err(|e| e.to_string())
+}
+
+/// Characters of transcript sent for speaker identification; introductions
+/// happen at the start of a meeting.
+const SPEAKER_MAPPING_CONTEXT_CHARS: usize = 6000;
+
+/// Proposes which calendar attendee each diarized speaker is. Nothing is
+/// renamed: confirmed entries are applied with `rename_speaker`.
+#[tauri::command]
+pub async fn propose_speaker_mapping(
+    session_id: String,
+    attendees: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::mistral::chat::SpeakerMapping>, String> {
+    let attendees: Vec<String> = attendees
+        .into_iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .collect();
+    if attendees.is_empty() {
+        return Err("Aucun participant dans l'evenement du calendrier".to_string());
+    }
+    let api_key = {
+        let key = state.api_key.lock().map_err(|e| e.to_string())?;
+        key.clone()
+    };
+    let unlocked = is_unlocked(&state)?;
+    let (transcript, speakers) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        ensure_accessible(&session, unlocked)?;
+        let speakers: Vec<String> = db
+            .get_session_participants(&session_id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        let transcript: String = session_transcript(&db, &session_id)?
+            .chars()
+            .take(SPEAKER_MAPPING_CONTEXT_CHARS)
+            .collect();
+        (transcript, speakers)
+    };
+    if speakers.is_empty() {
+        return Err("Aucun intervenant identifie dans cette session".to_string());
+    }
+
+    crate::mistral::chat::propose_speaker_mapping(&api_key, &transcript, &speakers, &attendees)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Regenerates the summary of a finished session, e.g. after speakers were
+/// renamed and given roles.
+#[tauri::command]
+pub async fn regenerate_summary(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Summary, String> {
+    let api_key = {
+        let key = state.api_key.lock().map_err(|e| e.to_string())?;
+        key.clone()
+    };
+    let unlocked = is_unlocked(&state)?;
+    let (transcript, participants, params, fallback) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let session = ensure_editable(&db, &session_id)?;
+        ensure_accessible(&session, unlocked)?;
+        let participants: Vec<String> = db
+            .get_session_participants(&session_id)
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(|p| p.describe())
+            .collect();
+        let params = ChatParams::from_settings(&db, ChatTask::Summary);
+        let mut transcript = session_transcript(&db, &session_id)?;
+        if crate::disfluency::enabled(&db) {
+            transcript = crate::disfluency::remove_disfluencies(&transcript);
+        }
+        (transcript, participants, params, fallback_model(&db))
+    };
+    if transcript.is_empty() {
+        return Err("Aucune transcription disponible pour cette session.".to_string());
+    }
+
+    let (summary, model) = if uses_demo_provider(&state.db) {
+        (crate::mistral::demo::summary(), "demo".to_string())
+    } else {
+        crate::mistral::chat::generate_summary_with_fallback(
+            &api_key,
+            &transcript,
+            &participants,
+            &params,
+            fallback.as_deref(),
+        )
+        .await
+        .map_err(|e| e.to_string())?
+    };
+    let summary_json = serde_json::to_string(&summary).map_err(|e| e.to_string())?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.save_summary(&session_id, &summary_json, Some(&model))
+        .map_err(|e| e.to_string())?;
+    db.set_session_topics(&session_id, &summary.topics)
+        .map_err(|e| e.to_string())?;
+    Ok(summary)
+}
+
+// ── Export ───────────────────────────────────────────────────────────
+
+/// Writes the transcript document of a session (`markdown` or `pdf`) into
+/// `dir` and returns the file path.
+fn write_export_document(
+    format: &str,
+    dir: &std::path::Path,
+    base_name: &str,
+    export: &SessionExport,
+) -> Result<std::path::PathBuf, String> {
+    match format {
+        "markdown" => {
+            // Generate markdown content
+            let md = crate::export::export_markdown(export);
+            let file_path = dir.join(format!("{}.md", base_name));
+            crate::export::export_to_file(&md, &file_path)
+                .map_err(|e| format!("Erreur ecriture fichier: {}", e))?;
+            Ok(file_path)
+        }
+        "pdf" => {
+            let file_path = dir.join(format!("{}.pdf", base_name));
+            crate::export::export_pdf(export, &file_path)?;
+            Ok(file_path)
+        }
+        other => Err(format!("Export {} pas encore supporte", other)),
+    }
+}
+
+/// Everything a session export is built from.
+struct ExportSource {
+    session: Session,
+    segments: Vec<Segment>,
+    summary: Option<Summary>,
+    attachments: Vec<Attachment>,
+    participants: Vec<SpeakerProfile>,
+    speaker_colors: HashMap<String, String>,
+    /// Paragraph mode, from the `export_paragraphs` setting.
+    paragraphs: bool,
+    branding: crate::export::branding::Branding,
+}
+
+/// Setting ("true") grouping the segments of exports into paragraphs.
+const EXPORT_PARAGRAPHS_SETTING: &str = "export_paragraphs";
+
+impl ExportSource {
+    fn load(db: &crate::db::Database, session_id: &str) -> Result<Self, String> {
+        let session = db.get_session(session_id).map_err(|e| e.to_string())?;
+        let mut segments = db.get_segments(session_id).map_err(|e| e.to_string())?;
+        // Exports read without hesitations; the stored text stays verbatim
+        if crate::disfluency::enabled(db) {
+            for segment in &mut segments {
+                segment.text = crate::disfluency::remove_disfluencies(&segment.text);
+            }
+        }
+        // Also covers segments stored before the filter was enabled
+        if let Some(filter) = crate::profanity::ProfanityFilter::from_settings(db) {
+            for segment in &mut segments {
+                segment.text = filter.apply(&segment.text);
+            }
+        }
+        let summary: Option<Summary> = session
+            .summary_json
+            .as_ref()
+            .and_then(|json| serde_json::from_str(json).ok());
+        let attachments = db.get_attachments(session_id).map_err(|e| e.to_string())?;
+        let participants = db.get_session_participants(session_id).map_err(|e| e.to_string())?;
+        let explicit_colors = db.get_speaker_colors(session_id).map_err(|e| e.to_string())?;
+        let speaker_colors = crate::export::resolve_speaker_colors(&segments, &explicit_colors);
+        let paragraphs =
+            db.get_setting(EXPORT_PARAGRAPHS_SETTING).ok().flatten().as_deref() == Some("true");
+        Ok(Self {
+            session,
+            segments,
+            summary,
+            attachments,
+            participants,
+            speaker_colors,
+            paragraphs,
+            branding: crate::export::branding::Branding::from_settings(db),
+        })
+    }
+
+    fn export(&self, annotate_languages: bool) -> SessionExport<'_> {
+        SessionExport {
+            session_id: Some(&self.session.id),
+            title: &self.session.title,
+            date: &self.session.created_at,
+            duration_secs: self.session.duration_secs,
+            segments: &self.segments,
+            summary: &self.summary,
+            attachments: &self.attachments,
+            participants: &self.participants,
+            speaker_colors: &self.speaker_colors,
+            annotate_languages,
+            translation: None,
+            paragraphs: self.paragraphs,
+            pdf: crate::export::PdfOptions::default(),
+            branding: &self.branding,
+        }
+    }
+}
+
+/// Document options of `export_session`.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    /// Marks where the spoken language changes.
+    pub annotate_languages: bool,
+    pub pdf: crate::export::PdfOptions,
+}
+
+/// Sanitized "<title>_<short id>" used as the export file name.
+fn export_base_name(title: &str, session_id: &str) -> String {
+    let safe_title: String = title
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
+        .collect();
+    format!("{}_{}", safe_title, session_id.split('-').next().unwrap_or("export"))
+}
+
+/// Exports a session transcript. With `bundle` set to `folder` or `zip`, the
+/// recording is included next to the document, transcoded when
+/// `audio_format` names a compressed format (e.g. `m4a`).
+/// With `upload`, the file is also sent to the configured upload target.
+#[tauri::command]
+pub async fn export_session(
+    session_id: String,
+    format: String,
+    bundle: Option<String>,
+    audio_format: Option<String>,
+    options: Option<ExportOptions>,
+    upload: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let path = write_session_export(
+        &session_id,
+        &format,
+        bundle.as_deref(),
+        audio_format.as_deref(),
+        &options.unwrap_or_default(),
+        &state,
+    )
+    .await?;
+    if upload.unwrap_or(false) {
+        let path = std::path::Path::new(&path);
+        if path.is_dir() {
+            return Err("Un dossier ne peut pas etre envoye, choisissez le bundle zip.".to_string());
+        }
+        let target = {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            crate::upload::UploadTarget::from_settings(&db)
+        }
+        .ok_or("Aucune destination d'envoi configuree.")?;
+        target.upload(path).await?;
+    }
+    Ok(path)
+}
+
+/// Exports the PDF transcript of a session encrypted with a password, for
+/// minutes that only their recipients may open (see
+/// `crate::export::protect`).
+#[tauri::command]
+pub async fn export_protected_pdf(
+    session_id: String,
+    protection: crate::export::protect::PdfProtection,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    protection.validate()?;
+    let unlocked = is_unlocked(&state)?;
+    let source = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let source = ExportSource::load(&db, &session_id)?;
+        ensure_accessible(&source.session, unlocked)?;
+        source
+    };
+    let export = source.export(false);
+    let export_dir = resolve_export_dir(&state)?;
+    let base_name = export_base_name(&source.session.title, &session_id);
+
+    // Rendered and encrypted in a temp folder, so that the unprotected copy
+    // never reaches the export folder, which may be synced
+    let staging_dir = std::env::temp_dir().join(format!("poptranscribe_protected_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&staging_dir).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    let result = write_export_document("pdf", &staging_dir, &base_name, &export).and_then(|staged| {
+        crate::export::protect::protect_pdf(&staged, &protection)?;
+        let file_path = export_dir.join(format!("{}.pdf", base_name));
+        crate::audio::archive::move_file(&staged, &file_path)?;
+        Ok(file_path)
+    });
+    let _ = std::fs::remove_dir_all(&staging_dir);
+    Ok(result?.to_string_lossy().to_string())
+}
+
+/// Exports a session with its translation into `language`, placed below
+/// each segment (`layout` "interleaved", the default) or beside it
+/// ("parallel"). The session must have been translated with
+/// `translate_session`.
+#[tauri::command]
+pub async fn export_bilingual(
+    session_id: String,
+    format: String,
+    language: String,
+    layout: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let language = parse_language_code(&language)?;
+    let layout = match layout.as_deref() {
+        None => crate::export::BilingualLayout::Interleaved,
+        Some(name) => crate::export::BilingualLayout::from_name(name)
+            .ok_or_else(|| format!("Disposition inconnue: {}", name))?,
+    };
+    let unlocked = is_unlocked(&state)?;
+    let (source, texts) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let source = ExportSource::load(&db, &session_id)?;
+        ensure_accessible(&source.session, unlocked)?;
+        let texts: HashMap<i64, String> = db
+            .get_translated_segments(&session_id, &language)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter_map(|s| Some((s.segment.id, s.translation?)))
+            .collect();
+        (source, texts)
+    };
+    if texts.is_empty() {
+        return Err(format!(
+            "Aucune traduction en {}: traduisez d'abord la session.",
+            language
+        ));
+    }
+
+    let mut export = source.export(false);
+    export.translation = Some(crate::export::Translation {
+        language: &language,
+        texts: &texts,
+        layout,
+    });
+    let export_dir = resolve_export_dir(&state)?;
+    let base_name = format!("{}_{}", export_base_name(&source.session.title, &session_id), language);
+    let file_path = write_export_document(&format, &export_dir, &base_name, &export)?;
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+/// Writes an anonymized export of a session: emails, phone numbers and the
+/// names of people (speakers, participants, and the names the AI finds in
+/// the text unless `detect_names` is false) are replaced with placeholders.
+/// Only the written file is anonymized, the session is left unchanged.
+#[tauri::command]
+pub async fn redact_session(
+    session_id: String,
+    format: String,
+    detect_names: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let unlocked = is_unlocked(&state)?;
+    let mut source = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let source = ExportSource::load(&db, &session_id)?;
+        ensure_accessible(&source.session, unlocked)?;
+        source
+    };
+
+    let mut names: Vec<String> = source
+        .segments
+        .iter()
+        .filter_map(|s| s.speaker.clone())
+        .chain(source.participants.iter().map(|p| p.name.clone()))
+        .collect();
+    if detect_names.unwrap_or(true) && !uses_demo_provider(&state.db) {
+        let api_key = {
+            let key = state.api_key.lock().map_err(|e| e.to_string())?;
+            key.clone()
+        };
+        let transcript = source.segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join("\n");
+        if !transcript.is_empty() {
+            let found = crate::mistral::chat::extract_person_names(&api_key, &transcript)
+                .await
+                .map_err(|e| format!("Erreur de detection des noms: {}", e))?;
+            names.extend(found);
+        }
+    }
+    let redactor = crate::redact::Redactor::new(names);
+
+    // Derived copy: speakers are renamed and every text redacted
+    for segment in &mut source.segments {
+        segment.text = redactor.redact(&segment.text);
+        segment.speaker = segment
+            .speaker
+            .as_deref()
+            .map(|s| redactor.placeholder(s).unwrap_or(s).to_string());
+    }
+    if let Some(ref mut summary) = source.summary {
+        for text in summary.key_points.iter_mut().chain(summary.decisions.iter_mut()) {
+            *text = redactor.redact(text);
+        }
+        for item in &mut summary.action_items {
+            item.description = redactor.redact(&item.description);
+            item.assignee = item.assignee.as_deref().map(|a| redactor.redact(a));
+        }
+    }
+    source.speaker_colors = source
+        .speaker_colors
+        .iter()
+        .map(|(speaker, color)| {
+            (redactor.placeholder(speaker).unwrap_or(speaker).to_string(), color.clone())
+        })
+        .collect();
+    source.participants.clear();
+    source.attachments.clear();
+    source.session.title = redactor.redact(&source.session.title);
+
+    let export_dir = resolve_export_dir(&state)?;
+    let base_name = format!("{}_anonyme", export_base_name(&source.session.title, &session_id));
+    // Shared copies do not link back to the app
+    let mut export = source.export(false);
+    export.session_id = None;
+    let file_path = write_export_document(&format, &export_dir, &base_name, &export)?;
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+async fn write_session_export(
+    session_id: &str,
+    format: &str,
+    bundle: Option<&str>,
+    audio_format: Option<&str>,
+    options: &ExportOptions,
+    state: &State<'_, AppState>,
+) -> Result<String, String> {
+    // Load session detail from DB
+    let unlocked = is_unlocked(state)?;
+    let source = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let source = ExportSource::load(&db, session_id)?;
+        ensure_accessible(&source.session, unlocked)?;
+        source
+    };
+    let mut export = source.export(options.annotate_languages);
+    export.pdf = options.pdf;
+
+    let export_dir = resolve_export_dir(state)?;
+    let base_name = export_base_name(&source.session.title, session_id);
+
+    let as_zip = match bundle {
+        None => {
+            let file_path = write_export_document(format, &export_dir, &base_name, &export)?;
+            return Ok(file_path.to_string_lossy().to_string());
+        }
+        Some("folder") => false,
+        Some("zip") => true,
+        Some(other) => return Err(format!("Type de bundle inconnu: {}", other)),
+    };
+
+    // Zips are staged in a temp folder first
+    let bundle_dir = if as_zip {
+        std::env::temp_dir().join(format!("poptranscribe_bundle_{}", session_id))
+    } else {
+        export_dir.join(&base_name)
+    };
+    std::fs::create_dir_all(&bundle_dir).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+
+    write_export_document(format, &bundle_dir, &base_name, &export)?;
+
+    for attachment in &source.attachments {
+        std::fs::copy(&attachment.path, bundle_dir.join(&attachment.file_name))
+            .map_err(|e| format!("Erreur copie piece jointe: {}", e))?;
+    }
+
+    if let Some(audio_path) = source.session.audio_path.as_deref().filter(|p| !p.is_empty()) {
+        let audio_path = std::path::Path::new(audio_path);
+        if !audio_path.exists() {
+            return Err(format!("Fichier audio introuvable: {}", audio_path.display()));
+        }
+        match audio_format.and_then(crate::audio::transcode::AudioFormat::from_name) {
+            Some(fmt) => {
+                let output = bundle_dir.join(format!("{}.{}", base_name, fmt.extension()));
+                crate::audio::transcode::transcode(audio_path, &output, fmt).await?;
+            }
+            None => {
+                let ext = audio_path.extension().and_then(|e| e.to_str()).unwrap_or("wav");
+                std::fs::copy(audio_path, bundle_dir.join(format!("{}.{}", base_name, ext)))
+                    .map_err(|e| format!("Erreur copie audio: {}", e))?;
+            }
+        }
+    }
+
+    if as_zip {
+        let zip_path = export_dir.join(format!("{}.zip", base_name));
+        let result = crate::export::zip_directory(&bundle_dir, &zip_path);
+        let _ = std::fs::remove_dir_all(&bundle_dir);
+        result?;
+        Ok(zip_path.to_string_lossy().to_string())
+    } else {
+        Ok(bundle_dir.to_string_lossy().to_string())
+    }
+}
+
+/// Writes the document of a finished session in the `auto_export_format`
+/// setting (`markdown` or `pdf`, unset to disable) to `auto_export_dir`,
+/// or the export directory. Protected sessions are never written out.
+/// Returns the written file.
+pub(crate) fn auto_export_session(
+    db: &std::sync::Mutex<crate::db::Database>,
+    session_id: &str,
+) -> Result<Option<std::path::PathBuf>, String> {
+    let (format, dir, source) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let format = match db.get_setting("auto_export_format").ok().flatten() {
+            Some(format) if !format.is_empty() => format,
+            _ => return Ok(None),
+        };
+        let source = ExportSource::load(&db, session_id)?;
+        if source.session.protected {
+            return Ok(None);
+        }
+        (format, export_dir_setting(&db, "auto_export_dir"), source)
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    let base_name = export_base_name(&source.session.title, session_id);
+    write_export_document(&format, &dir, &base_name, &source.export(false)).map(Some)
+}
+
+/// Commits an auto-exported Markdown file to the `git_sync_repo`
+/// repository, and pushes it when `git_sync_push` is "true".
+async fn sync_export_to_git(
+    db: &std::sync::Mutex<crate::db::Database>,
+    session_id: &str,
+    file: std::path::PathBuf,
+) -> Result<(), String> {
+    if file.extension().and_then(|e| e.to_str()) != Some("md") {
+        return Ok(());
+    }
+    let (repo, push, title) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let repo = match db.get_setting("git_sync_repo").ok().flatten() {
+            Some(repo) if !repo.is_empty() => std::path::PathBuf::from(repo),
+            _ => return Ok(()),
+        };
+        let push = db.get_setting("git_sync_push").ok().flatten().as_deref() == Some("true");
+        let title = db.get_session(session_id).map_err(|e| e.to_string())?.title;
+        (repo, push, title)
+    };
+    tokio::task::spawn_blocking(move || {
+        crate::export::git_sync::commit_file(&repo, &file, &format!("Notes: {}", title))?;
+        if push {
+            crate::export::git_sync::push(&repo)?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Uploads a zip of the transcript (Markdown) and the recording of a session
+/// to the S3 bucket as `poptranscribe/<id>.zip`, then deletes the local
+/// recording; the session stays in the database as transcript-only.
+/// Returns the object key.
+pub(crate) async fn archive_session_to_s3(
+    db: &std::sync::Mutex<crate::db::Database>,
+    session_id: &str,
+) -> Result<String, String> {
+    let (config, source) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let config = crate::upload::s3::S3Config::from_settings(&db).ok_or("Stockage S3 non configure.")?;
+        (config, ExportSource::load(&db, session_id)?)
+    };
+    let base_name = export_base_name(&source.session.title, session_id);
+    let staging = std::env::temp_dir().join(format!("poptranscribe_s3_{}", session_id));
+    let zip_path = staging.with_extension("zip");
+    let bundled = (|| {
+        std::fs::create_dir_all(&staging).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+        write_export_document("markdown", &staging, &base_name, &source.export(false))?;
+        let audio_path = source.session.audio_path.as_deref().map(std::path::Path::new);
+        if let Some(audio_path) = audio_path.filter(|p| p.is_file()) {
+            let ext = audio_path.extension().and_then(|e| e.to_str()).unwrap_or("wav");
+            std::fs::copy(audio_path, staging.join(format!("{}.{}", base_name, ext)))
+                .map_err(|e| format!("Erreur copie audio: {}", e))?;
+        }
+        crate::export::zip_directory(&staging, &zip_path)
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    bundled?;
+
+    let key = format!("poptranscribe/{}.zip", session_id);
+    let sent = config.put_object(&key, &zip_path).await;
+    let _ = std::fs::remove_file(&zip_path);
+    sent?;
+
+    // The recording is only deleted once the bucket holds it
+    if let Some(audio_path) = source.session.audio_path.as_deref().filter(|p| !p.is_empty()) {
+        std::fs::remove_file(audio_path).ok();
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.clear_session_audio(session_id).map_err(|e| e.to_string())?;
+    }
+    Ok(key)
+}
+
+/// Archives the sessions older than `s3_archive_after_days` that still have
+/// their recording, except protected ones. Returns how many were archived.
+pub(crate) async fn archive_old_sessions_to_s3(
+    db: &std::sync::Mutex<crate::db::Database>,
+) -> Result<usize, String> {
+    let sessions = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let Some(days) = db
+            .get_setting("s3_archive_after_days")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|&d| d > 0)
+        else {
+            return Ok(0);
+        };
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days);
+        db.list_sessions_with_audio_before(&cutoff.to_rfc3339())
+            .map_err(|e| e.to_string())?
+    };
+    let mut archived = 0;
+    for session in sessions.iter().filter(|s| !s.protected) {
+        match archive_session_to_s3(db, &session.id).await {
+            Ok(_) => archived += 1,
+            Err(e) => eprintln!("[s3] Failed to archive {}: {}", session.id, e),
+        }
+    }
+    Ok(archived)
+}
+
+/// Archives a session to the configured S3 bucket, see
+/// `archive_session_to_s3`. Returns the object key.
+#[tauri::command]
+pub async fn archive_to_s3(session_id: String, state: State<'_, AppState>) -> Result<String, String> {
+    let unlocked = is_unlocked(&state)?;
+    {
+        let active = state.active_session.lock().map_err(|e| e.to_string())?;
+        if active.as_ref().is_some_and(|a| a.id == session_id) {
+            return Err("Impossible d'archiver la session en cours.".to_string());
+        }
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        ensure_accessible(&session, unlocked)?;
+    }
+    archive_session_to_s3(&state.db, &session_id).await
+}
+
+/// Exports the session recording alone, transcoded to `format` (`mp3` by
+/// default, or `m4a`/`aac`, `opus`, `flac`) for sharing.
+#[tauri::command]
+pub async fn export_audio(
+    session_id: String,
+    format: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let unlocked = is_unlocked(&state)?;
+    let session = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        ensure_accessible(&session, unlocked)?;
+        session
+    };
+
+    let format_name = format.unwrap_or_else(|| "mp3".to_string());
+    let fmt = crate::audio::transcode::AudioFormat::from_name(&format_name)
+        .ok_or_else(|| format!("Format audio non supporte: {}", format_name))?;
+
+    let audio_path = session
+        .audio_path
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .ok_or("Cette session n'a pas d'enregistrement audio")?;
+    let audio_path = std::path::Path::new(audio_path);
+    if !audio_path.exists() {
+        return Err(format!("Fichier audio introuvable: {}", audio_path.display()));
+    }
+
+    let export_dir = resolve_export_dir(&state)?;
+    let output = export_dir.join(format!(
+        "{}.{}",
+        export_base_name(&session.title, &session_id),
+        fmt.extension()
+    ));
+    crate::audio::transcode::transcode(audio_path, &output, fmt).await?;
+    Ok(output.to_string_lossy().to_string())
+}
+
+/// Use configured export directory, or default to ~/Documents/poptranscribe/exports/
+fn resolve_export_dir(state: &State<'_, AppState>) -> Result<std::path::PathBuf, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(export_dir_setting(&db, "export_dir"))
+}
+
+/// Directory named by the `key` setting, or the default export directory.
+fn export_dir_setting(db: &crate::db::Database, key: &str) -> std::path::PathBuf {
+    match db.get_setting(key).ok().flatten() {
+        Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
+        _ => dirs::document_dir()
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join("poptranscribe")
+            .join("exports"),
+    }
+}
+
+/// Result of a digest generation, with the exported file path when a format
+/// was requested.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WeeklyDigest {
+    pub start_date: String,
+    pub end_date: String,
+    pub session_count: usize,
+    pub digest: Digest,
+    pub export_path: Option<String>,
+}
+
+/// Estimated API cost of an operation, to confirm before running it:
+/// `transcription` re-transcribes the session `session_id` and regenerates
+/// its title and summary, `digest` summarizes the sessions between
+/// `start_date` and `end_date` (see `generate_weekly_digest`).
+#[tauri::command]
+pub async fn estimate_processing_cost(
+    operation: String,
+    session_id: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<crate::mistral::pricing::CostEstimate, String> {
+    use crate::mistral::pricing;
+
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    match operation.as_str() {
+        "transcription" => {
+            let session_id = session_id.ok_or("Session manquante.")?;
+            let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+            ensure_accessible(&session, unlocked)?;
+            let duration = session.duration_secs.ok_or("Duree de l'enregistrement inconnue.")?;
+            Ok(pricing::estimate_transcription(
+                duration,
+                crate::mistral::batch::BATCH_MODEL,
+                crate::mistral::chat::CHAT_MODEL,
+            ))
+        }
+        "digest" => {
+            let (Some(start_date), Some(end_date)) = (start_date, end_date) else {
+                return Err("Periode manquante.".to_string());
+            };
+            let mut sessions = db
+                .list_sessions_between(&start_date, &end_date)
+                .map_err(|e| e.to_string())?;
+            if !unlocked {
+                sessions.retain(|s| !s.protected);
+            }
+            let meetings = digest_meetings(&db, &sessions)?;
+            Ok(pricing::estimate_chat(
+                meetings.chars().count(),
+                pricing::DIGEST_OUTPUT_TOKENS,
+                crate::mistral::chat::CHAT_MODEL,
+            ))
+        }
+        other => Err(format!("Operation inconnue: {}", other)),
+    }
+}
+
+/// Text sent to `generate_digest`: each session's summary, or a truncated
+/// transcript when no summary was generated.
+fn digest_meetings(db: &crate::db::Database, sessions: &[Session]) -> Result<String, String> {
+    let mut meetings = String::new();
+    for session in sessions {
+        let date = session.created_at.get(..10).unwrap_or(&session.created_at);
+        meetings.push_str(&format!("### {} ({})\n", session.title, date));
+        let summary: Option<Summary> = session
+            .summary_json
+            .as_ref()
+            .and_then(|json| serde_json::from_str(json).ok());
+        match summary {
+            Some(summary) => {
+                for point in &summary.key_points {
+                    meetings.push_str(&format!("- Point: {}\n", point));
+                }
+                for decision in &summary.decisions {
+                    meetings.push_str(&format!("- Decision: {}\n", decision));
+                }
+                for item in &summary.action_items {
+                    match item.assignee {
+                        Some(ref assignee) => meetings.push_str(&format!(
+                            "- Action: {} ({})\n",
+                            item.description, assignee
+                        )),
+                        None => meetings
+                            .push_str(&format!("- Action: {}\n", item.description)),
+                    }
+                }
+            }
+            None => {
+                let segments = db.get_segments(&session.id).map_err(|e| e.to_string())?;
+                let transcript: String = segments
+                    .iter()
+                    .map(|s| s.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                meetings.push_str(&transcript.chars().take(4000).collect::<String>());
+                meetings.push('\n');
+            }
+        }
+        meetings.push('\n');
+    }
+    Ok(meetings)
+}
+
+#[tauri::command]
+pub async fn generate_weekly_digest(
+    start_date: String,
+    end_date: String,
+    format: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<WeeklyDigest, String> {
+    let api_key = {
+        let key = state.api_key.lock().map_err(|e| e.to_string())?;
+        key.clone()
+    };
+    let demo = uses_demo_provider(&state.db);
+    if api_key.is_empty() && !demo {
+        return Err("Cle API Mistral non configuree. Allez dans Parametres.".to_string());
+    }
+
+    // Gather each session's summary, or a truncated transcript when no
+    // summary was generated.
+    let unlocked = is_unlocked(&state)?;
+    let (sessions, meetings) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let mut sessions = db
+            .list_sessions_between(&start_date, &end_date)
+            .map_err(|e| e.to_string())?;
+        // Protected sessions only contribute to the digest once unlocked
+        if !unlocked {
+            sessions.retain(|s| !s.protected);
+        }
+        let meetings = digest_meetings(&db, &sessions)?;
+        (sessions, meetings)
+    };
+
+    if sessions.is_empty() {
+        return Err("Aucune session sur cette periode.".to_string());
+    }
+
+    let digest = if demo {
+        crate::mistral::demo::digest()
+    } else {
+        crate::mistral::chat::generate_digest(&api_key, &meetings)
+            .await
+            .map_err(|e| e.to_string())?
+    };
+
+    let export_path = match format.as_deref() {
+        None => None,
+        Some(fmt @ ("markdown" | "pdf")) => {
+            let export_dir = resolve_export_dir(&state)?;
+            let ext = if fmt == "pdf" { "pdf" } else { "md" };
+            let file_path = export_dir.join(format!("Synthese_{}_{}.{}", start_date, end_date, ext));
+            if fmt == "pdf" {
+                crate::export::export_digest_pdf(&start_date, &end_date, &sessions, &digest, &file_path)?;
+            } else {
+                let md = crate::export::export_digest_markdown(&start_date, &end_date, &sessions, &digest);
+                crate::export::export_to_file(&md, &file_path)
+                    .map_err(|e| format!("Erreur ecriture fichier: {}", e))?;
+            }
+            Some(file_path.to_string_lossy().to_string())
+        }
+        Some(other) => return Err(format!("Export {} pas encore supporte", other)),
+    };
+
+    Ok(WeeklyDigest {
+        start_date,
+        end_date,
+        session_count: sessions.len(),
+        digest,
+        export_path,
+    })
+}
+
+#[tauri::command]
+pub async fn update_session_title(
+    session_id: String,
+    title: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        ensure_editable(&db, &session_id)?;
+        db.update_session_title(&session_id, &title).map_err(|e| e.to_string())?;
+    }
+    crate::refresh_tray(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_session(
+    session_id: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        ensure_editable(&db, &session_id)?;
+        db.delete_session(&session_id).map_err(|e| e.to_string())?;
+    }
+    let _ = std::fs::remove_dir_all(data_dir().join("attachments").join(&session_id));
+    crate::refresh_tray(&app);
+    Ok(())
+}
+
+/// Deletes the recording of a session but keeps its transcript and summary,
+/// for policies that forbid retaining voice recordings.
+#[tauri::command]
+pub async fn delete_session_audio(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    {
+        let active = state.active_session.lock().map_err(|e| e.to_string())?;
+        if active.as_ref().map(|a| a.id == session_id).unwrap_or(false) {
+            return Err("Impossible de supprimer l'audio d'une session en cours".to_string());
+        }
+    }
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = ensure_editable(&db, &session_id)?;
+    if let Some(audio_path) = session.audio_path.as_deref().filter(|p| !p.is_empty()) {
+        match std::fs::remove_file(audio_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Erreur suppression audio: {}", e)),
+        }
+    }
+    // Full-quality copy kept next to the 16kHz recording ("both" quality)
+    let _ = std::fs::remove_file(
+        data_dir().join("audio").join(format!("{}_native.wav", session_id)),
+    );
+    db.clear_session_audio(&session_id).map_err(|e| e.to_string())
+}
+
+/// Creates a copy of a session, e.g. to redact it before sharing. The
+/// recording is copied too unless `include_audio` is false.
+#[tauri::command]
+pub async fn duplicate_session(
+    session_id: String,
+    title: Option<String>,
+    include_audio: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let (original, new_id) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let original = db.get_session(&session_id).map_err(|e| e.to_string())?;
+        let title = title
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| format!("{} (copie)", original.title));
+        let new_id = db
+            .duplicate_session(&session_id, &title)
+            .map_err(|e| e.to_string())?;
+        (original, new_id)
+    };
+
+    if include_audio.unwrap_or(true) {
+        if let Some(audio_path) = original.audio_path.filter(|p| !p.is_empty()) {
+            let source = std::path::PathBuf::from(audio_path);
+            let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("wav");
+            let dest = data_dir().join("audio").join(format!("{}.{}", new_id, ext));
+            // The recording can be large: copied without holding the database
+            let copy_dest = dest.clone();
+            let copied = tokio::task::spawn_blocking(move || std::fs::copy(&source, &copy_dest))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|copied| copied.map_err(|e| format!("Erreur copie audio: {}", e)));
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            let saved = copied.and_then(|_| {
+                db.update_session_audio_path(&new_id, &dest.to_string_lossy())
+                    .map_err(|e| e.to_string())
+            });
+            if let Err(e) = saved {
+                // No audio-less copy is left behind
+                let _ = std::fs::remove_file(&dest);
+                let _ = db.delete_session(&new_id);
+                return Err(e);
+            }
+        }
+    }
+
+    crate::refresh_tray(&app);
+    Ok(new_id)
+}
+
+// ── Email templates ──────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn list_email_templates(state: State<'_, AppState>) -> Result<Vec<EmailTemplate>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.list_email_templates().map_err(|e| e.to_string())
+}
+
+/// Adds a follow-up email template, or updates template `id`. Subject and
+/// body may use the placeholders of `crate::email::PLACEHOLDERS`.
+#[tauri::command]
+pub async fn save_email_template(
+    id: Option<i64>,
+    name: String,
+    subject: String,
+    body: String,
+    state: State<'_, AppState>,
+) -> Result<EmailTemplate, String> {
+    if name.trim().is_empty() {
+        return Err("Le nom du modele est vide.".to_string());
+    }
+    let unknown = crate::email::unknown_placeholders(&format!("{}\n{}", subject, body));
+    if !unknown.is_empty() {
+        return Err(format!("Variables inconnues: {}", unknown.join(", ")));
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let taken = db
+        .list_email_templates()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .any(|t| Some(t.id) != id && t.name.to_lowercase() == name.trim().to_lowercase());
+    if taken {
+        return Err(format!("Un modele nomme \"{}\" existe deja.", name.trim()));
+    }
+    db.save_email_template(id, &name, &subject, &body)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_email_template(id: i64, state: State<'_, AppState>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.delete_email_template(id).map_err(|e| e.to_string())
+}
+
+/// Writes the follow-up email of a session from template `template_id`, or
+/// from the built-in one. Recipients are the participants with an email.
+#[tauri::command]
+pub async fn render_follow_up_email(
+    session_id: String,
+    template_id: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<crate::email::RenderedEmail, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    let (subject, body) = match template_id {
+        Some(id) => {
+            let template = db
+                .get_email_template(id)
+                .map_err(|_| "Modele d'email introuvable.".to_string())?;
+            (template.subject, template.body)
+        }
+        None => (
+            crate::email::DEFAULT_SUBJECT.to_string(),
+            crate::email::DEFAULT_BODY.to_string(),
+        ),
+    };
+    let summary: Option<Summary> = session
+        .summary_json
+        .as_ref()
+        .and_then(|json| serde_json::from_str(json).ok());
+    let participants = db.get_session_participants(&session_id).map_err(|e| e.to_string())?;
+    let context = crate::email::EmailContext {
+        session: &session,
+        summary: summary.as_ref(),
+        participants: &participants,
+    };
+    Ok(context.render(&subject, &body))
+}
+
+// ── Import ───────────────────────────────────────────────────────────
+
+/// Creates a session from a transcript exported by another tool (SRT, WebVTT,
+/// JSON, Otter.ai text, Whisper JSON, Teams VTT), without audio. `format`
+/// overrides the detection from the file. Returns the new session id.
+#[tauri::command]
+pub async fn import_transcript(
+    path: String,
+    title: Option<String>,
+    format: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    use crate::import::TranscriptFormat;
+
+    let path = std::path::PathBuf::from(path);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Erreur lecture fichier: {}", e))?;
+    let format = match format.as_deref() {
+        Some(name) => TranscriptFormat::from_name(name)
+            .ok_or_else(|| format!("Format de transcription inconnu: {}", name))?,
+        None => TranscriptFormat::detect(&path, &content).ok_or_else(|| {
+            format!(
+                "Format de transcription non supporte: {} (srt, vtt, json, txt)",
+                path.display()
+            )
+        })?,
+    };
+    let mut transcript = crate::import::parse_transcript(&content, format)?;
+
+    let title = title
+        .filter(|t| !t.trim().is_empty())
+        .or_else(|| transcript.title.clone())
+        .or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "Transcription importee".to_string());
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let cleanup = crate::cleanup::SegmentCleanup::from_db(&db);
+    for seg in &mut transcript.segments {
+        seg.text = cleanup.apply(&seg.text);
+    }
+    let session_id = db.create_session(&title, "import").map_err(|e| e.to_string())?;
+    db.set_session_language(&session_id, transcript.language.as_deref())
+        .map_err(|e| e.to_string())?;
+    if let Some(ref summary) = transcript.summary {
+        let json = serde_json::to_string(summary).map_err(|e| e.to_string())?;
+        db.save_summary(&session_id, &json, None).map_err(|e| e.to_string())?;
+        db.set_session_topics(&session_id, &summary.topics)
+            .map_err(|e| e.to_string())?;
+    }
+    let segments: Vec<NewSegment> = transcript
+        .segments
+        .iter()
+        .map(|seg| NewSegment {
+            text: &seg.text,
+            start: seg.start,
+            end: seg.end,
+            speaker: seg.speaker.as_deref(),
+            is_diarized: seg.speaker.is_some(),
+            language: transcript
+                .language
+                .as_deref()
+                .or_else(|| crate::language::detect_language(&seg.text)),
+        })
+        .collect();
+    db.save_segments_batch(&session_id, &segments)
+        .map_err(|e| e.to_string())?;
+    // Named speakers join the participants directory, once per session
+    let names: std::collections::BTreeSet<&str> = transcript
+        .segments
+        .iter()
+        .filter_map(|s| s.speaker.as_deref())
+        .filter(|s| !s.starts_with("speaker"))
+        .collect();
+    for name in names {
+        db.record_speaker_use(name).map_err(|e| e.to_string())?;
+    }
+    db.update_session_duration(&session_id, transcript.duration_secs())
+        .map_err(|e| e.to_string())?;
+    drop(db);
+
+    crate::refresh_tray(&app);
+    Ok(session_id)
+}
+
+/// Audio files picked up by `bulk_import`.
+const IMPORT_AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "m4a", "aac", "flac", "ogg", "opus"];
+
+pub(crate) fn is_importable_audio(path: &std::path::Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| IMPORT_AUDIO_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Creates a session for an existing audio file: the file is copied into
+/// the recordings folder and the session dated from its modification time.
+/// Returns the session id and the path of the copy.
+pub(crate) fn import_audio_file(
+    db: &crate::db::Database,
+    source: &std::path::Path,
+) -> Result<(String, std::path::PathBuf), String> {
+    let title = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Enregistrement importe".to_string());
+    let ext = source
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("wav")
+        .to_ascii_lowercase();
+
+    let session_id = db.create_session(&title, "import").map_err(|e| e.to_string())?;
+    let audio_dir = data_dir().join("audio");
+    std::fs::create_dir_all(&audio_dir).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    let dest = audio_dir.join(format!("{}.{}", session_id, ext));
+    if let Err(e) = std::fs::copy(source, &dest) {
+        let _ = db.delete_session(&session_id);
+        return Err(format!("Erreur copie audio: {}", e));
+    }
+    db.update_session_audio_path(&session_id, &dest.to_string_lossy())
+        .map_err(|e| e.to_string())?;
+
+    if let Ok(modified) = std::fs::metadata(source).and_then(|m| m.modified()) {
+        let created_at: chrono::DateTime<chrono::Utc> = modified.into();
+        db.set_session_created_at(&session_id, &created_at.to_rfc3339())
+            .map_err(|e| e.to_string())?;
+    }
+    // WAV headers give the duration; other formats get it from transcription
+    if let Ok(reader) = hound::WavReader::open(&dest) {
+        let spec = reader.spec();
+        db.update_session_duration(&session_id, reader.duration() as f64 / spec.sample_rate as f64)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok((session_id, dest))
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BulkImportProgress {
+    /// 1-based position in the queue.
+    pub index: usize,
+    pub total: usize,
+    pub session_id: String,
+    pub file_name: String,
+    /// `processing`, `done` or `error`
+    pub status: &'static str,
+    pub error: Option<String>,
+}
+
+/// Creates a session for every audio file of `folder` (not recursive) and
+/// transcribes them one after the other in the background, reporting
+/// through `bulk-import-progress` events. Returns the new session ids.
+#[tauri::command]
+pub async fn bulk_import(folder: String, app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    let mut files: Vec<std::path::PathBuf> = std::fs::read_dir(&folder)
+        .map_err(|e| format!("Impossible de lire le dossier: {}", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_importable_audio(p))
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err("Aucun fichier audio dans ce dossier".to_string());
+    }
+    // Copying large recordings must not block the async runtime
+    tokio::task::spawn_blocking(move || queue_audio_imports(&app, &files))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Creates a session for each file, then transcribes them one after the
+/// other in the background, reporting through `bulk-import-progress`
+/// events. Used by `bulk_import` and for files dropped on the app. A file
+/// that cannot be imported is reported with a `session-error` event and
+/// skipped; fails only when none could be. Blocking: files are copied.
+pub(crate) fn queue_audio_imports(
+    app: &tauri::AppHandle,
+    files: &[std::path::PathBuf],
+) -> Result<Vec<String>, String> {
+    let state = app.state::<AppState>();
+    {
+        let key = state.api_key.lock().map_err(|e| e.to_string())?;
+        if key.is_empty() && !uses_demo_provider(&state.db) {
+            return Err("Cle API Mistral non configuree. Allez dans Parametres.".to_string());
+        }
+    }
+
+    let mut queue = Vec::with_capacity(files.len());
+    let mut failures = Vec::new();
+    for file in files {
+        let file_name = file
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // Locked per file, so that the app stays usable during the copies
+        let imported = state
+            .db
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|db| import_audio_file(&db, file));
+        match imported {
+            Ok((session_id, audio_path)) => queue.push((session_id, audio_path, file_name)),
+            Err(e) => {
+                eprintln!("[import] {} skipped: {}", file.display(), e);
+                failures.push(format!("{}: {}", file_name, e));
+            }
+        }
+    }
+    if queue.is_empty() {
+        return Err(failures.join("; "));
+    }
+    for failure in &failures {
+        let _ = app.emit("session-error", format!("Import de {}", failure));
+    }
+    let session_ids: Vec<String> = queue.iter().map(|(id, _, _)| id.clone()).collect();
+
+    // Sequential, to stay within the API rate limits
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let total = queue.len();
+        for (i, (session_id, audio_path, file_name)) in queue.into_iter().enumerate() {
+            let progress = |status, error| BulkImportProgress {
+                index: i + 1,
+                total,
+                session_id: session_id.clone(),
+                file_name: file_name.clone(),
+                status,
+                error,
+            };
+            let _ = app.emit("bulk-import-progress", progress("processing", None));
+            let result = process_recording(app.clone(), session_id.clone(), audio_path).await;
+            let _ = app.emit(
+                "bulk-import-progress",
+                match result {
+                    Ok(()) => progress("done", None),
+                    Err(e) => progress("error", Some(e)),
+                },
+            );
+        }
+    });
+
+    Ok(session_ids)
+}
+
+// ── Attachments ──────────────────────────────────────────────────────
+
+/// Copies `path` into the session's attachment folder and records it.
+#[tauri::command]
+pub async fn attach_file(
+    session_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<Attachment, String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        ensure_editable(&db, &session_id)?;
+    }
+    let source = std::path::Path::new(&path);
+    let file_name = source
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Nom de fichier invalide")?
+        .to_string();
+
+    let dir = data_dir().join("attachments").join(&session_id);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+
+    // Avoid overwriting a previous attachment with the same name
+    let mut dest = dir.join(&file_name);
+    let mut n = 1;
+    while dest.exists() {
+        dest = dir.join(format!("{}_{}", n, file_name));
+        n += 1;
+    }
+    let size = std::fs::copy(source, &dest).map_err(|e| format!("Erreur copie fichier: {}", e))?;
+    let stored_name = dest
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(&file_name)
+        .to_string();
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = db
+        .add_attachment(&session_id, &stored_name, &dest.to_string_lossy(), size as i64)
+        .map_err(|e| e.to_string())?;
+    db.get_attachment(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_attachments(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<Attachment>, String> {
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    ensure_accessible(&session, unlocked)?;
+    db.get_attachments(&session_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_attachment(
+    attachment_id: i64,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let attachment = db.get_attachment(attachment_id).map_err(|e| e.to_string())?;
+    ensure_editable(&db, &attachment.session_id)?;
+    let _ = std::fs::remove_file(&attachment.path);
+    db.delete_attachment(attachment_id).map_err(|e| e.to_string())
+}
+
+// ── Archived sessions ────────────────────────────────────────────────
+
+/// Loads a session, refusing it when archived: approved minutes must not
+/// change without an explicit `unarchive_session`.
+fn ensure_editable(db: &crate::db::Database, session_id: &str) -> Result<Session, String> {
+    let session = db.get_session(session_id).map_err(|e| e.to_string())?;
+    if session.archived {
+        return Err("Session archivee en lecture seule: desarchivez-la pour la modifier".to_string());
+    }
+    Ok(session)
+}
+
+/// Marks a session as finalized; edits and deletion are refused until it
+/// is unarchived.
+#[tauri::command]
+pub async fn archive_session(session_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_session(&session_id).map_err(|e| e.to_string())?;
+    db.set_session_archived(&session_id, true).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn unarchive_session(session_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_session(&session_id).map_err(|e| e.to_string())?;
+    db.set_session_archived(&session_id, false).map_err(|e| e.to_string())
+}
+
+/// Runs the audio archival policy immediately (it also runs daily in the
+/// background). Returns the number of recordings archived.
+#[tauri::command]
+pub async fn archive_old_audio(state: State<'_, AppState>) -> Result<usize, String> {
+    let db = Arc::clone(&state.db);
+    crate::audio::archive::archive_old_audio(&db).await
+}
+
+// ── Settings ─────────────────────────────────────────────────────────
+
+#[tauri::command]
+pub async fn get_api_key(state: State<'_, AppState>) -> Result<String, String> {
+    let key = state.api_key.lock().map_err(|e| e.to_string())?;
+    Ok(key.clone())
+}
+
+#[tauri::command]
+pub async fn set_api_key(key: String, state: State<'_, AppState>) -> Result<(), String> {
+    let mut api_key = state.api_key.lock().map_err(|e| e.to_string())?;
+    *api_key = key.clone();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_setting("api_key", &key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_setting(key: String, state: State<'_, AppState>) -> Result<Option<String>, String> {
+    if key == crate::passcode::PASSCODE_SETTING {
+        return Err("Parametre non accessible".to_string());
+    }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_setting(&key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_setting(
+    key: String,
+    value: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    // The passcode hash is only written through `set_passcode`
+    if key == crate::passcode::PASSCODE_SETTING {
+        return Err("Parametre non accessible".to_string());
+    }
+    // If the key is "api_key", also update the in-memory cache
+    if key == "api_key" {
+        let mut api_key = state.api_key.lock().map_err(|e| e.to_string())?;
+        *api_key = value.clone();
+    }
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.set_setting(&key, &value).map_err(|e| e.to_string())?;
+    }
+    if key == crate::watch::WATCH_FOLDER_SETTING {
+        crate::watch::restart(&app)?;
+    }
+    #[cfg(desktop)]
+    if key == crate::autostart::LAUNCH_AT_LOGIN_SETTING {
+        crate::autostart::apply(&app, value == "true")?;
+    }
+    Ok(())
+}
+
+// ── Passcode ─────────────────────────────────────────────────────────
+
+fn is_unlocked(state: &State<'_, AppState>) -> Result<bool, String> {
+    let unlocked = state.unlocked.lock().map_err(|e| e.to_string())?;
+    Ok(*unlocked)
+}
+
+/// Refuses access to a protected session's content while locked.
+fn ensure_accessible(session: &Session, unlocked: bool) -> Result<(), String> {
+    if session.protected && !unlocked {
+        return Err("Session protegee: saisissez le code d'acces".to_string());
+    }
+    Ok(())
+}
+
+fn stored_passcode_hash(state: &State<'_, AppState>) -> Result<Option<String>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.get_setting(crate::passcode::PASSCODE_SETTING)
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LockStatus {
+    pub has_passcode: bool,
+    pub unlocked: bool,
+}
+
+#[tauri::command]
+pub async fn get_lock_status(state: State<'_, AppState>) -> Result<LockStatus, String> {
+    Ok(LockStatus {
+        has_passcode: stored_passcode_hash(&state)?.is_some(),
+        unlocked: is_unlocked(&state)?,
+    })
+}
+
+/// Sets or changes the app passcode; changing it requires the current one.
+#[tauri::command]
+pub async fn set_passcode(
+    current: Option<String>,
+    passcode: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if let Some(hash) = stored_passcode_hash(&state)? {
+        let current = current.unwrap_or_default();
+        if !crate::passcode::verify_passcode(&current, &hash) {
+            return Err("Code d'acces actuel incorrect".to_string());
+        }
+    }
+    if passcode.chars().count() < crate::passcode::MIN_PASSCODE_LEN {
+        return Err(format!(
+            "Le code d'acces doit contenir au moins {} caracteres",
+            crate::passcode::MIN_PASSCODE_LEN
+        ));
+    }
+    let hash = crate::passcode::hash_passcode(&passcode)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_setting(crate::passcode::PASSCODE_SETTING, &hash)
+        .map_err(|e| e.to_string())
+}
+
+/// Verifies the passcode and reveals protected sessions until `lock_sessions`
+/// is called or the app restarts.
+#[tauri::command]
+pub async fn unlock_sessions(passcode: String, state: State<'_, AppState>) -> Result<(), String> {
+    let hash = stored_passcode_hash(&state)?
+        .ok_or_else(|| "Aucun code d'acces configure".to_string())?;
+    if !crate::passcode::verify_passcode(&passcode, &hash) {
+        return Err("Code d'acces incorrect".to_string());
+    }
+    let mut unlocked = state.unlocked.lock().map_err(|e| e.to_string())?;
+    *unlocked = true;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn lock_sessions(state: State<'_, AppState>) -> Result<(), String> {
+    let mut unlocked = state.unlocked.lock().map_err(|e| e.to_string())?;
+    *unlocked = false;
+    Ok(())
+}
+
+/// Marks a session as protected. Requires a configured passcode, and
+/// removing the protection requires being unlocked.
+#[tauri::command]
+pub async fn set_session_protected(
+    session_id: String,
+    protected: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if stored_passcode_hash(&state)?.is_none() {
+        return Err("Configurez d'abord un code d'acces dans les Parametres".to_string());
+    }
+    let unlocked = is_unlocked(&state)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
+    if !protected {
+        ensure_accessible(&session, unlocked)?;
+    }
+    db.set_session_protected(&session_id, protected)
+        .map_err(|e| e.to_string())
+}
+
+// ── Audio devices ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AudioDevice {
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// Reads the input-device settings: `input_device` applies to both modes
+/// unless `input_device_visio` / `input_device_inperson` is set.
+fn input_device_preferences(db: &crate::db::Database) -> DevicePreferences {
+    let setting = |key: &str| {
+        db.get_setting(key)
+            .ok()
+            .flatten()
+            .filter(|v| !v.trim().is_empty())
+    };
+    DevicePreferences {
+        default: setting("input_device"),
+        visio: setting("input_device_visio"),
+        in_person: setting("input_device_inperson"),
+    }
+}
+
+#[tauri::command]
+pub async fn list_input_devices() -> Result<Vec<AudioDevice>, String> {
+    let host = cpal::default_host();
+    let default_name = host
+        .default_input_device()
+        .and_then(|d| d.name().ok());
+
+    let devices = host
+        .input_devices()
+        .map_err(|e| format!("Impossible de lister les peripheriques audio: {}", e))?;
+
+    let mut result = Vec::new();
+    for device in devices {
+        if let Ok(name) = device.name() {
+            let is_default = default_name.as_deref() == Some(&name);
+            result.push(AudioDevice { name, is_default });
+        }
+    }
+    Ok(result)
+}
+
+/// Length of the recording made by `test_microphone`.
+const MIC_TEST_SECS: u64 = 3;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MicrophoneTest {
+    /// Device that was tested (`None` = system default)
+    pub device_name: Option<String>,
+    pub sample_rate: u32,
+    pub duration_secs: f64,
+    /// Levels as fractions of full scale (0.0 to 1.0)
+    pub peak: f64,
+    pub rms: f64,
+    pub peak_dbfs: f64,
+    pub rms_dbfs: f64,
+}
+
+/// Records a few seconds from `device_name` (or the in-person input device)
+/// with the configured gain, reports its levels and optionally plays it back.
+#[tauri::command]
+pub async fn test_microphone(
+    device_name: Option<String>,
+    playback: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<MicrophoneTest, String> {
+    {
+        let active = state.active_session.lock().map_err(|e| e.to_string())?;
+        if active.is_some() {
+            return Err("Impossible de tester le micro pendant un enregistrement".to_string());
+        }
+    }
+
+    let (devices, gain) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let mut devices = input_device_preferences(&db);
+        if device_name.is_some() {
+            devices.in_person = device_name;
+        }
+        let gain = db
+            .get_setting("input_gain")
+            .ok()
+            .flatten()
+            .and_then(|v| v.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        (devices, gain)
+    };
+
+    tokio::task::spawn_blocking(move || {
+        use crate::audio::mixer::{measure_levels, AudioLevels};
+        use std::sync::mpsc::RecvTimeoutError;
+        use std::time::{Duration, Instant};
+
+        let device_name = devices.in_person.clone().or_else(|| devices.default.clone());
+        let mut capturer = AudioCapturer::new(CaptureMode::InPerson, devices);
+        capturer.set_gain(gain);
+        let receiver = capturer
+            .start()
+            .map_err(|e| format!("Impossible d'ouvrir le micro: {}", e))?;
+        let sample_rate = capturer.actual_sample_rate;
+
+        let deadline = Instant::now() + Duration::from_secs(MIC_TEST_SECS);
+        let mut samples: Vec<i16> = Vec::new();
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match receiver.recv_timeout(remaining) {
+                Ok(chunk) => samples.extend(chunk),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        capturer.stop();
+
+        if samples.is_empty() {
+            return Err("Aucun son recu du micro".to_string());
+        }
+
+        let levels = measure_levels(&samples);
+        if playback.unwrap_or(false) {
+            crate::audio::playback::play_blocking(&samples, sample_rate)
+                .map_err(|e| format!("Lecture impossible: {}", e))?;
+        }
+
+        Ok(MicrophoneTest {
+            device_name,
+            sample_rate,
+            duration_secs: samples.len() as f64 / sample_rate as f64,
+            peak: levels.peak,
+            rms: levels.rms,
+            peak_dbfs: AudioLevels::to_dbfs(levels.peak),
+            rms_dbfs: AudioLevels::to_dbfs(levels.rms),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Running applications that can be selected as the only system-audio
+/// source in visio mode (Windows; empty elsewhere).
+#[tauri::command]
+pub async fn list_audio_applications() -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(crate::audio::capture::running_applications)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ── Diagnostics ──────────────────────────────────────────────────────
 
-#[tauri::command]
-pub async fn get_sessions(state: State<'_, AppState>) -> Result<Vec<Session>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.list_sessions().map_err(|e| e.to_string())
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Error,
+    /// Not applicable on this platform or blocked by an earlier failure
+    Skipped,
 }
 
-#[tauri::command]
-pub async fn get_session_detail(
-    session_id: String,
-    state: State<'_, AppState>,
-) -> Result<SessionDetail, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
-    let segments = db.get_segments(&session_id).map_err(|e| e.to_string())?;
-    let summary: Option<Summary> = session
-        .summary_json
-        .as_ref()
-        .and_then(|json| serde_json::from_str(json).ok());
-    Ok(SessionDetail {
-        session,
-        segments,
-        summary,
-    })
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HealthCheck {
+    pub status: HealthStatus,
+    pub message: String,
 }
 
-// ── Search ───────────────────────────────────────────────────────────
+impl HealthCheck {
+    fn ok(message: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Ok,
+            message: message.into(),
+        }
+    }
 
-#[tauri::command]
-pub async fn search_text(
-    query: String,
-    session_id: Option<String>,
-    state: State<'_, AppState>,
-) -> Result<Vec<Segment>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.search_text(&query, session_id.as_deref())
-        .map_err(|e| e.to_string())
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Error,
+            message: message.into(),
+        }
+    }
+
+    fn skipped(message: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Skipped,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HealthReport {
+    pub network: HealthCheck,
+    pub api_key: HealthCheck,
+    pub input_device: HealthCheck,
+    pub screen_recording: HealthCheck,
 }
 
+/// Checks everything a recording depends on, for the settings screen.
 #[tauri::command]
-pub async fn search_llm(
-    query: String,
-    session_id: String,
-    live_text: Option<String>,
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let segments = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
-        db.get_segments(&session_id).map_err(|e| e.to_string())?
-    };
+pub async fn run_health_check(state: State<'_, AppState>) -> Result<HealthReport, String> {
+    use crate::mistral::KeyStatus;
+
     let api_key = {
         let key = state.api_key.lock().map_err(|e| e.to_string())?;
         key.clone()
     };
 
-    let mut transcript: String = segments
-        .iter()
-        .map(|s| {
-            if let Some(ref speaker) = s.speaker {
-                format!("{}: {}", speaker, s.text)
-            } else {
-                s.text.clone()
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    // Append live (in-progress) text from real-time transcription
-    if let Some(ref lt) = live_text {
-        if !lt.is_empty() {
-            if !transcript.is_empty() {
-                transcript.push('\n');
-            }
-            transcript.push_str(lt);
+    let (network, api_key) = match crate::mistral::check_api_key(&api_key).await {
+        Err(e) => (
+            HealthCheck::error(format!("api.mistral.ai injoignable: {}", e)),
+            HealthCheck::skipped("Reseau indisponible"),
+        ),
+        Ok(status) => {
+            let network = HealthCheck::ok("api.mistral.ai joignable");
+            let api_key = match status {
+                _ if api_key.is_empty() => HealthCheck::error("Aucune cle API configuree"),
+                KeyStatus::Valid => HealthCheck::ok("Cle API valide"),
+                KeyStatus::Rejected => HealthCheck::error("Cle API refusee par Mistral"),
+                KeyStatus::Unexpected(code) => {
+                    HealthCheck::error(format!("Reponse inattendue de Mistral (HTTP {})", code))
+                }
+            };
+            (network, api_key)
         }
-    }
+    };
 
-    if transcript.is_empty() {
-        return Err("Aucune transcription disponible pour cette session.".to_string());
-    }
+    let input_device = match cpal::default_host().default_input_device() {
+        Some(device) => HealthCheck::ok(
+            device.name().unwrap_or_else(|_| "Peripherique par defaut".to_string()),
+        ),
+        None => HealthCheck::error("Aucun microphone disponible"),
+    };
 
-    crate::mistral::chat::search_transcript(&api_key, &transcript, &query)
+    #[cfg(target_os = "macos")]
+    let screen_recording = match tokio::task::spawn_blocking(crate::audio::capture::check_screen_capture)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?
+    {
+        Ok(()) => HealthCheck::ok("Enregistrement de l'ecran autorise"),
+        Err(e) => HealthCheck::error(format!(
+            "Autorisation d'enregistrement de l'ecran manquante: {}",
+            e
+        )),
+    };
+    #[cfg(not(target_os = "macos"))]
+    let screen_recording = HealthCheck::skipped("Non requis sur cette plateforme");
+
+    Ok(HealthReport {
+        network,
+        api_key,
+        input_device,
+        screen_recording,
+    })
 }
 
-// ── Speaker management ──────────────────────────────────────────────
+#[tauri::command]
+pub async fn check_permissions(
+    app: tauri::AppHandle,
+) -> Result<crate::permissions::Permissions, String> {
+    Ok(crate::permissions::check(&app))
+}
 
+/// Asks for the missing capture permissions and returns their new state.
 #[tauri::command]
-pub async fn rename_speaker(
-    session_id: String,
-    old_name: String,
-    new_name: String,
-    state: State<'_, AppState>,
-) -> Result<usize, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.rename_speaker(&session_id, &old_name, &new_name)
-        .map_err(|e| e.to_string())
+pub async fn request_permissions(
+    app: tauri::AppHandle,
+) -> Result<crate::permissions::Permissions, String> {
+    Ok(crate::permissions::request(&app).await)
 }
 
-// ── Export ───────────────────────────────────────────────────────────
+// ── Database maintenance ─────────────────────────────────────────────
+
+/// Days between two runs when "maintenance_schedule" is "monthly".
+const MAINTENANCE_INTERVAL_DAYS: i64 = 30;
+
+/// Whether the monthly maintenance is enabled and due.
+pub(crate) fn maintenance_due(db: &crate::db::Database) -> bool {
+    if db.get_setting("maintenance_schedule").ok().flatten().as_deref() != Some("monthly") {
+        return false;
+    }
+    db.get_setting("last_maintenance_at")
+        .ok()
+        .flatten()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok())
+        .map(|last| chrono::Utc::now().signed_duration_since(last).num_days() >= MAINTENANCE_INTERVAL_DAYS)
+        .unwrap_or(true)
+}
+
+/// Runs the maintenance and records when, holding the database for its
+/// whole duration.
+pub(crate) fn run_maintenance(
+    db: &std::sync::Mutex<crate::db::Database>,
+) -> Result<crate::db::MaintenanceReport, String> {
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let report = db.maintain().map_err(|e| format!("Maintenance de la base impossible: {}", e))?;
+    let _ = db.set_setting("last_maintenance_at", &chrono::Utc::now().to_rfc3339());
+    eprintln!(
+        "[maintenance] Database compacted from {} to {} bytes",
+        report.size_before, report.size_after
+    );
+    Ok(report)
+}
 
+/// Compacts the database (VACUUM, planner statistics, search index), which
+/// reclaims the space left by deleted sessions.
 #[tauri::command]
-pub async fn export_session(
-    session_id: String,
-    format: String,
+pub async fn maintain_database(
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    match format.as_str() {
-        "markdown" => {
-            // Load session detail from DB
-            let (session, segments, summary) = {
-                let db = state.db.lock().map_err(|e| e.to_string())?;
-                let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
-                let segments = db.get_segments(&session_id).map_err(|e| e.to_string())?;
-                let summary: Option<Summary> = session
-                    .summary_json
-                    .as_ref()
-                    .and_then(|json| serde_json::from_str(json).ok());
-                (session, segments, summary)
-            };
+) -> Result<crate::db::MaintenanceReport, String> {
+    {
+        let active = state.active_session.lock().map_err(|e| e.to_string())?;
+        if active.is_some() {
+            return Err("Maintenance impossible pendant un enregistrement.".to_string());
+        }
+    }
+    let db = Arc::clone(&state.db);
+    tokio::task::spawn_blocking(move || run_maintenance(&db))
+        .await
+        .map_err(|e| e.to_string())?
+}
 
-            // Generate markdown content
-            let md = crate::export::export_markdown(
-                &session.title,
-                &session.created_at,
-                session.duration_secs,
-                &segments,
-                &summary,
-            );
+/// Directory holding the database and the recordings.
+#[tauri::command]
+pub fn get_data_dir() -> String {
+    data_dir().to_string_lossy().into_owned()
+}
 
-            // Use configured export directory, or default to ~/Documents/poptranscribe/exports/
-            let export_dir = {
-                let db = state.db.lock().map_err(|e| e.to_string())?;
-                match db.get_setting("export_dir").ok().flatten() {
-                    Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
-                    _ => dirs::document_dir()
-                        .unwrap_or_else(|| std::path::PathBuf::from("."))
-                        .join("poptranscribe")
-                        .join("exports"),
-                }
-            };
+/// Folders of the data directory that move along with the database.
+pub(crate) const DATA_SUBDIRS: [&str; 3] = ["audio", "attachments", "archive"];
 
-            // Sanitize title for filename
-            let safe_title: String = session
-                .title
-                .chars()
-                .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
-                .collect();
-            let filename = format!("{}_{}.md", safe_title, session_id.split('-').next().unwrap_or("export"));
-            let file_path = export_dir.join(&filename);
+/// Files of the data directory that move along with the database.
+const DATA_FILES: [&str; 4] = [
+    "poptranscribe.db",
+    "poptranscribe.db-wal",
+    "poptranscribe.db-shm",
+    crate::backup::KEY_FILE,
+];
 
-            crate::export::export_to_file(&md, &file_path)
-                .map_err(|e| format!("Erreur ecriture fichier: {}", e))?;
+fn copy_dir_all(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
 
-            Ok(file_path.to_string_lossy().to_string())
+/// Copies the database, the recordings and the backup key to `new_dir` and
+/// opens the copy.
+fn copy_data_dir(
+    db: &crate::db::Database,
+    old_dir: &std::path::Path,
+    new_dir: &std::path::Path,
+) -> Result<crate::db::Database, String> {
+    let db_path = new_dir.join("poptranscribe.db");
+    db.copy_to(&db_path)
+        .map_err(|e| format!("Copie de la base impossible: {}", e))?;
+    for sub in DATA_SUBDIRS {
+        let from = old_dir.join(sub);
+        if from.is_dir() {
+            copy_dir_all(&from, &new_dir.join(sub))
+                .map_err(|e| format!("Copie du dossier {} impossible: {}", sub, e))?;
         }
-        "pdf" => {
-            let (session, segments, summary) = {
-                let db = state.db.lock().map_err(|e| e.to_string())?;
-                let session = db.get_session(&session_id).map_err(|e| e.to_string())?;
-                let segments = db.get_segments(&session_id).map_err(|e| e.to_string())?;
-                let summary: Option<Summary> = session
-                    .summary_json
-                    .as_ref()
-                    .and_then(|json| serde_json::from_str(json).ok());
-                (session, segments, summary)
-            };
+    }
+    // Without it, the backups made so far could not be restored
+    let key = old_dir.join(crate::backup::KEY_FILE);
+    if key.is_file() {
+        std::fs::copy(&key, new_dir.join(crate::backup::KEY_FILE))
+            .map_err(|e| format!("Copie de la cle de sauvegarde impossible: {}", e))?;
+    }
+    let moved = crate::db::Database::new(&db_path)
+        .map_err(|e| format!("Ouverture de la nouvelle base impossible: {}", e))?;
+    moved
+        .relocate_files(&old_dir.to_string_lossy(), &new_dir.to_string_lossy())
+        .map_err(|e| e.to_string())?;
+    Ok(moved)
+}
 
-            let export_dir = {
-                let db = state.db.lock().map_err(|e| e.to_string())?;
-                match db.get_setting("export_dir").ok().flatten() {
-                    Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
-                    _ => dirs::document_dir()
-                        .unwrap_or_else(|| std::path::PathBuf::from("."))
-                        .join("poptranscribe")
-                        .join("exports"),
-                }
-            };
+/// Moves the database, the recordings and the backup key to `new_dir` (an
+/// external drive, a synced folder...) and reopens the database there. The
+/// old files are deleted once the new location is in use. Returns the new
+/// directory.
+#[tauri::command]
+pub async fn move_data_dir(state: State<'_, AppState>, new_dir: String) -> Result<String, String> {
+    {
+        let active = state.active_session.lock().map_err(|e| e.to_string())?;
+        if active.is_some() {
+            return Err("Deplacement impossible pendant un enregistrement.".to_string());
+        }
+    }
+    let new_dir = if new_dir.trim().is_empty() {
+        crate::app_state::default_data_dir()
+    } else {
+        std::path::PathBuf::from(new_dir.trim())
+    };
+    let old_dir = data_dir();
+    if new_dir == old_dir {
+        return Ok(new_dir.to_string_lossy().into_owned());
+    }
+    if new_dir.join("poptranscribe.db").exists() {
+        return Err("Ce dossier contient deja une base PopTranscribe.".to_string());
+    }
+    if new_dir.starts_with(&old_dir) {
+        return Err("Le nouveau dossier ne peut pas etre dans l'ancien.".to_string());
+    }
 
-            let safe_title: String = session
-                .title
-                .chars()
-                .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
-                .collect();
-            let filename = format!("{}_{}.pdf", safe_title, session_id.split('-').next().unwrap_or("export"));
-            let file_path = export_dir.join(&filename);
+    let db_arc = Arc::clone(&state.db);
+    tokio::task::spawn_blocking(move || {
+        let mut db = db_arc.lock().map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(&new_dir).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+        let created: Vec<_> = DATA_SUBDIRS
+            .iter()
+            .map(|sub| new_dir.join(sub))
+            .filter(|dir| !dir.exists())
+            .collect();
 
-            crate::export::export_pdf(
-                &session.title,
-                &session.created_at,
-                session.duration_secs,
-                &segments,
-                &summary,
-                &file_path,
-            )?;
+        // Leaves the destination as it was found
+        let clean_up = || {
+            for name in DATA_FILES {
+                std::fs::remove_file(new_dir.join(name)).ok();
+            }
+            for dir in &created {
+                std::fs::remove_dir_all(dir).ok();
+            }
+        };
+        let moved = copy_data_dir(&db, &old_dir, &new_dir).inspect_err(|_| clean_up())?;
+        if let Err(e) = crate::app_state::set_data_dir(&new_dir) {
+            // Closes the copy before its files are removed
+            drop(moved);
+            clean_up();
+            return Err(format!("Enregistrement du dossier impossible: {}", e));
+        }
+        // Dropping the old connection closes the old file
+        *db = moved;
 
-            Ok(file_path.to_string_lossy().to_string())
+        for name in DATA_FILES {
+            std::fs::remove_file(old_dir.join(name)).ok();
         }
-        other => Err(format!("Export {} pas encore supporte", other)),
-    }
+        for sub in DATA_SUBDIRS {
+            std::fs::remove_dir_all(old_dir.join(sub)).ok();
+        }
+        eprintln!("[data] Data moved from {} to {}", old_dir.display(), new_dir.display());
+        Ok(new_dir.to_string_lossy().into_owned())
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Checks the database file, its search index and the recordings on disk.
+/// With `repair`, also fixes what it found (see `Database::check`).
 #[tauri::command]
-pub async fn update_session_title(
-    session_id: String,
-    title: String,
+pub async fn check_database(
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.update_session_title(&session_id, &title).map_err(|e| e.to_string())
+    repair: Option<bool>,
+) -> Result<crate::db::IntegrityReport, String> {
+    let repair = repair.unwrap_or(false);
+    if repair {
+        let active = state.active_session.lock().map_err(|e| e.to_string())?;
+        if active.is_some() {
+            return Err("Reparation impossible pendant un enregistrement.".to_string());
+        }
+    }
+    let db = Arc::clone(&state.db);
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        db.check(&data_dir().join("audio"), repair)
+            .map_err(|e| format!("Verification de la base impossible: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// ── Encrypted backups ────────────────────────────────────────────────
+
+enum BackupDestination {
+    Upload(crate::upload::UploadTarget),
+    S3(crate::upload::s3::S3Config),
+}
+
+/// Creates an encrypted backup (see `crate::backup`) and sends it to the
+/// `backup_target`. Returns the name of the sent file.
+pub(crate) async fn run_backup(db: &Arc<std::sync::Mutex<crate::db::Database>>) -> Result<String, String> {
+    let destination = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        match db.get_setting(crate::backup::BACKUP_TARGET_SETTING).ok().flatten().as_deref() {
+            Some("upload") => BackupDestination::Upload(
+                crate::upload::UploadTarget::from_settings(&db).ok_or("Destination d'envoi non configuree.")?,
+            ),
+            Some("s3") => BackupDestination::S3(
+                crate::upload::s3::S3Config::from_settings(&db).ok_or("Stockage S3 non configure.")?,
+            ),
+            _ => return Err("Destination de sauvegarde non configuree.".to_string()),
+        }
+    };
+    let dir = data_dir();
+    let key = crate::backup::load_or_create_key(&dir)?;
+    let name = format!("poptranscribe-{}.ptbackup", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+    let output = std::env::temp_dir().join(&name);
+
+    let (snapshot_db, snapshot_output) = (Arc::clone(db), output.clone());
+    tokio::task::spawn_blocking(move || crate::backup::create(&snapshot_db, &dir, &key, &snapshot_output))
+        .await
+        .map_err(|e| e.to_string())??;
+    let sent = match destination {
+        BackupDestination::Upload(target) => target.upload(&output).await,
+        BackupDestination::S3(config) => config.put_object(&format!("poptranscribe/backups/{}", name), &output).await,
+    };
+    let _ = std::fs::remove_file(&output);
+    sent?;
+
+    let db = db.lock().map_err(|e| e.to_string())?;
+    let _ = db.set_setting(crate::backup::LAST_BACKUP_SETTING, &chrono::Utc::now().to_rfc3339());
+    eprintln!("[backup] Sent {}", name);
+    Ok(name)
 }
 
 #[tauri::command]
-pub async fn delete_session(
-    session_id: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.delete_session(&session_id).map_err(|e| e.to_string())
+pub async fn backup_now(state: State<'_, AppState>) -> Result<String, String> {
+    run_backup(&state.db).await
 }
 
-// ── Settings ─────────────────────────────────────────────────────────
+/// Key of the encrypted backups, to keep somewhere safe: restoring on
+/// another device needs it. Requires the passcode, when one is set.
+#[tauri::command]
+pub async fn get_backup_key(state: State<'_, AppState>) -> Result<String, String> {
+    if stored_passcode_hash(&state)?.is_some() && !is_unlocked(&state)? {
+        return Err("Saisissez d'abord le code d'acces".to_string());
+    }
+    crate::backup::load_or_create_key(&data_dir()).map(|key| crate::backup::encode_key(&key))
+}
 
+/// Replaces the database and restores the files of an encrypted backup
+/// downloaded from the backup target. `key` is the one of the device that
+/// made it, this device's key by default (there must be one); it becomes
+/// this device's key if it had none.
 #[tauri::command]
-pub async fn get_api_key(state: State<'_, AppState>) -> Result<String, String> {
-    let key = state.api_key.lock().map_err(|e| e.to_string())?;
-    Ok(key.clone())
+pub async fn restore_backup(
+    path: String,
+    key: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    {
+        let active = state.active_session.lock().map_err(|e| e.to_string())?;
+        if active.is_some() {
+            return Err("Restauration impossible pendant un enregistrement.".to_string());
+        }
+    }
+    let dir = data_dir();
+    let given = key.as_deref().map(crate::backup::decode_key).transpose()?;
+    let key = match given {
+        Some(key) => key,
+        None => crate::backup::load_key(&dir)?,
+    };
+
+    let db = Arc::clone(&state.db);
+    let restore_dir = dir.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut db = db.lock().map_err(|e| e.to_string())?;
+        crate::backup::restore(&mut db, std::path::Path::new(&path), &key, &restore_dir)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    if given.is_some() && !crate::backup::has_key(&dir) {
+        crate::backup::save_key(&dir, &key)?;
+    }
+
+    // Settings come from the backup too
+    let api_key = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting("api_key").ok().flatten().unwrap_or_default()
+    };
+    *state.api_key.lock().map_err(|e| e.to_string())? = api_key;
+    if let Err(e) = crate::watch::restart(&app) {
+        eprintln!("[watch] {}", e);
+    }
+    Ok(())
 }
 
+// ── Scheduled recordings ─────────────────────────────────────────────
+
+/// Schedules a recording. `start_at` is RFC 3339; `recurrence` is `daily`,
+/// `weekdays`, `weekly` or absent for a single recording.
 #[tauri::command]
-pub async fn set_api_key(key: String, state: State<'_, AppState>) -> Result<(), String> {
-    let mut api_key = state.api_key.lock().map_err(|e| e.to_string())?;
-    *api_key = key.clone();
+pub async fn create_scheduled_recording(
+    title: String,
+    mode: String,
+    start_at: String,
+    duration_minutes: u32,
+    recurrence: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<crate::db::ScheduledRecording, String> {
+    if mode != "visio" && mode != "presentiel" {
+        return Err(format!("Mode inconnu: {}", mode));
+    }
+    let start = chrono::DateTime::parse_from_rfc3339(&start_at)
+        .map_err(|_| format!("Date invalide: {}", start_at))?;
+    if duration_minutes == 0 {
+        return Err("La duree doit etre d'au moins une minute".to_string());
+    }
+    let recurrence = recurrence.filter(|r| !r.is_empty());
+    if let Some(ref r) = recurrence {
+        if !crate::db::RECURRENCES.contains(&r.as_str()) {
+            return Err(format!("Recurrence inconnue: {}", r));
+        }
+    }
+    let title = if title.trim().is_empty() { "Enregistrement programme".to_string() } else { title };
+
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.set_setting("api_key", &key).map_err(|e| e.to_string())
+    let id = db
+        .create_scheduled_recording(&title, &mode, &start.to_rfc3339(), duration_minutes, recurrence.as_deref())
+        .map_err(|e| e.to_string())?;
+    db.list_scheduled_recordings()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| "Enregistrement programme introuvable".to_string())
 }
 
 #[tauri::command]
-pub async fn get_setting(key: String, state: State<'_, AppState>) -> Result<Option<String>, String> {
+pub async fn list_scheduled_recordings(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::db::ScheduledRecording>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.get_setting(&key).map_err(|e| e.to_string())
+    db.list_scheduled_recordings().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn set_setting(key: String, value: String, state: State<'_, AppState>) -> Result<(), String> {
-    // If the key is "api_key", also update the in-memory cache
-    if key == "api_key" {
-        let mut api_key = state.api_key.lock().map_err(|e| e.to_string())?;
-        *api_key = value.clone();
-    }
+pub async fn delete_scheduled_recording(id: i64, state: State<'_, AppState>) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.set_setting(&key, &value).map_err(|e| e.to_string())
+    db.delete_scheduled_recording(id).map_err(|e| e.to_string())
 }
 
-// ── Audio devices ────────────────────────────────────────────────────
+// ── Sync ─────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, serde::Serialize)]
-pub struct AudioDevice {
-    pub name: String,
-    pub is_default: bool,
+/// Syncs with the `sync_folder` setting, leaving out the session being
+/// recorded. Emits `sync-complete` when sessions changed here.
+pub(crate) async fn run_sync(app: &tauri::AppHandle) -> Result<crate::sync::SyncReport, String> {
+    let state = app.state::<AppState>();
+    let folder = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        crate::sync::folder(&db).ok_or("Aucun dossier de synchronisation configure")?
+    };
+    let recording = state
+        .active_session
+        .lock()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .map(|a| a.id.clone());
+    let db = Arc::clone(&state.db);
+    let report = tokio::task::spawn_blocking(move || crate::sync::sync(&db, &folder, recording.as_deref()))
+        .await
+        .map_err(|e| e.to_string())??;
+    if report.pulled > 0 || report.deleted > 0 {
+        let _ = app.emit("sync-complete", &report);
+    }
+    Ok(report)
 }
 
 #[tauri::command]
-pub async fn list_input_devices() -> Result<Vec<AudioDevice>, String> {
-    let host = cpal::default_host();
-    let default_name = host
-        .default_input_device()
-        .and_then(|d| d.name().ok());
+pub async fn sync_sessions(app: tauri::AppHandle) -> Result<crate::sync::SyncReport, String> {
+    run_sync(&app).await
+}
 
-    let devices = host
-        .input_devices()
-        .map_err(|e| format!("Impossible de lister les peripheriques audio: {}", e))?;
+// ── Deep links ───────────────────────────────────────────────────────
 
-    let mut result = Vec::new();
-    for device in devices {
-        if let Ok(name) = device.name() {
-            let is_default = default_name.as_deref() == Some(&name);
-            result.push(AudioDevice { name, is_default });
-        }
-    }
-    Ok(result)
+/// Link the app was launched with, asked once by the UI after loading;
+/// later links arrive as `deep-link` events.
+#[tauri::command]
+pub async fn take_pending_deep_link(
+    state: State<'_, AppState>,
+) -> Result<Option<crate::deeplink::DeepLink>, String> {
+    let mut pending = state.pending_link.lock().map_err(|e| e.to_string())?;
+    Ok(pending.take())
 }
 
 // ── Folder picker ────────────────────────────────────────────────────
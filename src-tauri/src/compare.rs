@@ -0,0 +1,177 @@
+//! Word-level comparison of the live transcript of a session with its
+//! diarized batch transcript, to see what the batch pass changed.
+//!
+//! Words are compared case- and punctuation-insensitively; the changes
+//! keep the words as written.
+
+use serde::Serialize;
+use similar::{Algorithm, DiffTag};
+
+use crate::db::Segment;
+
+/// Time given to the diff algorithm before it settles for a coarser diff.
+const DIFF_DEADLINE_SECS: u64 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Equal,
+    /// Words only in the diarized transcript.
+    Insert,
+    /// Words only in the live transcript.
+    Delete,
+    Replace,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub live: String,
+    pub diarized: String,
+    /// Diarized segment where the change is, to locate it.
+    pub segment_id: Option<i64>,
+    pub start_time: Option<f64>,
+    pub speaker: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TranscriptComparison {
+    pub live_words: usize,
+    pub diarized_words: usize,
+    /// Share of words unchanged, from 0 to 1.
+    pub similarity: f64,
+    pub changes: Vec<Change>,
+    /// Diarized segments without a speaker, a sign of a diarization
+    /// regression.
+    pub unattributed_segments: Vec<i64>,
+}
+
+fn word_key(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Compares the live text with the diarized `segments`, in order.
+pub fn compare(live: &str, segments: &[Segment]) -> TranscriptComparison {
+    let live_words: Vec<&str> = live.split_whitespace().collect();
+    // Diarized words with the index of their segment
+    let diarized: Vec<(&str, usize)> = segments
+        .iter()
+        .enumerate()
+        .flat_map(|(i, s)| s.text.split_whitespace().map(move |w| (w, i)))
+        .collect();
+
+    let old: Vec<String> = live_words.iter().map(|w| word_key(w)).collect();
+    let new: Vec<String> = diarized.iter().map(|(w, _)| word_key(w)).collect();
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(DIFF_DEADLINE_SECS);
+    let ops = similar::capture_diff_slices_deadline(Algorithm::Myers, &old, &new, Some(deadline));
+
+    let mut unchanged = 0;
+    let changes: Vec<Change> = ops
+        .iter()
+        .map(|op| {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            let kind = match tag {
+                DiffTag::Equal => ChangeKind::Equal,
+                DiffTag::Insert => ChangeKind::Insert,
+                DiffTag::Delete => ChangeKind::Delete,
+                DiffTag::Replace => ChangeKind::Replace,
+            };
+            if kind == ChangeKind::Equal {
+                unchanged += old_range.len();
+            }
+            // A deletion is located at the diarized word that follows it
+            let segment = diarized
+                .get(new_range.start)
+                .or_else(|| diarized.last())
+                .map(|&(_, i)| &segments[i]);
+            Change {
+                kind,
+                live: live_words[old_range].join(" "),
+                diarized: diarized[new_range]
+                    .iter()
+                    .map(|(w, _)| *w)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                segment_id: segment.map(|s| s.id),
+                start_time: segment.map(|s| s.start_time),
+                speaker: segment.and_then(|s| s.speaker.clone()),
+            }
+        })
+        .collect();
+
+    let total = live_words.len().max(diarized.len());
+    TranscriptComparison {
+        live_words: live_words.len(),
+        diarized_words: diarized.len(),
+        similarity: if total == 0 {
+            1.0
+        } else {
+            unchanged as f64 / total as f64
+        },
+        changes,
+        unattributed_segments: segments
+            .iter()
+            .filter(|s| s.speaker.is_none())
+            .map(|s| s.id)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(id: i64, text: &str, start: f64, speaker: Option<&str>) -> Segment {
+        Segment {
+            id,
+            session_id: "s".to_string(),
+            text: text.to_string(),
+            start_time: start,
+            end_time: start + 2.0,
+            speaker: speaker.map(str::to_string),
+            is_diarized: true,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_live_and_diarized() {
+        let segments = vec![
+            segment(1, "Bonjour a tous.", 0.0, Some("Marie")),
+            segment(2, "On deploie sur Kubernetes demain.", 2.0, None),
+        ];
+        let comparison = compare(
+            "bonjour a tous on deploie sur cube air net demain",
+            &segments,
+        );
+
+        assert_eq!((comparison.live_words, comparison.diarized_words), (10, 8));
+        assert_eq!(comparison.unattributed_segments, vec![2]);
+        let edits: Vec<&Change> = comparison
+            .changes
+            .iter()
+            .filter(|c| c.kind != ChangeKind::Equal)
+            .collect();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].kind, ChangeKind::Replace);
+        assert_eq!(
+            (edits[0].live.as_str(), edits[0].diarized.as_str()),
+            ("cube air net", "Kubernetes")
+        );
+        assert_eq!(
+            (edits[0].segment_id, edits[0].start_time),
+            (Some(2), Some(2.0))
+        );
+        assert_eq!(comparison.similarity, 0.7);
+    }
+
+    #[test]
+    fn test_compare_empty() {
+        let comparison = compare("", &[]);
+        assert!(comparison.changes.is_empty());
+        assert_eq!(comparison.similarity, 1.0);
+    }
+}
@@ -0,0 +1,84 @@
+//! Action items detected in the live transcript while recording. They are
+//! kept until the final summary is generated, which takes in those it did
+//! not find itself.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveActionItem {
+    pub id: i64,
+    pub session_id: String,
+    pub description: String,
+    pub assignee: Option<String>,
+    /// Last live segment of the passage the item was detected in.
+    pub segment_id: Option<i64>,
+    pub created_at: String,
+}
+
+impl Database {
+    pub fn add_live_action_item(
+        &self,
+        session_id: &str,
+        description: &str,
+        assignee: Option<&str>,
+        segment_id: Option<i64>,
+    ) -> Result<LiveActionItem, rusqlite::Error> {
+        let created_at = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO live_action_items (session_id, description, assignee, segment_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![session_id, description, assignee, segment_id, created_at],
+        )?;
+        Ok(LiveActionItem {
+            id: self.conn.last_insert_rowid(),
+            session_id: session_id.to_string(),
+            description: description.to_string(),
+            assignee: assignee.map(str::to_string),
+            segment_id,
+            created_at,
+        })
+    }
+
+    /// Items detected during the recording of a session, in order.
+    pub fn get_live_action_items(&self, session_id: &str) -> Result<Vec<LiveActionItem>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, description, assignee, segment_id, created_at
+             FROM live_action_items WHERE session_id = ?1 ORDER BY id ASC",
+        )?;
+        let rows = stmt.query_map(params![session_id], |row| {
+            Ok(LiveActionItem {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                description: row.get(2)?,
+                assignee: row.get(3)?,
+                segment_id: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_action_items() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        db.add_live_action_item(&id, "Envoyer le devis", Some("Marie"), Some(3)).unwrap();
+        db.add_live_action_item(&id, "Relancer le client", None, None).unwrap();
+
+        let items = db.get_live_action_items(&id).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].assignee.as_deref(), Some("Marie"));
+        assert_eq!(items[1].description, "Relancer le client");
+
+        db.delete_session(&id).unwrap();
+        assert!(db.get_live_action_items(&id).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! Follow-up email templates, rendered by `crate::email` for a session.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailTemplate {
+    pub id: i64,
+    /// Unique, ignoring case.
+    pub name: String,
+    /// Subject and body, with `{{placeholder}}`s, see `crate::email`.
+    pub subject: String,
+    pub body: String,
+    pub created_at: String,
+}
+
+fn row_to_template(row: &rusqlite::Row) -> Result<EmailTemplate, rusqlite::Error> {
+    Ok(EmailTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        subject: row.get(2)?,
+        body: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+impl Database {
+    pub fn list_email_templates(&self) -> Result<Vec<EmailTemplate>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, subject, body, created_at FROM email_templates ORDER BY name_key ASC",
+        )?;
+        let rows = stmt.query_map([], row_to_template)?;
+        rows.collect()
+    }
+
+    pub fn get_email_template(&self, id: i64) -> Result<EmailTemplate, rusqlite::Error> {
+        self.conn.query_row(
+            "SELECT id, name, subject, body, created_at FROM email_templates WHERE id = ?1",
+            params![id],
+            row_to_template,
+        )
+    }
+
+    /// Adds a template, or updates template `id`. Fails when another
+    /// template has the same name.
+    pub fn save_email_template(
+        &self,
+        id: Option<i64>,
+        name: &str,
+        subject: &str,
+        body: &str,
+    ) -> Result<EmailTemplate, rusqlite::Error> {
+        let name = name.trim();
+        let id = match id {
+            Some(id) => {
+                let changed = self.conn.execute(
+                    "UPDATE email_templates SET name = ?1, name_key = ?2, subject = ?3, body = ?4 WHERE id = ?5",
+                    params![name, name.to_lowercase(), subject, body, id],
+                )?;
+                if changed == 0 {
+                    return Err(rusqlite::Error::QueryReturnedNoRows);
+                }
+                id
+            }
+            None => {
+                self.conn.execute(
+                    "INSERT INTO email_templates (name, name_key, subject, body, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![name, name.to_lowercase(), subject, body, chrono::Utc::now().to_rfc3339()],
+                )?;
+                self.conn.last_insert_rowid()
+            }
+        };
+        self.get_email_template(id)
+    }
+
+    pub fn delete_email_template(&self, id: i64) -> Result<(), rusqlite::Error> {
+        self.conn.execute("DELETE FROM email_templates WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_templates() {
+        let db = Database::new_in_memory().unwrap();
+        let client = db.save_email_template(None, "Client ", "Suivi : {{title}}", "{{key_points}}").unwrap();
+        assert_eq!(client.name, "Client");
+        db.save_email_template(None, "Equipe", "CR {{date}}", "{{action_items}}").unwrap();
+        // Names are unique, ignoring case
+        assert!(db.save_email_template(None, "client", "", "").is_err());
+
+        let updated = db
+            .save_email_template(Some(client.id), "Client", "Compte rendu : {{title}}", "{{decisions}}")
+            .unwrap();
+        assert_eq!(updated.subject, "Compte rendu : {{title}}");
+        assert_eq!(updated.created_at, client.created_at);
+        assert!(db.save_email_template(Some(999), "Autre", "", "").is_err());
+
+        let templates = db.list_email_templates().unwrap();
+        assert_eq!(templates.len(), 2);
+        assert_eq!(templates[0].name, "Client");
+
+        db.delete_email_template(client.id).unwrap();
+        assert_eq!(db.list_email_templates().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,230 @@
+//! Undo journal for destructive edits (speaker renames and reassignments,
+//! segment deletions). Each entry stores what is needed to revert it.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::{Database, Segment};
+
+/// Number of journal entries kept; older ones can no longer be undone.
+const JOURNAL_LIMIT: i64 = 100;
+
+/// A journaled operation, as listed to the user ("Annuler le renommage").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub id: i64,
+    pub session_id: String,
+    /// `rename_speaker`, `set_segment_speaker` or `delete_segments`.
+    pub kind: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(super) enum JournalEntry {
+    RenameSpeaker {
+        old_name: String,
+        new_name: String,
+        /// Segments that were renamed; others already named `new_name`
+        /// (a merge) are left alone on undo.
+        segment_ids: Vec<i64>,
+        /// Whether a session color followed the rename.
+        color_moved: bool,
+    },
+    SetSegmentSpeaker {
+        segment_id: i64,
+        old_speaker: Option<String>,
+        new_speaker: String,
+    },
+    DeleteSegments {
+        segments: Vec<Segment>,
+    },
+}
+
+impl JournalEntry {
+    fn kind(&self) -> &'static str {
+        match self {
+            JournalEntry::RenameSpeaker { .. } => "rename_speaker",
+            JournalEntry::SetSegmentSpeaker { .. } => "set_segment_speaker",
+            JournalEntry::DeleteSegments { .. } => "delete_segments",
+        }
+    }
+}
+
+impl Database {
+    /// Records an operation, dropping the oldest entries past the limit.
+    pub(super) fn journal(&self, session_id: &str, entry: &JournalEntry) -> Result<(), rusqlite::Error> {
+        let payload = serde_json::to_string(entry)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO operations (session_id, kind, payload, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![session_id, entry.kind(), payload, now],
+        )?;
+        self.conn.execute(
+            "DELETE FROM operations WHERE id <= (SELECT MAX(id) FROM operations) - ?1",
+            params![JOURNAL_LIMIT],
+        )?;
+        Ok(())
+    }
+
+    /// Most recent undoable operation, optionally restricted to a session.
+    pub fn last_operation(&self, session_id: Option<&str>) -> Result<Option<Operation>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, kind, created_at FROM operations
+             WHERE ?1 IS NULL OR session_id = ?1
+             ORDER BY id DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query_map(params![session_id], |row| {
+            Ok(Operation {
+                id: row.get(0)?,
+                session_id: row.get(1)?,
+                kind: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+        rows.next().transpose()
+    }
+
+    /// Reverts the most recent operation (of a session, if given) and removes
+    /// it from the journal. Returns `None` when there is nothing to undo.
+    pub fn undo_last_operation(&self, session_id: Option<&str>) -> Result<Option<Operation>, rusqlite::Error> {
+        let Some(operation) = self.last_operation(session_id)? else {
+            return Ok(None);
+        };
+        let payload: String = self.conn.query_row(
+            "SELECT payload FROM operations WHERE id = ?1",
+            params![operation.id],
+            |row| row.get(0),
+        )?;
+        let entry: JournalEntry = serde_json::from_str(&payload).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        match entry {
+            JournalEntry::RenameSpeaker { old_name, new_name, segment_ids, color_moved } => {
+                for id in segment_ids {
+                    tx.execute(
+                        "UPDATE segments SET speaker = ?1 WHERE id = ?2 AND speaker = ?3",
+                        params![old_name, id, new_name],
+                    )?;
+                }
+                if color_moved {
+                    tx.execute(
+                        "UPDATE OR REPLACE speaker_colors SET speaker = ?1 WHERE session_id = ?2 AND speaker = ?3",
+                        params![old_name, operation.session_id, new_name],
+                    )?;
+                }
+            }
+            JournalEntry::SetSegmentSpeaker { segment_id, old_speaker, new_speaker } => {
+                tx.execute(
+                    "UPDATE segments SET speaker = ?1 WHERE id = ?2 AND speaker = ?3",
+                    params![old_speaker, segment_id, new_speaker],
+                )?;
+            }
+            JournalEntry::DeleteSegments { segments } => {
+                for seg in segments {
+                    tx.execute(
+                        "INSERT INTO segments (id, session_id, text, start_time, end_time, speaker, is_diarized, language)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            seg.id,
+                            seg.session_id,
+                            seg.text,
+                            seg.start_time,
+                            seg.end_time,
+                            seg.speaker,
+                            seg.is_diarized as i32,
+                            seg.language
+                        ],
+                    )?;
+                }
+            }
+        }
+        tx.execute("DELETE FROM operations WHERE id = ?1", params![operation.id])?;
+        tx.commit()?;
+        Ok(Some(operation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_rename_speaker() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        db.save_segment(&id, "Hello", 0.0, 1.0, Some("speaker_1"), true).unwrap();
+        db.save_segment(&id, "Hi", 1.0, 2.0, Some("Marie"), true).unwrap();
+        db.set_session_speaker_color(&id, "speaker_1", "#123456").unwrap();
+
+        // Merge speaker_1 into the existing Marie, then undo
+        db.rename_speaker(&id, "speaker_1", "Marie").unwrap();
+        let op = db.undo_last_operation(Some(&id)).unwrap().unwrap();
+        assert_eq!(op.kind, "rename_speaker");
+
+        let segments = db.get_segments(&id).unwrap();
+        assert_eq!(segments[0].speaker.as_deref(), Some("speaker_1"));
+        assert_eq!(segments[1].speaker.as_deref(), Some("Marie"));
+        let colors = db.get_speaker_colors(&id).unwrap();
+        assert_eq!(colors.get("speaker_1").map(String::as_str), Some("#123456"));
+
+        assert!(db.undo_last_operation(Some(&id)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_set_segment_speaker_and_undo() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        let first = db.save_segment(&id, "Bonjour", 0.0, 1.0, Some("speaker_1"), true).unwrap();
+        db.save_segment(&id, "Salut", 1.0, 2.0, Some("speaker_1"), true).unwrap();
+        let other = db.create_session("Other", "visio").unwrap();
+
+        assert!(!db.set_segment_speaker(&other, first, "speaker_2").unwrap());
+        assert!(db.set_segment_speaker(&id, first, "speaker_2").unwrap());
+        let speakers: Vec<_> = db.get_segments(&id).unwrap().into_iter().map(|s| s.speaker).collect();
+        assert_eq!(speakers, vec![Some("speaker_2".to_string()), Some("speaker_1".to_string())]);
+
+        let op = db.undo_last_operation(Some(&id)).unwrap().unwrap();
+        assert_eq!(op.kind, "set_segment_speaker");
+        assert!(db.get_segments(&id).unwrap().iter().all(|s| s.speaker.as_deref() == Some("speaker_1")));
+    }
+
+    #[test]
+    fn test_undo_clear_segments() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        db.save_segment(&id, "Bonjour tout le monde", 0.0, 1.0, None, false).unwrap();
+        db.save_segment(&id, "Diarized", 1.0, 2.0, Some("S1"), true).unwrap();
+
+        db.clear_segments(&id, false).unwrap();
+        assert!(db.get_segments(&id).unwrap().is_empty());
+
+        db.undo_last_operation(None).unwrap().unwrap();
+        let segments = db.get_segments(&id).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].speaker.as_deref(), Some("S1"));
+        // Restored segments are searchable again
+        assert_eq!(db.search_text("bonjour", Some(&id)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_undo_delete_segment_is_per_session() {
+        let db = Database::new_in_memory().unwrap();
+        let a = db.create_session("A", "visio").unwrap();
+        let b = db.create_session("B", "visio").unwrap();
+        let seg = db.save_segment(&a, "Oups", 0.0, 1.0, None, true).unwrap();
+        db.save_segment(&b, "Autre", 0.0, 1.0, Some("S1"), true).unwrap();
+
+        db.delete_segment(seg).unwrap();
+        db.rename_speaker(&b, "S1", "Paul").unwrap();
+
+        let op = db.undo_last_operation(Some(&a)).unwrap().unwrap();
+        assert_eq!(op.kind, "delete_segments");
+        assert_eq!(db.get_segment(seg).unwrap().text, "Oups");
+        // The rename in the other session is still undoable
+        assert_eq!(db.last_operation(None).unwrap().unwrap().session_id, b);
+    }
+}
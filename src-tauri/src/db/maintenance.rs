@@ -0,0 +1,273 @@
+//! Database housekeeping: reclaiming the space of deleted sessions, keeping
+//! the query planner statistics and the search index compact, checking the
+//! database against the recordings on disk and moving it elsewhere.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use rusqlite::params;
+use serde::Serialize;
+
+use super::Database;
+
+/// Sizes of the database file around a `maintain` run, in bytes.
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceReport {
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+impl Database {
+    /// Size of the database, from its page count.
+    pub fn size_bytes(&self) -> Result<u64, rusqlite::Error> {
+        let pages: i64 = self.conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: i64 = self.conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+        Ok((pages * page_size) as u64)
+    }
+
+    /// Merges the full-text index segments, refreshes the planner
+    /// statistics and rewrites the file without its free pages.
+    pub fn maintain(&self) -> Result<MaintenanceReport, rusqlite::Error> {
+        let size_before = self.size_bytes()?;
+        self.conn
+            .execute_batch("INSERT INTO segments_fts(segments_fts) VALUES('optimize');")?;
+        self.conn.execute_batch("PRAGMA optimize;")?;
+        self.conn.execute_batch("VACUUM;")?;
+        Ok(MaintenanceReport {
+            size_before,
+            size_after: self.size_bytes()?,
+        })
+    }
+}
+
+/// Problems found by `check`, and whether they were repaired.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    /// Messages from `PRAGMA integrity_check`; empty when the file is sound.
+    pub integrity_errors: Vec<String>,
+    /// Whether the full-text index matches the segments table.
+    pub fts_consistent: bool,
+    /// Sessions whose recording no longer exists on disk.
+    pub missing_audio: Vec<String>,
+    /// Files of the audio folder that belong to no session.
+    pub orphaned_audio: Vec<String>,
+    pub repaired: bool,
+}
+
+impl IntegrityReport {
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty()
+            && self.fts_consistent
+            && self.missing_audio.is_empty()
+            && self.orphaned_audio.is_empty()
+    }
+}
+
+/// Session id a file of the audio folder belongs to: `{id}.wav`,
+/// `{id}_native.wav`, `{id}.partial.wav` or an imported `{id}.{ext}`.
+fn audio_file_session(file_name: &str) -> &str {
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+    stem.strip_suffix("_native").unwrap_or(stem)
+}
+
+impl Database {
+    /// Messages reported by `PRAGMA integrity_check`, without the lone "ok".
+    pub fn integrity_errors(&self) -> Result<Vec<String>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let messages = rows.collect::<Result<Vec<_>, _>>()?;
+        Ok(messages.into_iter().filter(|m| m != "ok").collect())
+    }
+
+    /// Compares the full-text index with the segments it is built from.
+    pub fn search_index_consistent(&self) -> Result<bool, rusqlite::Error> {
+        match self.conn.execute(
+            "INSERT INTO segments_fts(segments_fts, rank) VALUES('integrity-check', 1)",
+            [],
+        ) {
+            Ok(_) => Ok(true),
+            Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == rusqlite::ErrorCode::DatabaseCorrupt => {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes a consistent copy of the database to `path`, which must not
+    /// exist yet.
+    pub fn copy_to(&self, path: &Path) -> Result<(), rusqlite::Error> {
+        self.conn
+            .execute("VACUUM INTO ?1", [path.to_string_lossy()])
+            .map(|_| ())
+    }
+
+    /// Points recordings and attachments stored under `old_dir` to the same
+    /// files under `new_dir`. Only paths inside `old_dir` match, not those of
+    /// a sibling sharing its prefix (`/data2` for `/data`). Returns the number
+    /// of updated rows.
+    pub fn relocate_files(&self, old_dir: &str, new_dir: &str) -> Result<usize, rusqlite::Error> {
+        let old_dir = old_dir.trim_end_matches(['/', '\\']);
+        let new_dir = new_dir.trim_end_matches(['/', '\\']);
+        let tx = self.conn.unchecked_transaction()?;
+        // The separator is kept from the old path, either kind
+        let mut updated = tx.execute(
+            "UPDATE sessions SET audio_path = ?2 || substr(audio_path, length(?1) + 1)
+             WHERE substr(audio_path, 1, length(?1) + 1) IN (?1 || '/', ?1 || '\\')",
+            params![old_dir, new_dir],
+        )?;
+        updated += tx.execute(
+            "UPDATE attachments SET path = ?2 || substr(path, length(?1) + 1)
+             WHERE substr(path, 1, length(?1) + 1) IN (?1 || '/', ?1 || '\\')",
+            params![old_dir, new_dir],
+        )?;
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Rebuilds the full-text index from the segments table.
+    pub fn rebuild_search_index(&self) -> Result<(), rusqlite::Error> {
+        self.conn
+            .execute_batch("INSERT INTO segments_fts(segments_fts) VALUES('rebuild');")
+    }
+
+    /// Checks the database file, the search index and the recordings of
+    /// `audio_dir`. With `repair`, rebuilds the indexes, marks sessions whose
+    /// recording is gone as transcript-only and deletes orphaned files.
+    pub fn check(&self, audio_dir: &Path, repair: bool) -> Result<IntegrityReport, rusqlite::Error> {
+        let mut report = IntegrityReport {
+            integrity_errors: self.integrity_errors()?,
+            fts_consistent: self.search_index_consistent()?,
+            ..Default::default()
+        };
+
+        let sessions = self.list_sessions()?;
+        let ids: HashSet<&str> = sessions.iter().map(|s| s.id.as_str()).collect();
+        for session in &sessions {
+            if let Some(path) = session.audio_path.as_deref().filter(|p| !p.is_empty()) {
+                if !Path::new(path).exists() {
+                    report.missing_audio.push(session.id.clone());
+                }
+            }
+        }
+        if let Ok(entries) = std::fs::read_dir(audio_dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.path().is_file() && !ids.contains(audio_file_session(&name)) {
+                    report.orphaned_audio.push(entry.path().to_string_lossy().into_owned());
+                }
+            }
+        }
+        report.orphaned_audio.sort();
+
+        if !repair || report.is_healthy() {
+            return Ok(report);
+        }
+        if !report.integrity_errors.is_empty() {
+            self.conn.execute_batch("REINDEX;")?;
+        }
+        if !report.fts_consistent {
+            self.rebuild_search_index()?;
+        }
+        for id in &report.missing_audio {
+            self.clear_session_audio(id)?;
+        }
+        for path in &report.orphaned_audio {
+            if let Err(e) = std::fs::remove_file(path) {
+                eprintln!("[check] Could not delete {}: {}", path, e);
+            }
+        }
+        report.repaired = true;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maintain_reclaims_space() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        let text = "bla ".repeat(500);
+        for i in 0..200 {
+            db.save_segment(&id, &text, i as f64, i as f64 + 1.0, None, false).unwrap();
+        }
+        db.delete_session(&id).unwrap();
+
+        let report = db.maintain().unwrap();
+        assert!(report.size_after < report.size_before);
+        assert_eq!(report.size_after, db.size_bytes().unwrap());
+    }
+
+    #[test]
+    fn test_relocate_files() {
+        let db = Database::new_in_memory().unwrap();
+        let moved = db.create_session("Moved", "visio").unwrap();
+        db.update_session_audio_path(&moved, "/old/data/audio/a.wav").unwrap();
+        let outside = db.create_session("Outside", "visio").unwrap();
+        db.update_session_audio_path(&outside, "/elsewhere/b.wav").unwrap();
+        let sibling = db.create_session("Sibling", "visio").unwrap();
+        db.update_session_audio_path(&sibling, "/old/data2/audio/c.wav").unwrap();
+
+        assert_eq!(db.relocate_files("/old/data/", "/new/place").unwrap(), 1);
+        assert_eq!(
+            db.get_session(&moved).unwrap().audio_path.as_deref(),
+            Some("/new/place/audio/a.wav")
+        );
+        assert_eq!(db.get_session(&outside).unwrap().audio_path.as_deref(), Some("/elsewhere/b.wav"));
+        assert_eq!(
+            db.get_session(&sibling).unwrap().audio_path.as_deref(),
+            Some("/old/data2/audio/c.wav")
+        );
+    }
+
+    #[test]
+    fn test_search_index_check_and_rebuild() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        db.save_segment(&id, "bonjour", 0.0, 1.0, None, false).unwrap();
+        assert!(db.search_index_consistent().unwrap());
+
+        db.conn
+            .execute("INSERT INTO segments_fts(rowid, text) VALUES (999, 'fantome')", [])
+            .unwrap();
+        assert!(!db.search_index_consistent().unwrap());
+        db.rebuild_search_index().unwrap();
+        assert!(db.search_index_consistent().unwrap());
+    }
+
+    #[test]
+    fn test_check_finds_and_repairs_audio_problems() {
+        let dir = std::env::temp_dir().join(format!("poptranscribe_check_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Database::new_in_memory().unwrap();
+
+        let kept = db.create_session("Kept", "visio").unwrap();
+        let kept_path = dir.join(format!("{}.wav", kept));
+        std::fs::write(&kept_path, b"RIFF").unwrap();
+        std::fs::write(dir.join(format!("{}_native.wav", kept)), b"RIFF").unwrap();
+        db.update_session_audio_path(&kept, kept_path.to_str().unwrap()).unwrap();
+
+        let lost = db.create_session("Lost", "visio").unwrap();
+        db.update_session_audio_path(&lost, dir.join("gone.wav").to_str().unwrap()).unwrap();
+        let orphan = dir.join("deleted-session.wav");
+        std::fs::write(&orphan, b"RIFF").unwrap();
+
+        let report = db.check(&dir, false).unwrap();
+        assert!(report.integrity_errors.is_empty());
+        assert!(report.fts_consistent);
+        assert_eq!(report.missing_audio, vec![lost.clone()]);
+        assert_eq!(report.orphaned_audio, vec![orphan.to_string_lossy().into_owned()]);
+        assert!(!report.repaired);
+
+        let report = db.check(&dir, true).unwrap();
+        assert!(report.repaired);
+        assert!(!orphan.exists());
+        assert!(kept_path.exists());
+        assert!(db.get_session(&lost).unwrap().transcript_only);
+        assert!(db.check(&dir, false).unwrap().is_healthy());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,6 +1,36 @@
+mod action_items;
+mod email_templates;
+mod journal;
+mod maintenance;
+mod replacements;
+mod revisions;
+mod schedule;
+mod sync;
+mod topics;
+mod transcript;
+mod translations;
+mod versions;
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
 use rusqlite::{Connection, params};
 use serde::{Serialize, Deserialize};
 
+use journal::JournalEntry;
+pub use action_items::LiveActionItem;
+pub use email_templates::EmailTemplate;
+pub use journal::Operation;
+pub use maintenance::{IntegrityReport, MaintenanceReport};
+pub use replacements::Replacement;
+pub use revisions::SegmentRevision;
+pub use schedule::{ScheduledRecording, RECURRENCES};
+pub use sync::{SessionStamp, SyncedSession};
+pub use topics::TopicCount;
+pub use transcript::FullTranscript;
+pub use translations::TranslatedSegment;
+pub use versions::{TranscriptVersion, VersionSegment};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
@@ -10,6 +40,33 @@ pub struct Session {
     pub created_at: String,
     pub duration_secs: Option<f64>,
     pub summary_json: Option<String>,
+    /// Content hidden until the app passcode is entered.
+    pub protected: bool,
+    /// Finalized minutes: read-only until explicitly unarchived.
+    pub archived: bool,
+    /// Language pinned before recording, overriding auto-detection.
+    pub language: Option<String>,
+    /// The recording was deleted on purpose; only the transcript remains.
+    pub transcript_only: bool,
+    /// Model that generated the summary; `None` for imported summaries and
+    /// those generated before it was recorded.
+    pub summary_model: Option<String>,
+    /// Distinct speakers found by diarization.
+    pub speaker_count: Option<i64>,
+    /// Spoken language detected by the transcription, unlike the pinned
+    /// `language`.
+    pub detected_language: Option<String>,
+}
+
+/// Segment to insert with `save_segments_batch`.
+#[derive(Debug, Clone, Copy)]
+pub struct NewSegment<'a> {
+    pub text: &'a str,
+    pub start: f64,
+    pub end: f64,
+    pub speaker: Option<&'a str>,
+    pub is_diarized: bool,
+    pub language: Option<&'a str>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,24 +78,370 @@ pub struct Segment {
     pub end_time: f64,
     pub speaker: Option<String>,
     pub is_diarized: bool,
+    /// Detected language code ("fr", "en"...), when known.
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// A file (slides, agenda, ...) attached to a session and copied into the
+/// data directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: i64,
+    pub session_id: String,
+    pub file_name: String,
+    pub path: String,
+    pub size_bytes: i64,
+    pub created_at: String,
+}
+
+/// Aggregated usage figures for the statistics dashboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Statistics {
+    pub total_sessions: i64,
+    pub recorded_hours: f64,
+    pub total_words: i64,
+    pub average_duration_secs: Option<f64>,
+    pub sessions_per_week: Vec<WeekCount>,
+    pub top_speakers: Vec<SpeakerStat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekCount {
+    /// ISO-like `YYYY-WW` key (SQLite `%W`, weeks starting on Monday).
+    pub week: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakerStat {
+    pub speaker: String,
+    pub segment_count: i64,
+    pub speaking_secs: f64,
+}
+
+/// Global speaker profile (participants directory), keyed by display name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpeakerProfile {
+    pub name: String,
+    pub color: Option<String>,
+    pub role: Option<String>,
+    pub company: Option<String>,
+    pub email: Option<String>,
+}
+
+impl SpeakerProfile {
+    /// "Marie (CFO, Acme)", used in prompts and export headers.
+    pub fn describe(&self) -> String {
+        let details: Vec<&str> = [self.role.as_deref(), self.company.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|d| !d.is_empty())
+            .collect();
+        if details.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, details.join(", "))
+        }
+    }
+}
+
+/// Schema changes applied on top of `init_schema`, in order. The number of
+/// applied migrations is tracked in `PRAGMA user_version`; only ever append.
+const MIGRATIONS: &[&str] = &[
+    // 1: speaker profile metadata
+    "ALTER TABLE speaker_profiles ADD COLUMN role TEXT;
+     ALTER TABLE speaker_profiles ADD COLUMN company TEXT;
+     ALTER TABLE speaker_profiles ADD COLUMN email TEXT;",
+    // 2: participants directory usage, seeded from names already given
+    "ALTER TABLE speaker_profiles ADD COLUMN use_count INTEGER NOT NULL DEFAULT 0;
+     ALTER TABLE speaker_profiles ADD COLUMN last_used_at TEXT;
+     INSERT OR IGNORE INTO speaker_profiles (name)
+        SELECT DISTINCT speaker FROM segments
+        WHERE speaker IS NOT NULL AND speaker NOT LIKE 'speaker%';
+     UPDATE speaker_profiles SET use_count = (
+        SELECT COUNT(DISTINCT session_id) FROM segments WHERE speaker = speaker_profiles.name
+     );",
+    // 3: passcode-protected sessions
+    "ALTER TABLE sessions ADD COLUMN protected INTEGER NOT NULL DEFAULT 0;",
+    // 4: read-only archived sessions
+    "ALTER TABLE sessions ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;",
+    // 5: undo journal
+    "CREATE TABLE operations (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id),
+        kind TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        created_at TEXT NOT NULL
+     );",
+    // 6: per-segment language
+    "ALTER TABLE segments ADD COLUMN language TEXT;",
+    // 7: per-session language lock
+    "ALTER TABLE sessions ADD COLUMN language TEXT;",
+    // 8: realtime transcript, a fallback when batch processing fails
+    "ALTER TABLE sessions ADD COLUMN live_transcript TEXT;",
+    // 9: sessions whose recording was stripped after transcription
+    "ALTER TABLE sessions ADD COLUMN transcript_only INTEGER NOT NULL DEFAULT 0;",
+    // 10: scheduled recordings
+    "CREATE TABLE scheduled_recordings (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        title TEXT NOT NULL,
+        mode TEXT NOT NULL,
+        start_at TEXT NOT NULL,
+        duration_minutes INTEGER NOT NULL,
+        recurrence TEXT,
+        last_run_at TEXT,
+        created_at TEXT NOT NULL
+     );",
+    // 11: cached answers of transcript questions
+    "CREATE TABLE search_cache (
+        session_id TEXT NOT NULL REFERENCES sessions(id),
+        transcript_hash TEXT NOT NULL,
+        query TEXT NOT NULL,
+        answer TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        PRIMARY KEY (session_id, transcript_hash, query)
+     );",
+    // 12: indexes for the per-session segment reads and the session list
+    "CREATE INDEX IF NOT EXISTS idx_segments_session_start ON segments(session_id, start_time);
+     CREATE INDEX IF NOT EXISTS idx_sessions_created_at ON sessions(created_at);",
+    // 13: model behind each generated summary
+    "ALTER TABLE sessions ADD COLUMN summary_model TEXT;",
+    // 14: metadata extracted while processing
+    "ALTER TABLE sessions ADD COLUMN speaker_count INTEGER;
+     ALTER TABLE sessions ADD COLUMN detected_language TEXT;",
+    // 15: topic index, keyed by the folded topic
+    "CREATE TABLE session_topics (
+        session_id TEXT NOT NULL REFERENCES sessions(id),
+        topic TEXT NOT NULL,
+        topic_key TEXT NOT NULL,
+        PRIMARY KEY (topic_key, session_id)
+     );
+     CREATE INDEX IF NOT EXISTS idx_session_topics_session ON session_topics(session_id);",
+    // 16: transcript version, bumped on every change the full transcript
+    // depends on, to invalidate cached transcripts
+    "ALTER TABLE sessions ADD COLUMN transcript_version INTEGER NOT NULL DEFAULT 0;
+     CREATE TRIGGER segments_insert_version AFTER INSERT ON segments BEGIN
+        UPDATE sessions SET transcript_version = transcript_version + 1 WHERE id = NEW.session_id;
+     END;
+     CREATE TRIGGER segments_update_version AFTER UPDATE ON segments BEGIN
+        UPDATE sessions SET transcript_version = transcript_version + 1 WHERE id = NEW.session_id;
+     END;
+     CREATE TRIGGER segments_delete_version AFTER DELETE ON segments BEGIN
+        UPDATE sessions SET transcript_version = transcript_version + 1 WHERE id = OLD.session_id;
+     END;
+     CREATE TRIGGER live_transcript_version AFTER UPDATE OF live_transcript ON sessions BEGIN
+        UPDATE sessions SET transcript_version = transcript_version + 1 WHERE id = NEW.id;
+     END;",
+    // 17: action items detected while recording
+    "CREATE TABLE live_action_items (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id),
+        description TEXT NOT NULL,
+        assignee TEXT,
+        segment_id INTEGER,
+        created_at TEXT NOT NULL
+     );
+     CREATE INDEX IF NOT EXISTS idx_live_action_items_session ON live_action_items(session_id);",
+    // 18: segment translations, dropped with their segment or when its text
+    // changes
+    "CREATE TABLE segment_translations (
+        segment_id INTEGER NOT NULL REFERENCES segments(id),
+        language TEXT NOT NULL,
+        text TEXT NOT NULL,
+        PRIMARY KEY (segment_id, language)
+     );
+     CREATE TRIGGER segments_delete_translations AFTER DELETE ON segments BEGIN
+        DELETE FROM segment_translations WHERE segment_id = OLD.id;
+     END;
+     CREATE TRIGGER segments_text_translations AFTER UPDATE OF text ON segments BEGIN
+        DELETE FROM segment_translations WHERE segment_id = NEW.id;
+     END;",
+    // 19: user replacement dictionary, keyed by the lowercased pattern
+    "CREATE TABLE replacements (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        pattern TEXT NOT NULL,
+        replacement TEXT NOT NULL,
+        pattern_key TEXT NOT NULL UNIQUE,
+        created_at TEXT NOT NULL
+     );",
+    // 20: alternative transcript versions (AI-corrected text...)
+    "CREATE TABLE transcript_versions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL REFERENCES sessions(id),
+        kind TEXT NOT NULL,
+        model TEXT,
+        created_at TEXT NOT NULL,
+        segments_json TEXT NOT NULL
+     );
+     CREATE INDEX IF NOT EXISTS idx_transcript_versions_session ON transcript_versions(session_id);",
+    // 21: prior texts of edited segments
+    "CREATE TABLE segment_revisions (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        segment_id INTEGER NOT NULL,
+        session_id TEXT NOT NULL REFERENCES sessions(id),
+        text TEXT NOT NULL,
+        created_at TEXT NOT NULL
+     );
+     CREATE INDEX IF NOT EXISTS idx_segment_revisions_segment ON segment_revisions(segment_id);
+     CREATE TRIGGER segments_text_revisions AFTER UPDATE OF text ON segments
+     WHEN OLD.text != NEW.text BEGIN
+        INSERT INTO segment_revisions (segment_id, session_id, text, created_at)
+        VALUES (OLD.id, OLD.session_id, OLD.text, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+     END;",
+    // 22: change times and tombstones for sync between devices. Segment
+    // changes bump `transcript_version`, which stamps the session too
+    "ALTER TABLE sessions ADD COLUMN updated_at TEXT;
+     UPDATE sessions SET updated_at = created_at;
+     CREATE TRIGGER sessions_updated_at AFTER UPDATE ON sessions
+     WHEN NEW.updated_at IS OLD.updated_at BEGIN
+        UPDATE sessions SET updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = NEW.id;
+     END;
+     CREATE TABLE deleted_sessions (
+        session_id TEXT PRIMARY KEY,
+        deleted_at TEXT NOT NULL
+     );",
+    // 23: follow-up email templates
+    "CREATE TABLE email_templates (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        name_key TEXT NOT NULL UNIQUE,
+        subject TEXT NOT NULL,
+        body TEXT NOT NULL,
+        created_at TEXT NOT NULL
+     );",
+];
+
+/// Columns read by `row_to_session`, in order.
+const SESSION_COLUMNS: &str =
+    "id, title, mode, audio_path, created_at, duration_secs, summary_json, protected, archived, language, transcript_only, summary_model, speaker_count, detected_language";
+
+/// Lowercases and strips the common latin accents, for name matching.
+pub(crate) fn fold_name(s: &str) -> String {
+    s.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'à' | 'â' | 'ä' | 'á' | 'ã' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' | 'í' => 'i',
+            'ô' | 'ö' | 'ó' | 'õ' => 'o',
+            'ù' | 'û' | 'ü' | 'ú' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            _ => c,
+        })
+        .collect()
+}
+
+/// How well `query` matches `name` (higher is better), or `None`:
+/// prefix of the name, prefix of a word, substring, then in-order subsequence.
+fn fuzzy_score(query: &str, name: &str) -> Option<u32> {
+    let query = fold_name(query.trim());
+    let name = fold_name(name);
+    if query.is_empty() {
+        return Some(0);
+    }
+    if name.starts_with(&query) {
+        return Some(4);
+    }
+    if name
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .any(|word| word.starts_with(&query))
+    {
+        return Some(3);
+    }
+    if name.contains(&query) {
+        return Some(2);
+    }
+    let mut chars = name.chars();
+    if query.chars().all(|q| chars.any(|c| c == q)) {
+        return Some(1);
+    }
+    None
 }
 
 pub struct Database {
     conn: Connection,
+    transcript_cache: RefCell<transcript::TranscriptCache>,
+}
+
+fn row_to_segment(row: &rusqlite::Row<'_>) -> Result<Segment, rusqlite::Error> {
+    let is_diarized_int: i32 = row.get(6)?;
+    Ok(Segment {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        text: row.get(2)?,
+        start_time: row.get(3)?,
+        end_time: row.get(4)?,
+        speaker: row.get(5)?,
+        is_diarized: is_diarized_int != 0,
+        language: row.get(7)?,
+    })
+}
+
+fn row_to_session(row: &rusqlite::Row<'_>) -> Result<Session, rusqlite::Error> {
+    let protected: i32 = row.get(7)?;
+    let archived: i32 = row.get(8)?;
+    let transcript_only: i32 = row.get(10)?;
+    Ok(Session {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        mode: row.get(2)?,
+        audio_path: row.get(3)?,
+        created_at: row.get(4)?,
+        duration_secs: row.get(5)?,
+        summary_json: row.get(6)?,
+        protected: protected != 0,
+        archived: archived != 0,
+        language: row.get(9)?,
+        transcript_only: transcript_only != 0,
+        summary_model: row.get(11)?,
+        speaker_count: row.get(12)?,
+        detected_language: row.get(13)?,
+    })
+}
+
+fn row_to_attachment(row: &rusqlite::Row<'_>) -> Result<Attachment, rusqlite::Error> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        file_name: row.get(2)?,
+        path: row.get(3)?,
+        size_bytes: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+fn row_to_speaker_profile(row: &rusqlite::Row<'_>) -> Result<SpeakerProfile, rusqlite::Error> {
+    Ok(SpeakerProfile {
+        name: row.get(0)?,
+        color: row.get(1)?,
+        role: row.get(2)?,
+        company: row.get(3)?,
+        email: row.get(4)?,
+    })
 }
 
 impl Database {
     pub fn new(path: &std::path::Path) -> Result<Self, rusqlite::Error> {
         let conn = Connection::open(path)?;
-        let db = Self { conn };
+        let db = Self {
+            conn,
+            transcript_cache: RefCell::default(),
+        };
         db.init_schema()?;
+        db.migrate()?;
         Ok(db)
     }
 
     pub fn new_in_memory() -> Result<Self, rusqlite::Error> {
         let conn = Connection::open_in_memory()?;
-        let db = Self { conn };
+        let db = Self {
+            conn,
+            transcript_cache: RefCell::default(),
+        };
         db.init_schema()?;
+        db.migrate()?;
         Ok(db)
     }
 
@@ -79,6 +482,27 @@ impl Database {
                 INSERT INTO segments_fts(rowid, text) VALUES (new.id, new.text);
             END;
 
+            CREATE TABLE IF NOT EXISTS attachments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL REFERENCES sessions(id),
+                file_name TEXT NOT NULL,
+                path TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS speaker_profiles (
+                name TEXT PRIMARY KEY,
+                color TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS speaker_colors (
+                session_id TEXT NOT NULL REFERENCES sessions(id),
+                speaker TEXT NOT NULL,
+                color TEXT NOT NULL,
+                PRIMARY KEY (session_id, speaker)
+            );
+
             CREATE TABLE IF NOT EXISTS settings (
                 key TEXT PRIMARY KEY,
                 value TEXT NOT NULL
@@ -87,6 +511,17 @@ impl Database {
         Ok(())
     }
 
+    fn migrate(&self) -> Result<(), rusqlite::Error> {
+        let version: i64 = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", i + 1))?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
     // ── Sessions ──────────────────────────────────────────────────────
 
     pub fn create_session(&self, title: &str, mode: &str) -> Result<String, rusqlite::Error> {
@@ -101,39 +536,57 @@ impl Database {
 
     pub fn get_session(&self, id: &str) -> Result<Session, rusqlite::Error> {
         self.conn.query_row(
-            "SELECT id, title, mode, audio_path, created_at, duration_secs, summary_json
-             FROM sessions WHERE id = ?1",
+            &format!("SELECT {} FROM sessions WHERE id = ?1", SESSION_COLUMNS),
             params![id],
-            |row| {
-                Ok(Session {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    mode: row.get(2)?,
-                    audio_path: row.get(3)?,
-                    created_at: row.get(4)?,
-                    duration_secs: row.get(5)?,
-                    summary_json: row.get(6)?,
-                })
-            },
+            row_to_session,
         )
     }
 
     pub fn list_sessions(&self) -> Result<Vec<Session>, rusqlite::Error> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, title, mode, audio_path, created_at, duration_secs, summary_json
-             FROM sessions ORDER BY created_at DESC",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            Ok(Session {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                mode: row.get(2)?,
-                audio_path: row.get(3)?,
-                created_at: row.get(4)?,
-                duration_secs: row.get(5)?,
-                summary_json: row.get(6)?,
-            })
-        })?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM sessions ORDER BY created_at DESC",
+            SESSION_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], row_to_session)?;
+        rows.collect()
+    }
+
+    /// The `limit` most recently created sessions.
+    pub fn list_recent_sessions(&self, limit: u32) -> Result<Vec<Session>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM sessions ORDER BY created_at DESC LIMIT ?1",
+            SESSION_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![limit], row_to_session)?;
+        rows.collect()
+    }
+
+    /// Lists sessions whose creation date (UTC, `YYYY-MM-DD`) falls within
+    /// `[start_date, end_date]`, oldest first.
+    pub fn list_sessions_between(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<Session>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM sessions WHERE substr(created_at, 1, 10) BETWEEN ?1 AND ?2
+             ORDER BY created_at ASC",
+            SESSION_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![start_date, end_date], row_to_session)?;
+        rows.collect()
+    }
+
+    /// Sessions created before `cutoff` (RFC 3339) that still reference an
+    /// audio file.
+    pub fn list_sessions_with_audio_before(&self, cutoff: &str) -> Result<Vec<Session>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM sessions
+             WHERE audio_path IS NOT NULL AND audio_path != '' AND created_at < ?1
+             ORDER BY created_at ASC",
+            SESSION_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![cutoff], row_to_session)?;
         rows.collect()
     }
 
@@ -161,16 +614,196 @@ impl Database {
         Ok(())
     }
 
+    pub fn set_session_protected(&self, id: &str, protected: bool) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE sessions SET protected = ?1 WHERE id = ?2",
+            params![protected as i32, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_session_archived(&self, id: &str, archived: bool) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE sessions SET archived = ?1 WHERE id = ?2",
+            params![archived as i32, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_session_speaker_count(&self, id: &str, count: i64) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE sessions SET speaker_count = ?1 WHERE id = ?2",
+            params![count, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_session_detected_language(&self, id: &str, language: &str) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE sessions SET detected_language = ?1 WHERE id = ?2",
+            params![language, id],
+        )?;
+        Ok(())
+    }
+
+    /// Backdates a session, e.g. to the recording date of an imported file.
+    pub fn set_session_created_at(&self, id: &str, created_at: &str) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE sessions SET created_at = ?1 WHERE id = ?2",
+            params![created_at, id],
+        )?;
+        Ok(())
+    }
+
+    /// Forgets the recording of a session and marks it transcript-only.
+    pub fn clear_session_audio(&self, id: &str) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE sessions SET audio_path = NULL, transcript_only = 1 WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_session_language(&self, id: &str, language: Option<&str>) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE sessions SET language = ?1 WHERE id = ?2",
+            params![language, id],
+        )?;
+        Ok(())
+    }
+
+    /// Stores the realtime text of a session: checkpointed while recording,
+    /// then replaced by the full text sent at the end.
+    pub fn save_live_transcript(&self, id: &str, text: &str) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE sessions SET live_transcript = ?1 WHERE id = ?2",
+            params![text, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_live_transcript(&self, id: &str) -> Result<Option<String>, rusqlite::Error> {
+        self.conn.query_row(
+            "SELECT live_transcript FROM sessions WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+    }
+
+    /// Answer previously given to `query` on the transcript whose hash is
+    /// `transcript_hash`.
+    pub fn get_cached_answer(
+        &self,
+        session_id: &str,
+        transcript_hash: &str,
+        query: &str,
+    ) -> Result<Option<String>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT answer FROM search_cache
+             WHERE session_id = ?1 AND transcript_hash = ?2 AND query = ?3",
+        )?;
+        let mut rows = stmt.query_map(params![session_id, transcript_hash, query], |row| {
+            row.get::<_, String>(0)
+        })?;
+        match rows.next() {
+            Some(result) => Ok(Some(result?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Stores an answer. Answers for older versions of the transcript are
+    /// dropped, as they can no longer be hit.
+    pub fn cache_answer(
+        &self,
+        session_id: &str,
+        transcript_hash: &str,
+        query: &str,
+        answer: &str,
+    ) -> Result<(), rusqlite::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "DELETE FROM search_cache WHERE session_id = ?1 AND transcript_hash != ?2",
+            params![session_id, transcript_hash],
+        )?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO search_cache (session_id, transcript_hash, query, answer, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![session_id, transcript_hash, query, answer, now],
+        )?;
+        Ok(())
+    }
+
+    /// Ids of the passcode-protected sessions.
+    pub fn protected_session_ids(&self) -> Result<HashSet<String>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare("SELECT id FROM sessions WHERE protected = 1")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    }
+
     pub fn delete_session(&self, id: &str) -> Result<(), rusqlite::Error> {
         self.conn.execute("DELETE FROM segments WHERE session_id = ?1", params![id])?;
+        self.conn.execute("DELETE FROM attachments WHERE session_id = ?1", params![id])?;
+        self.conn.execute("DELETE FROM speaker_colors WHERE session_id = ?1", params![id])?;
+        self.conn.execute("DELETE FROM operations WHERE session_id = ?1", params![id])?;
+        self.conn.execute("DELETE FROM search_cache WHERE session_id = ?1", params![id])?;
+        self.conn.execute("DELETE FROM session_topics WHERE session_id = ?1", params![id])?;
+        self.conn.execute("DELETE FROM live_action_items WHERE session_id = ?1", params![id])?;
+        self.conn.execute("DELETE FROM transcript_versions WHERE session_id = ?1", params![id])?;
+        self.conn.execute("DELETE FROM segment_revisions WHERE session_id = ?1", params![id])?;
         self.conn.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO deleted_sessions (session_id, deleted_at)
+             VALUES (?1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))",
+            params![id],
+        )?;
         Ok(())
     }
 
-    pub fn save_summary(&self, session_id: &str, summary_json: &str) -> Result<(), rusqlite::Error> {
+    /// Copies a session (metadata, summary and segments) under a new id and
+    /// title. The copy has no audio; callers attach a copied file if needed.
+    /// It is never archived, so that it can be edited.
+    pub fn duplicate_session(&self, id: &str, title: &str) -> Result<String, rusqlite::Error> {
+        let new_id = uuid::Uuid::new_v4().to_string();
+        let tx = self.conn.unchecked_transaction()?;
+        let copied = tx.execute(
+            "INSERT INTO sessions (id, title, mode, audio_path, created_at, duration_secs, summary_json, protected, language, transcript_only, summary_model)
+             SELECT ?1, ?2, mode, NULL, created_at, duration_secs, summary_json, protected, language, transcript_only, summary_model
+             FROM sessions WHERE id = ?3",
+            params![new_id, title, id],
+        )?;
+        if copied == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        tx.execute(
+            "INSERT INTO segments (session_id, text, start_time, end_time, speaker, is_diarized, language)
+             SELECT ?1, text, start_time, end_time, speaker, is_diarized, language
+             FROM segments WHERE session_id = ?2 ORDER BY id ASC",
+            params![new_id, id],
+        )?;
+        tx.execute(
+            "INSERT INTO speaker_colors (session_id, speaker, color)
+             SELECT ?1, speaker, color FROM speaker_colors WHERE session_id = ?2",
+            params![new_id, id],
+        )?;
+        tx.execute(
+            "INSERT INTO session_topics (session_id, topic, topic_key)
+             SELECT ?1, topic, topic_key FROM session_topics WHERE session_id = ?2",
+            params![new_id, id],
+        )?;
+        tx.commit()?;
+        Ok(new_id)
+    }
+
+    /// Stores a summary and the model that generated it, if any.
+    pub fn save_summary(
+        &self,
+        session_id: &str,
+        summary_json: &str,
+        model: Option<&str>,
+    ) -> Result<(), rusqlite::Error> {
         self.conn.execute(
-            "UPDATE sessions SET summary_json = ?1 WHERE id = ?2",
-            params![summary_json, session_id],
+            "UPDATE sessions SET summary_json = ?1, summary_model = ?2 WHERE id = ?3",
+            params![summary_json, model, session_id],
         )?;
         Ok(())
     }
@@ -194,27 +827,72 @@ impl Database {
         Ok(self.conn.last_insert_rowid())
     }
 
+    /// Inserts segments in a single transaction, language included, and
+    /// returns their ids in order. Nothing is inserted if one of them fails.
+    pub fn save_segments_batch(
+        &self,
+        session_id: &str,
+        segments: &[NewSegment],
+    ) -> Result<Vec<i64>, rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut ids = Vec::with_capacity(segments.len());
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO segments (session_id, text, start_time, end_time, speaker, is_diarized, language)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for seg in segments {
+                stmt.execute(params![
+                    session_id,
+                    seg.text,
+                    seg.start,
+                    seg.end,
+                    seg.speaker,
+                    seg.is_diarized as i32,
+                    seg.language,
+                ])?;
+                ids.push(tx.last_insert_rowid());
+            }
+        }
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    pub fn get_segment(&self, id: i64) -> Result<Segment, rusqlite::Error> {
+        self.conn.query_row(
+            "SELECT id, session_id, text, start_time, end_time, speaker, is_diarized, language
+             FROM segments WHERE id = ?1",
+            params![id],
+            row_to_segment,
+        )
+    }
+
     pub fn get_segments(&self, session_id: &str) -> Result<Vec<Segment>, rusqlite::Error> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, session_id, text, start_time, end_time, speaker, is_diarized
+            "SELECT id, session_id, text, start_time, end_time, speaker, is_diarized, language
              FROM segments WHERE session_id = ?1 ORDER BY start_time ASC",
         )?;
-        let rows = stmt.query_map(params![session_id], |row| {
-            let is_diarized_int: i32 = row.get(6)?;
-            Ok(Segment {
-                id: row.get(0)?,
-                session_id: row.get(1)?,
-                text: row.get(2)?,
-                start_time: row.get(3)?,
-                end_time: row.get(4)?,
-                speaker: row.get(5)?,
-                is_diarized: is_diarized_int != 0,
-            })
-        })?;
+        let rows = stmt.query_map(params![session_id], row_to_segment)?;
         rows.collect()
     }
 
+    pub fn set_segment_language(&self, id: i64, language: Option<&str>) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE segments SET language = ?1 WHERE id = ?2",
+            params![language, id],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes the segments of a session (only the diarized ones if asked).
+    /// Journaled, so it can be undone.
     pub fn clear_segments(&self, session_id: &str, diarized_only: bool) -> Result<(), rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let segments: Vec<Segment> = self
+            .get_segments(session_id)?
+            .into_iter()
+            .filter(|s| !diarized_only || s.is_diarized)
+            .collect();
         if diarized_only {
             self.conn.execute(
                 "DELETE FROM segments WHERE session_id = ?1 AND is_diarized = 1",
@@ -226,7 +904,20 @@ impl Database {
                 params![session_id],
             )?;
         }
-        Ok(())
+        if !segments.is_empty() {
+            self.journal(session_id, &JournalEntry::DeleteSegments { segments })?;
+        }
+        tx.commit()
+    }
+
+    /// Deletes one segment. Journaled, so it can be undone.
+    pub fn delete_segment(&self, id: i64) -> Result<(), rusqlite::Error> {
+        let segment = self.get_segment(id)?;
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute("DELETE FROM segments WHERE id = ?1", params![id])?;
+        let session_id = segment.session_id.clone();
+        self.journal(&session_id, &JournalEntry::DeleteSegments { segments: vec![segment] })?;
+        tx.commit()
     }
 
     pub fn clear_live_segments(&self, session_id: &str) -> Result<(), rusqlite::Error> {
@@ -237,40 +928,233 @@ impl Database {
         Ok(())
     }
 
+    /// Sets the speakers attributed to live segments during the recording.
+    /// Diarized segments are left alone; unlike `set_segment_speaker`, this
+    /// is not a user edit and is not journaled.
+    pub fn set_live_speakers(&self, session_id: &str, speakers: &[(i64, String)]) -> Result<(), rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE segments SET speaker = ?1 WHERE id = ?2 AND session_id = ?3 AND is_diarized = 0",
+            )?;
+            for (id, speaker) in speakers {
+                stmt.execute(params![speaker, id, session_id])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Shifts every segment of a session by `delta_secs` (negative moves
+    /// them earlier), clamping at zero. Returns the number of segments moved.
+    pub fn shift_segments(&self, session_id: &str, delta_secs: f64) -> Result<usize, rusqlite::Error> {
+        let changed = self.conn.execute(
+            "UPDATE segments SET start_time = MAX(0, start_time + ?1), end_time = MAX(0, end_time + ?1)
+             WHERE session_id = ?2",
+            params![delta_secs, session_id],
+        )?;
+        Ok(changed)
+    }
+
     pub fn rename_speaker(
         &self,
         session_id: &str,
         old_name: &str,
         new_name: &str,
     ) -> Result<usize, rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let segment_ids: Vec<i64> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id FROM segments WHERE session_id = ?1 AND speaker = ?2")?;
+            let rows = stmt.query_map(params![session_id, old_name], |row| row.get(0))?;
+            rows.collect::<Result<_, _>>()?
+        };
         let changed = self.conn.execute(
             "UPDATE segments SET speaker = ?1 WHERE session_id = ?2 AND speaker = ?3",
             params![new_name, session_id, old_name],
         )?;
+        // Keep the session color attached to the renamed speaker
+        let color_moved = self.conn.execute(
+            "UPDATE OR REPLACE speaker_colors SET speaker = ?1 WHERE session_id = ?2 AND speaker = ?3",
+            params![new_name, session_id, old_name],
+        )? > 0;
+        if changed > 0 {
+            self.record_speaker_use(new_name)?;
+            self.journal(
+                session_id,
+                &JournalEntry::RenameSpeaker {
+                    old_name: old_name.to_string(),
+                    new_name: new_name.to_string(),
+                    segment_ids,
+                    color_moved,
+                },
+            )?;
+        }
+        tx.commit()?;
         Ok(changed)
     }
 
+    /// Moves a single segment to `speaker`, e.g. one line attributed to the
+    /// wrong person, leaving the rest of the session alone. Returns `false`
+    /// when the segment does not belong to the session.
+    pub fn set_segment_speaker(
+        &self,
+        session_id: &str,
+        segment_id: i64,
+        speaker: &str,
+    ) -> Result<bool, rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let old_speaker: Option<String> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT speaker FROM segments WHERE id = ?1 AND session_id = ?2")?;
+            let mut rows = stmt.query_map(params![segment_id, session_id], |row| row.get(0))?;
+            match rows.next() {
+                Some(row) => row?,
+                None => return Ok(false),
+            }
+        };
+        if old_speaker.as_deref() == Some(speaker) {
+            return Ok(true);
+        }
+        self.conn.execute(
+            "UPDATE segments SET speaker = ?1 WHERE id = ?2",
+            params![speaker, segment_id],
+        )?;
+        self.record_speaker_use(speaker)?;
+        self.journal(
+            session_id,
+            &JournalEntry::SetSegmentSpeaker {
+                segment_id,
+                old_speaker,
+                new_speaker: speaker.to_string(),
+            },
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    // ── Speaker colors ────────────────────────────────────────────────
+
+    /// Sets the color of a speaker within one session.
+    pub fn set_session_speaker_color(
+        &self,
+        session_id: &str,
+        speaker: &str,
+        color: &str,
+    ) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO speaker_colors (session_id, speaker, color) VALUES (?1, ?2, ?3)
+             ON CONFLICT(session_id, speaker) DO UPDATE SET color = excluded.color",
+            params![session_id, speaker, color],
+        )?;
+        Ok(())
+    }
+
+    /// Sets the global color of a speaker profile, used in every session
+    /// without a session-specific color.
+    pub fn set_profile_color(&self, name: &str, color: &str) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO speaker_profiles (name, color) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET color = excluded.color",
+            params![name, color],
+        )?;
+        Ok(())
+    }
+
+    /// Creates or updates the metadata of a speaker profile, keeping its color.
+    pub fn upsert_speaker_profile(&self, profile: &SpeakerProfile) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO speaker_profiles (name, role, company, email) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(name) DO UPDATE SET
+                role = excluded.role, company = excluded.company, email = excluded.email",
+            params![profile.name, profile.role, profile.company, profile.email],
+        )?;
+        Ok(())
+    }
+
+    /// Adds a name to the participants directory, or bumps its usage.
+    pub fn record_speaker_use(&self, name: &str) -> Result<(), rusqlite::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO speaker_profiles (name, use_count, last_used_at) VALUES (?1, 1, ?2)
+             ON CONFLICT(name) DO UPDATE SET
+                use_count = use_count + 1, last_used_at = excluded.last_used_at",
+            params![name, now],
+        )?;
+        Ok(())
+    }
+
+    /// Directory names fuzzily matching `query`, best match first, then the
+    /// most frequently used.
+    pub fn suggest_speakers(&self, query: &str, limit: usize) -> Result<Vec<SpeakerProfile>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, color, role, company, email, use_count FROM speaker_profiles
+             ORDER BY use_count DESC, last_used_at DESC, name ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row_to_speaker_profile(row)?, row.get::<_, i64>(5)?))
+        })?;
+        let mut scored = Vec::new();
+        for row in rows {
+            let (profile, use_count) = row?;
+            if let Some(score) = fuzzy_score(query, &profile.name) {
+                scored.push((score, use_count, profile));
+            }
+        }
+        // Stable sort: equal scores keep the usage order from the query
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+        Ok(scored.into_iter().take(limit).map(|(_, _, p)| p).collect())
+    }
+
+    pub fn list_speaker_profiles(&self) -> Result<Vec<SpeakerProfile>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, color, role, company, email FROM speaker_profiles ORDER BY name ASC",
+        )?;
+        let rows = stmt.query_map([], row_to_speaker_profile)?;
+        rows.collect()
+    }
+
+    /// Profiles of the speakers appearing in a session, in order of first
+    /// appearance. Speakers without a profile get an empty one.
+    pub fn get_session_participants(&self, session_id: &str) -> Result<Vec<SpeakerProfile>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.speaker, sp.color, sp.role, sp.company, sp.email
+             FROM segments s
+             LEFT JOIN speaker_profiles sp ON sp.name = s.speaker
+             WHERE s.session_id = ?1 AND s.speaker IS NOT NULL
+             GROUP BY s.speaker
+             ORDER BY MIN(s.start_time) ASC",
+        )?;
+        let rows = stmt.query_map(params![session_id], row_to_speaker_profile)?;
+        rows.collect()
+    }
+
+    /// Explicit colors of the speakers of a session: the session-specific
+    /// color wins over the global profile one. Uncolored speakers are omitted.
+    pub fn get_speaker_colors(&self, session_id: &str) -> Result<HashMap<String, String>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT s.speaker, COALESCE(sc.color, sp.color)
+             FROM segments s
+             LEFT JOIN speaker_colors sc ON sc.session_id = s.session_id AND sc.speaker = s.speaker
+             LEFT JOIN speaker_profiles sp ON sp.name = s.speaker
+             WHERE s.session_id = ?1 AND s.speaker IS NOT NULL
+               AND COALESCE(sc.color, sp.color) IS NOT NULL",
+        )?;
+        let rows = stmt.query_map(params![session_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        rows.collect()
+    }
+
     pub fn search_text(
         &self,
         query: &str,
         session_id: Option<&str>,
     ) -> Result<Vec<Segment>, rusqlite::Error> {
-        fn row_to_segment(row: &rusqlite::Row<'_>) -> Result<Segment, rusqlite::Error> {
-            let is_diarized_int: i32 = row.get(6)?;
-            Ok(Segment {
-                id: row.get(0)?,
-                session_id: row.get(1)?,
-                text: row.get(2)?,
-                start_time: row.get(3)?,
-                end_time: row.get(4)?,
-                speaker: row.get(5)?,
-                is_diarized: is_diarized_int != 0,
-            })
-        }
-
         if let Some(sid) = session_id {
             let mut stmt = self.conn.prepare(
-                "SELECT s.id, s.session_id, s.text, s.start_time, s.end_time, s.speaker, s.is_diarized
+                "SELECT s.id, s.session_id, s.text, s.start_time, s.end_time, s.speaker, s.is_diarized, s.language
                  FROM segments s
                  INNER JOIN segments_fts fts ON s.id = fts.rowid
                  WHERE segments_fts MATCH ?1 AND s.session_id = ?2
@@ -280,7 +1164,7 @@ impl Database {
             rows.collect()
         } else {
             let mut stmt = self.conn.prepare(
-                "SELECT s.id, s.session_id, s.text, s.start_time, s.end_time, s.speaker, s.is_diarized
+                "SELECT s.id, s.session_id, s.text, s.start_time, s.end_time, s.speaker, s.is_diarized, s.language
                  FROM segments s
                  INNER JOIN segments_fts fts ON s.id = fts.rowid
                  WHERE segments_fts MATCH ?1
@@ -291,6 +1175,118 @@ impl Database {
         }
     }
 
+    // ── Attachments ───────────────────────────────────────────────────
+
+    pub fn add_attachment(
+        &self,
+        session_id: &str,
+        file_name: &str,
+        path: &str,
+        size_bytes: i64,
+    ) -> Result<i64, rusqlite::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO attachments (session_id, file_name, path, size_bytes, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![session_id, file_name, path, size_bytes, now],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_attachments(&self, session_id: &str) -> Result<Vec<Attachment>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, file_name, path, size_bytes, created_at
+             FROM attachments WHERE session_id = ?1 ORDER BY id ASC",
+        )?;
+        let rows = stmt.query_map(params![session_id], row_to_attachment)?;
+        rows.collect()
+    }
+
+    pub fn get_attachment(&self, id: i64) -> Result<Attachment, rusqlite::Error> {
+        self.conn.query_row(
+            "SELECT id, session_id, file_name, path, size_bytes, created_at
+             FROM attachments WHERE id = ?1",
+            params![id],
+            row_to_attachment,
+        )
+    }
+
+    pub fn delete_attachment(&self, id: i64) -> Result<(), rusqlite::Error> {
+        self.conn.execute("DELETE FROM attachments WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // ── Statistics ────────────────────────────────────────────────────
+
+    /// Computes dashboard totals with SQL aggregates. `top_speakers_limit`
+    /// caps the number of speakers returned, ordered by speaking time.
+    /// Protected sessions are left out unless `include_protected`.
+    pub fn get_statistics(
+        &self,
+        top_speakers_limit: usize,
+        include_protected: bool,
+    ) -> Result<Statistics, rusqlite::Error> {
+        let (total_sessions, total_duration, average_duration_secs): (i64, f64, Option<f64>) =
+            self.conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(duration_secs), 0), AVG(duration_secs)
+                 FROM sessions WHERE ?1 OR protected = 0",
+                params![include_protected],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )?;
+
+        // Word count approximated as the number of space-separated tokens.
+        let total_words: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(
+                 LENGTH(TRIM(text)) - LENGTH(REPLACE(TRIM(text), ' ', '')) + 1
+             ), 0)
+             FROM segments
+             WHERE TRIM(text) != ''
+               AND (?1 OR session_id NOT IN (SELECT id FROM sessions WHERE protected = 1))",
+            params![include_protected],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT strftime('%Y-%W', substr(created_at, 1, 10)) AS week, COUNT(*)
+             FROM sessions WHERE ?1 OR protected = 0
+             GROUP BY week ORDER BY week ASC",
+        )?;
+        let sessions_per_week = stmt
+            .query_map(params![include_protected], |row| {
+                Ok(WeekCount {
+                    week: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT speaker, COUNT(*), SUM(end_time - start_time) AS speaking
+             FROM segments
+             WHERE speaker IS NOT NULL
+               AND (?2 OR session_id NOT IN (SELECT id FROM sessions WHERE protected = 1))
+             GROUP BY speaker ORDER BY speaking DESC LIMIT ?1",
+        )?;
+        let top_speakers = stmt
+            .query_map(params![top_speakers_limit as i64, include_protected], |row| {
+                Ok(SpeakerStat {
+                    speaker: row.get(0)?,
+                    segment_count: row.get(1)?,
+                    speaking_secs: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Statistics {
+            total_sessions,
+            recorded_hours: total_duration / 3600.0,
+            total_words,
+            average_duration_secs,
+            sessions_per_week,
+            top_speakers,
+        })
+    }
+
     // ── Settings ──────────────────────────────────────────────────────
 
     pub fn get_setting(&self, key: &str) -> Result<Option<String>, rusqlite::Error> {
@@ -339,6 +1335,32 @@ mod tests {
         assert_eq!(sessions.len(), 2);
     }
 
+    #[test]
+    fn test_list_sessions_between() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Today", "visio").unwrap();
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let sessions = db.list_sessions_between(&today, &today).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, id);
+        let sessions = db.list_sessions_between("2000-01-01", "2000-01-07").unwrap();
+        assert!(sessions.is_empty());
+    }
+
+    #[test]
+    fn test_list_sessions_with_audio_before() {
+        let db = Database::new_in_memory().unwrap();
+        let with_audio = db.create_session("With audio", "visio").unwrap();
+        db.create_session("No audio", "visio").unwrap();
+        db.update_session_audio_path(&with_audio, "/tmp/a.wav").unwrap();
+        let future = (chrono::Utc::now() + chrono::Duration::days(1)).to_rfc3339();
+        let sessions = db.list_sessions_with_audio_before(&future).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, with_audio);
+        let past = (chrono::Utc::now() - chrono::Duration::days(1)).to_rfc3339();
+        assert!(db.list_sessions_with_audio_before(&past).unwrap().is_empty());
+    }
+
     #[test]
     fn test_update_session_duration() {
         let db = Database::new_in_memory().unwrap();
@@ -366,14 +1388,171 @@ mod tests {
         assert_eq!(session.title, "New Title");
     }
 
+    #[test]
+    fn test_duplicate_session() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Original", "visio").unwrap();
+        db.update_session_audio_path(&id, "/tmp/audio.wav").unwrap();
+        db.save_summary(&id, r#"{"key_points": []}"#, None).unwrap();
+        db.save_segment(&id, "Bonjour", 0.0, 1.0, Some("S1"), true).unwrap();
+        db.save_segment(&id, "Salut", 1.0, 2.0, Some("S2"), true).unwrap();
+
+        let copy_id = db.duplicate_session(&id, "Copie").unwrap();
+        let copy = db.get_session(&copy_id).unwrap();
+        assert_eq!(copy.title, "Copie");
+        assert_eq!(copy.mode, "visio");
+        assert!(copy.audio_path.is_none());
+        assert_eq!(copy.summary_json.as_deref(), Some(r#"{"key_points": []}"#));
+        let segments = db.get_segments(&copy_id).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].speaker.as_deref(), Some("S2"));
+        // FTS index covers the copied segments too
+        assert_eq!(db.search_text("bonjour", Some(&copy_id)).unwrap().len(), 1);
+        // Original untouched
+        assert_eq!(db.get_segments(&id).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_duplicate_session_not_found() {
+        let db = Database::new_in_memory().unwrap();
+        assert!(db.duplicate_session("nonexistent-id", "Copie").is_err());
+    }
+
+    #[test]
+    fn test_protected_sessions() {
+        let db = Database::new_in_memory().unwrap();
+        let a = db.create_session("Public", "visio").unwrap();
+        let b = db.create_session("RH", "visio").unwrap();
+        assert!(!db.get_session(&b).unwrap().protected);
+
+        db.set_session_protected(&b, true).unwrap();
+        assert!(db.get_session(&b).unwrap().protected);
+        let ids = db.protected_session_ids().unwrap();
+        assert!(ids.contains(&b) && !ids.contains(&a));
+        // Copies stay protected
+        let copy = db.duplicate_session(&b, "Copie").unwrap();
+        assert!(db.get_session(&copy).unwrap().protected);
+    }
+
+    #[test]
+    fn test_live_transcript() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        assert!(db.get_live_transcript(&id).unwrap().is_none());
+        db.save_live_transcript(&id, "Bonjour a tous").unwrap();
+        assert_eq!(db.get_live_transcript(&id).unwrap().as_deref(), Some("Bonjour a tous"));
+    }
+
+    #[test]
+    fn test_save_segments_batch() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        let segments = [
+            NewSegment {
+                text: "Bonjour",
+                start: 0.0,
+                end: 1.0,
+                speaker: Some("speaker_0"),
+                is_diarized: true,
+                language: Some("fr"),
+            },
+            NewSegment {
+                text: "Hello",
+                start: 1.0,
+                end: 2.0,
+                speaker: None,
+                is_diarized: false,
+                language: None,
+            },
+        ];
+        let ids = db.save_segments_batch(&id, &segments).unwrap();
+        assert_eq!(ids.len(), 2);
+
+        let saved = db.get_segments(&id).unwrap();
+        assert_eq!(saved.iter().map(|s| s.id).collect::<Vec<_>>(), ids);
+        assert_eq!(saved[0].language.as_deref(), Some("fr"));
+        assert_eq!(saved[0].speaker.as_deref(), Some("speaker_0"));
+        assert!(!saved[1].is_diarized);
+        // Indexed for search like single inserts
+        assert_eq!(db.search_text("Hello", Some(&id)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_search_cache() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        assert!(db.get_cached_answer(&id, "h1", "budget ?").unwrap().is_none());
+        db.cache_answer(&id, "h1", "budget ?", "10 k").unwrap();
+        assert_eq!(db.get_cached_answer(&id, "h1", "budget ?").unwrap().as_deref(), Some("10 k"));
+        // A new transcript version invalidates the old answers
+        db.cache_answer(&id, "h2", "date ?", "lundi").unwrap();
+        assert!(db.get_cached_answer(&id, "h1", "budget ?").unwrap().is_none());
+        db.delete_session(&id).unwrap();
+        assert!(db.get_cached_answer(&id, "h2", "date ?").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_session_language() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        assert!(db.get_session(&id).unwrap().language.is_none());
+        db.set_session_language(&id, Some("en")).unwrap();
+        assert_eq!(db.get_session(&id).unwrap().language.as_deref(), Some("en"));
+        let copy = db.duplicate_session(&id, "Copie").unwrap();
+        assert_eq!(db.get_session(&copy).unwrap().language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_archived_sessions() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Comite", "visio").unwrap();
+        assert!(!db.get_session(&id).unwrap().archived);
+        db.set_session_archived(&id, true).unwrap();
+        assert!(db.get_session(&id).unwrap().archived);
+        let copy = db.duplicate_session(&id, "Copie").unwrap();
+        assert!(!db.get_session(&copy).unwrap().archived);
+        db.set_session_archived(&id, false).unwrap();
+        assert!(!db.get_session(&id).unwrap().archived);
+    }
+
+    #[test]
+    fn test_clear_session_audio() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Entretien", "presentiel").unwrap();
+        db.update_session_audio_path(&id, "/tmp/entretien.wav").unwrap();
+        assert!(!db.get_session(&id).unwrap().transcript_only);
+
+        db.clear_session_audio(&id).unwrap();
+        let session = db.get_session(&id).unwrap();
+        assert!(session.audio_path.is_none());
+        assert!(session.transcript_only);
+        let copy = db.duplicate_session(&id, "Copie").unwrap();
+        assert!(db.get_session(&copy).unwrap().transcript_only);
+    }
+
+    #[test]
+    fn test_session_processing_metadata() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        let session = db.get_session(&id).unwrap();
+        assert_eq!((session.speaker_count, session.detected_language), (None, None));
+
+        db.set_session_speaker_count(&id, 3).unwrap();
+        db.set_session_detected_language(&id, "fr").unwrap();
+        let session = &db.list_sessions().unwrap()[0];
+        assert_eq!(session.speaker_count, Some(3));
+        assert_eq!(session.detected_language.as_deref(), Some("fr"));
+    }
+
     #[test]
     fn test_save_summary() {
         let db = Database::new_in_memory().unwrap();
         let id = db.create_session("Test", "visio").unwrap();
         let summary = r#"{"key_points": ["point 1"]}"#;
-        db.save_summary(&id, summary).unwrap();
+        db.save_summary(&id, summary, Some("mistral-small-latest")).unwrap();
         let session = db.get_session(&id).unwrap();
         assert_eq!(session.summary_json.as_deref(), Some(summary));
+        assert_eq!(session.summary_model.as_deref(), Some("mistral-small-latest"));
     }
 
     #[test]
@@ -392,6 +1571,17 @@ mod tests {
         assert!(segments[1].is_diarized);
     }
 
+    #[test]
+    fn test_get_segment() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        let seg_id = db.save_segment(&id, "Ajout manuel", 4.0, 6.0, Some("Marie"), true).unwrap();
+        let segment = db.get_segment(seg_id).unwrap();
+        assert_eq!(segment.text, "Ajout manuel");
+        assert_eq!(segment.speaker.as_deref(), Some("Marie"));
+        assert!(db.get_segment(seg_id + 1).is_err());
+    }
+
     #[test]
     fn test_clear_segments_all() {
         let db = Database::new_in_memory().unwrap();
@@ -416,6 +1606,19 @@ mod tests {
         assert!(segments[0].is_diarized);
     }
 
+    #[test]
+    fn test_set_live_speakers() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        let live = db.save_segment(&id, "Live seg", 0.0, 1.0, None, false).unwrap();
+        let diarized = db.save_segment(&id, "Diarized seg", 1.0, 2.0, Some("S1"), true).unwrap();
+        db.set_live_speakers(&id, &[(live, "speaker_0".to_string()), (diarized, "speaker_1".to_string())])
+            .unwrap();
+        let segments = db.get_segments(&id).unwrap();
+        assert_eq!(segments[0].speaker.as_deref(), Some("speaker_0"));
+        assert_eq!(segments[1].speaker.as_deref(), Some("S1"));
+    }
+
     #[test]
     fn test_clear_segments_diarized_only() {
         let db = Database::new_in_memory().unwrap();
@@ -451,6 +1654,25 @@ mod tests {
         assert_eq!(results[0].session_id, id1);
     }
 
+    #[test]
+    fn test_shift_segments() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        db.save_segment(&id, "Premier", 1.0, 3.0, None, false).unwrap();
+        db.save_segment(&id, "Second", 5.0, 7.5, None, false).unwrap();
+
+        assert_eq!(db.shift_segments(&id, 2.0).unwrap(), 2);
+        let segments = db.get_segments(&id).unwrap();
+        assert_eq!(segments[0].start_time, 3.0);
+        assert_eq!(segments[1].end_time, 9.5);
+
+        db.shift_segments(&id, -4.0).unwrap();
+        let segments = db.get_segments(&id).unwrap();
+        assert_eq!(segments[0].start_time, 0.0);
+        assert_eq!(segments[0].end_time, 1.0);
+        assert_eq!(segments[1].start_time, 4.0);
+    }
+
     #[test]
     fn test_rename_speaker() {
         let db = Database::new_in_memory().unwrap();
@@ -466,6 +1688,116 @@ mod tests {
         assert_eq!(segments[2].speaker.as_deref(), Some("Speaker 2"));
     }
 
+    #[test]
+    fn test_speaker_colors() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        db.save_segment(&id, "Hello", 0.0, 1.0, Some("Marie"), true).unwrap();
+        db.save_segment(&id, "Hi", 1.0, 2.0, Some("Paul"), true).unwrap();
+        db.save_segment(&id, "Hey", 2.0, 3.0, Some("Speaker 3"), true).unwrap();
+
+        db.set_profile_color("Marie", "#111111").unwrap();
+        db.set_profile_color("Paul", "#222222").unwrap();
+        db.set_session_speaker_color(&id, "Paul", "#333333").unwrap();
+
+        let colors = db.get_speaker_colors(&id).unwrap();
+        assert_eq!(colors.len(), 2);
+        assert_eq!(colors["Marie"], "#111111");
+        assert_eq!(colors["Paul"], "#333333");
+
+        // Session colors follow speaker renames
+        db.rename_speaker(&id, "Paul", "Paul D.").unwrap();
+        let colors = db.get_speaker_colors(&id).unwrap();
+        assert_eq!(colors["Paul D."], "#333333");
+    }
+
+    #[test]
+    fn test_migrations_are_recorded() {
+        let db = Database::new_in_memory().unwrap();
+        let version: i64 = db.conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(version as usize, MIGRATIONS.len());
+        // Re-running is a no-op
+        db.migrate().unwrap();
+    }
+
+    #[test]
+    fn test_segment_reads_use_index() {
+        let db = Database::new_in_memory().unwrap();
+        let plan: String = db
+            .conn
+            .query_row(
+                "EXPLAIN QUERY PLAN SELECT id FROM segments WHERE session_id = 'x' ORDER BY start_time ASC",
+                [],
+                |row| row.get(3),
+            )
+            .unwrap();
+        assert!(plan.contains("idx_segments_session_start"), "{}", plan);
+    }
+
+    #[test]
+    fn test_speaker_profiles() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        db.save_segment(&id, "Bonjour", 0.0, 1.0, Some("Marie"), true).unwrap();
+        db.save_segment(&id, "Salut", 1.0, 2.0, Some("Paul"), true).unwrap();
+        db.save_segment(&id, "Re", 2.0, 3.0, Some("Marie"), true).unwrap();
+
+        db.set_profile_color("Marie", "#111111").unwrap();
+        db.upsert_speaker_profile(&SpeakerProfile {
+            name: "Marie".to_string(),
+            role: Some("CFO".to_string()),
+            company: Some("Acme".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let profiles = db.list_speaker_profiles().unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].color.as_deref(), Some("#111111"));
+        assert_eq!(profiles[0].describe(), "Marie (CFO, Acme)");
+
+        let participants = db.get_session_participants(&id).unwrap();
+        assert_eq!(participants.len(), 2);
+        assert_eq!(participants[0].name, "Marie");
+        assert_eq!(participants[0].role.as_deref(), Some("CFO"));
+        assert_eq!(participants[1].describe(), "Paul");
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("ma", "Marie Dupont"), Some(4));
+        assert_eq!(fuzzy_score("dup", "Marie Dupont"), Some(3));
+        assert_eq!(fuzzy_score("ri", "Marie Dupont"), Some(2));
+        assert_eq!(fuzzy_score("mdp", "Marie Dupont"), Some(1));
+        assert_eq!(fuzzy_score("hel", "Hélène"), Some(4));
+        assert_eq!(fuzzy_score("xyz", "Marie Dupont"), None);
+    }
+
+    #[test]
+    fn test_suggest_speakers_from_renames() {
+        let db = Database::new_in_memory().unwrap();
+        let a = db.create_session("A", "visio").unwrap();
+        let b = db.create_session("B", "visio").unwrap();
+        db.save_segment(&a, "Bonjour", 0.0, 1.0, Some("speaker_1"), true).unwrap();
+        db.save_segment(&a, "Salut", 1.0, 2.0, Some("speaker_2"), true).unwrap();
+        db.save_segment(&b, "Re", 0.0, 1.0, Some("speaker_1"), true).unwrap();
+
+        db.rename_speaker(&a, "speaker_1", "Marc").unwrap();
+        db.rename_speaker(&a, "speaker_2", "Marie").unwrap();
+        db.rename_speaker(&b, "speaker_1", "Marie").unwrap();
+        // No matching segment: not recorded
+        db.rename_speaker(&b, "speaker_9", "Martin").unwrap();
+
+        let names: Vec<String> = db
+            .suggest_speakers("mar", 10)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["Marie", "Marc"]);
+        assert_eq!(db.suggest_speakers("", 1).unwrap().len(), 1);
+    }
+
     #[test]
     fn test_settings() {
         let db = Database::new_in_memory().unwrap();
@@ -476,6 +1808,82 @@ mod tests {
         assert_eq!(db.get_setting("api_key").unwrap().unwrap(), "new-key");
     }
 
+    #[test]
+    fn test_attachments() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Test", "visio").unwrap();
+        let att_id = db.add_attachment(&id, "slides.pdf", "/tmp/slides.pdf", 1024).unwrap();
+        db.add_attachment(&id, "agenda.txt", "/tmp/agenda.txt", 12).unwrap();
+
+        let attachments = db.get_attachments(&id).unwrap();
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].file_name, "slides.pdf");
+        assert_eq!(db.get_attachment(att_id).unwrap().size_bytes, 1024);
+
+        db.delete_attachment(att_id).unwrap();
+        assert_eq!(db.get_attachments(&id).unwrap().len(), 1);
+
+        db.delete_session(&id).unwrap();
+        assert!(db.get_attachments(&id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_statistics() {
+        let db = Database::new_in_memory().unwrap();
+        let id1 = db.create_session("Meeting 1", "visio").unwrap();
+        let id2 = db.create_session("Meeting 2", "inperson").unwrap();
+        db.update_session_duration(&id1, 3600.0).unwrap();
+        db.update_session_duration(&id2, 1800.0).unwrap();
+        db.save_segment(&id1, "Bonjour tout le monde", 0.0, 10.0, Some("Alice"), true).unwrap();
+        db.save_segment(&id1, "Salut", 10.0, 12.0, Some("Bob"), true).unwrap();
+        db.save_segment(&id2, "On commence", 0.0, 3.0, Some("Alice"), true).unwrap();
+
+        let stats = db.get_statistics(5, false).unwrap();
+        assert_eq!(stats.total_sessions, 2);
+        assert_eq!(stats.recorded_hours, 1.5);
+        assert_eq!(stats.total_words, 7);
+        assert_eq!(stats.average_duration_secs, Some(2700.0));
+        assert_eq!(stats.sessions_per_week.iter().map(|w| w.count).sum::<i64>(), 2);
+        assert_eq!(stats.top_speakers.len(), 2);
+        assert_eq!(stats.top_speakers[0].speaker, "Alice");
+        assert_eq!(stats.top_speakers[0].segment_count, 2);
+        assert_eq!(stats.top_speakers[0].speaking_secs, 13.0);
+    }
+
+    #[test]
+    fn test_get_statistics_protected() {
+        let db = Database::new_in_memory().unwrap();
+        let public = db.create_session("Public", "visio").unwrap();
+        let secret = db.create_session("RH", "visio").unwrap();
+        db.update_session_duration(&public, 600.0).unwrap();
+        db.update_session_duration(&secret, 1200.0).unwrap();
+        db.save_segment(&public, "Bonjour", 0.0, 5.0, Some("Alice"), true).unwrap();
+        db.save_segment(&secret, "Entretien annuel", 0.0, 60.0, Some("Carole"), true).unwrap();
+        db.set_session_protected(&secret, true).unwrap();
+
+        let locked = db.get_statistics(5, false).unwrap();
+        assert_eq!(locked.total_sessions, 1);
+        assert_eq!(locked.average_duration_secs, Some(600.0));
+        assert_eq!(locked.total_words, 1);
+        assert_eq!(locked.sessions_per_week.iter().map(|w| w.count).sum::<i64>(), 1);
+        assert!(locked.top_speakers.iter().all(|s| s.speaker != "Carole"));
+
+        let unlocked = db.get_statistics(5, true).unwrap();
+        assert_eq!(unlocked.total_sessions, 2);
+        assert_eq!(unlocked.top_speakers[0].speaker, "Carole");
+    }
+
+    #[test]
+    fn test_get_statistics_empty() {
+        let db = Database::new_in_memory().unwrap();
+        let stats = db.get_statistics(5, false).unwrap();
+        assert_eq!(stats.total_sessions, 0);
+        assert_eq!(stats.total_words, 0);
+        assert!(stats.average_duration_secs.is_none());
+        assert!(stats.sessions_per_week.is_empty());
+        assert!(stats.top_speakers.is_empty());
+    }
+
     #[test]
     fn test_get_session_not_found() {
         let db = Database::new_in_memory().unwrap();
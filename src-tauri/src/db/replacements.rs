@@ -0,0 +1,121 @@
+//! User replacement dictionary ("K8S" -> "Kubernetes", misheard product
+//! names), applied to incoming segments and on demand to stored sessions.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replacement {
+    pub id: i64,
+    /// Word or phrase to replace, matched as whole words, ignoring case.
+    pub pattern: String,
+    pub replacement: String,
+    pub created_at: String,
+}
+
+impl Database {
+    pub fn list_replacements(&self) -> Result<Vec<Replacement>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, pattern, replacement, created_at FROM replacements ORDER BY pattern_key ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Replacement {
+                id: row.get(0)?,
+                pattern: row.get(1)?,
+                replacement: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Adds a replacement, or updates the one with the same pattern
+    /// (ignoring case).
+    pub fn set_replacement(&self, pattern: &str, replacement: &str) -> Result<Replacement, rusqlite::Error> {
+        let pattern = pattern.trim();
+        let replacement = replacement.trim();
+        let created_at = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO replacements (pattern, replacement, pattern_key, created_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(pattern_key) DO UPDATE SET pattern = excluded.pattern, replacement = excluded.replacement",
+            params![pattern, replacement, pattern.to_lowercase(), created_at],
+        )?;
+        self.conn.query_row(
+            "SELECT id, pattern, replacement, created_at FROM replacements WHERE pattern_key = ?1",
+            params![pattern.to_lowercase()],
+            |row| {
+                Ok(Replacement {
+                    id: row.get(0)?,
+                    pattern: row.get(1)?,
+                    replacement: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            },
+        )
+    }
+
+    pub fn delete_replacement(&self, id: i64) -> Result<(), rusqlite::Error> {
+        self.conn.execute("DELETE FROM replacements WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Rewrites the text of the segments of a session with `apply`, and
+    /// returns how many changed.
+    pub fn rewrite_segments(
+        &self,
+        session_id: &str,
+        apply: impl Fn(&str) -> String,
+    ) -> Result<usize, rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut changed = 0;
+        {
+            let mut select = tx.prepare("SELECT id, text FROM segments WHERE session_id = ?1")?;
+            let rows: Vec<(i64, String)> = select
+                .query_map(params![session_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<_, _>>()?;
+            let mut update = tx.prepare_cached("UPDATE segments SET text = ?1 WHERE id = ?2")?;
+            for (id, text) in rows {
+                let new_text = apply(&text);
+                if new_text != text {
+                    update.execute(params![new_text, id])?;
+                    changed += 1;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replacements() {
+        let db = Database::new_in_memory().unwrap();
+        db.set_replacement("K8S", "Kubernetes").unwrap();
+        let updated = db.set_replacement("k8s ", "Kubernetes (K8s)").unwrap();
+        db.set_replacement("Popscribe", "PopTranscribe").unwrap();
+        let replacements = db.list_replacements().unwrap();
+        assert_eq!(replacements.len(), 2);
+        assert_eq!(replacements[0].replacement, "Kubernetes (K8s)");
+        assert_eq!(replacements[0].pattern, "k8s");
+
+        db.delete_replacement(updated.id).unwrap();
+        assert_eq!(db.list_replacements().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rewrite_segments() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        db.save_segment(&id, "On deploie sur k8s", 0.0, 1.0, None, true).unwrap();
+        db.save_segment(&id, "Merci", 1.0, 2.0, None, true).unwrap();
+        let changed = db.rewrite_segments(&id, |t| t.replace("k8s", "Kubernetes")).unwrap();
+        assert_eq!(changed, 1);
+        assert_eq!(db.get_segments(&id).unwrap()[0].text, "On deploie sur Kubernetes");
+    }
+}
@@ -0,0 +1,100 @@
+//! Prior texts of edited segments. A trigger records the replaced text on
+//! every change, whatever made it (manual edit, replacements, adopted
+//! version, revert), so that several reviewers can see and undo each
+//! other's edits.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentRevision {
+    pub id: i64,
+    pub segment_id: i64,
+    /// Text of the segment before the edit.
+    pub text: String,
+    /// When it was replaced.
+    pub created_at: String,
+}
+
+impl Database {
+    /// Edits the text of a segment; the previous one is kept as a revision.
+    pub fn update_segment_text(&self, id: i64, text: &str) -> Result<(), rusqlite::Error> {
+        let changed = self
+            .conn
+            .execute("UPDATE segments SET text = ?1 WHERE id = ?2", params![text, id])?;
+        if changed == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        Ok(())
+    }
+
+    /// Revisions of a segment, most recent first.
+    pub fn get_segment_revisions(&self, segment_id: i64) -> Result<Vec<SegmentRevision>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, segment_id, text, created_at FROM segment_revisions
+             WHERE segment_id = ?1 ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map(params![segment_id], |row| {
+            Ok(SegmentRevision {
+                id: row.get(0)?,
+                segment_id: row.get(1)?,
+                text: row.get(2)?,
+                created_at: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn get_segment_revision(&self, id: i64) -> Result<SegmentRevision, rusqlite::Error> {
+        self.conn.query_row(
+            "SELECT id, segment_id, text, created_at FROM segment_revisions WHERE id = ?1",
+            params![id],
+            |row| {
+                Ok(SegmentRevision {
+                    id: row.get(0)?,
+                    segment_id: row.get(1)?,
+                    text: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            },
+        )
+    }
+
+    /// Puts the text of a revision back. The text it replaces becomes a
+    /// revision in turn, so a revert can itself be reverted.
+    pub fn revert_segment_revision(&self, revision_id: i64) -> Result<(), rusqlite::Error> {
+        let revision = self.get_segment_revision(revision_id)?;
+        self.update_segment_text(revision.segment_id, &revision.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_revisions() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Relecture", "visio").unwrap();
+        let segment = db.save_segment(&id, "Budget de 10 k", 0.0, 1.0, None, true).unwrap();
+        assert!(db.get_segment_revisions(segment).unwrap().is_empty());
+
+        db.update_segment_text(segment, "Budget de 10 000 euros").unwrap();
+        // Same text: nothing to keep
+        db.update_segment_text(segment, "Budget de 10 000 euros").unwrap();
+        db.update_segment_text(segment, "Budget de 12 000 euros").unwrap();
+        let revisions = db.get_segment_revisions(segment).unwrap();
+        let texts: Vec<&str> = revisions.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(texts, vec!["Budget de 10 000 euros", "Budget de 10 k"]);
+
+        db.revert_segment_revision(revisions[1].id).unwrap();
+        assert_eq!(db.get_segment(segment).unwrap().text, "Budget de 10 k");
+        assert_eq!(db.get_segment_revisions(segment).unwrap()[0].text, "Budget de 12 000 euros");
+
+        assert!(db.update_segment_text(segment + 1, "x").is_err());
+        db.delete_session(&id).unwrap();
+        assert!(db.get_segment_revisions(segment).unwrap().is_empty());
+    }
+}
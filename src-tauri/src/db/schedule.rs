@@ -0,0 +1,202 @@
+//! Scheduled recordings: sessions started and stopped automatically at a
+//! given time, optionally repeating.
+
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Weekday};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+/// Accepted values of `ScheduledRecording::recurrence`.
+pub const RECURRENCES: &[&str] = &["daily", "weekdays", "weekly"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledRecording {
+    pub id: i64,
+    pub title: String,
+    pub mode: String,
+    /// Next start, RFC 3339. Advanced after each run of a recurring entry.
+    pub start_at: String,
+    pub duration_minutes: u32,
+    /// `daily`, `weekdays`, `weekly`, or `None` for a single recording.
+    pub recurrence: Option<String>,
+    pub last_run_at: Option<String>,
+}
+
+impl ScheduledRecording {
+    pub fn start_time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.start_at)
+            .ok()
+            .map(|t| t.with_timezone(&Local))
+    }
+
+    /// Whether the start time has passed and this start was not run yet.
+    pub fn is_due(&self, now: DateTime<Local>) -> bool {
+        let Some(start) = self.start_time() else {
+            return false;
+        };
+        let already_run = self
+            .last_run_at
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t >= start)
+            .unwrap_or(false);
+        start <= now && !already_run
+    }
+}
+
+/// First occurrence of a recurring schedule strictly after `after`, keeping
+/// the local time of day of `start` across DST changes. `None` for single
+/// recordings.
+pub fn next_occurrence(
+    start: DateTime<Local>,
+    recurrence: Option<&str>,
+    after: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    let step = match recurrence? {
+        "weekly" => Duration::weeks(1),
+        "daily" | "weekdays" => Duration::days(1),
+        _ => return None,
+    };
+    let weekdays_only = recurrence == Some("weekdays");
+    let mut next = start.naive_local();
+    loop {
+        next += step;
+        if weekdays_only && matches!(next.weekday(), Weekday::Sat | Weekday::Sun) {
+            continue;
+        }
+        // A time skipped by DST has no local mapping; take the next day
+        if let Some(candidate) = Local.from_local_datetime(&next).earliest() {
+            if candidate > after {
+                return Some(candidate);
+            }
+        }
+    }
+}
+
+fn row_to_scheduled(row: &rusqlite::Row<'_>) -> Result<ScheduledRecording, rusqlite::Error> {
+    Ok(ScheduledRecording {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        mode: row.get(2)?,
+        start_at: row.get(3)?,
+        duration_minutes: row.get(4)?,
+        recurrence: row.get(5)?,
+        last_run_at: row.get(6)?,
+    })
+}
+
+impl Database {
+    pub fn create_scheduled_recording(
+        &self,
+        title: &str,
+        mode: &str,
+        start_at: &str,
+        duration_minutes: u32,
+        recurrence: Option<&str>,
+    ) -> Result<i64, rusqlite::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO scheduled_recordings (title, mode, start_at, duration_minutes, recurrence, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![title, mode, start_at, duration_minutes, recurrence, now],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// All schedules, soonest first.
+    pub fn list_scheduled_recordings(&self) -> Result<Vec<ScheduledRecording>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, title, mode, start_at, duration_minutes, recurrence, last_run_at
+             FROM scheduled_recordings ORDER BY start_at ASC",
+        )?;
+        let rows = stmt.query_map([], row_to_scheduled)?;
+        rows.collect()
+    }
+
+    pub fn delete_scheduled_recording(&self, id: i64) -> Result<(), rusqlite::Error> {
+        self.conn
+            .execute("DELETE FROM scheduled_recordings WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Marks the current start as run (or skipped) and, for recurring
+    /// entries, moves `start_at` to the next occurrence after `now`.
+    pub fn complete_scheduled_run(
+        &self,
+        schedule: &ScheduledRecording,
+        now: DateTime<Local>,
+    ) -> Result<(), rusqlite::Error> {
+        let next = schedule
+            .start_time()
+            .and_then(|start| next_occurrence(start, schedule.recurrence.as_deref(), now));
+        let start_at = next
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_else(|| schedule.start_at.clone());
+        self.conn.execute(
+            "UPDATE scheduled_recordings SET start_at = ?1, last_run_at = ?2 WHERE id = ?3",
+            params![start_at, now.to_rfc3339(), schedule.id],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_next_occurrence() {
+        // 2025-01-10 is a Friday
+        let start = at(2025, 1, 10, 9);
+        assert_eq!(next_occurrence(start, None, start), None);
+        assert_eq!(next_occurrence(start, Some("daily"), start), Some(at(2025, 1, 11, 9)));
+        assert_eq!(next_occurrence(start, Some("weekdays"), start), Some(at(2025, 1, 13, 9)));
+        assert_eq!(
+            next_occurrence(start, Some("weekly"), at(2025, 1, 20, 8)),
+            Some(at(2025, 1, 24, 9))
+        );
+    }
+
+    #[test]
+    fn test_scheduled_run_cycle() {
+        let db = Database::new_in_memory().unwrap();
+        let start = at(2025, 1, 10, 9);
+        db.create_scheduled_recording("Standup", "visio", &start.to_rfc3339(), 15, Some("daily"))
+            .unwrap();
+        db.create_scheduled_recording("Comite", "presentiel", &at(2025, 1, 10, 14).to_rfc3339(), 60, None)
+            .unwrap();
+
+        let now = at(2025, 1, 10, 9) + Duration::minutes(1);
+        let due: Vec<_> = db
+            .list_scheduled_recordings()
+            .unwrap()
+            .into_iter()
+            .filter(|s| s.is_due(now))
+            .collect();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].title, "Standup");
+
+        db.complete_scheduled_run(&due[0], now).unwrap();
+        let find = |title: &str| {
+            db.list_scheduled_recordings()
+                .unwrap()
+                .into_iter()
+                .find(|s| s.title == title)
+                .unwrap()
+        };
+        let standup = find("Standup");
+        assert_eq!(standup.start_time(), Some(at(2025, 1, 11, 9)));
+        assert!(!standup.is_due(now));
+
+        let later = at(2025, 1, 10, 15);
+        assert!(find("Comite").is_due(later));
+        db.complete_scheduled_run(&find("Comite"), later).unwrap();
+        // Single recordings stay listed but never run again
+        assert!(!find("Comite").is_due(at(2025, 1, 11, 15)));
+    }
+}
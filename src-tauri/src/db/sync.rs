@@ -0,0 +1,221 @@
+//! Records exchanged by `crate::sync`. Sessions carry the time of their last
+//! change, kept by trigger, and deleted sessions leave a tombstone, so that
+//! two databases can be merged session by session, the latest change
+//! winning.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::{row_to_segment, row_to_session, Database, Segment, Session, SESSION_COLUMNS};
+
+/// Last change of a session, or its deletion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionStamp {
+    pub session_id: String,
+    pub updated_at: String,
+    pub deleted: bool,
+}
+
+/// A session with its transcript, as copied to another device. The audio
+/// path is local and not part of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedSession {
+    pub session: Session,
+    pub updated_at: String,
+    pub live_transcript: Option<String>,
+    pub segments: Vec<Segment>,
+}
+
+/// The topics of a stored summary, the rest of which is not needed here.
+#[derive(Deserialize)]
+struct SummaryTopics {
+    #[serde(default)]
+    topics: Vec<String>,
+}
+
+impl Database {
+    /// Stamps of every session and tombstone.
+    pub fn list_session_stamps(&self) -> Result<Vec<SessionStamp>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, COALESCE(updated_at, created_at), 0 FROM sessions
+             UNION ALL
+             SELECT session_id, deleted_at, 1 FROM deleted_sessions",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let deleted: i32 = row.get(2)?;
+            Ok(SessionStamp {
+                session_id: row.get(0)?,
+                updated_at: row.get(1)?,
+                deleted: deleted != 0,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn get_synced_session(&self, id: &str) -> Result<SyncedSession, rusqlite::Error> {
+        let (mut session, updated_at, live_transcript) = self.conn.query_row(
+            &format!(
+                "SELECT {}, COALESCE(updated_at, created_at), live_transcript FROM sessions WHERE id = ?1",
+                SESSION_COLUMNS
+            ),
+            params![id],
+            |row| Ok((row_to_session(row)?, row.get::<_, String>(14)?, row.get(15)?)),
+        )?;
+        session.audio_path = None;
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, text, start_time, end_time, speaker, is_diarized, language
+             FROM segments WHERE session_id = ?1 ORDER BY id ASC",
+        )?;
+        let segments = stmt
+            .query_map(params![id], row_to_segment)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SyncedSession {
+            session,
+            updated_at,
+            live_transcript,
+            segments,
+        })
+    }
+
+    /// Creates or replaces a session with a copy from another device, and
+    /// keeps its change time. The local audio path is kept. Segments get new
+    /// ids, so the versions, revisions and undo journal of the old ones are
+    /// dropped. Topics are indexed again from the summary.
+    pub fn apply_synced_session(&self, synced: &SyncedSession) -> Result<(), rusqlite::Error> {
+        let s = &synced.session;
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO sessions (id, title, mode, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![s.id, s.title, s.mode, s.created_at],
+        )?;
+        tx.execute(
+            "UPDATE sessions SET title = ?1, mode = ?2, created_at = ?3, duration_secs = ?4, summary_json = ?5,
+                protected = ?6, archived = ?7, language = ?8, transcript_only = ?9, summary_model = ?10,
+                speaker_count = ?11, detected_language = ?12, live_transcript = ?13
+             WHERE id = ?14",
+            params![
+                s.title,
+                s.mode,
+                s.created_at,
+                s.duration_secs,
+                s.summary_json,
+                s.protected as i32,
+                s.archived as i32,
+                s.language,
+                s.transcript_only as i32,
+                s.summary_model,
+                s.speaker_count,
+                s.detected_language,
+                synced.live_transcript,
+                s.id,
+            ],
+        )?;
+        tx.execute("DELETE FROM transcript_versions WHERE session_id = ?1", params![s.id])?;
+        tx.execute("DELETE FROM segments WHERE session_id = ?1", params![s.id])?;
+        tx.execute("DELETE FROM segment_revisions WHERE session_id = ?1", params![s.id])?;
+        tx.execute("DELETE FROM operations WHERE session_id = ?1", params![s.id])?;
+        let topics = s
+            .summary_json
+            .as_deref()
+            .and_then(|json| serde_json::from_str::<SummaryTopics>(json).ok())
+            .map(|summary| summary.topics)
+            .unwrap_or_default();
+        super::topics::write_session_topics(&tx, &s.id, &topics)?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO segments (session_id, text, start_time, end_time, speaker, is_diarized, language)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for seg in &synced.segments {
+                stmt.execute(params![
+                    s.id,
+                    seg.text,
+                    seg.start_time,
+                    seg.end_time,
+                    seg.speaker,
+                    seg.is_diarized as i32,
+                    seg.language,
+                ])?;
+            }
+        }
+        // Last, so that the trigger does not stamp the copy as a local change
+        tx.execute(
+            "UPDATE sessions SET updated_at = ?1 WHERE id = ?2",
+            params![synced.updated_at, s.id],
+        )?;
+        tx.execute("DELETE FROM deleted_sessions WHERE session_id = ?1", params![s.id])?;
+        tx.commit()
+    }
+
+    /// Deletes a session deleted on another device, keeping the time of the
+    /// deletion on its tombstone.
+    pub fn apply_session_deletion(&self, id: &str, deleted_at: &str) -> Result<(), rusqlite::Error> {
+        self.delete_session(id)?;
+        self.conn.execute(
+            "UPDATE deleted_sessions SET deleted_at = ?1 WHERE session_id = ?2",
+            params![deleted_at, id],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_stamps_follow_changes() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Point", "visio").unwrap();
+        db.conn
+            .execute("UPDATE sessions SET updated_at = '2020-01-01T00:00:00Z' WHERE id = ?1", params![id])
+            .unwrap();
+        db.save_segment(&id, "Bonjour", 0.0, 1.0, None, true).unwrap();
+        let stamps = db.list_session_stamps().unwrap();
+        assert_eq!(stamps.len(), 1);
+        assert!(stamps[0].updated_at.as_str() > "2020-01-01T00:00:00Z");
+
+        db.delete_session(&id).unwrap();
+        let stamps = db.list_session_stamps().unwrap();
+        assert_eq!(stamps.len(), 1);
+        assert!(stamps[0].deleted);
+    }
+
+    #[test]
+    fn test_apply_synced_session() {
+        let laptop = Database::new_in_memory().unwrap();
+        let id = laptop.create_session("Revue", "visio").unwrap();
+        laptop.update_session_audio_path(&id, "/laptop/audio.wav").unwrap();
+        laptop.save_segment(&id, "Bonjour", 0.0, 1.0, Some("Marie"), true).unwrap();
+        laptop
+            .save_summary(
+                &id,
+                r#"{"key_points":[],"decisions":[],"action_items":[],"topics":["Budget"]}"#,
+                None,
+            )
+            .unwrap();
+        let synced = laptop.get_synced_session(&id).unwrap();
+        assert!(synced.session.audio_path.is_none());
+
+        let desktop = Database::new_in_memory().unwrap();
+        desktop.apply_synced_session(&synced).unwrap();
+        let session = desktop.get_session(&id).unwrap();
+        assert_eq!(session.title, "Revue");
+        let segments = desktop.get_segments(&id).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].speaker.as_deref(), Some("Marie"));
+        assert_eq!(desktop.list_sessions_by_topic("budget").unwrap().len(), 1);
+        let stamps = desktop.list_session_stamps().unwrap();
+        assert_eq!(stamps[0].updated_at, synced.updated_at);
+
+        // Undo entries of replaced segments are dropped
+        desktop.rename_speaker(&id, "Marie", "Claire").unwrap();
+        desktop.apply_synced_session(&synced).unwrap();
+        assert!(desktop.last_operation(Some(&id)).unwrap().is_none());
+
+        desktop.apply_session_deletion(&id, "2030-01-01T00:00:00Z").unwrap();
+        let stamps = desktop.list_session_stamps().unwrap();
+        assert_eq!(stamps[0].updated_at, "2030-01-01T00:00:00Z");
+        assert!(stamps[0].deleted);
+    }
+}
@@ -0,0 +1,119 @@
+//! Topic index: the themes extracted with each summary, stored per session
+//! so that every meeting about a topic can be listed without a full-text
+//! search.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::{fold_name, row_to_session, Database, Session, SESSION_COLUMNS};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicCount {
+    pub topic: String,
+    pub session_count: i64,
+}
+
+impl Database {
+    /// Replaces the topics of a session. Topics are matched case- and
+    /// accent-insensitively; duplicates and blanks are dropped.
+    pub fn set_session_topics(&self, session_id: &str, topics: &[String]) -> Result<(), rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        write_session_topics(&tx, session_id, topics)?;
+        tx.commit()
+    }
+
+    pub fn get_session_topics(&self, session_id: &str) -> Result<Vec<String>, rusqlite::Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT topic FROM session_topics WHERE session_id = ?1 ORDER BY rowid ASC")?;
+        let rows = stmt.query_map(params![session_id], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Sessions indexed under `topic`, most recent first.
+    pub fn list_sessions_by_topic(&self, topic: &str) -> Result<Vec<Session>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM sessions
+             WHERE id IN (SELECT session_id FROM session_topics WHERE topic_key = ?1)
+             ORDER BY created_at DESC",
+            SESSION_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![fold_name(topic.trim())], row_to_session)?;
+        rows.collect()
+    }
+
+    /// Every indexed topic with its number of sessions, most frequent first.
+    /// The spelling shown is the first one seen for the topic.
+    pub fn list_topics(&self, include_protected: bool) -> Result<Vec<TopicCount>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            // With MIN(), SQLite takes the bare `topic` from the first row
+            "SELECT st.topic, COUNT(*), MIN(st.rowid)
+             FROM session_topics st JOIN sessions s ON s.id = st.session_id
+             WHERE ?1 OR s.protected = 0
+             GROUP BY st.topic_key
+             ORDER BY COUNT(*) DESC, st.topic_key ASC",
+        )?;
+        let rows = stmt.query_map(params![include_protected], |row| {
+            Ok(TopicCount {
+                topic: row.get(0)?,
+                session_count: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+/// `set_session_topics` within a transaction of the caller.
+pub(super) fn write_session_topics(
+    conn: &Connection,
+    session_id: &str,
+    topics: &[String],
+) -> Result<(), rusqlite::Error> {
+    conn.execute("DELETE FROM session_topics WHERE session_id = ?1", params![session_id])?;
+    for topic in topics {
+        let topic = topic.trim();
+        if topic.is_empty() {
+            continue;
+        }
+        conn.execute(
+            "INSERT OR IGNORE INTO session_topics (session_id, topic, topic_key) VALUES (?1, ?2, ?3)",
+            params![session_id, topic, fold_name(topic)],
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_topics_index() {
+        let db = Database::new_in_memory().unwrap();
+        let first = db.create_session("Point RH", "visio").unwrap();
+        let second = db.create_session("Comite", "visio").unwrap();
+        db.set_session_topics(&first, &["Recrutement".to_string(), "budget".to_string()])
+            .unwrap();
+        db.set_session_topics(&second, &["recrutement ".to_string(), "".to_string()])
+            .unwrap();
+
+        let sessions = db.list_sessions_by_topic("RECRUTEMENT").unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(db.get_session_topics(&second).unwrap(), vec!["recrutement"]);
+
+        let topics = db.list_topics(true).unwrap();
+        assert_eq!(topics[0].topic, "Recrutement");
+        assert_eq!(topics[0].session_count, 2);
+        assert_eq!(topics[1].topic, "budget");
+
+        db.set_session_protected(&second, true).unwrap();
+        assert_eq!(db.list_topics(false).unwrap()[0].session_count, 1);
+
+        // Replacing drops the previous topics
+        db.set_session_topics(&first, &["planning".to_string()]).unwrap();
+        assert_eq!(db.list_sessions_by_topic("budget").unwrap().len(), 0);
+
+        db.delete_session(&second).unwrap();
+        assert!(db.list_sessions_by_topic("recrutement").unwrap().is_empty());
+    }
+}
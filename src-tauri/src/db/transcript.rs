@@ -0,0 +1,102 @@
+//! Full-transcript assembly with an in-memory cache. Triggers bump
+//! `sessions.transcript_version` on every segment or live-transcript
+//! change, so a cached transcript is reused only while it is current.
+
+use std::sync::Arc;
+
+use rusqlite::params;
+
+use super::Database;
+
+/// Number of sessions whose transcript is kept in memory.
+const CACHE_SIZE: usize = 8;
+
+#[derive(Debug, PartialEq)]
+pub struct FullTranscript {
+    /// Segment texts, one per line.
+    pub plain: String,
+    /// Same lines, prefixed with their speaker when known.
+    pub with_speakers: String,
+}
+
+/// Recently assembled transcripts, most recently used first.
+#[derive(Default)]
+pub(super) struct TranscriptCache {
+    entries: Vec<(String, i64, Arc<FullTranscript>)>,
+}
+
+impl Database {
+    /// Transcript of a session: its segments in order, or the saved
+    /// realtime text when none were stored. Served from the cache unless
+    /// the session changed since it was assembled.
+    pub fn get_full_transcript(&self, session_id: &str) -> Result<Arc<FullTranscript>, rusqlite::Error> {
+        let version: i64 = self.conn.query_row(
+            "SELECT transcript_version FROM sessions WHERE id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )?;
+
+        let mut cache = self.transcript_cache.borrow_mut();
+        if let Some(pos) = cache.entries.iter().position(|(id, _, _)| id == session_id) {
+            let entry = cache.entries.remove(pos);
+            if entry.1 == version {
+                let transcript = entry.2.clone();
+                cache.entries.insert(0, entry);
+                return Ok(transcript);
+            }
+        }
+
+        let transcript = Arc::new(self.assemble_transcript(session_id)?);
+        cache.entries.insert(0, (session_id.to_string(), version, transcript.clone()));
+        cache.entries.truncate(CACHE_SIZE);
+        Ok(transcript)
+    }
+
+    fn assemble_transcript(&self, session_id: &str) -> Result<FullTranscript, rusqlite::Error> {
+        let segments = self.get_segments(session_id)?;
+        if segments.is_empty() {
+            let live = self.get_live_transcript(session_id)?.unwrap_or_default();
+            return Ok(FullTranscript {
+                plain: live.clone(),
+                with_speakers: live,
+            });
+        }
+        let plain = segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join("\n");
+        let with_speakers = segments
+            .iter()
+            .map(|s| match s.speaker {
+                Some(ref speaker) => format!("{}: {}", speaker, s.text),
+                None => s.text.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(FullTranscript { plain, with_speakers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_transcript_cache_invalidation() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        db.save_live_transcript(&id, "texte en direct").unwrap();
+        assert_eq!(db.get_full_transcript(&id).unwrap().plain, "texte en direct");
+
+        let first = db.save_segment(&id, "Bonjour", 0.0, 1.0, Some("Alice"), true).unwrap();
+        db.save_segment(&id, "Salut", 1.0, 2.0, None, true).unwrap();
+        let transcript = db.get_full_transcript(&id).unwrap();
+        assert_eq!(transcript.plain, "Bonjour\nSalut");
+        assert_eq!(transcript.with_speakers, "Alice: Bonjour\nSalut");
+        // Unchanged session: same cached value
+        assert!(Arc::ptr_eq(&transcript, &db.get_full_transcript(&id).unwrap()));
+
+        db.set_segment_speaker(&id, first, "Bob").unwrap();
+        assert_eq!(db.get_full_transcript(&id).unwrap().with_speakers, "Bob: Bonjour\nSalut");
+
+        db.delete_segment(first).unwrap();
+        assert_eq!(db.get_full_transcript(&id).unwrap().plain, "Salut");
+    }
+}
@@ -0,0 +1,100 @@
+//! Translations of segments, one per target language, stored next to the
+//! original text. Triggers drop a translation when its segment is deleted
+//! or its text edited, so a stored translation is always of the current
+//! text.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::{row_to_segment, Database, Segment};
+
+/// A segment with its translation in the requested language, if any.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslatedSegment {
+    #[serde(flatten)]
+    pub segment: Segment,
+    pub translation: Option<String>,
+}
+
+impl Database {
+    /// Stores translations of segments into `language`, replacing existing ones.
+    pub fn save_segment_translations(
+        &self,
+        language: &str,
+        translations: &[(i64, String)],
+    ) -> Result<(), rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO segment_translations (segment_id, language, text) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(segment_id, language) DO UPDATE SET text = excluded.text",
+            )?;
+            for (segment_id, text) in translations {
+                stmt.execute(params![segment_id, language, text])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Segments of a session in order, with their translation into `language`.
+    pub fn get_translated_segments(
+        &self,
+        session_id: &str,
+        language: &str,
+    ) -> Result<Vec<TranslatedSegment>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.id, s.session_id, s.text, s.start_time, s.end_time, s.speaker, s.is_diarized, s.language, t.text
+             FROM segments s
+             LEFT JOIN segment_translations t ON t.segment_id = s.id AND t.language = ?2
+             WHERE s.session_id = ?1 ORDER BY s.start_time ASC",
+        )?;
+        let rows = stmt.query_map(params![session_id, language], |row| {
+            Ok(TranslatedSegment {
+                segment: row_to_segment(row)?,
+                translation: row.get(8)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Languages a session has translations in, at least partly.
+    pub fn list_translation_languages(&self, session_id: &str) -> Result<Vec<String>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT t.language FROM segment_translations t
+             JOIN segments s ON s.id = t.segment_id
+             WHERE s.session_id = ?1 ORDER BY t.language",
+        )?;
+        let rows = stmt.query_map(params![session_id], |row| row.get(0))?;
+        rows.collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_translations() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        let first = db.save_segment(&id, "Bonjour", 0.0, 1.0, None, true).unwrap();
+        let second = db.save_segment(&id, "Merci", 1.0, 2.0, None, true).unwrap();
+        db.save_segment_translations("en", &[(first, "Hello".to_string()), (second, "Thanks".to_string())])
+            .unwrap();
+
+        let translated = db.get_translated_segments(&id, "en").unwrap();
+        assert_eq!(translated[0].translation.as_deref(), Some("Hello"));
+        assert!(db.get_translated_segments(&id, "de").unwrap()[0].translation.is_none());
+        assert_eq!(db.list_translation_languages(&id).unwrap(), vec!["en"]);
+
+        // Editing or deleting a segment drops its translations
+        db.conn
+            .execute("UPDATE segments SET text = 'Bonsoir' WHERE id = ?1", params![first])
+            .unwrap();
+        db.delete_segment(second).unwrap();
+        let translated = db.get_translated_segments(&id, "en").unwrap();
+        assert_eq!(translated.len(), 1);
+        assert!(translated[0].translation.is_none());
+        assert!(db.list_translation_languages(&id).unwrap().is_empty());
+    }
+}
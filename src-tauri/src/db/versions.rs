@@ -0,0 +1,139 @@
+//! Alternative versions of a session transcript, such as the one corrected
+//! by `polish_transcript`. A version holds the text of every segment; the
+//! segments themselves only change when a version is adopted.
+
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::Database;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionSegment {
+    pub segment_id: i64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptVersion {
+    pub id: i64,
+    pub session_id: String,
+    /// What produced the version, e.g. "polished".
+    pub kind: String,
+    pub model: Option<String>,
+    pub created_at: String,
+    pub segments: Vec<VersionSegment>,
+}
+
+fn row_to_version(row: &rusqlite::Row<'_>) -> Result<TranscriptVersion, rusqlite::Error> {
+    let segments_json: String = row.get(5)?;
+    Ok(TranscriptVersion {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        kind: row.get(2)?,
+        model: row.get(3)?,
+        created_at: row.get(4)?,
+        segments: serde_json::from_str(&segments_json).unwrap_or_default(),
+    })
+}
+
+impl Database {
+    pub fn save_transcript_version(
+        &self,
+        session_id: &str,
+        kind: &str,
+        model: Option<&str>,
+        segments: &[VersionSegment],
+    ) -> Result<TranscriptVersion, rusqlite::Error> {
+        let created_at = chrono::Utc::now().to_rfc3339();
+        let segments_json = serde_json::to_string(segments).unwrap_or_else(|_| "[]".to_string());
+        self.conn.execute(
+            "INSERT INTO transcript_versions (session_id, kind, model, created_at, segments_json)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![session_id, kind, model, created_at, segments_json],
+        )?;
+        Ok(TranscriptVersion {
+            id: self.conn.last_insert_rowid(),
+            session_id: session_id.to_string(),
+            kind: kind.to_string(),
+            model: model.map(str::to_string),
+            created_at,
+            segments: segments.to_vec(),
+        })
+    }
+
+    /// Versions of a session, most recent first.
+    pub fn list_transcript_versions(&self, session_id: &str) -> Result<Vec<TranscriptVersion>, rusqlite::Error> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, session_id, kind, model, created_at, segments_json FROM transcript_versions
+             WHERE session_id = ?1 ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map(params![session_id], row_to_version)?;
+        rows.collect()
+    }
+
+    pub fn get_transcript_version(&self, id: i64) -> Result<TranscriptVersion, rusqlite::Error> {
+        self.conn.query_row(
+            "SELECT id, session_id, kind, model, created_at, segments_json FROM transcript_versions WHERE id = ?1",
+            params![id],
+            row_to_version,
+        )
+    }
+
+    /// Sets the text of segments of a session, by id. Returns how many
+    /// changed; ids of other sessions or deleted segments are skipped.
+    pub fn set_segment_texts(&self, session_id: &str, texts: &[VersionSegment]) -> Result<usize, rusqlite::Error> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut changed = 0;
+        {
+            let mut stmt = tx.prepare_cached(
+                "UPDATE segments SET text = ?1 WHERE id = ?2 AND session_id = ?3 AND text != ?1",
+            )?;
+            for segment in texts {
+                changed += stmt.execute(params![segment.text, segment.segment_id, session_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_versions() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Call", "visio").unwrap();
+        let first = db.save_segment(&id, "On passe sur cube", 0.0, 1.0, None, true).unwrap();
+        let second = db.save_segment(&id, "Merci", 1.0, 2.0, None, true).unwrap();
+
+        let version = db
+            .save_transcript_version(
+                &id,
+                "polished",
+                Some("mistral-small-latest"),
+                &[
+                    VersionSegment {
+                        segment_id: first,
+                        text: "On passe sur Kube".to_string(),
+                    },
+                    VersionSegment {
+                        segment_id: second,
+                        text: "Merci".to_string(),
+                    },
+                ],
+            )
+            .unwrap();
+        assert_eq!(db.get_transcript_version(version.id).unwrap(), version);
+        assert_eq!(db.list_transcript_versions(&id).unwrap().len(), 1);
+        // Saving a version leaves the segments alone
+        assert_eq!(db.get_segments(&id).unwrap()[0].text, "On passe sur cube");
+
+        assert_eq!(db.set_segment_texts(&id, &version.segments).unwrap(), 1);
+        assert_eq!(db.get_segments(&id).unwrap()[0].text, "On passe sur Kube");
+
+        db.delete_session(&id).unwrap();
+        assert!(db.list_transcript_versions(&id).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! `poptranscribe://session/{id}?t=123` links. Exported notes write them on
+//! their timestamps; opening one brings the app to that session, at that
+//! moment of the recording.
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::app_state::AppState;
+
+pub const SCHEME: &str = "poptranscribe";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeepLink {
+    pub session_id: String,
+    /// Seconds from the start of the recording.
+    pub t: Option<f64>,
+}
+
+/// Link to a moment of a session, to the second.
+pub fn session_url(session_id: &str, seconds: f64) -> String {
+    format!("{}://session/{}?t={}", SCHEME, session_id, seconds.max(0.0) as u64)
+}
+
+pub fn parse(url: &str) -> Option<DeepLink> {
+    let rest = url.strip_prefix(SCHEME)?.strip_prefix("://")?;
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let session_id = path.strip_prefix("session/")?.trim_end_matches('/');
+    if session_id.is_empty() || !session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    let t = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("t="))
+        .and_then(|t| t.parse::<f64>().ok())
+        .filter(|t| t.is_finite() && *t >= 0.0);
+    Some(DeepLink {
+        session_id: session_id.to_string(),
+        t,
+    })
+}
+
+/// First link among command line arguments: on Windows and Linux the OS
+/// passes the opened URL as an argument.
+pub fn link_in_args<I: IntoIterator<Item = String>>(args: I) -> Option<DeepLink> {
+    args.into_iter().find_map(|arg| parse(arg.trim_matches('"')))
+}
+
+/// Shows the session of a link, through the `deep-link` event. Other URLs
+/// are ignored.
+pub fn open(app: &tauri::AppHandle, url: &str) {
+    let Some(link) = parse(url) else {
+        eprintln!("[deep-link] Ignored link: {}", url);
+        return;
+    };
+    #[cfg(desktop)]
+    crate::show_main_window(app);
+    let _ = app.emit("deep-link", link);
+}
+
+/// Handles the links the app is opened with. On Linux, and on Windows in
+/// development, the scheme is registered at runtime; bundles declare it.
+///
+/// A link that launched the app is kept as pending: the webview is not
+/// listening yet and asks for it once loaded.
+pub fn listen(app: &tauri::AppHandle) {
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("[deep-link] Scheme registration failed: {}", e);
+    }
+    let launch = app
+        .deep_link()
+        .get_current()
+        .ok()
+        .flatten()
+        .and_then(|urls| urls.iter().find_map(|url| parse(url.as_str())))
+        .or_else(|| link_in_args(std::env::args().skip(1)));
+    if let Some(link) = launch {
+        if let Ok(mut pending) = app.state::<AppState>().pending_link.lock() {
+            *pending = Some(link);
+        }
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            open(&handle, url.as_str());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_deep_link() {
+        let url = session_url("0b8e-42", 123.9);
+        assert_eq!(url, "poptranscribe://session/0b8e-42?t=123");
+        assert_eq!(
+            parse(&url),
+            Some(DeepLink {
+                session_id: "0b8e-42".to_string(),
+                t: Some(123.0),
+            })
+        );
+        assert_eq!(parse("poptranscribe://session/abc/").unwrap().t, None);
+        assert_eq!(parse("poptranscribe://session/abc?t=-4").unwrap().t, None);
+        assert_eq!(parse("poptranscribe://session/../etc"), None);
+        assert_eq!(parse("https://session/abc"), None);
+    }
+
+    #[test]
+    fn test_link_in_args() {
+        let args = ["--autostart", "\"poptranscribe://session/abc?t=5\""].map(String::from);
+        assert_eq!(link_in_args(args).map(|l| l.session_id).as_deref(), Some("abc"));
+        assert_eq!(link_in_args(vec!["--autostart".to_string()]), None);
+    }
+}
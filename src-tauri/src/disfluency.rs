@@ -0,0 +1,101 @@
+//! Removal of disfluencies ("euh", "bah", words said twice) from the text
+//! of exports and of the transcript sent for summaries, enabled by the
+//! `remove_disfluencies` setting. The stored segments stay verbatim.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+pub const REMOVE_DISFLUENCIES_SETTING: &str = "remove_disfluencies";
+
+/// Hesitation sounds, French and English, with their drawn-out spellings.
+const FILLERS: &[&str] = &["euh+", "heu+", "hum+", "hm+", "bah", "uh+", "um+", "erm", "hmm+"];
+
+/// Words that are repeated on purpose ("nous nous sommes vus").
+const LEGIT_REPEATS: &[&str] = &["nous", "vous"];
+
+fn fillers_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // With the comma or ellipsis that follows them
+        Regex::new(&format!(r"(?i)\b(?:{})\b(?:\s*(?:,|\.\.\.|…))?", FILLERS.join("|")))
+            .expect("valid fillers regex")
+    })
+}
+
+pub fn enabled(db: &crate::db::Database) -> bool {
+    db.get_setting(REMOVE_DISFLUENCIES_SETTING).ok().flatten().as_deref() == Some("true")
+}
+
+fn word_key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+/// Removes fillers and repeated words, line by line.
+pub fn remove_disfluencies(text: &str) -> String {
+    let removed = fillers_regex().replace_all(text, "");
+    let mut result = String::with_capacity(removed.len());
+    for (i, line) in removed.split('\n').enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+        let mut words: Vec<String> = Vec::new();
+        for word in line.split_whitespace() {
+            let key = word_key(word);
+            match words.last_mut() {
+                // Punctuation left alone by a filler: "fini euh."
+                Some(previous) if key.is_empty() && word.starts_with([',', '.', '…']) => {
+                    previous.push_str(word);
+                    continue;
+                }
+                // "le le" but not "le, le"
+                Some(previous)
+                    if !key.is_empty()
+                        && previous.ends_with(|c: char| c.is_alphanumeric())
+                        && word_key(previous) == key
+                        && !LEGIT_REPEATS.contains(&key.as_str()) =>
+                {
+                    continue;
+                }
+                // Punctuation left at the start of the line
+                None if key.is_empty() => continue,
+                _ => {}
+            }
+            words.push(word.to_string());
+        }
+        let mut line = words.join(" ");
+        // Capital letter of a removed leading filler ("Euh, on commence")
+        let original = removed_start(text, i);
+        if original.is_some_and(char::is_uppercase) {
+            if let Some(first) = line.chars().next().filter(|c| c.is_lowercase()) {
+                line = first.to_uppercase().collect::<String>() + &line[first.len_utf8()..];
+            }
+        }
+        result.push_str(&line);
+    }
+    result
+}
+
+/// First letter of line `index` of the original text.
+fn removed_start(text: &str, index: usize) -> Option<char> {
+    text.split('\n').nth(index)?.chars().find(|c| c.is_alphabetic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_disfluencies() {
+        assert_eq!(
+            remove_disfluencies("Euh, on commence le le point... euh budget, bah c'est fini euh."),
+            "On commence le point... budget, c'est fini."
+        );
+        assert_eq!(
+            remove_disfluencies("Marie: hum je je pense\nPaul: Nous nous sommes vus, vus."),
+            "Marie: je pense\nPaul: Nous nous sommes vus, vus."
+        );
+        // Whole words only
+        assert_eq!(remove_disfluencies("Un humain, un bahut"), "Un humain, un bahut");
+    }
+}
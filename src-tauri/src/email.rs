@@ -0,0 +1,199 @@
+//! Follow-up emails of a session, written from a template of
+//! `crate::db::EmailTemplate` (or the built-in one) whose `{{placeholder}}`s
+//! are replaced by the session details and the sections of its summary.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::db::{Session, SpeakerProfile};
+use crate::mistral::chat::Summary;
+
+/// Placeholders a template may use.
+pub const PLACEHOLDERS: &[&str] = &[
+    "title",
+    "date",
+    "duration",
+    "participants",
+    "key_points",
+    "decisions",
+    "action_items",
+    "topics",
+];
+
+pub const DEFAULT_SUBJECT: &str = "Compte rendu : {{title}} ({{date}})";
+
+pub const DEFAULT_BODY: &str = "Bonjour,
+
+Voici le compte rendu de la reunion \"{{title}}\" du {{date}}.
+
+Points cles :
+{{key_points}}
+
+Decisions :
+{{decisions}}
+
+Actions a suivre :
+{{action_items}}
+
+Bonne journee,
+";
+
+/// Shown for an empty section of the summary.
+const NONE: &str = "- Aucun";
+
+fn placeholder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\{\{\s*(\w+)\s*\}\}").expect("valid regex"))
+}
+
+/// What a template is rendered from.
+pub struct EmailContext<'a> {
+    pub session: &'a Session,
+    pub summary: Option<&'a Summary>,
+    pub participants: &'a [SpeakerProfile],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub body: String,
+    /// Emails of the participants with a profile email, in order.
+    pub recipients: Vec<String>,
+}
+
+fn bullets(items: impl Iterator<Item = String>) -> String {
+    let lines: Vec<String> = items.map(|item| format!("- {}", item)).collect();
+    if lines.is_empty() {
+        NONE.to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+impl EmailContext<'_> {
+    fn value(&self, placeholder: &str) -> Option<String> {
+        let summary = self.summary;
+        Some(match placeholder {
+            "title" => self.session.title.clone(),
+            "date" => self.session.created_at.get(..10).unwrap_or(&self.session.created_at).to_string(),
+            "duration" => self
+                .session
+                .duration_secs
+                .map(crate::export::format_duration)
+                .unwrap_or_default(),
+            "participants" => self
+                .participants
+                .iter()
+                .map(SpeakerProfile::describe)
+                .collect::<Vec<_>>()
+                .join(", "),
+            "key_points" => bullets(summary.into_iter().flat_map(|s| s.key_points.iter().cloned())),
+            "decisions" => bullets(summary.into_iter().flat_map(|s| s.decisions.iter().cloned())),
+            "action_items" => bullets(summary.into_iter().flat_map(|s| {
+                s.action_items.iter().map(|item| match item.assignee {
+                    Some(ref assignee) => format!("{} ({})", item.description, assignee),
+                    None => item.description.clone(),
+                })
+            })),
+            "topics" => summary.map(|s| s.topics.join(", ")).unwrap_or_default(),
+            _ => return None,
+        })
+    }
+
+    fn fill(&self, template: &str) -> String {
+        placeholder_regex()
+            .replace_all(template, |caps: &regex::Captures| {
+                self.value(&caps[1]).unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
+
+    pub fn render(&self, subject: &str, body: &str) -> RenderedEmail {
+        let mut recipients: Vec<String> = Vec::new();
+        for email in self.participants.iter().filter_map(|p| p.email.as_deref()) {
+            let email = email.trim();
+            if !email.is_empty() && !recipients.iter().any(|r| r.eq_ignore_ascii_case(email)) {
+                recipients.push(email.to_string());
+            }
+        }
+        RenderedEmail {
+            // A list placeholder must not break the subject line
+            subject: self.fill(subject).lines().map(str::trim).collect::<Vec<_>>().join(" "),
+            body: self.fill(body),
+            recipients,
+        }
+    }
+}
+
+/// Placeholders of `template` that are not in `PLACEHOLDERS`.
+pub fn unknown_placeholders(template: &str) -> Vec<String> {
+    let mut unknown: Vec<String> = Vec::new();
+    for caps in placeholder_regex().captures_iter(template) {
+        let name = &caps[1];
+        if !PLACEHOLDERS.contains(&name) && !unknown.iter().any(|u| u == name) {
+            unknown.push(name.to_string());
+        }
+    }
+    unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::mistral::chat::ActionItem;
+
+    #[test]
+    fn test_render_email() {
+        let db = Database::new_in_memory().unwrap();
+        let id = db.create_session("Point budget", "visio").unwrap();
+        let session = db.get_session(&id).unwrap();
+        let summary = Summary {
+            key_points: vec!["Budget en hausse".to_string()],
+            decisions: vec![],
+            action_items: vec![ActionItem {
+                description: "Envoyer le devis".to_string(),
+                assignee: Some("Marie".to_string()),
+            }],
+            topics: vec!["budget".to_string()],
+        };
+        let participants = vec![
+            SpeakerProfile {
+                name: "Marie".to_string(),
+                email: Some("marie@acme.com".to_string()),
+                ..Default::default()
+            },
+            SpeakerProfile {
+                name: "Paul".to_string(),
+                ..Default::default()
+            },
+        ];
+        let context = EmailContext {
+            session: &session,
+            summary: Some(&summary),
+            participants: &participants,
+        };
+        let email = context.render(
+            "Suivi {{ title }} {{key_points}}",
+            "{{key_points}}\n{{decisions}}\n{{action_items}}\n{{participants}} {{inconnu}}",
+        );
+        assert_eq!(email.subject, "Suivi Point budget - Budget en hausse");
+        assert_eq!(
+            email.body,
+            "- Budget en hausse\n- Aucun\n- Envoyer le devis (Marie)\nMarie, Paul {{inconnu}}"
+        );
+        assert_eq!(email.recipients, vec!["marie@acme.com"]);
+
+        let email = EmailContext { summary: None, ..context }.render(DEFAULT_SUBJECT, DEFAULT_BODY);
+        assert!(email.subject.starts_with("Compte rendu : Point budget ("));
+        assert!(!email.body.contains("{{"));
+    }
+
+    #[test]
+    fn test_unknown_placeholders() {
+        assert_eq!(unknown_placeholders(DEFAULT_BODY), Vec::<String>::new());
+        assert_eq!(unknown_placeholders("{{titre}} {{date}} {{titre}}"), vec!["titre"]);
+    }
+}
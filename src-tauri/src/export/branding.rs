@@ -0,0 +1,115 @@
+//! Corporate branding of exported documents, configured by the
+//! `branding_logo`, `branding_company`, `branding_header`, `branding_footer`
+//! and `branding_accent_color` settings. Each exporter renders what its
+//! format allows: the PDF shows the logo above the title, the company and
+//! header text at the top of every page, the footer text with the page
+//! number at the bottom, and headings in the accent color.
+
+use std::path::{Path, PathBuf};
+
+use crate::db::Database;
+
+/// Largest size of the logo, in mm; its aspect ratio is kept.
+const LOGO_MAX_WIDTH_MM: f64 = 40.0;
+const LOGO_MAX_HEIGHT_MM: f64 = 15.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Branding {
+    /// PNG or JPEG image.
+    pub logo_path: Option<PathBuf>,
+    pub company: Option<String>,
+    pub header: Option<String>,
+    pub footer: Option<String>,
+    /// `#RRGGBB`, invalid colors are ignored.
+    pub accent_color: Option<String>,
+}
+
+impl Branding {
+    /// The configured branding, empty when nothing is set.
+    pub fn from_settings(db: &Database) -> Self {
+        let setting = |key: &str| {
+            db.get_setting(key)
+                .ok()
+                .flatten()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Self {
+            logo_path: setting("branding_logo").map(PathBuf::from),
+            company: setting("branding_company"),
+            header: setting("branding_header"),
+            footer: setting("branding_footer"),
+            accent_color: setting("branding_accent_color"),
+        }
+    }
+
+    pub fn accent_rgb(&self) -> Option<(u8, u8, u8)> {
+        self.accent_color.as_deref().and_then(super::parse_hex_color)
+    }
+
+    /// Top of every page: the company, then the header text.
+    pub fn header_line(&self) -> Option<String> {
+        let parts: Vec<&str> = [self.company.as_deref(), self.header.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" — "))
+    }
+
+    /// Bottom of page `page` (1-based), numbered once a footer is set.
+    pub fn footer_line(&self, page: usize) -> Option<String> {
+        self.footer.as_ref().map(|footer| format!("{} — {}", footer, page))
+    }
+}
+
+/// Blends transparent pixels with white: PDF images carry no alpha channel.
+fn flatten_alpha(image: &image::DynamicImage) -> image::RgbImage {
+    let rgba = image.to_rgba8();
+    image::RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((u16::from(c) * u16::from(a) + 255 * (255 - u16::from(a))) / 255) as u8;
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// The logo as a PDF element, scaled to fit `LOGO_MAX_WIDTH_MM` by
+/// `LOGO_MAX_HEIGHT_MM`.
+pub fn pdf_logo(path: &Path) -> Result<genpdf::elements::Image, String> {
+    let image = image::open(path).map_err(|e| format!("Logo illisible ({}): {}", path.display(), e))?;
+    let rgb = flatten_alpha(&image);
+    let dpi = (f64::from(rgb.width()) * 25.4 / LOGO_MAX_WIDTH_MM)
+        .max(f64::from(rgb.height()) * 25.4 / LOGO_MAX_HEIGHT_MM);
+    genpdf::elements::Image::from_dynamic_image(image::DynamicImage::ImageRgb8(rgb))
+        .map(|logo| logo.with_dpi(dpi))
+        .map_err(|e| format!("Logo illisible ({}): {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branding_from_settings() {
+        let db = Database::new_in_memory().unwrap();
+        assert_eq!(Branding::from_settings(&db), Branding::default());
+
+        db.set_setting("branding_company", "Acme").unwrap();
+        db.set_setting("branding_header", "  ").unwrap();
+        db.set_setting("branding_footer", "Confidentiel").unwrap();
+        db.set_setting("branding_accent_color", "#0055AA").unwrap();
+        let branding = Branding::from_settings(&db);
+        assert_eq!(branding.header_line().as_deref(), Some("Acme"));
+        assert_eq!(branding.footer_line(3).as_deref(), Some("Confidentiel — 3"));
+        assert_eq!(branding.accent_rgb(), Some((0x00, 0x55, 0xAA)));
+    }
+
+    #[test]
+    fn test_flatten_alpha() {
+        let mut rgba = image::RgbaImage::new(2, 1);
+        rgba.put_pixel(0, 0, image::Rgba([0, 0, 0, 0]));
+        rgba.put_pixel(1, 0, image::Rgba([200, 0, 0, 255]));
+        let rgb = flatten_alpha(&image::DynamicImage::ImageRgba8(rgba));
+        assert_eq!(rgb.get_pixel(0, 0).0, [255, 255, 255]);
+        assert_eq!(rgb.get_pixel(1, 0).0, [200, 0, 0]);
+    }
+}
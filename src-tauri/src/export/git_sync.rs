@@ -0,0 +1,142 @@
+//! Versioning of exported notes in a local git repository, configured by
+//! the `git_sync_repo` setting.
+
+use std::path::{Path, PathBuf};
+
+use git2::{Cred, CredentialType, PushOptions, RemoteCallbacks, Repository, Signature};
+
+/// Path of `file` inside the working tree of `repo`. Files exported
+/// elsewhere are copied to the root of the working tree first.
+fn tracked_path(repo: &Repository, file: &Path) -> Result<PathBuf, String> {
+    let workdir = repo
+        .workdir()
+        .ok_or("Le depot git n'a pas de copie de travail")?
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+    let file = file.canonicalize().map_err(|e| e.to_string())?;
+    if let Ok(relative) = file.strip_prefix(&workdir) {
+        return Ok(relative.to_path_buf());
+    }
+    let name = file.file_name().ok_or("Nom de fichier invalide")?;
+    std::fs::copy(&file, workdir.join(name)).map_err(|e| format!("Erreur copie vers le depot: {}", e))?;
+    Ok(PathBuf::from(name))
+}
+
+/// Commits `file` on the current branch of the repository at `repo_path`.
+/// Uses the repository's `user.name`/`user.email`, or a PopTranscribe
+/// identity when none is configured.
+pub fn commit_file(repo_path: &Path, file: &Path, message: &str) -> Result<git2::Oid, String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Depot git introuvable: {}", e))?;
+    let relative = tracked_path(&repo, file)?;
+
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    index.add_path(&relative).map_err(|e| e.to_string())?;
+    index.write().map_err(|e| e.to_string())?;
+    let tree = index
+        .write_tree()
+        .and_then(|id| repo.find_tree(id))
+        .map_err(|e| e.to_string())?;
+
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("PopTranscribe", "poptranscribe@localhost"))
+        .map_err(|e| e.to_string())?;
+    // None on the first commit of an empty repository
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+        .map_err(|e| format!("Commit git impossible: {}", e))
+}
+
+/// Pushes the current branch to `origin`, authenticating with the SSH
+/// agent or the configured credential helper.
+pub fn push(repo_path: &Path) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Depot git introuvable: {}", e))?;
+    let head = repo.head().map_err(|e| e.to_string())?;
+    let branch = head.name().ok_or("Branche courante invalide")?;
+    let mut remote = repo
+        .find_remote("origin")
+        .map_err(|e| format!("Remote origin introuvable: {}", e))?;
+    let config = repo.config().map_err(|e| e.to_string())?;
+
+    let mut callbacks = RemoteCallbacks::new();
+    let mut tried = CredentialType::empty();
+    callbacks.credentials(move |url, username, allowed| {
+        let kind = next_credential(&mut tried, allowed)?;
+        if kind == CredentialType::USERNAME {
+            Cred::username(username.unwrap_or("git"))
+        } else if kind == CredentialType::SSH_KEY {
+            Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else {
+            Cred::credential_helper(&config, url, username)
+        }
+    });
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+    remote
+        .push(&[format!("{0}:{0}", branch)], Some(&mut options))
+        .map_err(|e| format!("Push git impossible: {}", e))
+}
+
+/// Kind of credential to offer next. libgit2 asks again each time the
+/// remote rejects one; the agent and the helper would return the same
+/// credential forever, so each kind is offered once.
+fn next_credential(tried: &mut CredentialType, allowed: CredentialType) -> Result<CredentialType, git2::Error> {
+    let kind = if allowed.is_username() {
+        CredentialType::USERNAME
+    } else if allowed.is_ssh_key() {
+        CredentialType::SSH_KEY
+    } else {
+        CredentialType::USER_PASS_PLAINTEXT
+    };
+    if tried.contains(kind) {
+        return Err(git2::Error::from_str("Identifiants refuses par le remote"));
+    }
+    tried.insert(kind);
+    Ok(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_file_inside_and_outside_repo() {
+        let dir = std::env::temp_dir().join(format!("poptranscribe_git_{}", std::process::id()));
+        let repo_dir = dir.join("notes");
+        std::fs::create_dir_all(&repo_dir).unwrap();
+        let repo = Repository::init(&repo_dir).unwrap();
+
+        let inside = repo_dir.join("call.md");
+        std::fs::write(&inside, "# Call").unwrap();
+        commit_file(&repo_dir, &inside, "Call").unwrap();
+
+        let outside = dir.join("review.md");
+        std::fs::write(&outside, "# Review").unwrap();
+        let oid = commit_file(&repo_dir, &outside, "Review").unwrap();
+
+        let commit = repo.find_commit(oid).unwrap();
+        assert_eq!(commit.message(), Some("Review"));
+        assert_eq!(commit.parent_count(), 1);
+        let tree = commit.tree().unwrap();
+        assert!(tree.get_name("call.md").is_some());
+        assert!(tree.get_name("review.md").is_some());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_credentials_offered_once() {
+        let mut tried = CredentialType::empty();
+        assert_eq!(next_credential(&mut tried, CredentialType::USERNAME).unwrap(), CredentialType::USERNAME);
+        let ssh = CredentialType::SSH_KEY | CredentialType::USER_PASS_PLAINTEXT;
+        assert_eq!(next_credential(&mut tried, ssh).unwrap(), CredentialType::SSH_KEY);
+        // The agent key was rejected
+        assert!(next_credential(&mut tried, ssh).is_err());
+
+        let mut tried = CredentialType::empty();
+        let https = CredentialType::USER_PASS_PLAINTEXT;
+        assert_eq!(next_credential(&mut tried, https).unwrap(), https);
+        assert!(next_credential(&mut tried, https).is_err());
+    }
+}
@@ -0,0 +1,38 @@
+//! genpdf elements for the compact PDF layout.
+
+/// Relative widths of the two columns and of the gutter between them.
+const COLUMN_WEIGHTS: [usize; 3] = [12, 1, 12];
+
+/// Lays its element out in two columns per page, the left one filled first,
+/// like a newspaper.
+pub struct Columns<E> {
+    element: E,
+}
+
+impl<E: genpdf::Element> Columns<E> {
+    pub fn new(element: E) -> Self {
+        Self { element }
+    }
+}
+
+impl<E: genpdf::Element> genpdf::Element for Columns<E> {
+    fn render(
+        &mut self,
+        context: &genpdf::Context,
+        area: genpdf::render::Area<'_>,
+        style: genpdf::style::Style,
+    ) -> Result<genpdf::RenderResult, genpdf::error::Error> {
+        let mut result = genpdf::RenderResult::default();
+        let areas = area.split_horizontally(&COLUMN_WEIGHTS);
+        for column in [&areas[0], &areas[2]] {
+            let column_result = self.element.render(context, column.clone(), style)?;
+            result.size.height = result.size.height.max(column_result.size.height);
+            result.has_more = column_result.has_more;
+            if !result.has_more {
+                break;
+            }
+        }
+        result.size.width = area.size().width;
+        Ok(result)
+    }
+}
@@ -1,5 +1,238 @@
-use crate::db::Segment;
-use crate::mistral::chat::Summary;
+pub mod branding;
+pub mod git_sync;
+mod layout;
+mod outline;
+pub mod protect;
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::db::{Attachment, Segment, Session, SpeakerProfile};
+use crate::mistral::chat::{Digest, Summary};
+
+/// Session data rendered by the Markdown and PDF exporters.
+pub struct SessionExport<'a> {
+    /// When set, Markdown timestamps link back to the session
+    /// (`poptranscribe://session/{id}?t=...`).
+    pub session_id: Option<&'a str>,
+    pub title: &'a str,
+    pub date: &'a str,
+    pub duration_secs: Option<f64>,
+    pub segments: &'a [Segment],
+    pub summary: &'a Option<Summary>,
+    pub attachments: &'a [Attachment],
+    /// Speakers of the session with their profile metadata.
+    pub participants: &'a [SpeakerProfile],
+    /// Speaker name -> `#RRGGBB`, see `resolve_speaker_colors`.
+    pub speaker_colors: &'a HashMap<String, String>,
+    /// Marks segments where the spoken language changes, e.g. `[EN]`.
+    pub annotate_languages: bool,
+    /// Translated text shown with the original, for bilingual exports.
+    pub translation: Option<Translation<'a>>,
+    /// Renders consecutive segments of a speaker as one paragraph, see
+    /// `group_paragraphs`.
+    pub paragraphs: bool,
+    pub pdf: PdfOptions,
+    pub branding: &'a branding::Branding,
+}
+
+/// Layout of the transcript in PDF exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PdfOptions {
+    /// Speaker names in their color, see `resolve_speaker_colors`.
+    pub speaker_colors: bool,
+    /// Speaker and time in a margin column, the text of the turn wrapping
+    /// beside them.
+    pub hanging_indent: bool,
+    /// Smaller text in two columns per page. Ignored by the parallel
+    /// bilingual layout, which already uses two columns.
+    pub compact: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            speaker_colors: true,
+            hanging_indent: true,
+            compact: false,
+        }
+    }
+}
+
+/// How a bilingual export places the translation of each segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BilingualLayout {
+    /// The translation below the original line.
+    Interleaved,
+    /// Original and translation side by side (Markdown table, two PDF columns).
+    Parallel,
+}
+
+impl BilingualLayout {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "interleaved" => Some(Self::Interleaved),
+            "parallel" => Some(Self::Parallel),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Translation<'a> {
+    /// ISO 639-1 code of the translation.
+    pub language: &'a str,
+    /// Segment id -> translated text. Untranslated segments are shown as is.
+    pub texts: &'a HashMap<i64, String>,
+    pub layout: BilingualLayout,
+}
+
+/// Pause after which the next segment of a speaker starts a new paragraph.
+const PARAGRAPH_MAX_GAP_SECS: f64 = 30.0;
+
+/// Ranges of consecutive segments with the same speaker and language and
+/// no long pause between them.
+fn paragraph_ranges(segments: &[Segment]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        if let Some(range) = ranges.last_mut() {
+            let previous = &segments[i - 1];
+            if previous.speaker == segment.speaker
+                && previous.language == segment.language
+                && segment.start_time - previous.end_time <= PARAGRAPH_MAX_GAP_SECS
+            {
+                range.end = i + 1;
+                continue;
+            }
+        }
+        ranges.push(i..i + 1);
+    }
+    ranges
+}
+
+fn join_texts<'t>(texts: impl Iterator<Item = &'t str>) -> String {
+    texts.map(str::trim).filter(|t| !t.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// Segments merged into paragraphs, one per speaker turn, so that long
+/// meetings do not read as one timestamp per sentence. A paragraph has the
+/// id and start time of its first segment and the end time of its last.
+pub fn group_paragraphs(segments: &[Segment]) -> Vec<Segment> {
+    paragraph_ranges(segments)
+        .into_iter()
+        .map(|range| {
+            let members = &segments[range];
+            let last = &members[members.len() - 1];
+            Segment {
+                text: join_texts(members.iter().map(|s| s.text.as_str())),
+                end_time: last.end_time,
+                ..members[0].clone()
+            }
+        })
+        .collect()
+}
+
+/// Translations of the paragraphs of `group_paragraphs`, by paragraph id.
+/// Untranslated segments of a paragraph keep their original text.
+fn group_translations(segments: &[Segment], texts: &HashMap<i64, String>) -> HashMap<i64, String> {
+    paragraph_ranges(segments)
+        .into_iter()
+        .filter_map(|range| {
+            let members = &segments[range];
+            members.iter().any(|s| texts.contains_key(&s.id)).then(|| {
+                let text = join_texts(
+                    members
+                        .iter()
+                        .map(|s| texts.get(&s.id).unwrap_or(&s.text).as_str()),
+                );
+                (members[0].id, text)
+            })
+        })
+        .collect()
+}
+
+/// Paragraph mode view of the segments and of their translation.
+struct Paragraphs {
+    segments: Vec<Segment>,
+    texts: Option<HashMap<i64, String>>,
+}
+
+impl Paragraphs {
+    fn new(segments: &[Segment], translation: Option<Translation>) -> Self {
+        Self {
+            segments: group_paragraphs(segments),
+            texts: translation.map(|t| group_translations(segments, t.texts)),
+        }
+    }
+}
+
+/// Palette used for speakers without an explicit color.
+const SPEAKER_PALETTE: [&str; 8] = [
+    "#2563EB", "#DC2626", "#059669", "#D97706", "#7C3AED", "#DB2777", "#0891B2", "#4B5563",
+];
+
+/// Deterministic palette color for a speaker name, so that uncolored
+/// speakers still render identically everywhere.
+pub fn default_speaker_color(speaker: &str) -> &'static str {
+    // FNV-1a, stable across runs and platforms
+    let hash = speaker
+        .bytes()
+        .fold(0x811c9dc5_u32, |h, b| (h ^ b as u32).wrapping_mul(0x01000193));
+    SPEAKER_PALETTE[hash as usize % SPEAKER_PALETTE.len()]
+}
+
+/// Completes explicit speaker colors with palette defaults for every
+/// speaker appearing in `segments`.
+pub fn resolve_speaker_colors(
+    segments: &[Segment],
+    explicit: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut colors = explicit.clone();
+    for speaker in segments.iter().filter_map(|s| s.speaker.as_deref()) {
+        colors
+            .entry(speaker.to_string())
+            .or_insert_with(|| default_speaker_color(speaker).to_string());
+    }
+    colors
+}
+
+/// "Marie (CFO, Acme) <marie@acme.com>" for export headers.
+fn describe_participant(profile: &SpeakerProfile) -> String {
+    match profile.email.as_deref().filter(|e| !e.is_empty()) {
+        Some(email) => format!("{} <{}>", profile.describe(), email),
+        None => profile.describe(),
+    }
+}
+
+/// `[EN]`-style marker when `segment` switches to another language than
+/// the last known one; updates `last` accordingly.
+fn language_switch(segment: &Segment, last: &mut Option<String>) -> Option<String> {
+    let language = segment.language.as_deref()?;
+    if last.as_deref() == Some(language) {
+        return None;
+    }
+    *last = Some(language.to_string());
+    Some(format!("[{}]", language.to_uppercase()))
+}
+
+/// Parses a `#RRGGBB` color.
+pub fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+    Some((r, g, b))
+}
+
+/// Text of a Markdown table cell, on one line and with pipes escaped.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
 
 /// Formats a timestamp in seconds to `[MM:SS]` or `[HH:MM:SS]` if >= 1 hour.
 fn format_timestamp(seconds: f64) -> String {
@@ -15,8 +248,18 @@ fn format_timestamp(seconds: f64) -> String {
     }
 }
 
+/// Markdown timestamp of a segment, linked to that moment of the session
+/// when its id is known.
+fn linked_timestamp(segment: &Segment, session_id: Option<&str>) -> String {
+    let ts = format_timestamp(segment.start_time);
+    match session_id {
+        Some(id) => format!("[{}]({})", ts, crate::deeplink::session_url(id, segment.start_time)),
+        None => ts,
+    }
+}
+
 /// Formats a duration in seconds to a human-readable string.
-fn format_duration(duration_secs: f64) -> String {
+pub(crate) fn format_duration(duration_secs: f64) -> String {
     let total_secs = duration_secs as u64;
     let hours = total_secs / 3600;
     let minutes = (total_secs % 3600) / 60;
@@ -32,13 +275,29 @@ fn format_duration(duration_secs: f64) -> String {
 }
 
 /// Formats a session's transcript and summary into a Markdown document.
-pub fn export_markdown(
-    title: &str,
-    date: &str,
-    duration_secs: Option<f64>,
-    segments: &[Segment],
-    summary: &Option<Summary>,
-) -> String {
+pub fn export_markdown(export: &SessionExport) -> String {
+    let SessionExport {
+        session_id,
+        title,
+        date,
+        duration_secs,
+        segments,
+        summary,
+        attachments,
+        participants,
+        annotate_languages,
+        translation,
+        paragraphs,
+        ..
+    } = *export;
+    let grouped = paragraphs.then(|| Paragraphs::new(segments, translation));
+    let (segments, translation) = match grouped {
+        Some(ref grouped) => (
+            &grouped.segments[..],
+            translation.map(|t| Translation { texts: grouped.texts.as_ref().unwrap_or(t.texts), ..t }),
+        ),
+        None => (segments, translation),
+    };
     let mut md = String::new();
 
     // Header
@@ -47,15 +306,43 @@ pub fn export_markdown(
     if let Some(dur) = duration_secs {
         md.push_str(&format!("**Duree:** {}\n", format_duration(dur)));
     }
+    if !participants.is_empty() {
+        let names: Vec<String> = participants.iter().map(describe_participant).collect();
+        md.push_str(&format!("**Participants:** {}\n", names.join(", ")));
+    }
 
     // Transcription section
     md.push_str("\n## Transcription\n\n");
-    for segment in segments {
-        let ts = format_timestamp(segment.start_time);
-        if let Some(ref speaker) = segment.speaker {
-            md.push_str(&format!("{} **{}:** {}\n", ts, speaker, segment.text));
-        } else {
-            md.push_str(&format!("{} {}\n", ts, segment.text));
+    if let Some(Translation { language, texts, layout: BilingualLayout::Parallel }) = translation {
+        md.push_str(&format!("| Temps | Intervenant | Original | {} |\n", language.to_uppercase()));
+        md.push_str("|---|---|---|---|\n");
+        for segment in segments {
+            let translated = texts.get(&segment.id).unwrap_or(&segment.text);
+            md.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                linked_timestamp(segment, session_id),
+                table_cell(segment.speaker.as_deref().unwrap_or("")),
+                table_cell(&segment.text),
+                table_cell(translated),
+            ));
+        }
+    } else {
+        let mut last_language = None;
+        for segment in segments {
+            let mut ts = linked_timestamp(segment, session_id);
+            if annotate_languages {
+                if let Some(marker) = language_switch(segment, &mut last_language) {
+                    ts = format!("{} {}", ts, marker);
+                }
+            }
+            if let Some(ref speaker) = segment.speaker {
+                md.push_str(&format!("{} **{}:** {}\n", ts, speaker, segment.text));
+            } else {
+                md.push_str(&format!("{} {}\n", ts, segment.text));
+            }
+            if let Some(translated) = translation.and_then(|t| t.texts.get(&segment.id)) {
+                md.push_str(&format!("> *{}*\n\n", translated));
+            }
         }
     }
 
@@ -89,19 +376,46 @@ pub fn export_markdown(
         }
     }
 
+    // Attachments section (only if present)
+    if !attachments.is_empty() {
+        md.push_str("\n## Pieces jointes\n\n");
+        for attachment in attachments {
+            md.push_str(&format!("- {}\n", attachment.file_name));
+        }
+    }
+
     md
 }
 
 /// Generates a PDF document from session data and saves it to the given path.
-pub fn export_pdf(
-    title: &str,
-    date: &str,
-    duration_secs: Option<f64>,
-    segments: &[Segment],
-    summary: &Option<Summary>,
-    output_path: &std::path::Path,
-) -> Result<(), String> {
+pub fn export_pdf(export: &SessionExport, output_path: &std::path::Path) -> Result<(), String> {
     use genpdf::Element as _;
+    use outline::{Anchor, AnchorKey, CountingDecorator, Navigation};
+
+    let SessionExport {
+        title,
+        date,
+        duration_secs,
+        segments,
+        summary,
+        attachments,
+        participants,
+        speaker_colors,
+        annotate_languages,
+        translation,
+        paragraphs,
+        pdf,
+        branding,
+        ..
+    } = *export;
+    let grouped = paragraphs.then(|| Paragraphs::new(segments, translation));
+    let (segments, translation) = match grouped {
+        Some(ref grouped) => (
+            &grouped.segments[..],
+            translation.map(|t| Translation { texts: grouped.texts.as_ref().unwrap_or(t.texts), ..t }),
+        ),
+        None => (segments, translation),
+    };
 
     let font_family = load_macos_fonts()?;
 
@@ -109,14 +423,31 @@ pub fn export_pdf(
     doc.set_title(title);
     doc.set_minimal_conformance();
 
-    let mut decorator = genpdf::SimplePageDecorator::new();
-    decorator.set_margins(genpdf::Margins::all(25));
-    doc.set_page_decorator(decorator);
+    let navigation = Navigation::new();
+    doc.set_page_decorator(CountingDecorator::new(navigation.clone(), branding));
     doc.set_font_size(10);
+    let heading = |size: u8| {
+        let style = genpdf::style::Style::new().bold().with_font_size(size);
+        match branding.accent_rgb() {
+            Some((r, g, b)) => style.with_color(genpdf::style::Color::Rgb(r, g, b)),
+            None => style,
+        }
+    };
+
+    // Logo, a broken one is left out rather than failing the export
+    if let Some(ref path) = branding.logo_path {
+        match branding::pdf_logo(path) {
+            Ok(logo) => {
+                doc.push(logo);
+                doc.push(genpdf::elements::Break::new(1.0_f32));
+            }
+            Err(e) => eprintln!("[export] {}", e),
+        }
+    }
 
     // Title
     doc.push(genpdf::elements::Paragraph::new(title)
-        .styled(genpdf::style::Style::new().bold().with_font_size(18)));
+        .styled(heading(18)));
     doc.push(genpdf::elements::Break::new(1.5_f32));
 
     // Metadata
@@ -128,67 +459,186 @@ pub fn export_pdf(
             .styled(genpdf::style::Style::new().with_font_size(10)
                 .with_color(genpdf::style::Color::Rgb(100, 100, 100))));
     }
+    if !participants.is_empty() {
+        let names: Vec<String> = participants.iter().map(describe_participant).collect();
+        doc.push(genpdf::elements::Paragraph::new(format!("Participants : {}", names.join(", ")))
+            .styled(genpdf::style::Style::new().with_font_size(10)
+                .with_color(genpdf::style::Color::Rgb(100, 100, 100))));
+    }
     doc.push(genpdf::elements::Break::new(2.0_f32));
 
     // Transcription header
-    doc.push(genpdf::elements::Paragraph::new("Transcription")
-        .styled(genpdf::style::Style::new().bold().with_font_size(14)));
+    let key = navigation.bookmark("Transcription", 0);
+    doc.push(Anchor::new(genpdf::elements::Paragraph::new("Transcription")
+        .styled(heading(14)), key, &navigation));
     doc.push(genpdf::elements::Break::new(1.0_f32));
 
-    // Segments
-    for segment in segments {
-        let ts = format_timestamp(segment.start_time);
+    // Segments, with their translation below or in a second column
+    let mut table = match translation {
+        Some(Translation { language, layout: BilingualLayout::Parallel, .. }) => {
+            let mut table = genpdf::elements::TableLayout::new(vec![1, 1]);
+            table.set_cell_decorator(genpdf::elements::FrameCellDecorator::new(false, true, false));
+            let header = genpdf::style::Style::new().bold().with_font_size(10);
+            table
+                .row()
+                .element(genpdf::elements::Paragraph::new("Original").styled(header).padded(1))
+                .element(genpdf::elements::Paragraph::new(language.to_uppercase()).styled(header).padded(1))
+                .push()
+                .map_err(|e| format!("Erreur generation PDF: {}", e))?;
+            Some(table)
+        }
+        _ => None,
+    };
+    let text_size = if pdf.compact { 8 } else { 10 };
+    let text_style = genpdf::style::Style::new().with_font_size(text_size);
+    let timestamp_style = genpdf::style::Style::new().with_font_size(text_size - 1)
+        .with_color(genpdf::style::Color::Rgb(120, 120, 120));
+    let translation_style = genpdf::style::Style::new().italic().with_font_size(text_size)
+        .with_color(genpdf::style::Color::Rgb(100, 100, 100));
+    let speaker_style = |speaker: &str| {
+        let style = genpdf::style::Style::new().bold().with_font_size(text_size);
+        match speaker_colors.get(speaker).and_then(|c| parse_hex_color(c)) {
+            Some((r, g, b)) if pdf.speaker_colors => style.with_color(genpdf::style::Color::Rgb(r, g, b)),
+            _ => style,
+        }
+    };
+    // "[00:12] Marie : text" on one line
+    let inline_turn = |ts: &str, segment: &Segment| {
         let mut para = genpdf::elements::Paragraph::default();
-        para.push(genpdf::style::StyledString::new(
-            format!("{} ", ts),
-            genpdf::style::Style::new().with_font_size(9)
-                .with_color(genpdf::style::Color::Rgb(120, 120, 120)),
-        ));
+        para.push(genpdf::style::StyledString::new(format!("{} ", ts), timestamp_style));
         if let Some(ref speaker) = segment.speaker {
-            para.push(genpdf::style::StyledString::new(
-                format!("{} : ", speaker),
-                genpdf::style::Style::new().bold().with_font_size(10),
-            ));
+            para.push(genpdf::style::StyledString::new(format!("{} : ", speaker), speaker_style(speaker)));
+        }
+        para.push(genpdf::style::StyledString::new(segment.text.clone(), text_style));
+        para
+    };
+
+    let mut transcript = genpdf::elements::LinearLayout::vertical();
+    let mut last_language = None;
+    let mut last_speaker: Option<&str> = None;
+    // Long sessions get a bookmark every `SECTION_SECS`
+    let long = segments.last().is_some_and(|s| s.end_time > outline::SECTION_SECS);
+    let mut next_section = 0.0;
+    for segment in segments {
+        let section = (long && segment.start_time >= next_section).then(|| {
+            let index = (segment.start_time / outline::SECTION_SECS).floor();
+            next_section = (index + 1.0) * outline::SECTION_SECS;
+            navigation.bookmark(
+                format!("Partie {} {}", index as u64 + 1, format_timestamp(index * outline::SECTION_SECS)),
+                1,
+            )
+        });
+        let mut ts = format_timestamp(segment.start_time);
+        if annotate_languages {
+            if let Some(marker) = language_switch(segment, &mut last_language) {
+                ts = format!("{} {}", ts, marker);
+            }
         }
-        para.push(genpdf::style::StyledString::new(
-            segment.text.clone(),
-            genpdf::style::Style::new().with_font_size(10),
-        ));
-        doc.push(para);
+        let mut keys = vec![AnchorKey::Segment(segment.id)];
+        keys.extend(section);
+        let translated = translation.and_then(|t| t.texts.get(&segment.id));
+        match table {
+            Some(ref mut table) => {
+                let translated = translated.unwrap_or(&segment.text);
+                table
+                    .row()
+                    .element(Anchor::with_keys(inline_turn(&ts, segment), keys, &navigation).padded(1))
+                    .element(
+                        genpdf::elements::Paragraph::new(translated.clone())
+                            .styled(text_style)
+                            .padded(1),
+                    )
+                    .push()
+                    .map_err(|e| format!("Erreur generation PDF: {}", e))?;
+            }
+            None if pdf.hanging_indent => {
+                // The name is only repeated when the speaker changes
+                let mut label = genpdf::elements::LinearLayout::vertical();
+                if let Some(speaker) = segment.speaker.as_deref().filter(|s| Some(*s) != last_speaker) {
+                    label.push(genpdf::elements::Paragraph::new(speaker).styled(speaker_style(speaker)));
+                }
+                label.push(genpdf::elements::Paragraph::new(ts).styled(timestamp_style));
+                let mut body = genpdf::elements::LinearLayout::vertical();
+                body.push(genpdf::elements::Paragraph::new(segment.text.clone()).styled(text_style));
+                if let Some(translated) = translated {
+                    body.push(genpdf::elements::Paragraph::new(translated.clone()).styled(translation_style));
+                }
+                let mut turn = genpdf::elements::TableLayout::new(if pdf.compact { vec![1, 3] } else { vec![1, 5] });
+                turn.row()
+                    .element(label.padded(genpdf::Margins::trbl(0, 2, 0, 0)))
+                    .element(body)
+                    .push()
+                    .map_err(|e| format!("Erreur generation PDF: {}", e))?;
+                transcript.push(Anchor::with_keys(
+                    turn.padded(genpdf::Margins::trbl(0, 0, 1, 0)),
+                    keys,
+                    &navigation,
+                ));
+            }
+            None => {
+                transcript.push(Anchor::with_keys(inline_turn(&ts, segment), keys, &navigation));
+                if let Some(translated) = translated {
+                    transcript.push(
+                        genpdf::elements::Paragraph::new(translated.clone())
+                            .styled(translation_style)
+                            .padded(genpdf::Margins::trbl(0, 0, 1, 5)),
+                    );
+                }
+            }
+        }
+        last_speaker = segment.speaker.as_deref();
+    }
+    match table {
+        Some(table) => doc.push(table),
+        None if pdf.compact => doc.push(layout::Columns::new(transcript)),
+        None => doc.push(transcript),
     }
 
-    // Summary
+    // Summary, its points linked to the transcript passage they come from
     if let Some(ref summary) = summary {
         doc.push(genpdf::elements::Break::new(2.0_f32));
-        doc.push(genpdf::elements::Paragraph::new("Resume")
-            .styled(genpdf::style::Style::new().bold().with_font_size(14)));
+        let key = navigation.bookmark("Resume", 0);
+        doc.push(Anchor::new(genpdf::elements::Paragraph::new("Resume")
+            .styled(heading(14)), key, &navigation));
         doc.push(genpdf::elements::Break::new(1.0_f32));
 
-        if !summary.key_points.is_empty() {
-            doc.push(genpdf::elements::Paragraph::new("Points cles")
-                .styled(genpdf::style::Style::new().bold().with_font_size(12)));
+        let mut points = 0;
+        let mut linked_list = |items: &[String]| {
             let mut list = genpdf::elements::UnorderedList::new();
-            for point in &summary.key_points {
-                list.push(genpdf::elements::Paragraph::new(point.clone()));
+            for item in items {
+                if let Some(segment_id) = outline::best_segment(item, segments) {
+                    navigation.link(points, segment_id);
+                }
+                list.push(Anchor::new(
+                    genpdf::elements::Paragraph::new(item.clone()),
+                    AnchorKey::Point(points),
+                    &navigation,
+                ));
+                points += 1;
             }
-            doc.push(list);
+            list
+        };
+
+        if !summary.key_points.is_empty() {
+            let key = navigation.bookmark("Points cles", 1);
+            doc.push(Anchor::new(genpdf::elements::Paragraph::new("Points cles")
+                .styled(heading(12)), key, &navigation));
+            doc.push(linked_list(&summary.key_points));
         }
 
         if !summary.decisions.is_empty() {
             doc.push(genpdf::elements::Break::new(1.0_f32));
-            doc.push(genpdf::elements::Paragraph::new("Decisions")
-                .styled(genpdf::style::Style::new().bold().with_font_size(12)));
-            let mut list = genpdf::elements::UnorderedList::new();
-            for decision in &summary.decisions {
-                list.push(genpdf::elements::Paragraph::new(decision.clone()));
-            }
-            doc.push(list);
+            let key = navigation.bookmark("Decisions", 1);
+            doc.push(Anchor::new(genpdf::elements::Paragraph::new("Decisions")
+                .styled(heading(12)), key, &navigation));
+            doc.push(linked_list(&summary.decisions));
         }
 
         if !summary.action_items.is_empty() {
             doc.push(genpdf::elements::Break::new(1.0_f32));
-            doc.push(genpdf::elements::Paragraph::new("Actions a suivre")
-                .styled(genpdf::style::Style::new().bold().with_font_size(12)));
+            let key = navigation.bookmark("Actions a suivre", 1);
+            doc.push(Anchor::new(genpdf::elements::Paragraph::new("Actions a suivre")
+                .styled(heading(12)), key, &navigation));
             let mut list = genpdf::elements::UnorderedList::new();
             for item in &summary.action_items {
                 let text = if let Some(ref assignee) = item.assignee {
@@ -202,12 +652,151 @@ pub fn export_pdf(
         }
     }
 
+    // Attachments
+    if !attachments.is_empty() {
+        doc.push(genpdf::elements::Break::new(2.0_f32));
+        let key = navigation.bookmark("Pieces jointes", 0);
+        doc.push(Anchor::new(genpdf::elements::Paragraph::new("Pieces jointes")
+            .styled(heading(14)), key, &navigation));
+        let mut list = genpdf::elements::UnorderedList::new();
+        for attachment in attachments {
+            list.push(genpdf::elements::Paragraph::new(attachment.file_name.clone()));
+        }
+        doc.push(list);
+    }
+
     // Create parent directory
     if let Some(parent) = output_path.parent() {
         std::fs::create_dir_all(parent)
             .map_err(|e| format!("Erreur creation dossier: {}", e))?;
     }
 
+    doc.render_to_file(output_path)
+        .map_err(|e| format!("Erreur generation PDF: {}", e))?;
+    navigation.apply(output_path)?;
+
+    Ok(())
+}
+
+/// Formats a multi-session digest into a Markdown document.
+pub fn export_digest_markdown(
+    start_date: &str,
+    end_date: &str,
+    sessions: &[Session],
+    digest: &Digest,
+) -> String {
+    let mut md = String::new();
+
+    md.push_str(&format!("# Synthese du {} au {}\n\n", start_date, end_date));
+
+    md.push_str("## Reunions\n\n");
+    for session in sessions {
+        let date = session.created_at.get(..10).unwrap_or(&session.created_at);
+        if let Some(dur) = session.duration_secs {
+            md.push_str(&format!("- {} — {} ({})\n", date, session.title, format_duration(dur)));
+        } else {
+            md.push_str(&format!("- {} — {}\n", date, session.title));
+        }
+    }
+
+    if !digest.themes.is_empty() {
+        md.push_str("\n## Themes\n\n");
+        for theme in &digest.themes {
+            md.push_str(&format!("- {}\n", theme));
+        }
+    }
+
+    if !digest.decisions.is_empty() {
+        md.push_str("\n## Decisions\n\n");
+        for decision in &digest.decisions {
+            md.push_str(&format!("- {}\n", decision));
+        }
+    }
+
+    if !digest.outstanding_actions.is_empty() {
+        md.push_str("\n## Actions en suspens\n\n");
+        for item in &digest.outstanding_actions {
+            if let Some(ref assignee) = item.assignee {
+                md.push_str(&format!("- [ ] {} (Assignee: {})\n", item.description, assignee));
+            } else {
+                md.push_str(&format!("- [ ] {}\n", item.description));
+            }
+        }
+    }
+
+    md
+}
+
+/// Generates a PDF version of a multi-session digest.
+pub fn export_digest_pdf(
+    start_date: &str,
+    end_date: &str,
+    sessions: &[Session],
+    digest: &Digest,
+    output_path: &std::path::Path,
+) -> Result<(), String> {
+    use genpdf::Element as _;
+
+    let font_family = load_macos_fonts()?;
+    let title = format!("Synthese du {} au {}", start_date, end_date);
+
+    let mut doc = genpdf::Document::new(font_family);
+    doc.set_title(&title);
+    doc.set_minimal_conformance();
+
+    let mut decorator = genpdf::SimplePageDecorator::new();
+    decorator.set_margins(genpdf::Margins::all(25));
+    doc.set_page_decorator(decorator);
+    doc.set_font_size(10);
+
+    doc.push(genpdf::elements::Paragraph::new(title)
+        .styled(genpdf::style::Style::new().bold().with_font_size(18)));
+    doc.push(genpdf::elements::Break::new(1.5_f32));
+
+    doc.push(genpdf::elements::Paragraph::new("Reunions")
+        .styled(genpdf::style::Style::new().bold().with_font_size(14)));
+    let mut list = genpdf::elements::UnorderedList::new();
+    for session in sessions {
+        let date = session.created_at.get(..10).unwrap_or(&session.created_at);
+        list.push(genpdf::elements::Paragraph::new(format!("{} — {}", date, session.title)));
+    }
+    doc.push(list);
+
+    let sections: [(&str, Vec<String>); 3] = [
+        ("Themes", digest.themes.clone()),
+        ("Decisions", digest.decisions.clone()),
+        (
+            "Actions en suspens",
+            digest
+                .outstanding_actions
+                .iter()
+                .map(|item| match item.assignee {
+                    Some(ref assignee) => format!("{} (Assignee : {})", item.description, assignee),
+                    None => item.description.clone(),
+                })
+                .collect(),
+        ),
+    ];
+
+    for (heading, items) in sections {
+        if items.is_empty() {
+            continue;
+        }
+        doc.push(genpdf::elements::Break::new(1.0_f32));
+        doc.push(genpdf::elements::Paragraph::new(heading)
+            .styled(genpdf::style::Style::new().bold().with_font_size(14)));
+        let mut list = genpdf::elements::UnorderedList::new();
+        for item in items {
+            list.push(genpdf::elements::Paragraph::new(item));
+        }
+        doc.push(list);
+    }
+
+    if let Some(parent) = output_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    }
+
     doc.render_to_file(output_path)
         .map_err(|e| format!("Erreur generation PDF: {}", e))?;
 
@@ -239,6 +828,57 @@ fn load_macos_fonts() -> Result<genpdf::fonts::FontFamily<genpdf::fonts::FontDat
     })
 }
 
+/// Packs the files of `dir` (non-recursive) into a zip archive at `output`.
+/// Already-compressed audio and large WAVs are stored without recompression.
+pub fn zip_directory(dir: &std::path::Path, output: &std::path::Path) -> Result<(), String> {
+    use zip::write::SimpleFileOptions;
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Erreur creation dossier: {}", e))?;
+    }
+    let file = std::fs::File::create(output)
+        .map_err(|e| format!("Erreur creation archive: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Erreur lecture dossier: {}", e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or("Nom de fichier invalide")?
+            .to_string();
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let is_audio = matches!(extension.as_str(), "wav" | "opus" | "flac" | "mp3" | "m4a");
+        let method = if is_audio {
+            zip::CompressionMethod::Stored
+        } else {
+            zip::CompressionMethod::Deflated
+        };
+        let options = SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(size >= u32::MAX as u64);
+
+        zip.start_file(name, options)
+            .map_err(|e| format!("Erreur archive zip: {}", e))?;
+        let mut src = std::fs::File::open(&path)
+            .map_err(|e| format!("Erreur lecture fichier: {}", e))?;
+        std::io::copy(&mut src, &mut zip).map_err(|e| format!("Erreur archive zip: {}", e))?;
+    }
+
+    zip.finish().map_err(|e| format!("Erreur archive zip: {}", e))?;
+    Ok(())
+}
+
 /// Writes content to a file at the given path.
 pub fn export_to_file(content: &str, path: &std::path::Path) -> Result<(), std::io::Error> {
     if let Some(parent) = path.parent() {
@@ -250,8 +890,136 @@ pub fn export_to_file(content: &str, path: &std::path::Path) -> Result<(), std::
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::Segment;
-    use crate::mistral::chat::{ActionItem, Summary};
+    use crate::db::{Attachment, Segment, Session, SpeakerProfile};
+    use crate::mistral::chat::{ActionItem, Digest, Summary};
+
+    fn markdown(
+        title: &str,
+        date: &str,
+        duration_secs: Option<f64>,
+        segments: &[Segment],
+        summary: &Option<Summary>,
+    ) -> String {
+        export_markdown(&SessionExport {
+            session_id: None,
+            title,
+            date,
+            duration_secs,
+            segments,
+            summary,
+            attachments: &[],
+            participants: &[],
+            speaker_colors: &HashMap::new(),
+            annotate_languages: false,
+            translation: None,
+            paragraphs: false,
+            pdf: PdfOptions::default(),
+            branding: &branding::Branding::default(),
+        })
+    }
+
+    fn bilingual(segments: &[Segment], texts: &HashMap<i64, String>, layout: BilingualLayout) -> String {
+        export_markdown(&SessionExport {
+            session_id: None,
+            title: "Reunion",
+            date: "2025-02-01",
+            duration_secs: None,
+            segments,
+            summary: &None,
+            attachments: &[],
+            participants: &[],
+            speaker_colors: &HashMap::new(),
+            annotate_languages: false,
+            translation: Some(Translation { language: "en", texts, layout }),
+            paragraphs: false,
+            pdf: PdfOptions::default(),
+            branding: &branding::Branding::default(),
+        })
+    }
+
+    #[test]
+    fn test_export_markdown_bilingual() {
+        let mut segments = vec![
+            make_segment("Bonjour a tous", 0.0, 2.0, Some("Marie")),
+            make_segment("Prix | delai", 2.0, 4.0, None),
+        ];
+        segments[1].id = 2;
+        let texts = HashMap::from([(1, "Hello everyone".to_string())]);
+
+        let md = bilingual(&segments, &texts, BilingualLayout::Interleaved);
+        assert!(md.contains("[00:00] **Marie:** Bonjour a tous\n> *Hello everyone*\n"));
+        assert!(md.contains("[00:02] Prix | delai\n"));
+
+        let md = bilingual(&segments, &texts, BilingualLayout::Parallel);
+        assert!(md.contains("| Temps | Intervenant | Original | EN |\n"));
+        assert!(md.contains("| [00:00] | Marie | Bonjour a tous | Hello everyone |\n"));
+        // Untranslated segments repeat the original; pipes are escaped
+        assert!(md.contains("| [00:02] |  | Prix \\| delai | Prix \\| delai |\n"));
+    }
+
+    #[test]
+    fn test_export_markdown_deep_links() {
+        let segments = vec![make_segment("Bonjour", 75.4, 77.0, Some("Marie"))];
+        let md = export_markdown(&SessionExport {
+            session_id: Some("abc-123"),
+            title: "Reunion",
+            date: "2025-02-01",
+            duration_secs: None,
+            segments: &segments,
+            summary: &None,
+            attachments: &[],
+            participants: &[],
+            speaker_colors: &HashMap::new(),
+            annotate_languages: false,
+            translation: None,
+            paragraphs: false,
+            pdf: PdfOptions::default(),
+            branding: &branding::Branding::default(),
+        });
+        assert!(md.contains("[[01:15]](poptranscribe://session/abc-123?t=75) **Marie:** Bonjour\n"));
+    }
+
+    #[test]
+    fn test_export_markdown_paragraphs() {
+        let mut segments = vec![
+            make_segment("On commence.", 0.0, 2.0, Some("Marie")),
+            make_segment("Premier point.", 2.5, 4.0, Some("Marie")),
+            make_segment("D'accord.", 4.0, 5.0, Some("Paul")),
+            make_segment("Apres la pause.", 60.0, 62.0, Some("Paul")),
+        ];
+        for (i, segment) in segments.iter_mut().enumerate() {
+            segment.id = i as i64 + 1;
+        }
+        let texts = HashMap::from([(2, "First item.".to_string())]);
+
+        let paragraphs = group_paragraphs(&segments);
+        assert_eq!(paragraphs.len(), 3);
+        assert_eq!(paragraphs[0].text, "On commence. Premier point.");
+        assert_eq!((paragraphs[0].id, paragraphs[0].end_time), (1, 4.0));
+
+        let md = export_markdown(&SessionExport {
+            session_id: None,
+            title: "Reunion",
+            date: "2025-02-01",
+            duration_secs: None,
+            segments: &segments,
+            summary: &None,
+            attachments: &[],
+            participants: &[],
+            speaker_colors: &HashMap::new(),
+            annotate_languages: false,
+            translation: Some(Translation {
+                language: "en",
+                texts: &texts,
+                layout: BilingualLayout::Interleaved,
+            }),
+            paragraphs: true,
+            pdf: PdfOptions::default(),
+            branding: &branding::Branding::default(),
+        });
+        assert!(md.contains("[00:00] **Marie:** On commence. Premier point.\n> *On commence. First item.*\n"));
+        assert!(md.contains("[00:04] **Paul:** D'accord.\n[01:00] **Paul:** Apres la pause.\n"));
+    }
 
     fn make_segment(text: &str, start: f64, end: f64, speaker: Option<&str>) -> Segment {
         Segment {
@@ -262,9 +1030,45 @@ mod tests {
             end_time: end,
             speaker: speaker.map(|s| s.to_string()),
             is_diarized: speaker.is_some(),
+            language: None,
         }
     }
 
+    #[test]
+    fn test_export_markdown_annotates_language_switches() {
+        let mut segments = vec![
+            make_segment("On commence", 0.0, 2.0, Some("Marie")),
+            make_segment("Ensuite", 2.0, 4.0, Some("Marie")),
+            make_segment("Let's ship it", 4.0, 6.0, Some("Paul")),
+            make_segment("OK", 6.0, 7.0, Some("Paul")),
+        ];
+        segments[0].language = Some("fr".to_string());
+        segments[1].language = Some("fr".to_string());
+        segments[2].language = Some("en".to_string());
+
+        let md = export_markdown(&SessionExport {
+            session_id: None,
+            title: "Reunion",
+            date: "2025-02-01",
+            duration_secs: None,
+            segments: &segments,
+            summary: &None,
+            attachments: &[],
+            participants: &[],
+            speaker_colors: &HashMap::new(),
+            annotate_languages: true,
+            translation: None,
+            paragraphs: false,
+            pdf: PdfOptions::default(),
+            branding: &branding::Branding::default(),
+        });
+
+        assert!(md.contains("[00:00] [FR] **Marie:** On commence\n"));
+        assert!(md.contains("[00:02] **Marie:** Ensuite\n"));
+        assert!(md.contains("[00:04] [EN] **Paul:** Let's ship it\n"));
+        assert!(md.contains("[00:06] **Paul:** OK\n"));
+    }
+
     #[test]
     fn test_export_markdown_with_speakers() {
         let segments = vec![
@@ -288,9 +1092,10 @@ mod tests {
                     assignee: None,
                 },
             ],
+            topics: vec!["budget".to_string()],
         });
 
-        let md = export_markdown(
+        let md = markdown(
             "Reunion Equipe",
             "2025-01-15",
             Some(900.0),
@@ -322,7 +1127,7 @@ mod tests {
             make_segment("Salut", 2.0, 4.0, Some("Speaker 2")),
         ];
 
-        let md = export_markdown(
+        let md = markdown(
             "Reunion rapide",
             "2025-02-01",
             Some(60.0),
@@ -334,13 +1139,45 @@ mod tests {
         assert!(md.contains("## Transcription"));
         assert!(!md.contains("## Resume"));
         assert!(!md.contains("### Points cles"));
+        assert!(!md.contains("## Pieces jointes"));
+    }
+
+    #[test]
+    fn test_export_markdown_with_attachments() {
+        let attachments = vec![Attachment {
+            id: 1,
+            session_id: "test-session".to_string(),
+            file_name: "slides.pdf".to_string(),
+            path: "/tmp/slides.pdf".to_string(),
+            size_bytes: 1024,
+            created_at: "2025-02-01T10:00:00+00:00".to_string(),
+        }];
+
+        let md = export_markdown(&SessionExport {
+            session_id: None,
+            title: "Reunion",
+            date: "2025-02-01",
+            duration_secs: None,
+            segments: &[],
+            summary: &None,
+            attachments: &attachments,
+            participants: &[],
+            speaker_colors: &HashMap::new(),
+            annotate_languages: false,
+            translation: None,
+            paragraphs: false,
+            pdf: PdfOptions::default(),
+            branding: &branding::Branding::default(),
+        });
+
+        assert!(md.contains("## Pieces jointes\n\n- slides.pdf"));
     }
 
     #[test]
     fn test_export_markdown_empty_segments() {
         let segments: Vec<Segment> = vec![];
 
-        let md = export_markdown(
+        let md = markdown(
             "Reunion vide",
             "2025-03-01",
             None,
@@ -366,7 +1203,7 @@ mod tests {
             make_segment("Beaucoup plus tard", 7384.0, 7400.0, None),
         ];
 
-        let md = export_markdown("Longue reunion", "2025-04-01", Some(7400.0), &segments, &None);
+        let md = markdown("Longue reunion", "2025-04-01", Some(7400.0), &segments, &None);
 
         // 0 seconds -> [00:00]
         assert!(md.contains("[00:00] Debut"));
@@ -380,6 +1217,135 @@ mod tests {
         assert!(md.contains("**Duree:** 2h 03min 20s"));
     }
 
+    #[test]
+    fn test_export_digest_markdown() {
+        let sessions = vec![Session {
+            id: "s1".to_string(),
+            title: "Point sprint".to_string(),
+            mode: "visio".to_string(),
+            audio_path: None,
+            created_at: "2025-01-13T09:00:00+00:00".to_string(),
+            duration_secs: Some(1800.0),
+            summary_json: None,
+            protected: false,
+            archived: false,
+            language: None,
+            transcript_only: false,
+            summary_model: None,
+            speaker_count: None,
+            detected_language: None,
+        }];
+        let digest = Digest {
+            themes: vec!["Recrutement".to_string()],
+            decisions: vec![],
+            outstanding_actions: vec![ActionItem {
+                description: "Publier l'offre".to_string(),
+                assignee: Some("Marie".to_string()),
+            }],
+        };
+
+        let md = export_digest_markdown("2025-01-13", "2025-01-19", &sessions, &digest);
+
+        assert!(md.contains("# Synthese du 2025-01-13 au 2025-01-19"));
+        assert!(md.contains("- 2025-01-13 — Point sprint (30min 00s)"));
+        assert!(md.contains("## Themes\n\n- Recrutement"));
+        assert!(!md.contains("## Decisions"));
+        assert!(md.contains("- [ ] Publier l'offre (Assignee: Marie)"));
+    }
+
+    #[test]
+    fn test_export_markdown_participants() {
+        let participants = vec![
+            SpeakerProfile {
+                name: "Marie".to_string(),
+                role: Some("CFO".to_string()),
+                email: Some("marie@acme.com".to_string()),
+                ..Default::default()
+            },
+            SpeakerProfile {
+                name: "Paul".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let md = export_markdown(&SessionExport {
+            session_id: None,
+            title: "Reunion",
+            date: "2025-02-01",
+            duration_secs: None,
+            segments: &[],
+            summary: &None,
+            attachments: &[],
+            participants: &participants,
+            speaker_colors: &HashMap::new(),
+            annotate_languages: false,
+            translation: None,
+            paragraphs: false,
+            pdf: PdfOptions::default(),
+            branding: &branding::Branding::default(),
+        });
+
+        assert!(md.contains("**Participants:** Marie (CFO) <marie@acme.com>, Paul\n"));
+    }
+
+    #[test]
+    fn test_default_speaker_color_is_stable() {
+        assert_eq!(default_speaker_color("Speaker 1"), default_speaker_color("Speaker 1"));
+        assert!(SPEAKER_PALETTE.contains(&default_speaker_color("Marie")));
+    }
+
+    #[test]
+    fn test_resolve_speaker_colors() {
+        let segments = vec![
+            make_segment("Bonjour", 0.0, 1.0, Some("Marie")),
+            make_segment("Salut", 1.0, 2.0, Some("Paul")),
+            make_segment("Note", 2.0, 3.0, None),
+        ];
+        let mut explicit = HashMap::new();
+        explicit.insert("Marie".to_string(), "#FF0000".to_string());
+
+        let colors = resolve_speaker_colors(&segments, &explicit);
+        assert_eq!(colors.len(), 2);
+        assert_eq!(colors["Marie"], "#FF0000");
+        assert_eq!(colors["Paul"], default_speaker_color("Paul"));
+    }
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#FF8000"), Some((255, 128, 0)));
+        assert_eq!(parse_hex_color("#ff8000"), Some((255, 128, 0)));
+        assert_eq!(parse_hex_color("FF8000"), None);
+        assert_eq!(parse_hex_color("#FFF"), None);
+        assert_eq!(parse_hex_color("#GG0000"), None);
+    }
+
+    #[test]
+    fn test_pdf_options_defaults() {
+        let options: PdfOptions = serde_json::from_str(r#"{"compact": true}"#).unwrap();
+        assert!(options.compact);
+        assert!(options.speaker_colors && options.hanging_indent);
+    }
+
+    #[test]
+    fn test_zip_directory() {
+        let dir = std::env::temp_dir().join("poptranscribe_test_zip");
+        let output = std::env::temp_dir().join("poptranscribe_test_zip.zip");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.md"), "# Test").unwrap();
+        std::fs::write(dir.join("audio.wav"), b"RIFF").unwrap();
+
+        zip_directory(&dir, &output).unwrap();
+
+        let archive = zip::ZipArchive::new(std::fs::File::open(&output).unwrap()).unwrap();
+        assert_eq!(archive.len(), 2);
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"notes.md"));
+        assert!(names.contains(&"audio.wav"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_file(&output);
+    }
+
     #[test]
     fn test_export_to_file() {
         let dir = std::env::temp_dir().join("poptranscribe_test_export");
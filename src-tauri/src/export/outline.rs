@@ -0,0 +1,419 @@
+//! Navigation in exported PDFs: bookmarks to the sections of the document
+//! and links from the summary points to the transcript.
+//!
+//! genpdf neither knows pages in advance nor writes outlines, so elements of
+//! interest are wrapped in an `Anchor` that records where it lands while the
+//! document is laid out, and the outline and links are added to the rendered
+//! file with lopdf.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::rc::Rc;
+
+use lopdf::{dictionary, Document, Object, ObjectId, StringFormat};
+
+use super::branding::Branding;
+use crate::db::{fold_name, Segment};
+
+/// A4, the genpdf default.
+const PAGE_WIDTH_MM: f64 = 210.0;
+const PAGE_HEIGHT_MM: f64 = 297.0;
+const PAGE_MARGIN_MM: f64 = 25.0;
+
+/// Space taken by the branding footer at the bottom of the pages.
+const FOOTER_HEIGHT_MM: f64 = 8.0;
+
+/// Length of the transcript sections of long sessions.
+pub const SECTION_SECS: f64 = 600.0;
+
+/// Shortest words considered when matching a summary point.
+const MIN_WORD_LEN: usize = 4;
+
+/// Shared words needed for a point to link to a segment.
+const MIN_SHARED_WORDS: usize = 2;
+
+/// What an anchor marks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AnchorKey {
+    Bookmark(usize),
+    Segment(i64),
+    Point(usize),
+}
+
+/// Where an anchored element was drawn: 1-based page, top and height in mm
+/// from the top of the page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement {
+    pub page: u32,
+    pub top: f64,
+    pub height: f64,
+}
+
+/// Outline entry, nested under the previous entry of a lower level.
+#[derive(Debug, Clone)]
+pub struct Bookmark {
+    pub title: String,
+    pub level: usize,
+}
+
+/// Layout state shared by the page decorator and the anchors.
+#[derive(Default)]
+pub struct Navigation {
+    page: Cell<u32>,
+    /// Bottom of the content area of the pages, in mm from their top.
+    content_bottom: Cell<f64>,
+    placements: RefCell<HashMap<AnchorKey, Placement>>,
+    bookmarks: RefCell<Vec<Bookmark>>,
+    /// Summary point -> segment it refers to.
+    links: RefCell<Vec<(usize, i64)>>,
+}
+
+impl Navigation {
+    pub fn new() -> Rc<Self> {
+        Rc::new(Self::default())
+    }
+
+    /// Registers an outline entry, to be anchored with the returned key.
+    pub fn bookmark(&self, title: impl Into<String>, level: usize) -> AnchorKey {
+        let mut bookmarks = self.bookmarks.borrow_mut();
+        bookmarks.push(Bookmark { title: title.into(), level });
+        AnchorKey::Bookmark(bookmarks.len() - 1)
+    }
+
+    /// Registers a link from the summary point anchored with `Point(point)`
+    /// to the segment anchored with `Segment(segment_id)`.
+    pub fn link(&self, point: usize, segment_id: i64) {
+        self.links.borrow_mut().push((point, segment_id));
+    }
+
+    pub fn placement(&self, key: &AnchorKey) -> Option<Placement> {
+        self.placements.borrow().get(key).copied()
+    }
+
+    /// Adds the outline and the links to the PDF rendered at `path`.
+    pub fn apply(&self, path: &Path) -> Result<(), String> {
+        let bookmarks: Vec<(Bookmark, Placement)> = self
+            .bookmarks
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| Some((b.clone(), self.placement(&AnchorKey::Bookmark(i))?)))
+            .collect();
+        let links: Vec<(Placement, Placement)> = self
+            .links
+            .borrow()
+            .iter()
+            .filter_map(|(point, segment)| {
+                Some((
+                    self.placement(&AnchorKey::Point(*point))?,
+                    self.placement(&AnchorKey::Segment(*segment))?,
+                ))
+            })
+            .collect();
+        if bookmarks.is_empty() && links.is_empty() {
+            return Ok(());
+        }
+
+        let mut doc = Document::load(path).map_err(|e| format!("Lecture du PDF impossible: {}", e))?;
+        let pages = doc.get_pages();
+        add_links(&mut doc, &pages, &links)?;
+        add_outline(&mut doc, &pages, &bookmarks)?;
+        doc.save(path).map_err(|e| format!("Ecriture du PDF impossible: {}", e))?;
+        Ok(())
+    }
+}
+
+/// genpdf page decorator counting the pages as they are laid out, and
+/// drawing the branding header and footer.
+pub struct CountingDecorator {
+    inner: genpdf::SimplePageDecorator,
+    branding: Branding,
+    navigation: Rc<Navigation>,
+}
+
+impl CountingDecorator {
+    pub fn new(navigation: Rc<Navigation>, branding: &Branding) -> Self {
+        let mut inner = genpdf::SimplePageDecorator::new();
+        inner.set_margins(genpdf::Margins::all(PAGE_MARGIN_MM));
+        if let Some(header) = branding.header_line() {
+            inner.set_header(move |_page| {
+                genpdf::elements::Paragraph::new(header.clone())
+                    .aligned(genpdf::Alignment::Right)
+                    .styled(small_print())
+                    .padded(genpdf::Margins::trbl(0, 0, 4, 0))
+            });
+        }
+        Self { inner, branding: branding.clone(), navigation }
+    }
+}
+
+fn small_print() -> genpdf::style::Style {
+    genpdf::style::Style::new()
+        .with_font_size(8)
+        .with_color(genpdf::style::Color::Rgb(120, 120, 120))
+}
+
+impl genpdf::PageDecorator for CountingDecorator {
+    fn decorate_page<'a>(
+        &mut self,
+        context: &genpdf::Context,
+        area: genpdf::render::Area<'a>,
+        style: genpdf::style::Style,
+    ) -> Result<genpdf::render::Area<'a>, genpdf::error::Error> {
+        use genpdf::Element as _;
+
+        let mut area = self.inner.decorate_page(context, area, style)?;
+        let page = self.navigation.page.get() + 1;
+        self.navigation.page.set(page);
+        let mut bottom = PAGE_HEIGHT_MM - PAGE_MARGIN_MM;
+        if let Some(footer) = self.branding.footer_line(page as usize) {
+            let height = area.size().height.0;
+            let mut footer_area = area.clone();
+            footer_area.add_offset(genpdf::Position::new(0, height - FOOTER_HEIGHT_MM));
+            genpdf::elements::Paragraph::new(footer)
+                .aligned(genpdf::Alignment::Center)
+                .styled(small_print())
+                .render(context, footer_area, style)?;
+            area.set_height(genpdf::Mm::from(height - FOOTER_HEIGHT_MM));
+            bottom -= FOOTER_HEIGHT_MM;
+        }
+        self.navigation.content_bottom.set(bottom);
+        Ok(area)
+    }
+}
+
+/// Records where its element is first drawn, under one or more keys.
+pub struct Anchor<E> {
+    element: E,
+    keys: Vec<AnchorKey>,
+    navigation: Rc<Navigation>,
+}
+
+impl<E: genpdf::Element> Anchor<E> {
+    pub fn new(element: E, key: AnchorKey, navigation: &Rc<Navigation>) -> Self {
+        Self::with_keys(element, vec![key], navigation)
+    }
+
+    pub fn with_keys(element: E, keys: Vec<AnchorKey>, navigation: &Rc<Navigation>) -> Self {
+        Self { element, keys, navigation: navigation.clone() }
+    }
+}
+
+impl<E: genpdf::Element> genpdf::Element for Anchor<E> {
+    fn render(
+        &mut self,
+        context: &genpdf::Context,
+        area: genpdf::render::Area<'_>,
+        style: genpdf::style::Style,
+    ) -> Result<genpdf::RenderResult, genpdf::error::Error> {
+        let nav = &self.navigation;
+        // Areas shrink from the top as the page fills
+        let top = nav.content_bottom.get() - area.size().height.0;
+        let result = self.element.render(context, area, style)?;
+        if result.size.height.0 > 0.0 {
+            let placement = Placement {
+                page: nav.page.get(),
+                top,
+                height: result.size.height.0,
+            };
+            let mut placements = nav.placements.borrow_mut();
+            for key in &self.keys {
+                placements.entry(key.clone()).or_insert(placement);
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// The segment a summary point is most likely about: the one sharing the
+/// most significant words with it.
+pub fn best_segment(point: &str, segments: &[Segment]) -> Option<i64> {
+    let words = significant_words(point);
+    segments
+        .iter()
+        .map(|s| (significant_words(&s.text).intersection(&words).count(), s.id))
+        .filter(|(shared, _)| *shared >= MIN_SHARED_WORDS)
+        // First segment on ties
+        .max_by_key(|(shared, id)| (*shared, std::cmp::Reverse(*id)))
+        .map(|(_, id)| id)
+}
+
+fn significant_words(text: &str) -> HashSet<String> {
+    fold_name(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_WORD_LEN)
+        .map(str::to_string)
+        .collect()
+}
+
+fn mm_to_pt(mm: f64) -> i64 {
+    (mm * 72.0 / 25.4).round() as i64
+}
+
+/// Destination showing a page from the top of a placement.
+fn destination(page: ObjectId, placement: &Placement) -> Object {
+    Object::Array(vec![
+        Object::Reference(page),
+        Object::Name(b"XYZ".to_vec()),
+        Object::Null,
+        Object::Integer(mm_to_pt(PAGE_HEIGHT_MM - placement.top + 2.0)),
+        Object::Null,
+    ])
+}
+
+/// PDF text string, UTF-16 so that accents and names survive.
+fn text_string(text: &str) -> Object {
+    let mut bytes = vec![0xFE, 0xFF];
+    bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+    Object::String(bytes, StringFormat::Hexadecimal)
+}
+
+fn page_id(pages: &std::collections::BTreeMap<u32, ObjectId>, page: u32) -> Result<ObjectId, String> {
+    pages
+        .get(&page)
+        .copied()
+        .ok_or_else(|| format!("Page {} introuvable dans le PDF", page))
+}
+
+fn add_links(
+    doc: &mut Document,
+    pages: &std::collections::BTreeMap<u32, ObjectId>,
+    links: &[(Placement, Placement)],
+) -> Result<(), String> {
+    for (from, to) in links {
+        let annotation = doc.add_object(dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![
+                Object::Integer(mm_to_pt(PAGE_MARGIN_MM)),
+                Object::Integer(mm_to_pt(PAGE_HEIGHT_MM - from.top - from.height)),
+                Object::Integer(mm_to_pt(PAGE_WIDTH_MM - PAGE_MARGIN_MM)),
+                Object::Integer(mm_to_pt(PAGE_HEIGHT_MM - from.top)),
+            ],
+            "Border" => vec![Object::Integer(0), Object::Integer(0), Object::Integer(0)],
+            "Dest" => destination(page_id(pages, to.page)?, to),
+        });
+        let page = doc
+            .get_object_mut(page_id(pages, from.page)?)
+            .and_then(Object::as_dict_mut)
+            .map_err(|e| e.to_string())?;
+        match page.get_mut(b"Annots") {
+            Ok(Object::Array(annotations)) => annotations.push(Object::Reference(annotation)),
+            _ => page.set("Annots", vec![Object::Reference(annotation)]),
+        }
+    }
+    Ok(())
+}
+
+/// Parent of each bookmark: the closest previous one of a lower level.
+fn outline_parents(levels: &[usize]) -> Vec<Option<usize>> {
+    let mut stack: Vec<usize> = Vec::new();
+    levels
+        .iter()
+        .enumerate()
+        .map(|(i, level)| {
+            while stack.last().is_some_and(|&p| levels[p] >= *level) {
+                stack.pop();
+            }
+            let parent = stack.last().copied();
+            stack.push(i);
+            parent
+        })
+        .collect()
+}
+
+fn add_outline(
+    doc: &mut Document,
+    pages: &std::collections::BTreeMap<u32, ObjectId>,
+    bookmarks: &[(Bookmark, Placement)],
+) -> Result<(), String> {
+    if bookmarks.is_empty() {
+        return Ok(());
+    }
+    let levels: Vec<usize> = bookmarks.iter().map(|(b, _)| b.level).collect();
+    let parents = outline_parents(&levels);
+    let root = doc.new_object_id();
+    let ids: Vec<ObjectId> = bookmarks.iter().map(|_| doc.new_object_id()).collect();
+    let children = |parent: Option<usize>| -> Vec<usize> {
+        (0..bookmarks.len()).filter(|&i| parents[i] == parent).collect()
+    };
+
+    for (i, (bookmark, placement)) in bookmarks.iter().enumerate() {
+        let siblings = children(parents[i]);
+        let position = siblings.iter().position(|&s| s == i).unwrap_or(0);
+        let mut item = dictionary! {
+            "Title" => text_string(&bookmark.title),
+            "Parent" => Object::Reference(parents[i].map_or(root, |p| ids[p])),
+            "Dest" => destination(page_id(pages, placement.page)?, placement),
+        };
+        if position > 0 {
+            item.set("Prev", Object::Reference(ids[siblings[position - 1]]));
+        }
+        if let Some(&next) = siblings.get(position + 1) {
+            item.set("Next", Object::Reference(ids[next]));
+        }
+        let own = children(Some(i));
+        if let (Some(&first), Some(&last)) = (own.first(), own.last()) {
+            item.set("First", Object::Reference(ids[first]));
+            item.set("Last", Object::Reference(ids[last]));
+            // Negative: collapsed
+            item.set("Count", -(own.len() as i64));
+        }
+        doc.objects.insert(ids[i], Object::Dictionary(item));
+    }
+
+    let top = children(None);
+    doc.objects.insert(
+        root,
+        Object::Dictionary(dictionary! {
+            "Type" => "Outlines",
+            "First" => Object::Reference(ids[top[0]]),
+            "Last" => Object::Reference(ids[top[top.len() - 1]]),
+            "Count" => top.len() as i64,
+        }),
+    );
+    let catalog = doc.catalog_mut().map_err(|e| e.to_string())?;
+    catalog.set("Outlines", Object::Reference(root));
+    catalog.set("PageMode", "UseOutlines");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(id: i64, text: &str) -> Segment {
+        Segment {
+            id,
+            session_id: "s".to_string(),
+            text: text.to_string(),
+            start_time: 0.0,
+            end_time: 1.0,
+            speaker: None,
+            is_diarized: false,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_best_segment() {
+        let segments = vec![
+            segment(1, "Bonjour a tous, on commence."),
+            segment(2, "Le budget marketing sera révisé en septembre."),
+            segment(3, "On revoit le budget plus tard."),
+        ];
+        assert_eq!(best_segment("Revue du budget marketing en septembre", &segments), Some(2));
+        // Accents are ignored
+        assert_eq!(best_segment("Revise en septembre", &segments), Some(2));
+        assert_eq!(best_segment("Recrutement d'un stagiaire", &segments), None);
+    }
+
+    #[test]
+    fn test_outline_parents() {
+        assert_eq!(
+            outline_parents(&[0, 1, 1, 0, 1, 2, 0]),
+            vec![None, Some(0), Some(0), None, Some(3), Some(4), None]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Password protection of exported PDFs, with the standard security handler
+//! (AES-128): the user password opens the document with restricted
+//! permissions, the owner password lifts the restrictions.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use lopdf::encryption::crypt_filters::{Aes128CryptFilter, CryptFilter};
+use lopdf::{Document, EncryptionState, EncryptionVersion, Permissions};
+use serde::Deserialize;
+
+/// Shortest accepted user password.
+pub const MIN_PASSWORD_LEN: usize = 4;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PdfProtection {
+    /// Needed to open the document.
+    pub user_password: String,
+    /// Lifts the restrictions. When not given, a random one is used, so
+    /// that they cannot be lifted.
+    #[serde(default)]
+    pub owner_password: Option<String>,
+    #[serde(default = "default_true")]
+    pub allow_print: bool,
+    /// Copying text out of the document.
+    #[serde(default)]
+    pub allow_copy: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl PdfProtection {
+    /// What a reader opening the document with the user password may do.
+    fn permissions(&self) -> Permissions {
+        // Screen readers are always allowed to extract the text
+        let mut permissions = Permissions::COPYABLE_FOR_ACCESSIBILITY;
+        if self.allow_print {
+            permissions |= Permissions::PRINTABLE | Permissions::PRINTABLE_IN_HIGH_QUALITY;
+        }
+        if self.allow_copy {
+            permissions |= Permissions::COPYABLE;
+        }
+        permissions
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.user_password.chars().count() < MIN_PASSWORD_LEN {
+            return Err(format!(
+                "Le mot de passe doit contenir au moins {} caracteres",
+                MIN_PASSWORD_LEN
+            ));
+        }
+        if self.owner_password.as_deref() == Some(self.user_password.as_str()) {
+            return Err("Le mot de passe proprietaire doit differer de celui d'ouverture".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Encrypts the PDF at `path` in place.
+pub fn protect_pdf(path: &Path, protection: &PdfProtection) -> Result<(), String> {
+    protection.validate()?;
+    let mut doc = Document::load(path).map_err(|e| format!("Lecture du PDF impossible: {}", e))?;
+    let owner_password = protection
+        .owner_password
+        .clone()
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+
+    let filter: Arc<dyn CryptFilter> = Arc::new(Aes128CryptFilter);
+    let version = EncryptionVersion::V4 {
+        document: &doc,
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), filter)]),
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: &owner_password,
+        user_password: &protection.user_password,
+        permissions: protection.permissions(),
+    };
+    let state = EncryptionState::try_from(version).map_err(|e| format!("Chiffrement du PDF impossible: {}", e))?;
+    doc.encrypt(&state)
+        .map_err(|e| format!("Chiffrement du PDF impossible: {}", e))?;
+    doc.save(path)
+        .map_err(|e| format!("Erreur ecriture PDF: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protection(user: &str, owner: Option<&str>) -> PdfProtection {
+        PdfProtection {
+            user_password: user.to_string(),
+            owner_password: owner.map(str::to_string),
+            allow_print: true,
+            allow_copy: false,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(protection("rh-2025", None).validate().is_ok());
+        assert!(protection("abc", None).validate().is_err());
+        assert!(protection("rh-2025", Some("rh-2025")).validate().is_err());
+    }
+
+    #[test]
+    #[cfg_attr(not(target_os = "macos"), ignore = "the PDF export uses the macOS system fonts")]
+    fn test_protect_exported_pdf() {
+        use std::collections::HashMap;
+
+        let segments = vec![crate::db::Segment {
+            id: 1,
+            session_id: "rh".to_string(),
+            text: "Entretien annuel".to_string(),
+            start_time: 0.0,
+            end_time: 2.0,
+            speaker: Some("Marie".to_string()),
+            is_diarized: true,
+            language: None,
+        }];
+        let export = crate::export::SessionExport {
+            session_id: None,
+            title: "Entretien",
+            date: "2025-03-01",
+            duration_secs: Some(2.0),
+            segments: &segments,
+            summary: &None,
+            attachments: &[],
+            participants: &[],
+            speaker_colors: &HashMap::new(),
+            annotate_languages: false,
+            translation: None,
+            paragraphs: false,
+            pdf: crate::export::PdfOptions::default(),
+            branding: &crate::export::branding::Branding::default(),
+        };
+        let path = std::env::temp_dir().join(format!("poptranscribe_protect_{}.pdf", std::process::id()));
+        crate::export::export_pdf(&export, &path).unwrap();
+
+        protect_pdf(&path, &protection("rh-2025", Some("direction"))).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.windows(8).any(|w| w == b"/Encrypt"));
+        let doc = Document::load_with_password(&path, "rh-2025").unwrap();
+        assert!(!doc.get_pages().is_empty());
+        assert!(Document::load_with_password(&path, "mauvais").is_err());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_permissions() {
+        let permissions = protection("rh-2025", None).permissions();
+        assert!(permissions.contains(Permissions::PRINTABLE));
+        assert!(!permissions.contains(Permissions::COPYABLE));
+        assert!(!permissions.contains(Permissions::MODIFIABLE));
+    }
+}
@@ -0,0 +1,260 @@
+//! Adapters for the transcript exports of other tools (Otter.ai, Whisper,
+//! Microsoft Teams), on top of the generic SRT/VTT/JSON parsers.
+
+use super::{parse_timestamp, ImportedSegment, ImportedTranscript};
+use crate::mistral::chat::{ActionItem, Summary};
+
+/// Speaking rate used to estimate how long the last Otter paragraph lasts.
+const WORDS_PER_SEC: f64 = 2.5;
+
+/// Teams splits a speaker's turn every sentence or so; cues closer than
+/// this are merged back together.
+const TEAMS_MERGE_GAP_SECS: f64 = 1.0;
+
+/// Upper bound on a merged Teams segment, to keep long turns navigable.
+const TEAMS_MAX_MERGED_SECS: f64 = 30.0;
+
+/// Otter paragraph header: the speaker, two spaces, then `M:SS` or `H:MM:SS`.
+fn otter_header(line: &str) -> Option<(String, f64)> {
+    let (name, time) = line.trim_end().rsplit_once("  ")?;
+    let name = name.trim();
+    if name.is_empty() || name.len() > 60 || !time.trim().contains(':') {
+        return None;
+    }
+    Some((name.to_string(), parse_timestamp(time)?))
+}
+
+/// Parses an Otter.ai text export. Sections placed before the transcript
+/// (Summary, Outline, Action Items) become the imported summary.
+pub(super) fn parse_otter_txt(content: &str) -> ImportedTranscript {
+    let content = content.replace("\r\n", "\n");
+    let mut preamble: Vec<&str> = Vec::new();
+    let mut paragraphs: Vec<(String, f64, Vec<&str>)> = Vec::new();
+
+    for line in content.lines() {
+        if let Some((speaker, start)) = otter_header(line) {
+            paragraphs.push((speaker, start, Vec::new()));
+        } else if let Some((_, _, text)) = paragraphs.last_mut() {
+            if !line.trim().is_empty() {
+                text.push(line.trim());
+            }
+        } else {
+            preamble.push(line);
+        }
+    }
+
+    let starts: Vec<f64> = paragraphs.iter().map(|(_, start, _)| *start).collect();
+    let segments = paragraphs
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (_, _, text))| !text.is_empty())
+        .map(|(i, (speaker, start, text))| {
+            let text = text.join(" ");
+            // Each paragraph lasts until the next one starts
+            let end = match starts.get(i + 1) {
+                Some(next) => next.max(start),
+                None => start + text.split_whitespace().count() as f64 / WORDS_PER_SEC,
+            };
+            ImportedSegment {
+                text,
+                start,
+                end,
+                speaker: Some(speaker),
+            }
+        })
+        .collect();
+
+    ImportedTranscript {
+        summary: parse_otter_summary(&preamble),
+        segments,
+        ..Default::default()
+    }
+}
+
+fn parse_otter_summary(lines: &[&str]) -> Option<Summary> {
+    enum Section {
+        Ignored,
+        KeyPoints,
+        ActionItems,
+    }
+
+    let mut section = Section::Ignored;
+    let mut key_points = Vec::new();
+    let mut action_items = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match line.trim_end_matches(':').to_lowercase().as_str() {
+            "summary" | "outline" | "key points" => {
+                section = Section::KeyPoints;
+                continue;
+            }
+            "action items" => {
+                section = Section::ActionItems;
+                continue;
+            }
+            "keywords" | "transcript" => {
+                section = Section::Ignored;
+                continue;
+            }
+            _ => {}
+        }
+        let item = ["- [ ] ", "- [x] ", "- ", "• ", "* "]
+            .iter()
+            .find_map(|bullet| line.strip_prefix(bullet))
+            .unwrap_or(line)
+            .trim()
+            .to_string();
+        match section {
+            Section::KeyPoints => key_points.push(item),
+            Section::ActionItems => action_items.push(ActionItem {
+                description: item,
+                assignee: None,
+            }),
+            Section::Ignored => {}
+        }
+    }
+
+    if key_points.is_empty() && action_items.is_empty() {
+        return None;
+    }
+    Some(Summary {
+        key_points,
+        decisions: Vec::new(),
+        action_items,
+        topics: Vec::new(),
+    })
+}
+
+/// Whisper output has a top-level `text` next to `segments`, plus a
+/// `language` or per-segment decoding statistics.
+pub(super) fn looks_like_whisper(content: &str) -> bool {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(content) else {
+        return false;
+    };
+    let first_segment = value
+        .get("segments")
+        .and_then(|s| s.as_array())
+        .and_then(|s| s.first());
+    value.get("text").map(|t| t.is_string()).unwrap_or(false)
+        && first_segment.is_some()
+        && (value.get("language").is_some()
+            || first_segment
+                .map(|s| s.get("avg_logprob").is_some() || s.get("tokens").is_some())
+                .unwrap_or(false))
+}
+
+/// Language declared by Whisper, either a code (`fr`) or, from the API, an
+/// English name (`french`).
+pub(super) fn whisper_language(content: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    let language = value.get("language")?.as_str()?.trim().to_lowercase();
+    if language.len() == 2 && language.chars().all(|c| c.is_ascii_lowercase()) {
+        return Some(language);
+    }
+    let code = match language.as_str() {
+        "french" => "fr",
+        "english" => "en",
+        "german" => "de",
+        "spanish" => "es",
+        "italian" => "it",
+        "portuguese" => "pt",
+        "dutch" => "nl",
+        _ => return None,
+    };
+    Some(code.to_string())
+}
+
+/// Teams cue identifiers look like `<guid>/<n>-<m>`.
+fn is_teams_cue_id(line: &str) -> bool {
+    let Some((_, counter)) = line.trim().rsplit_once('/') else {
+        return false;
+    };
+    match counter.split_once('-') {
+        Some((a, b)) => {
+            !a.is_empty()
+                && !b.is_empty()
+                && a.chars().all(|c| c.is_ascii_digit())
+                && b.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+pub(super) fn looks_like_teams(content: &str) -> bool {
+    content.contains("<v ") && content.lines().any(is_teams_cue_id)
+}
+
+/// Joins consecutive cues of the same speaker separated by short gaps.
+pub(super) fn merge_teams_cues(cues: Vec<ImportedSegment>) -> Vec<ImportedSegment> {
+    let mut merged: Vec<ImportedSegment> = Vec::with_capacity(cues.len());
+    for cue in cues {
+        if let Some(last) = merged.last_mut() {
+            if last.speaker.is_some()
+                && last.speaker == cue.speaker
+                && cue.start - last.end <= TEAMS_MERGE_GAP_SECS
+                && cue.end - last.start <= TEAMS_MAX_MERGED_SECS
+            {
+                last.text.push(' ');
+                last.text.push_str(&cue.text);
+                last.end = cue.end;
+                continue;
+            }
+        }
+        merged.push(cue);
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{parse_transcript, TranscriptFormat};
+
+    #[test]
+    fn test_parse_otter_txt() {
+        let txt = "Summary\nBudget valide pour 2025.\n\nAction Items\n- [ ] Envoyer le devis\n\n\
+                   Marie Dupont  0:00\nBonjour a tous.\nOn commence.\n\n\
+                   Speaker 2  0:12\nTres bien, allons-y.\n";
+        let transcript = parse_transcript(txt, TranscriptFormat::OtterTxt).unwrap();
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[0].speaker.as_deref(), Some("Marie Dupont"));
+        assert_eq!(transcript.segments[0].text, "Bonjour a tous. On commence.");
+        assert_eq!(transcript.segments[0].end, 12.0);
+        assert_eq!(transcript.segments[1].end, 12.0 + 3.0 / WORDS_PER_SEC);
+
+        let summary = transcript.summary.unwrap();
+        assert_eq!(summary.key_points, vec!["Budget valide pour 2025."]);
+        assert_eq!(summary.action_items[0].description, "Envoyer le devis");
+    }
+
+    #[test]
+    fn test_whisper_json() {
+        let json = r#"{"text": " Bonjour.", "language": "french",
+            "segments": [{"id": 0, "start": 0.0, "end": 1.2, "text": " Bonjour.", "avg_logprob": -0.2}]}"#;
+        assert!(looks_like_whisper(json));
+        assert!(!looks_like_whisper(r#"{"segments": [{"text": "a"}]}"#));
+
+        let transcript = parse_transcript(json, TranscriptFormat::WhisperJson).unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("fr"));
+        assert_eq!(transcript.segments[0].text, "Bonjour.");
+    }
+
+    #[test]
+    fn test_teams_vtt() {
+        let vtt = "WEBVTT\n\n\
+                   5c1a2b3c-aaaa/12-0\n00:00:01.000 --> 00:00:03.000\n<v Paul Martin>On valide le devis</v>\n\n\
+                   5c1a2b3c-aaaa/12-1\n00:00:03.200 --> 00:00:05.000\n<v Paul Martin>avant vendredi.</v>\n\n\
+                   5c1a2b3c-aaaa/13-0\n00:00:05.500 --> 00:00:06.000\n<v Lea Bernard>OK.</v>\n";
+        assert!(looks_like_teams(vtt));
+        assert!(!looks_like_teams("WEBVTT\n\n00:01.000 --> 00:02.000\nBonjour\n"));
+
+        let transcript = parse_transcript(vtt, TranscriptFormat::TeamsVtt).unwrap();
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[0].text, "On valide le devis avant vendredi.");
+        assert_eq!(transcript.segments[0].end, 5.0);
+        assert_eq!(transcript.segments[1].speaker.as_deref(), Some("Lea Bernard"));
+    }
+}
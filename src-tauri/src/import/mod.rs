@@ -0,0 +1,315 @@
+mod adapters;
+
+use crate::mistral::chat::Summary;
+
+/// A transcript line read from another tool's export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedSegment {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+    pub speaker: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportedTranscript {
+    /// Title found in the file itself, if any.
+    pub title: Option<String>,
+    /// ISO 639-1 code declared by the file (Whisper).
+    pub language: Option<String>,
+    /// Summary exported alongside the transcript (Otter).
+    pub summary: Option<Summary>,
+    pub segments: Vec<ImportedSegment>,
+}
+
+impl ImportedTranscript {
+    pub fn duration_secs(&self) -> f64 {
+        self.segments.iter().map(|s| s.end).fold(0.0, f64::max)
+    }
+}
+
+/// Transcript formats accepted by `import_transcript`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Srt,
+    Vtt,
+    Json,
+    /// Otter.ai text export: `Name  0:15` headers followed by paragraphs.
+    OtterTxt,
+    /// OpenAI Whisper / WhisperX JSON output.
+    WhisperJson,
+    /// Microsoft Teams meeting transcript (WebVTT with voice tags).
+    TeamsVtt,
+}
+
+impl TranscriptFormat {
+    /// Parse a format name as used in command arguments.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "srt" => Some(Self::Srt),
+            "vtt" | "webvtt" => Some(Self::Vtt),
+            "json" => Some(Self::Json),
+            "otter" => Some(Self::OtterTxt),
+            "whisper" => Some(Self::WhisperJson),
+            "teams" => Some(Self::TeamsVtt),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from a file extension.
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("srt") => Some(Self::Srt),
+            Some("vtt") => Some(Self::Vtt),
+            Some("json") => Some(Self::Json),
+            Some("txt") => Some(Self::OtterTxt),
+            _ => None,
+        }
+    }
+
+    /// Like `from_path`, then recognizes Whisper JSON and Teams VTT from
+    /// their content.
+    pub fn detect(path: &std::path::Path, content: &str) -> Option<Self> {
+        match Self::from_path(path)? {
+            Self::Json if adapters::looks_like_whisper(content) => Some(Self::WhisperJson),
+            Self::Vtt if adapters::looks_like_teams(content) => Some(Self::TeamsVtt),
+            format => Some(format),
+        }
+    }
+}
+
+/// Parses the content of a transcript file. Fails when no segment is found.
+pub fn parse_transcript(content: &str, format: TranscriptFormat) -> Result<ImportedTranscript, String> {
+    let transcript = match format {
+        TranscriptFormat::Srt | TranscriptFormat::Vtt => ImportedTranscript {
+            segments: parse_cues(content),
+            ..Default::default()
+        },
+        TranscriptFormat::Json => parse_json(content)?,
+        TranscriptFormat::OtterTxt => adapters::parse_otter_txt(content),
+        TranscriptFormat::WhisperJson => ImportedTranscript {
+            language: adapters::whisper_language(content),
+            ..parse_json(content)?
+        },
+        TranscriptFormat::TeamsVtt => ImportedTranscript {
+            segments: adapters::merge_teams_cues(parse_cues(content)),
+            ..Default::default()
+        },
+    };
+    if transcript.segments.is_empty() {
+        return Err("Aucun segment trouve dans le fichier".to_string());
+    }
+    Ok(transcript)
+}
+
+/// Parses `HH:MM:SS,mmm`, `HH:MM:SS.mmm` or `MM:SS.mmm` into seconds.
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let parts: Vec<&str> = value.trim().split(':').collect();
+    if parts.len() < 2 || parts.len() > 3 {
+        return None;
+    }
+    let seconds: f64 = parts.last()?.replace(',', ".").parse().ok()?;
+    let mut total = seconds;
+    for (i, part) in parts[..parts.len() - 1].iter().rev().enumerate() {
+        let n: u64 = part.parse().ok()?;
+        total += n as f64 * 60f64.powi(i as i32 + 1);
+    }
+    Some(total)
+}
+
+/// SRT and WebVTT share the same cue layout: an optional identifier, a
+/// `start --> end` line, then the text lines up to a blank line. Blocks
+/// without a timing line (WEBVTT header, NOTE, STYLE) are skipped.
+fn parse_cues(content: &str) -> Vec<ImportedSegment> {
+    let content = content.replace("\r\n", "\n");
+    let mut segments = Vec::new();
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| !l.contains("-->"));
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+        let mut bounds = timing.split("-->");
+        let start = bounds.next().and_then(parse_timestamp);
+        // VTT cue settings may follow the end time
+        let end = bounds
+            .next()
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(parse_timestamp);
+        let (Some(start), Some(end)) = (start, end) else {
+            continue;
+        };
+
+        let raw = lines.map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
+        let (speaker, text) = split_speaker(&raw);
+        let text = strip_tags(&text);
+        if text.is_empty() {
+            continue;
+        }
+        segments.push(ImportedSegment { text, start, end, speaker });
+    }
+    segments
+}
+
+/// Extracts the speaker of a cue: a WebVTT voice tag (`<v Name>`), a
+/// `[Name]` prefix or a short `Name: ` prefix.
+fn split_speaker(text: &str) -> (Option<String>, String) {
+    if let Some(rest) = text.strip_prefix("<v") {
+        if let Some(close) = rest.find('>') {
+            // `<v.class Name>`: the name follows the first space
+            let name = rest[..close].split_once(' ').map(|(_, n)| n.trim()).unwrap_or("");
+            let body = rest[close + 1..].replace("</v>", "");
+            let speaker = (!name.is_empty()).then(|| name.to_string());
+            return (speaker, body.trim().to_string());
+        }
+    }
+    if let Some(rest) = text.strip_prefix('[') {
+        if let Some((name, body)) = rest.split_once(']') {
+            if !name.trim().is_empty() && name.len() <= 40 {
+                return (Some(name.trim().to_string()), body.trim().to_string());
+            }
+        }
+    }
+    if let Some((name, body)) = text.split_once(": ") {
+        let looks_like_name = name.len() <= 40
+            && name.split_whitespace().count() <= 3
+            && name.chars().next().map(|c| c.is_uppercase()).unwrap_or(false)
+            && !name.chars().any(|c| c.is_ascii_digit() || ".,;!?<>".contains(c));
+        if looks_like_name {
+            return (Some(name.trim().to_string()), body.trim().to_string());
+        }
+    }
+    (None, text.trim().to_string())
+}
+
+/// Removes inline markup such as `<i>`, `<c.yellow>` or `<00:00:01.000>`.
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Accepts either an array of segments or an object with a `segments`
+/// array and an optional `title`. Segment fields follow the common
+/// spellings: `start`/`start_time`, `end`/`end_time`, `speaker`/`speaker_id`.
+fn parse_json(content: &str) -> Result<ImportedTranscript, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("JSON invalide: {}", e))?;
+    let (title, items) = match &value {
+        serde_json::Value::Array(items) => (None, items.as_slice()),
+        serde_json::Value::Object(map) => (
+            map.get("title").and_then(|t| t.as_str()).map(str::to_string),
+            map.get("segments")
+                .and_then(|s| s.as_array())
+                .map(|s| s.as_slice())
+                .ok_or("JSON invalide: tableau \"segments\" manquant")?,
+        ),
+        _ => return Err("JSON invalide: objet ou tableau attendu".to_string()),
+    };
+
+    let field = |item: &serde_json::Value, names: &[&str]| {
+        names.iter().find_map(|n| item.get(*n)).cloned()
+    };
+    let time = |v: Option<serde_json::Value>| match v {
+        Some(serde_json::Value::Number(n)) => n.as_f64(),
+        Some(serde_json::Value::String(s)) => s.parse().ok().or_else(|| parse_timestamp(&s)),
+        _ => None,
+    };
+
+    let mut segments = Vec::new();
+    for item in items {
+        let Some(text) = field(item, &["text"]).and_then(|t| t.as_str().map(|s| s.trim().to_string())) else {
+            continue;
+        };
+        if text.is_empty() {
+            continue;
+        }
+        let start = time(field(item, &["start", "start_time"])).unwrap_or(0.0);
+        let end = time(field(item, &["end", "end_time"])).unwrap_or(start);
+        let speaker = match field(item, &["speaker", "speaker_id", "speaker_name"]) {
+            Some(serde_json::Value::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            Some(serde_json::Value::Number(n)) => Some(format!("speaker_{}", n)),
+            _ => None,
+        };
+        segments.push(ImportedSegment { text, start, end, speaker });
+    }
+    Ok(ImportedTranscript {
+        title,
+        segments,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("00:00:01,500"), Some(1.5));
+        assert_eq!(parse_timestamp("01:02:03.250"), Some(3723.25));
+        assert_eq!(parse_timestamp("02:03.000"), Some(123.0));
+        assert_eq!(parse_timestamp("abc"), None);
+    }
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "1\r\n00:00:01,000 --> 00:00:04,000\r\nMarie: Bonjour a tous.\r\n\r\n\
+                   2\r\n00:00:04,500 --> 00:00:06,000\r\n<i>On commence</i>\r\npar le budget.\r\n";
+        let transcript = parse_transcript(srt, TranscriptFormat::Srt).unwrap();
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[0].speaker.as_deref(), Some("Marie"));
+        assert_eq!(transcript.segments[0].text, "Bonjour a tous.");
+        assert_eq!(transcript.segments[1].speaker, None);
+        assert_eq!(transcript.segments[1].text, "On commence par le budget.");
+        assert_eq!(transcript.duration_secs(), 6.0);
+    }
+
+    #[test]
+    fn test_parse_vtt() {
+        let vtt = "WEBVTT\n\nNOTE exported\n\ncue-1\n00:01.000 --> 00:03.000 align:start\n\
+                   <v Paul Martin>Le devis est valide.</v>\n\n00:03.000 --> 00:05.000\n[Lea] D'accord.\n";
+        let transcript = parse_transcript(vtt, TranscriptFormat::Vtt).unwrap();
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[0].speaker.as_deref(), Some("Paul Martin"));
+        assert_eq!(transcript.segments[0].text, "Le devis est valide.");
+        assert_eq!(transcript.segments[0].start, 1.0);
+        assert_eq!(transcript.segments[1].speaker.as_deref(), Some("Lea"));
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let json = r#"{"title": "Comite", "segments": [
+            {"text": "Bonjour", "start": 0.5, "end": 1.5, "speaker": "Marie"},
+            {"text": "Salut", "start_time": "00:00:02.000", "end_time": 3, "speaker_id": 1},
+            {"text": "  "}
+        ]}"#;
+        let transcript = parse_transcript(json, TranscriptFormat::Json).unwrap();
+        assert_eq!(transcript.title.as_deref(), Some("Comite"));
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[1].start, 2.0);
+        assert_eq!(transcript.segments[1].speaker.as_deref(), Some("speaker_1"));
+
+        assert!(parse_transcript("[]", TranscriptFormat::Json).is_err());
+        assert!(parse_transcript("{", TranscriptFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_no_false_speaker() {
+        let (speaker, text) = split_speaker("Point 3: le budget");
+        assert_eq!(speaker, None);
+        assert_eq!(text, "Point 3: le budget");
+    }
+}
@@ -0,0 +1,70 @@
+//! Lightweight language detection for transcript segments, used when the
+//! provider does not label them (code-switching French/English meetings).
+
+/// Frequent function words per language; short segments rarely contain
+/// anything else that is discriminating.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "fr",
+        &[
+            "le", "la", "les", "un", "une", "des", "et", "est", "que", "qui", "pour", "dans",
+            "pas", "on", "je", "nous", "vous", "ce", "cette", "avec", "sur", "au", "du", "mais",
+            "alors", "donc", "c'est", "il", "elle", "ils", "oui", "non", "tres", "très",
+        ],
+    ),
+    (
+        "en",
+        &[
+            "the", "a", "an", "and", "is", "are", "that", "this", "for", "in", "of", "to",
+            "not", "we", "you", "i", "it", "with", "on", "but", "so", "what", "have", "do",
+            "yes", "no", "very", "it's", "they", "be", "will",
+        ],
+    ),
+];
+
+/// Minimum number of stopword hits before a language is reported.
+const MIN_HITS: usize = 2;
+
+/// Best-guess language code of `text`, or `None` when too short or ambiguous.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let words: Vec<String> = text
+        .split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’'))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase().replace('’', "'"))
+        .collect();
+
+    let mut best: Option<(&'static str, usize)> = None;
+    let mut tie = false;
+    for (code, stopwords) in STOPWORDS {
+        let hits = words.iter().filter(|w| stopwords.contains(&w.as_str())).count();
+        match best {
+            Some((_, best_hits)) if hits == best_hits => tie = true,
+            Some((_, best_hits)) if hits < best_hits => {}
+            _ => {
+                best = Some((*code, hits));
+                tie = false;
+            }
+        }
+    }
+    match best {
+        Some((code, hits)) if hits >= MIN_HITS && !tie => Some(code),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_french_and_english() {
+        assert_eq!(detect_language("Alors on valide le budget pour la semaine"), Some("fr"));
+        assert_eq!(detect_language("So we need to ship this for the release"), Some("en"));
+    }
+
+    #[test]
+    fn test_detect_unknown() {
+        assert_eq!(detect_language("OK"), None);
+        assert_eq!(detect_language(""), None);
+    }
+}
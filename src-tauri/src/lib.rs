@@ -2,187 +2,560 @@ pub mod audio;
 pub mod mistral;
 pub mod db;
 pub mod export;
+pub mod import;
 pub mod commands;
 pub mod app_state;
+pub mod passcode;
+pub mod language;
+pub mod redact;
+pub mod profanity;
+pub mod cleanup;
+pub mod punctuation;
+pub mod disfluency;
+pub mod compare;
+pub mod deeplink;
+pub mod sync;
+pub mod backup;
+pub mod email;
+pub mod watch;
+pub mod scheduler;
+pub mod power;
+pub mod permissions;
+pub mod upload;
+#[cfg(desktop)]
+pub mod autostart;
 
 use app_state::AppState;
 use db::Database;
-use tauri::{
-    menu::{AboutMetadataBuilder, MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Emitter, Manager, WindowEvent,
-};
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let db_path = dirs::data_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("poptranscribe")
-        .join("poptranscribe.db");
-
-    // Create parent directory if needed
-    if let Some(parent) = db_path.parent() {
-        std::fs::create_dir_all(parent).ok();
-    }
-
-    let db = Database::new(&db_path).expect("Failed to open database");
+    let builder = tauri::Builder::default();
+    // In-app updates and the tray exist on desktop only
+    #[cfg(desktop)]
+    let builder = builder
+        // First, so that a second launch exits before `setup` opens the
+        // database. Links it was given reach the deep link handler; files
+        // are imported.
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            show_main_window(app);
+            let cwd = std::path::Path::new(&cwd);
+            import_dropped_files(app, args.iter().skip(1).map(|arg| cwd.join(arg)).collect(), false);
+        }))
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(autostart::plugin())
+        .on_window_event(|window, event| match event {
+            // Intercept main window close: hide instead of quitting so the app stays in the tray
+            tauri::WindowEvent::CloseRequested { api, .. } => {
+                if window.label() == "main" {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                import_dropped_files(window.app_handle(), paths.clone(), true);
+            }
+            _ => {}
+        });
 
-    // Load API key from settings
-    let api_key = db.get_setting("api_key").ok().flatten().unwrap_or_default();
-    let state = AppState::new(db);
-    *state.api_key.lock().unwrap() = api_key;
+    // Runtime microphone permission, which Android does not ask for itself
+    #[cfg(target_os = "android")]
+    let builder = builder.plugin(tauri_plugin_microphone::init());
 
-    tauri::Builder::default()
+    builder
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_process::init())
-        .plugin(tauri_plugin_updater::Builder::new().build())
-        .manage(state)
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
         .invoke_handler(tauri::generate_handler![
             commands::start_session,
             commands::stop_session,
+            commands::open_mini_recorder,
+            commands::pause_session,
+            commands::resume_session,
             commands::get_sessions,
+            commands::list_sessions_by_topic,
+            commands::list_topics,
+            commands::get_full_transcript,
+            commands::translate_session,
+            commands::get_translated_transcript,
+            commands::list_translation_languages,
             commands::get_session_detail,
+            commands::get_statistics,
             commands::search_text,
             commands::search_llm,
             commands::rename_speaker,
+            commands::set_segment_speaker,
+            commands::get_speaker_colors,
+            commands::set_speaker_color,
+            commands::list_replacements,
+            commands::set_replacement,
+            commands::delete_replacement,
+            commands::apply_replacements,
+            commands::polish_transcript,
+            commands::list_transcript_versions,
+            commands::adopt_transcript_version,
+            commands::compare_transcript_versions,
+            commands::list_speaker_profiles,
+            commands::suggest_speakers,
+            commands::get_session_participants,
+            commands::update_speaker_profile,
+            commands::regenerate_summary,
+            commands::propose_speaker_mapping,
+            commands::create_segment,
+            commands::delete_segment,
+            commands::update_segment_text,
+            commands::get_segment_revisions,
+            commands::revert_segment_revision,
+            commands::undo_last_operation,
+            commands::shift_timestamps,
             commands::export_session,
+            commands::export_protected_pdf,
+            commands::export_bilingual,
+            commands::redact_session,
+            commands::export_audio,
+            commands::generate_weekly_digest,
+            commands::list_email_templates,
+            commands::save_email_template,
+            commands::delete_email_template,
+            commands::render_follow_up_email,
             commands::update_session_title,
             commands::delete_session,
+            commands::delete_session_audio,
+            commands::import_transcript,
+            commands::bulk_import,
+            commands::duplicate_session,
+            commands::archive_session,
+            commands::unarchive_session,
+            commands::archive_old_audio,
+            commands::attach_file,
+            commands::list_attachments,
+            commands::remove_attachment,
             commands::get_api_key,
             commands::set_api_key,
             commands::get_setting,
             commands::set_setting,
+            commands::get_lock_status,
+            commands::set_passcode,
+            commands::unlock_sessions,
+            commands::lock_sessions,
+            commands::set_session_protected,
             commands::list_input_devices,
+            commands::test_microphone,
+            commands::list_audio_applications,
+            commands::run_health_check,
+            commands::maintain_database,
+            commands::check_database,
+            commands::get_data_dir,
+            commands::move_data_dir,
+            commands::archive_to_s3,
+            commands::estimate_processing_cost,
+            commands::check_permissions,
+            commands::request_permissions,
+            commands::create_scheduled_recording,
+            commands::list_scheduled_recordings,
+            commands::delete_scheduled_recording,
+            commands::backup_now,
+            commands::get_backup_key,
+            commands::restore_backup,
+            commands::sync_sessions,
+            commands::take_pending_deep_link,
             commands::pick_folder,
         ])
         .setup(|app| {
-            // --- Application menu bar ---
-            let about = PredefinedMenuItem::about(app, Some("A propos de PopTranscribe"), Some(
-                AboutMetadataBuilder::new()
-                    .name(Some("PopTranscribe"))
-                    .version(Some("0.2.0"))
-                    .build()
-            ))?;
-            let settings_item = MenuItemBuilder::with_id("settings", "Parametres...")
-                .accelerator("CmdOrCtrl+,")
-                .build(app)?;
-            let separator = PredefinedMenuItem::separator(app)?;
-            let quit_menu = PredefinedMenuItem::quit(app, Some("Quitter PopTranscribe"))?;
+            // Opened here rather than before the builder, once the single
+            // instance plugin has exited a second launch: recovering the
+            // partial recordings of a running session would break it.
+            let db_path = app_state::data_dir().join("poptranscribe.db");
 
-            #[cfg(target_os = "macos")]
-            let app_submenu = {
-                let hide = PredefinedMenuItem::hide(app, Some("Masquer PopTranscribe"))?;
-                let hide_others = PredefinedMenuItem::hide_others(app, Some("Masquer les autres"))?;
-                let show_all = PredefinedMenuItem::show_all(app, Some("Tout afficher"))?;
-
-                SubmenuBuilder::new(app, "PopTranscribe")
-                    .item(&about)
-                    .separator()
-                    .item(&settings_item)
-                    .item(&separator)
-                    .item(&hide)
-                    .item(&hide_others)
-                    .item(&show_all)
-                    .separator()
-                    .item(&quit_menu)
-                    .build()?
-            };
-
-            #[cfg(not(target_os = "macos"))]
-            let app_submenu = SubmenuBuilder::new(app, "PopTranscribe")
-                .item(&about)
-                .separator()
-                .item(&settings_item)
-                .item(&separator)
-                .item(&quit_menu)
-                .build()?;
+            // Create parent directory if needed
+            if let Some(parent) = db_path.parent() {
+                std::fs::create_dir_all(parent).ok();
+            }
 
-            #[cfg(target_os = "macos")]
-            let edit_submenu = SubmenuBuilder::new(app, "Edition")
-                .undo()
-                .redo()
-                .separator()
-                .cut()
-                .copy()
-                .paste()
-                .select_all()
-                .build()?;
-
-            #[cfg(not(target_os = "macos"))]
-            let edit_submenu = SubmenuBuilder::new(app, "Edition")
-                .cut()
-                .copy()
-                .paste()
-                .select_all()
-                .build()?;
-
-            let app_menu = MenuBuilder::new(app)
-                .item(&app_submenu)
-                .item(&edit_submenu)
-                .build()?;
-
-            app.set_menu(app_menu)?;
-
-            // Handle menu events (settings)
-            app.on_menu_event(move |app_handle, event| {
-                if event.id().as_ref() == "settings" {
-                    let _ = app_handle.emit("open-settings", ());
+            let db = Database::new(&db_path).expect("Failed to open database");
+
+            // Recordings interrupted by a crash, kept by the autosave
+            let recovered = audio::autosave::recover_partial_recordings(
+                &db,
+                &app_state::data_dir().join("audio"),
+            );
+            if !recovered.is_empty() {
+                eprintln!("[autosave] {} interrupted recording(s) recovered", recovered.len());
+            }
+
+            // Load API key from settings
+            let api_key = db.get_setting("api_key").ok().flatten().unwrap_or_default();
+            let state = AppState::new(db);
+            *state.api_key.lock().unwrap() = api_key;
+            app.manage(state);
+
+            #[cfg(desktop)]
+            setup_desktop(app)?;
+
+            // --- poptranscribe:// links ---
+            deeplink::listen(app.handle());
+
+            // --- Audio files the app was opened with ---
+            #[cfg(desktop)]
+            import_dropped_files(app.handle(), std::env::args_os().skip(1).map(Into::into).collect(), false);
+
+            // --- Watch folder auto-import ---
+            if let Err(e) = watch::restart(app.handle()) {
+                eprintln!("[watch] {}", e);
+            }
+
+            // --- Sync through a shared folder, when configured ---
+            let app_sync = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let enabled = app_sync
+                        .state::<AppState>()
+                        .db
+                        .lock()
+                        .map(|db| sync::folder(&db).is_some())
+                        .unwrap_or(false);
+                    if enabled {
+                        if let Err(e) = commands::run_sync(&app_sync).await {
+                            eprintln!("[sync] {}", e);
+                        }
+                    }
+                    tokio::time::sleep(std::time::Duration::from_secs(sync::SYNC_INTERVAL_SECS)).await;
                 }
             });
 
-            // --- System tray ---
-            let open_item = MenuItemBuilder::with_id("open", "Ouvrir PopTranscribe").build(app)?;
-            let quit_item = MenuItemBuilder::with_id("quit", "Quitter").build(app)?;
-            let tray_menu = MenuBuilder::new(app)
-                .items(&[&open_item])
-                .separator()
-                .items(&[&quit_item])
-                .build()?;
-
-            // Create the system tray icon
-            let _tray = TrayIconBuilder::new()
-                .icon(app.default_window_icon().unwrap().clone())
-                .menu(&tray_menu)
-                .on_menu_event(|app, event| match event.id().as_ref() {
-                    "open" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.unminimize();
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
+            // --- Scheduled recordings ---
+            tauri::async_runtime::spawn(scheduler::run(app.handle().clone()));
+
+            // --- Monthly database maintenance, when enabled ---
+            let app_maintenance = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+                    let state = app_maintenance.state::<AppState>();
+                    let recording = state.active_session.lock().map(|a| a.is_some()).unwrap_or(true);
+                    let due = state.db.lock().map(|db| commands::maintenance_due(&db)).unwrap_or(false);
+                    if due && !recording {
+                        let db = std::sync::Arc::clone(&state.db);
+                        let _ = tokio::task::spawn_blocking(move || commands::run_maintenance(&db)).await;
+                    }
+                }
+            });
+
+            // --- Background audio archival (daily) ---
+            let db_archive = std::sync::Arc::clone(&app.state::<AppState>().db);
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    match audio::archive::archive_old_audio(&db_archive).await {
+                        Ok(0) => {}
+                        Ok(n) => eprintln!("[archive] {} recording(s) archived", n),
+                        Err(e) => eprintln!("[archive] Archival failed: {}", e),
                     }
-                    "quit" => {
-                        app.exit(0);
+                    match commands::archive_old_sessions_to_s3(&db_archive).await {
+                        Ok(0) => {}
+                        Ok(n) => eprintln!("[s3] {} session(s) archived to S3", n),
+                        Err(e) => eprintln!("[s3] Archival failed: {}", e),
                     }
-                    _ => {}
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.unminimize();
-                            let _ = window.show();
-                            let _ = window.set_focus();
+                    let backup_due = db_archive.lock().map(|db| backup::is_due(&db)).unwrap_or(false);
+                    if backup_due {
+                        if let Err(e) = commands::run_backup(&db_archive).await {
+                            eprintln!("[backup] Backup failed: {}", e);
                         }
                     }
-                })
-                .build(app)?;
+                    tokio::time::sleep(std::time::Duration::from_secs(24 * 3600)).await;
+                }
+            });
 
             Ok(())
         })
-        .on_window_event(|window, event| {
-            // Intercept window close: hide instead of quitting so the app stays in the tray
-            if let WindowEvent::CloseRequested { api, .. } = event {
-                api.prevent_close();
-                let _ = window.hide();
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, _event| {
+            // Files dropped on the dock icon, or opened with the app
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                let paths: Vec<std::path::PathBuf> = urls.iter().filter_map(|u| u.to_file_path().ok()).collect();
+                if !paths.is_empty() {
+                    import_dropped_files(_app, paths, true);
+                }
+            }
+        });
+}
+
+/// Audio files dropped on the window or the dock icon, or given on the
+/// command line: imported and transcribed in the background, like a bulk
+/// import, with `bulk-import-progress` events. Other paths are ignored,
+/// with an error when `report_none` is set.
+#[cfg(desktop)]
+fn import_dropped_files(app: &tauri::AppHandle, paths: Vec<std::path::PathBuf>, report_none: bool) {
+    use tauri::Emitter;
+
+    let files: Vec<std::path::PathBuf> = paths
+        .into_iter()
+        .filter(|p| p.is_file() && commands::is_importable_audio(p))
+        .collect();
+    if files.is_empty() {
+        if report_none {
+            let _ = app.emit("session-error", "Aucun fichier audio parmi les fichiers deposes");
+        }
+        return;
+    }
+    // Copying large recordings must not block the event loop
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || match commands::queue_audio_imports(&app, &files) {
+        Ok(session_ids) => {
+            let _ = app.emit("audio-files-imported", &session_ids);
+        }
+        Err(e) => {
+            let _ = app.emit("session-error", format!("Import: {}", e));
+        }
+    });
+}
+
+/// Menu bar and tray icon, which only exist on desktop.
+#[cfg(desktop)]
+fn setup_desktop(app: &mut tauri::App) -> tauri::Result<()> {
+    use tauri::{
+        Emitter,
+        menu::{AboutMetadataBuilder, MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder},
+        tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    };
+
+    // --- Application menu bar ---
+    let about = PredefinedMenuItem::about(app, Some("A propos de PopTranscribe"), Some(
+        AboutMetadataBuilder::new()
+            .name(Some("PopTranscribe"))
+            .version(Some("0.2.0"))
+            .build()
+    ))?;
+    let settings_item = MenuItemBuilder::with_id("settings", "Parametres...")
+        .accelerator("CmdOrCtrl+,")
+        .build(app)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let quit_menu = PredefinedMenuItem::quit(app, Some("Quitter PopTranscribe"))?;
+
+    #[cfg(target_os = "macos")]
+    let app_submenu = {
+        let hide = PredefinedMenuItem::hide(app, Some("Masquer PopTranscribe"))?;
+        let hide_others = PredefinedMenuItem::hide_others(app, Some("Masquer les autres"))?;
+        let show_all = PredefinedMenuItem::show_all(app, Some("Tout afficher"))?;
+
+        SubmenuBuilder::new(app, "PopTranscribe")
+            .item(&about)
+            .separator()
+            .item(&settings_item)
+            .item(&separator)
+            .item(&hide)
+            .item(&hide_others)
+            .item(&show_all)
+            .separator()
+            .item(&quit_menu)
+            .build()?
+    };
+
+    #[cfg(not(target_os = "macos"))]
+    let app_submenu = SubmenuBuilder::new(app, "PopTranscribe")
+        .item(&about)
+        .separator()
+        .item(&settings_item)
+        .item(&separator)
+        .item(&quit_menu)
+        .build()?;
+
+    #[cfg(target_os = "macos")]
+    let edit_submenu = SubmenuBuilder::new(app, "Edition")
+        .undo()
+        .redo()
+        .separator()
+        .cut()
+        .copy()
+        .paste()
+        .select_all()
+        .build()?;
+
+    #[cfg(not(target_os = "macos"))]
+    let edit_submenu = SubmenuBuilder::new(app, "Edition")
+        .cut()
+        .copy()
+        .paste()
+        .select_all()
+        .build()?;
+
+    let app_menu = MenuBuilder::new(app)
+        .item(&app_submenu)
+        .item(&edit_submenu)
+        .build()?;
+
+    app.set_menu(app_menu)?;
+
+    // Handle menu events (settings)
+    app.on_menu_event(move |app_handle, event| {
+        if event.id().as_ref() == "settings" {
+            let _ = app_handle.emit("open-settings", ());
+        }
+    });
+
+    // The main window is created hidden so that a login start can stay in the tray
+    if !autostart::should_start_hidden(app.handle()) {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+        }
+    }
+
+    // --- System tray ---
+    let tray_menu = build_tray_menu(app.handle())?;
+
+    // Create the system tray icon
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
+        .icon(app.default_window_icon().unwrap().clone())
+        .menu(&tray_menu)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "open" => show_main_window(app),
+            "record" => toggle_recording(app),
+            "quit" => {
+                app.exit(0);
+            }
+            id => {
+                // Recent session: open the app focused on it
+                if let Some(session_id) = id.strip_prefix(TRAY_SESSION_PREFIX) {
+                    show_main_window(app);
+                    let _ = app.emit("open-session", session_id);
+                }
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            match event {
+                TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } => show_main_window(tray.app_handle()),
+                TrayIconEvent::DoubleClick {
+                    button: MouseButton::Left,
+                    ..
+                } => toggle_recording(tray.app_handle()),
+                _ => {}
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(app)?;
+
+    Ok(())
+}
+
+#[cfg(desktop)]
+const TRAY_ID: &str = "main";
+
+/// Menu id prefix of the recent sessions listed in the tray.
+#[cfg(desktop)]
+const TRAY_SESSION_PREFIX: &str = "session:";
+
+/// Number of recent sessions listed in the tray.
+#[cfg(desktop)]
+const TRAY_RECENT_SESSIONS: u32 = 5;
+
+#[cfg(desktop)]
+pub(crate) fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Starts an in-person session with the default settings, or stops the
+/// active one. Failures are reported by notification since the window may
+/// be hidden.
+#[cfg(desktop)]
+fn toggle_recording(app: &tauri::AppHandle) {
+    use tauri::Emitter;
+    use tauri_plugin_notification::NotificationExt;
+
+    let active = app
+        .state::<AppState>()
+        .active_session
+        .lock()
+        .ok()
+        .and_then(|active| active.as_ref().map(|s| s.id.clone()));
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Some(session_id) = active {
+            commands::spawn_auto_stop(&app, &session_id, "tray");
+            return;
+        }
+        match commands::start_session("presentiel".to_string(), None, None, None, app.clone(), app.state())
+            .await
+        {
+            Ok(session_id) => {
+                let _ = app.emit("session-started", &session_id);
+            }
+            Err(e) => {
+                let _ = app
+                    .notification()
+                    .builder()
+                    .title("Enregistrement impossible")
+                    .body(&e)
+                    .show();
+            }
+        }
+    });
+}
+
+#[cfg(desktop)]
+fn build_tray_menu(app: &tauri::AppHandle) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{MenuBuilder, MenuItemBuilder};
+
+    let recent = app
+        .state::<AppState>()
+        .db
+        .lock()
+        .ok()
+        .and_then(|db| db.list_recent_sessions(TRAY_RECENT_SESSIONS).ok())
+        .unwrap_or_default();
+
+    let recording = app
+        .state::<AppState>()
+        .active_session
+        .lock()
+        .map(|active| active.is_some())
+        .unwrap_or(false);
+
+    let open_item = MenuItemBuilder::with_id("open", "Ouvrir PopTranscribe").build(app)?;
+    let record_label = if recording { "Arreter l'enregistrement" } else { "Enregistrer maintenant" };
+    let record_item = MenuItemBuilder::with_id("record", record_label).build(app)?;
+    let quit_item = MenuItemBuilder::with_id("quit", "Quitter").build(app)?;
+    let mut menu = MenuBuilder::new(app)
+        .items(&[&open_item, &record_item])
+        .separator();
+    if !recent.is_empty() {
+        for session in &recent {
+            let mut label: String = session.title.chars().take(40).collect();
+            if session.title.chars().count() > 40 {
+                label.push_str("...");
+            }
+            let item = MenuItemBuilder::with_id(format!("{}{}", TRAY_SESSION_PREFIX, session.id), label)
+                .build(app)?;
+            menu = menu.item(&item);
+        }
+        menu = menu.separator();
+    }
+    menu.items(&[&quit_item]).build()
+}
+
+/// Rebuilds the tray menu after sessions were created, renamed or deleted,
+/// or a recording started or stopped, so that it lists the most recent
+/// sessions and the right record action. No tray on mobile.
+pub fn refresh_tray(app: &tauri::AppHandle) {
+    #[cfg(desktop)]
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        match build_tray_menu(app) {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => eprintln!("[tray] Menu update failed: {}", e),
+        }
+    }
+    #[cfg(not(desktop))]
+    let _ = app;
 }
@@ -1,6 +1,9 @@
 use reqwest::multipart;
 use serde::Deserialize;
 
+/// Model of the batch (diarized) transcription.
+pub const BATCH_MODEL: &str = "voxtral-mini-latest";
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct TranscriptionSegment {
     pub text: String,
@@ -8,11 +11,16 @@ pub struct TranscriptionSegment {
     pub end: f64,
     #[serde(alias = "speaker")]
     pub speaker_id: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct TranscriptionResponse {
     pub text: String,
+    /// Dominant language detected for the whole file.
+    #[serde(default)]
+    pub language: Option<String>,
     #[serde(default)]
     pub segments: Vec<TranscriptionSegment>,
 }
@@ -33,18 +41,22 @@ pub async fn transcribe_batch(
 
     let file_part = multipart::Part::bytes(file_bytes)
         .file_name(file_name)
-        .mime_str("audio/wav")?;
+        .mime_str(crate::audio::transcode::mime_for_path(audio_path))?;
 
     let mut form = multipart::Form::new()
-        .text("model", "voxtral-mini-latest")
-        .part("file", file_part)
-        .text("timestamp_granularities", "segment");
+        .text("model", BATCH_MODEL)
+        .part("file", file_part);
 
     if diarize {
         form = form.text("diarize", "true");
     }
     // Note: language param is incompatible with timestamp_granularities per Mistral docs.
-    // The API auto-detects language, so we omit it.
+    // Without a pinned language the API auto-detects it (and handles code-switching);
+    // with one, segment timestamps come from diarization only.
+    match language {
+        Some(lang) => form = form.text("language", lang.to_string()),
+        None => form = form.text("timestamp_granularities", "segment"),
+    }
 
     let response = client
         .post("https://api.mistral.ai/v1/audio/transcriptions")
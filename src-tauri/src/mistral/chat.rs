@@ -1,10 +1,91 @@
 use serde::{Serialize, Deserialize};
 
+/// Model of the summary, search, title and digest calls.
+pub const CHAT_MODEL: &str = "mistral-small-latest";
+
+/// Model retried when `CHAT_MODEL` fails to summarize, unless the
+/// `fallback_model` setting names another one ("" disables the retry).
+pub const DEFAULT_FALLBACK_MODEL: &str = "mistral-large-latest";
+
+/// Sampling parameters of a chat call.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ChatParams {
+    pub temperature: f64,
+    pub max_tokens: u32,
+    pub top_p: f64,
+}
+
+/// Chat calls whose parameters can be tuned in settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatTask {
+    Summary,
+    Search,
+    Title,
+}
+
+impl ChatTask {
+    fn setting_prefix(self) -> &'static str {
+        match self {
+            Self::Summary => "summary",
+            Self::Search => "search",
+            Self::Title => "title",
+        }
+    }
+
+    /// Parameters used when the settings do not override them. The token
+    /// limits leave room for the summaries of long, dense meetings.
+    pub fn default_params(self) -> ChatParams {
+        match self {
+            Self::Summary => ChatParams { temperature: 0.2, max_tokens: 4000, top_p: 1.0 },
+            Self::Search => ChatParams { temperature: 0.3, max_tokens: 2000, top_p: 1.0 },
+            Self::Title => ChatParams { temperature: 0.3, max_tokens: 30, top_p: 1.0 },
+        }
+    }
+}
+
+impl ChatParams {
+    /// Reads `<task>_temperature`, `<task>_max_tokens` and `<task>_top_p`
+    /// (e.g. `summary_max_tokens`), falling back to the task defaults for
+    /// missing or out-of-range values.
+    pub fn from_settings(db: &crate::db::Database, task: ChatTask) -> Self {
+        let defaults = task.default_params();
+        let setting = |name: &str| {
+            db.get_setting(&format!("{}_{}", task.setting_prefix(), name))
+                .ok()
+                .flatten()
+        };
+        Self {
+            temperature: setting("temperature")
+                .and_then(|v| v.parse().ok())
+                .filter(|t: &f64| (0.0..=1.5).contains(t))
+                .unwrap_or(defaults.temperature),
+            max_tokens: setting("max_tokens")
+                .and_then(|v| v.parse().ok())
+                .filter(|&n: &u32| n > 0)
+                .unwrap_or(defaults.max_tokens),
+            top_p: setting("top_p")
+                .and_then(|v| v.parse().ok())
+                .filter(|p: &f64| *p > 0.0 && *p <= 1.0)
+                .unwrap_or(defaults.top_p),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Summary {
     pub key_points: Vec<String>,
     pub decisions: Vec<String>,
     pub action_items: Vec<ActionItem>,
+    /// Short themes of the meeting (e.g. "recrutement"), indexed to find
+    /// every session that touched them.
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+impl Summary {
+    pub fn is_empty(&self) -> bool {
+        self.key_points.is_empty() && self.decisions.is_empty() && self.action_items.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,11 +94,36 @@ pub struct ActionItem {
     pub assignee: Option<String>,
 }
 
+/// Consolidated digest over several sessions (e.g. a week of meetings).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub themes: Vec<String>,
+    pub decisions: Vec<String>,
+    pub outstanding_actions: Vec<ActionItem>,
+}
+
+/// Proposed identity of a diarized speaker, to be confirmed by the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakerMapping {
+    pub speaker: String,
+    pub name: String,
+    /// Transcript excerpt that motivated the guess (e.g. a self-introduction).
+    #[serde(default)]
+    pub evidence: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpeakerMappingResponse {
+    #[serde(default)]
+    mappings: Vec<SpeakerMapping>,
+}
+
 /// Sends the transcript + user query to Mistral chat and returns a natural language answer.
 pub async fn search_transcript(
     api_key: &str,
     transcript: &str,
     query: &str,
+    params: &ChatParams,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let client = reqwest::Client::new();
 
@@ -33,10 +139,11 @@ pub async fn search_transcript(
     ]);
 
     let body = serde_json::json!({
-        "model": "mistral-small-latest",
+        "model": CHAT_MODEL,
         "messages": messages,
-        "temperature": 0.3,
-        "max_tokens": 1000
+        "temperature": params.temperature,
+        "max_tokens": params.max_tokens,
+        "top_p": params.top_p
     });
 
     let response = client
@@ -61,29 +168,533 @@ pub async fn search_transcript(
     Ok(answer)
 }
 
+/// Formats the participants list prepended to summary prompts.
+fn participants_context(participants: &[String]) -> String {
+    if participants.is_empty() {
+        return String::new();
+    }
+    let mut context = String::from("Participants:\n");
+    for participant in participants {
+        context.push_str(&format!("- {}\n", participant));
+    }
+    context.push('\n');
+    context
+}
+
+/// Re-prompts after an invalid summary, before settling for what could be
+/// salvaged from the answers.
+const SUMMARY_REPAIR_ATTEMPTS: usize = 2;
+
 /// Sends the full diarized transcript to Mistral and returns a structured Summary.
+///
+/// `participants` optionally describes the speakers, one per line
+/// (e.g. "Marie (CFO, Acme)"), so the summary can refer to their roles.
+///
+/// When the answer does not match the schema, the model is asked to fix it,
+/// quoting the problems. If it never fully does, the most complete partial
+/// summary is returned; an error only when nothing usable came back.
 pub async fn generate_summary(
     api_key: &str,
+    model: &str,
     transcript: &str,
+    participants: &[String],
+    params: &ChatParams,
 ) -> Result<Summary, Box<dyn std::error::Error + Send + Sync>> {
     let client = reqwest::Client::new();
 
+    let mut messages = vec![
+        serde_json::json!({
+            "role": "system",
+            "content": "Tu es un assistant specialise dans la synthese de reunions. A partir de la transcription fournie, genere un resume structure au format JSON avec les champs suivants:\n- key_points: liste des points cles discutes\n- decisions: liste des decisions prises\n- action_items: liste des actions a mener, chacune avec 'description' et 'assignee' (null si non identifie)\n- topics: 3 a 6 themes abordes, en un ou deux mots minuscules (ex: 'recrutement', 'budget')\n\nReponds UNIQUEMENT avec le JSON, sans texte avant ou apres."
+        }),
+        serde_json::json!({
+            "role": "user",
+            "content": format!("{}Transcription de la reunion:\n\n{}", participants_context(participants), transcript)
+        }),
+    ];
+
+    let mut best: Option<ParsedSummary> = None;
+    let mut last_error = String::new();
+    for _ in 0..=SUMMARY_REPAIR_ATTEMPTS {
+        let body = serde_json::json!({
+            "model": model,
+            "messages": messages,
+            "temperature": params.temperature,
+            "max_tokens": params.max_tokens,
+            "top_p": params.top_p,
+            "response_format": {"type": "json_object"}
+        });
+        let content = chat_completion(&client, api_key, &body).await?;
+
+        let problems = match parse_summary(&content) {
+            Ok(parsed) if parsed.problems.is_empty() => return Ok(parsed.summary),
+            Ok(parsed) => {
+                let problems = parsed.problems.join("; ");
+                let more_complete = match &best {
+                    Some(b) => parsed.problems.len() < b.problems.len(),
+                    None => true,
+                };
+                if more_complete {
+                    best = Some(parsed);
+                }
+                problems
+            }
+            Err(e) => e,
+        };
+        eprintln!("[summary] Invalid summary: {}", problems);
+        last_error = problems.clone();
+        messages.push(serde_json::json!({ "role": "assistant", "content": content }));
+        messages.push(serde_json::json!({
+            "role": "user",
+            "content": format!(
+                "Ta reponse ne respecte pas le format demande: {}. Renvoie uniquement le JSON corrige.",
+                problems
+            )
+        }));
+    }
+
+    match best {
+        Some(parsed) => Ok(parsed.summary),
+        None => Err(format!("Resume invalide: {}", last_error).into()),
+    }
+}
+
+/// `generate_summary` with `CHAT_MODEL`, retried with `fallback` when it
+/// fails or yields an empty summary. Returns the summary and the model that
+/// produced it.
+pub async fn generate_summary_with_fallback(
+    api_key: &str,
+    transcript: &str,
+    participants: &[String],
+    params: &ChatParams,
+    fallback: Option<&str>,
+) -> Result<(Summary, String), Box<dyn std::error::Error + Send + Sync>> {
+    let primary = generate_summary(api_key, CHAT_MODEL, transcript, participants, params).await;
+    let fallback = fallback.filter(|model| *model != CHAT_MODEL);
+    match (&primary, fallback) {
+        (Ok(summary), _) if !summary.is_empty() => {}
+        (_, Some(model)) => {
+            match &primary {
+                Ok(_) => eprintln!("[summary] Empty summary from {}, retrying with {}", CHAT_MODEL, model),
+                Err(e) => eprintln!("[summary] {} failed ({}), retrying with {}", CHAT_MODEL, e, model),
+            }
+            match generate_summary(api_key, model, transcript, participants, params).await {
+                Ok(summary) if !summary.is_empty() || primary.is_err() => {
+                    return Ok((summary, model.to_string()));
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[summary] Fallback {} failed: {}", model, e),
+            }
+        }
+        _ => {}
+    }
+    primary.map(|summary| (summary, CHAT_MODEL.to_string()))
+}
+
+/// POSTs a chat completion request and returns the message content.
+async fn chat_completion(
+    client: &reqwest::Client,
+    api_key: &str,
+    body: &serde_json::Value,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let response = client
+        .post("https://api.mistral.ai/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Mistral API error {}: {}", status, body).into());
+    }
+
+    let result: serde_json::Value = response.json().await?;
+    Ok(result["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// A summary salvaged from a model answer, with what had to be dropped.
+#[derive(Debug)]
+struct ParsedSummary {
+    summary: Summary,
+    problems: Vec<String>,
+}
+
+/// Validates a summary answer field by field. Invalid items and fields are
+/// left out and reported in `problems`; the answer is only rejected when it
+/// is not a JSON object or has none of the expected fields.
+fn parse_summary(content: &str) -> Result<ParsedSummary, String> {
+    // Models sometimes wrap JSON in a Markdown code block
+    let content = content.trim();
+    let content = content
+        .strip_prefix("```json")
+        .or_else(|| content.strip_prefix("```"))
+        .and_then(|c| c.strip_suffix("```"))
+        .unwrap_or(content);
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("JSON invalide ({})", e))?;
+    let object = value.as_object().ok_or("la reponse n'est pas un objet JSON")?;
+    if !["key_points", "decisions", "action_items"]
+        .iter()
+        .any(|field| object.contains_key(*field))
+    {
+        return Err("aucun des champs key_points, decisions, action_items".to_string());
+    }
+
+    let mut problems = Vec::new();
+    let mut list = |field: &str| -> Vec<serde_json::Value> {
+        match object.get(field) {
+            Some(serde_json::Value::Array(items)) => items.clone(),
+            Some(_) => {
+                problems.push(format!("{} doit etre une liste", field));
+                Vec::new()
+            }
+            None => {
+                problems.push(format!("champ {} manquant", field));
+                Vec::new()
+            }
+        }
+    };
+    let key_points = list("key_points");
+    let decisions = list("decisions");
+    let action_items = list("action_items");
+    // Optional: a summary without topics is still complete
+    let topics = match object.get("topics") {
+        Some(serde_json::Value::Array(items)) => items.clone(),
+        Some(_) => {
+            problems.push("topics doit etre une liste".to_string());
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+
+    let mut strings = |field: &str, items: Vec<serde_json::Value>| -> Vec<String> {
+        items
+            .into_iter()
+            .filter_map(|item| match item {
+                serde_json::Value::String(text) => Some(text),
+                _ => {
+                    problems.push(format!("element non textuel dans {}", field));
+                    None
+                }
+            })
+            .collect()
+    };
+    let key_points = strings("key_points", key_points);
+    let decisions = strings("decisions", decisions);
+    let topics = strings("topics", topics);
+
+    let action_items = action_items
+        .into_iter()
+        .filter_map(|item| match item {
+            // A bare description is accepted as an unassigned action
+            serde_json::Value::String(description) => Some(ActionItem { description, assignee: None }),
+            serde_json::Value::Object(fields) => match fields.get("description").and_then(|d| d.as_str()) {
+                Some(description) => Some(ActionItem {
+                    description: description.to_string(),
+                    assignee: fields
+                        .get("assignee")
+                        .and_then(|a| a.as_str())
+                        .filter(|a| !a.is_empty())
+                        .map(str::to_string),
+                }),
+                None => {
+                    problems.push("action sans description".to_string());
+                    None
+                }
+            },
+            _ => {
+                problems.push("action invalide".to_string());
+                None
+            }
+        })
+        .collect();
+
+    Ok(ParsedSummary {
+        summary: Summary { key_points, decisions, action_items, topics },
+        problems,
+    })
+}
+
+/// Sends the summaries (or transcripts) of several meetings to Mistral and
+/// returns a consolidated Digest.
+pub async fn generate_digest(
+    api_key: &str,
+    meetings: &str,
+) -> Result<Digest, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+
     let messages = serde_json::json!([
         {
             "role": "system",
-            "content": "Tu es un assistant specialise dans la synthese de reunions. A partir de la transcription fournie, genere un resume structure au format JSON avec les champs suivants:\n- key_points: liste des points cles discutes\n- decisions: liste des decisions prises\n- action_items: liste des actions a mener, chacune avec 'description' et 'assignee' (null si non identifie)\n\nReponds UNIQUEMENT avec le JSON, sans texte avant ou apres."
+            "content": "Tu es un assistant qui consolide plusieurs reunions en une synthese periodique. A partir des reunions fournies, genere un JSON avec les champs suivants:\n- themes: liste des grands themes recurrents\n- decisions: liste de toutes les decisions prises, en precisant la reunion concernee\n- outstanding_actions: liste des actions encore a mener, chacune avec 'description' et 'assignee' (null si non identifie)\n\nReponds UNIQUEMENT avec le JSON, sans texte avant ou apres."
         },
         {
             "role": "user",
-            "content": format!("Transcription de la reunion:\n\n{}", transcript)
+            "content": format!("Reunions de la periode:\n\n{}", meetings)
         }
     ]);
 
     let body = serde_json::json!({
-        "model": "mistral-small-latest",
+        "model": CHAT_MODEL,
         "messages": messages,
         "temperature": 0.2,
-        "max_tokens": 2000,
+        "max_tokens": 3000,
+        "response_format": {"type": "json_object"}
+    });
+
+    let response = client
+        .post("https://api.mistral.ai/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Mistral API error {}: {}", status, body).into());
+    }
+
+    let result: serde_json::Value = response.json().await?;
+    let content = result["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or("{}");
+
+    let digest: Digest = serde_json::from_str(content)?;
+    Ok(digest)
+}
+
+/// Asks Mistral which attendee each diarized speaker is, based on
+/// self-introductions and how speakers address each other.
+pub async fn propose_speaker_mapping(
+    api_key: &str,
+    transcript: &str,
+    speakers: &[String],
+    attendees: &[String],
+) -> Result<Vec<SpeakerMapping>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+
+    let messages = serde_json::json!([
+        {
+            "role": "system",
+            "content": "Tu identifies les intervenants d'une transcription de reunion. A partir des presentations ('Bonjour, c'est Marie...') et de la facon dont les intervenants s'interpellent, associe chaque intervenant a un participant de la liste fournie. Genere un JSON avec un champ 'mappings': liste d'objets avec 'speaker' (l'identifiant de l'intervenant), 'name' (le participant, tel qu'ecrit dans la liste) et 'evidence' (court extrait justifiant l'association). N'inclus que les associations dont tu es raisonnablement sur. Reponds UNIQUEMENT avec le JSON, sans texte avant ou apres."
+        },
+        {
+            "role": "user",
+            "content": format!(
+                "Intervenants: {}\nParticipants: {}\n\nTranscription:\n\n{}",
+                speakers.join(", "),
+                attendees.join(", "),
+                transcript
+            )
+        }
+    ]);
+
+    let body = serde_json::json!({
+        "model": CHAT_MODEL,
+        "messages": messages,
+        "temperature": 0.1,
+        "max_tokens": 1000,
+        "response_format": {"type": "json_object"}
+    });
+
+    let response = client
+        .post("https://api.mistral.ai/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Mistral API error {}: {}", status, body).into());
+    }
+
+    let result: serde_json::Value = response.json().await?;
+    let content = result["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or("{}");
+
+    let parsed: SpeakerMappingResponse = serde_json::from_str(content)?;
+    Ok(sanitize_mappings(parsed.mappings, speakers, attendees))
+}
+
+/// Drops mappings referring to unknown speakers or attendees, and keeps a
+/// single mapping per speaker and per attendee (the first one proposed).
+fn sanitize_mappings(
+    mappings: Vec<SpeakerMapping>,
+    speakers: &[String],
+    attendees: &[String],
+) -> Vec<SpeakerMapping> {
+    let mut result: Vec<SpeakerMapping> = Vec::new();
+    for mut mapping in mappings {
+        let Some(name) = attendees
+            .iter()
+            .find(|a| a.trim().eq_ignore_ascii_case(mapping.name.trim()))
+        else {
+            continue;
+        };
+        mapping.name = name.clone();
+        if !speakers.contains(&mapping.speaker)
+            || result
+                .iter()
+                .any(|m| m.speaker == mapping.speaker || m.name == mapping.name)
+        {
+            continue;
+        }
+        result.push(mapping);
+    }
+    result
+}
+
+#[derive(Debug, Deserialize)]
+struct ActionItemsResponse {
+    #[serde(default)]
+    action_items: Vec<ActionItem>,
+}
+
+/// Looks for tasks handed out in a passage of a meeting in progress ("tu
+/// peux t'occuper de..."). `known` lists the items already found, so that
+/// they are not reported again.
+pub async fn detect_action_items(
+    api_key: &str,
+    passage: &str,
+    known: &[String],
+) -> Result<Vec<ActionItem>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+
+    let messages = serde_json::json!([
+        {
+            "role": "system",
+            "content": "Tu reperes les actions confiees pendant une reunion en cours ('tu peux t'occuper de...', 'je m'en charge', 'il faudra que Paul envoie...'). A partir de l'extrait fourni, genere un JSON avec un champ 'action_items': liste d'objets avec 'description' (l'action, formulee brievement) et 'assignee' (la personne concernee, null si non identifiee). N'inclus pas les actions deja connues. Si aucune action n'est confiee, renvoie une liste vide. Reponds UNIQUEMENT avec le JSON, sans texte avant ou apres."
+        },
+        {
+            "role": "user",
+            "content": format!(
+                "Actions deja connues:\n{}\n\nExtrait:\n\n{}",
+                if known.is_empty() { "aucune".to_string() } else { known.join("\n") },
+                passage
+            )
+        }
+    ]);
+
+    let body = serde_json::json!({
+        "model": CHAT_MODEL,
+        "messages": messages,
+        "temperature": 0.1,
+        "max_tokens": 500,
+        "response_format": {"type": "json_object"}
+    });
+
+    let response = client
+        .post("https://api.mistral.ai/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Mistral API error {}: {}", status, body).into());
+    }
+
+    let result: serde_json::Value = response.json().await?;
+    let content = result["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or("{}");
+
+    let parsed: ActionItemsResponse = serde_json::from_str(content)?;
+    Ok(parsed
+        .action_items
+        .into_iter()
+        .filter(|item| !item.description.trim().is_empty())
+        .filter(|item| !known.iter().any(|k| same_action(k, &item.description)))
+        .collect())
+}
+
+/// Whether two action descriptions are the same task, worded differently:
+/// most of the significant words of the shorter one appear in the other.
+pub fn same_action(a: &str, b: &str) -> bool {
+    let words = |s: &str| -> std::collections::HashSet<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.chars().count() > 3)
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    let smaller = a.len().min(b.len());
+    if smaller == 0 {
+        return false;
+    }
+    a.intersection(&b).count() * 3 >= smaller * 2
+}
+
+/// Adds to a summary the action items detected during the recording that
+/// it does not already list. Returns the number added.
+pub fn merge_detected_action_items(summary: &mut Summary, detected: Vec<ActionItem>) -> usize {
+    let mut added = 0;
+    for item in detected {
+        if summary
+            .action_items
+            .iter()
+            .any(|known| same_action(&known.description, &item.description))
+        {
+            continue;
+        }
+        summary.action_items.push(item);
+        added += 1;
+    }
+    added
+}
+
+#[derive(Debug, Deserialize)]
+struct TranslationsResponse {
+    #[serde(default)]
+    translations: Vec<SegmentTranslation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SegmentTranslation {
+    id: i64,
+    text: String,
+}
+
+/// Translates segments, given as (id, text), into the language of ISO
+/// 639-1 code `language`. Segments missing from the answer are left out.
+pub async fn translate_segments(
+    api_key: &str,
+    segments: &[(i64, String)],
+    language: &str,
+) -> Result<Vec<(i64, String)>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+
+    let input: Vec<serde_json::Value> = segments
+        .iter()
+        .map(|(id, text)| serde_json::json!({ "id": id, "text": text }))
+        .collect();
+    let messages = serde_json::json!([
+        {
+            "role": "system",
+            "content": format!("Tu traduis des segments de transcription de reunion dans la langue de code ISO 639-1 '{}'. Traduis chaque segment fidelement, en gardant le registre oral, sans fusionner ni omettre de segment. Genere un JSON avec un champ 'translations': liste d'objets avec 'id' (celui du segment) et 'text' (la traduction). Reponds UNIQUEMENT avec le JSON, sans texte avant ou apres.", language)
+        },
+        {
+            "role": "user",
+            "content": serde_json::to_string(&input)?
+        }
+    ]);
+
+    let body = serde_json::json!({
+        "model": CHAT_MODEL,
+        "messages": messages,
+        "temperature": 0.1,
+        "max_tokens": 4000,
         "response_format": {"type": "json_object"}
     });
 
@@ -105,14 +716,253 @@ pub async fn generate_summary(
         .as_str()
         .unwrap_or("{}");
 
-    let summary: Summary = serde_json::from_str(content)?;
+    let parsed: TranslationsResponse = serde_json::from_str(content)?;
+    Ok(parsed
+        .translations
+        .into_iter()
+        .filter(|t| !t.text.trim().is_empty() && segments.iter().any(|(id, _)| *id == t.id))
+        .map(|t| (t.id, t.text.trim().to_string()))
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct CorrectionsResponse {
+    #[serde(default)]
+    corrections: Vec<SegmentTranslation>,
+}
+
+/// Corrects the misrecognized domain terms of transcript segments, using a
+/// glossary of the expected spellings. Returns only the segments whose text
+/// changed.
+pub async fn polish_segments(
+    api_key: &str,
+    segments: &[(i64, String)],
+    glossary: &[String],
+) -> Result<Vec<(i64, String)>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+
+    let input: Vec<serde_json::Value> = segments
+        .iter()
+        .map(|(id, text)| serde_json::json!({ "id": id, "text": text }))
+        .collect();
+    let glossary = if glossary.is_empty() {
+        "(aucun)".to_string()
+    } else {
+        glossary.join(", ")
+    };
+    let messages = serde_json::json!([
+        {
+            "role": "system",
+            "content": format!("Tu corriges une transcription automatique de reunion. Corrige uniquement les termes techniques, noms propres et sigles mal reconnus, en t'appuyant sur ce glossaire: {}. Ne reformule pas, ne corrige pas le style ni la grammaire, ne fusionne pas de segment. Genere un JSON avec un champ 'corrections': liste d'objets avec 'id' (celui du segment) et 'text' (le texte corrige complet), seulement pour les segments modifies. Reponds UNIQUEMENT avec le JSON, sans texte avant ou apres.", glossary)
+        },
+        {
+            "role": "user",
+            "content": serde_json::to_string(&input)?
+        }
+    ]);
+
+    let body = serde_json::json!({
+        "model": CHAT_MODEL,
+        "messages": messages,
+        "temperature": 0.0,
+        "max_tokens": 4000,
+        "response_format": {"type": "json_object"}
+    });
+
+    let response = client
+        .post("https://api.mistral.ai/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Mistral API error {}: {}", status, body).into());
+    }
+
+    let result: serde_json::Value = response.json().await?;
+    let content = result["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or("{}");
+
+    let parsed: CorrectionsResponse = serde_json::from_str(content)?;
+    Ok(parsed
+        .corrections
+        .into_iter()
+        .filter_map(|c| {
+            let text = c.text.trim();
+            let (_, original) = segments.iter().find(|(id, _)| *id == c.id)?;
+            (!text.is_empty() && text != original.trim()).then(|| (c.id, text.to_string()))
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct PersonNamesResponse {
+    #[serde(default)]
+    names: Vec<String>,
+}
+
+/// Lists the names of people mentioned in a transcript, as written, for
+/// anonymization.
+pub async fn extract_person_names(
+    api_key: &str,
+    transcript: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+
+    let messages = serde_json::json!([
+        {
+            "role": "system",
+            "content": "Tu aides a anonymiser une transcription de reunion. Liste tous les noms et prenoms de personnes mentionnes, exactement tels qu'ils sont ecrits (une entree par forme: 'Marie', 'Marie Curie', 'Mme Curie'). N'inclus pas les noms d'entreprises, de produits ou de lieux. Genere un JSON avec un champ 'names': liste de chaines. Reponds UNIQUEMENT avec le JSON, sans texte avant ou apres."
+        },
+        {
+            "role": "user",
+            "content": transcript
+        }
+    ]);
+
+    let body = serde_json::json!({
+        "model": CHAT_MODEL,
+        "messages": messages,
+        "temperature": 0.0,
+        "max_tokens": 1000,
+        "response_format": {"type": "json_object"}
+    });
+
+    let response = client
+        .post("https://api.mistral.ai/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Mistral API error {}: {}", status, body).into());
+    }
+
+    let result: serde_json::Value = response.json().await?;
+    let content = result["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or("{}");
+
+    let parsed: PersonNamesResponse = serde_json::from_str(content)?;
+    Ok(parsed.names)
+}
+
+/// Transcript length sent for a live summary: the end of the meeting so far.
+const LIVE_SUMMARY_CONTEXT_CHARS: usize = 12000;
+
+/// Generates a brief summary of a meeting still in progress, to catch up on
+/// what was said so far.
+pub async fn generate_live_summary(
+    api_key: &str,
+    transcript: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+
+    let skip = transcript.chars().count().saturating_sub(LIVE_SUMMARY_CONTEXT_CHARS);
+    let context: String = transcript.chars().skip(skip).collect();
+
+    let messages = serde_json::json!([
+        {
+            "role": "system",
+            "content": "Tu resumes une reunion en cours pour quelqu'un qui la rejoint en retard. En 3 a 5 phrases courtes, indique les sujets abordes, ou en est la discussion et les decisions deja prises. Reponds UNIQUEMENT avec le resume, en texte simple."
+        },
+        {
+            "role": "user",
+            "content": context
+        }
+    ]);
+
+    let body = serde_json::json!({
+        "model": CHAT_MODEL,
+        "messages": messages,
+        "temperature": 0.2,
+        "max_tokens": 400
+    });
+
+    let response = client
+        .post("https://api.mistral.ai/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Mistral API error {}: {}", status, body).into());
+    }
+
+    let result: serde_json::Value = response.json().await?;
+    let summary = result["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
     Ok(summary)
 }
 
+/// Restores the punctuation and casing of a live segment, without changing
+/// its words.
+pub async fn restore_punctuation(
+    api_key: &str,
+    text: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+
+    let messages = serde_json::json!([
+        {
+            "role": "system",
+            "content": "Tu ponctues une phrase de transcription automatique: ajoute la ponctuation et les majuscules manquantes, sans ajouter, retirer ni modifier aucun mot. Reponds UNIQUEMENT avec la phrase corrigee."
+        },
+        {
+            "role": "user",
+            "content": text
+        }
+    ]);
+
+    let body = serde_json::json!({
+        "model": CHAT_MODEL,
+        "messages": messages,
+        "temperature": 0.0,
+        "max_tokens": 300
+    });
+
+    let response = client
+        .post("https://api.mistral.ai/v1/chat/completions")
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Mistral API error {}: {}", status, body).into());
+    }
+
+    let result: serde_json::Value = response.json().await?;
+    let restored = result["choices"][0]["message"]["content"]
+        .as_str()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    Ok(restored)
+}
+
 /// Generates a short, descriptive title for a meeting based on the transcript.
 pub async fn generate_title(
     api_key: &str,
     transcript: &str,
+    params: &ChatParams,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let client = reqwest::Client::new();
 
@@ -135,10 +985,11 @@ pub async fn generate_title(
     ]);
 
     let body = serde_json::json!({
-        "model": "mistral-small-latest",
+        "model": CHAT_MODEL,
         "messages": messages,
-        "temperature": 0.3,
-        "max_tokens": 30
+        "temperature": params.temperature,
+        "max_tokens": params.max_tokens,
+        "top_p": params.top_p
     });
 
     let response = client
@@ -188,10 +1039,130 @@ mod tests {
         assert!(summary.action_items[1].assignee.is_none());
     }
 
+    #[test]
+    fn test_deserialize_digest() {
+        let json = r#"{
+            "themes": ["Recrutement"],
+            "decisions": ["Ouvrir un poste backend"],
+            "outstanding_actions": [
+                {"description": "Rediger la fiche de poste", "assignee": "Marie"}
+            ]
+        }"#;
+
+        let digest: Digest = serde_json::from_str(json).unwrap();
+        assert_eq!(digest.themes, vec!["Recrutement".to_string()]);
+        assert_eq!(digest.outstanding_actions[0].assignee.as_deref(), Some("Marie"));
+    }
+
+    #[test]
+    fn test_participants_context() {
+        assert_eq!(participants_context(&[]), "");
+        let context = participants_context(&["Marie (CFO, Acme)".to_string(), "Paul".to_string()]);
+        assert_eq!(context, "Participants:\n- Marie (CFO, Acme)\n- Paul\n\n");
+    }
+
+    #[test]
+    fn test_sanitize_mappings() {
+        let speakers = vec!["speaker_1".to_string(), "speaker_2".to_string()];
+        let attendees = vec!["Marie Durand".to_string(), "Paul".to_string()];
+        let json = r#"{"mappings": [
+            {"speaker": "speaker_1", "name": "marie durand", "evidence": "Bonjour, c'est Marie"},
+            {"speaker": "speaker_1", "name": "Paul"},
+            {"speaker": "speaker_2", "name": "Marie Durand"},
+            {"speaker": "speaker_3", "name": "Paul"},
+            {"speaker": "speaker_2", "name": "Jacques"}
+        ]}"#;
+        let parsed: SpeakerMappingResponse = serde_json::from_str(json).unwrap();
+        let mappings = sanitize_mappings(parsed.mappings, &speakers, &attendees);
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].speaker, "speaker_1");
+        assert_eq!(mappings[0].name, "Marie Durand");
+    }
+
     #[test]
     fn test_deserialize_empty_summary() {
         let json = r#"{"key_points": [], "decisions": [], "action_items": []}"#;
         let summary: Summary = serde_json::from_str(json).unwrap();
         assert!(summary.key_points.is_empty());
     }
+
+    #[test]
+    fn test_chat_params_from_settings() {
+        let db = crate::db::Database::new_in_memory().unwrap();
+        assert_eq!(ChatParams::from_settings(&db, ChatTask::Summary), ChatTask::Summary.default_params());
+
+        db.set_setting("summary_max_tokens", "8000").unwrap();
+        db.set_setting("summary_temperature", "0.5").unwrap();
+        db.set_setting("summary_top_p", "2").unwrap();
+        let params = ChatParams::from_settings(&db, ChatTask::Summary);
+        assert_eq!(params.max_tokens, 8000);
+        assert_eq!(params.temperature, 0.5);
+        // Out of range: default kept
+        assert_eq!(params.top_p, 1.0);
+        assert_eq!(ChatParams::from_settings(&db, ChatTask::Title).max_tokens, 30);
+    }
+
+    #[test]
+    fn test_parse_summary_valid_in_code_block() {
+        let content = "```json\n{\"key_points\": [\"Budget\"], \"decisions\": [], \"action_items\": [{\"description\": \"Relancer\", \"assignee\": null}]}\n```";
+        let parsed = parse_summary(content).unwrap();
+        assert!(parsed.problems.is_empty());
+        assert_eq!(parsed.summary.key_points, vec!["Budget"]);
+        assert_eq!(parsed.summary.action_items[0].assignee, None);
+        // Topics are optional
+        assert!(parsed.summary.topics.is_empty());
+    }
+
+    #[test]
+    fn test_merge_detected_action_items() {
+        let mut summary = Summary {
+            key_points: vec![],
+            decisions: vec![],
+            action_items: vec![ActionItem {
+                description: "Envoyer le devis au client".to_string(),
+                assignee: Some("Marie".to_string()),
+            }],
+            topics: vec![],
+        };
+        let detected = vec![
+            ActionItem {
+                description: "Marie envoie le devis client".to_string(),
+                assignee: None,
+            },
+            ActionItem {
+                description: "Reserver la salle pour jeudi".to_string(),
+                assignee: Some("Paul".to_string()),
+            },
+        ];
+        assert_eq!(merge_detected_action_items(&mut summary, detected), 1);
+        assert_eq!(summary.action_items.len(), 2);
+        assert_eq!(summary.action_items[1].assignee.as_deref(), Some("Paul"));
+        assert!(!same_action("Relancer le fournisseur", "Envoyer le devis"));
+    }
+
+    #[test]
+    fn test_parse_summary_topics() {
+        let content = r#"{"key_points": [], "decisions": [], "action_items": [], "topics": ["recrutement", 4]}"#;
+        let parsed = parse_summary(content).unwrap();
+        assert_eq!(parsed.summary.topics, vec!["recrutement"]);
+        assert_eq!(parsed.problems.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_summary_keeps_valid_parts() {
+        let content = r#"{"key_points": ["Budget", 3], "decisions": "aucune", "action_items": ["Relancer", {"assignee": "Marie"}]}"#;
+        let parsed = parse_summary(content).unwrap();
+        assert_eq!(parsed.summary.key_points, vec!["Budget"]);
+        assert!(parsed.summary.decisions.is_empty());
+        assert_eq!(parsed.summary.action_items.len(), 1);
+        assert_eq!(parsed.summary.action_items[0].description, "Relancer");
+        assert_eq!(parsed.problems.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_summary_rejects_unusable_answers() {
+        assert!(parse_summary("Voici le resume").is_err());
+        assert!(parse_summary("[1, 2]").is_err());
+        assert!(parse_summary(r#"{"summary": "..."}"#).is_err());
+    }
 }
@@ -0,0 +1,188 @@
+//! Built-in "demo" provider, selected by the `provider` setting: fake live
+//! deltas, diarized segments and AI results generated locally, without an
+//! API key. Meant for demos, UI development and tests.
+
+use tokio::sync::mpsc;
+
+use super::batch::{TranscriptionResponse, TranscriptionSegment};
+use super::chat::{ActionItem, Digest, Summary};
+use super::realtime::{AudioMsg, RealtimeHandle, TranscriptionEvent};
+use crate::db::Database;
+
+/// Audio duration covered by each scripted sentence.
+const SENTENCE_SECS: f64 = 4.0;
+
+const SCRIPT: [&str; 8] = [
+    "Bonjour a tous, merci d'etre la.",
+    "On commence par le point sur le planning du projet.",
+    "La version beta est prete pour les tests internes.",
+    "Il reste deux anomalies bloquantes sur l'export PDF.",
+    "Je propose de decaler la livraison d'une semaine.",
+    "D'accord, on valide le nouveau calendrier.",
+    "Je m'occupe de prevenir le client des demain.",
+    "Parfait, on se retrouve jeudi pour le point suivant.",
+];
+
+/// Whether the `provider` setting selects the demo provider.
+pub fn is_enabled(db: &Database) -> bool {
+    db.get_setting("provider").ok().flatten().as_deref() == Some("demo")
+}
+
+fn sentence(index: usize) -> &'static str {
+    SCRIPT[index % SCRIPT.len()]
+}
+
+/// Stand-in for `realtime::connect_realtime`: emits one scripted sentence,
+/// word by word, per `SENTENCE_SECS` of audio received.
+pub fn connect_realtime(
+    source_sample_rate: u32,
+) -> (RealtimeHandle, mpsc::UnboundedReceiver<TranscriptionEvent>) {
+    let (handle, mut audio_rx) = RealtimeHandle::channel();
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    let samples_per_sentence = (SENTENCE_SECS * source_sample_rate.max(1) as f64) as usize;
+
+    tokio::spawn(async move {
+        let _ = event_tx.send(TranscriptionEvent::Language {
+            audio_language: "fr".to_string(),
+        });
+        let mut received = 0usize;
+        let mut spoken = 0usize;
+        let mut full_text = Vec::new();
+        while let Some(msg) = audio_rx.recv().await {
+            match msg {
+                AudioMsg::Chunk(samples) => {
+                    received += samples.len();
+                    while received >= (spoken + 1) * samples_per_sentence {
+                        let text = sentence(spoken);
+                        for word in text.split(' ') {
+                            let _ = event_tx.send(TranscriptionEvent::TextDelta {
+                                text: format!("{} ", word),
+                            });
+                        }
+                        let _ = event_tx.send(TranscriptionEvent::Segment {
+                            text: text.to_string(),
+                            start: spoken as f64 * SENTENCE_SECS,
+                            end: (spoken + 1) as f64 * SENTENCE_SECS,
+                        });
+                        full_text.push(text);
+                        spoken += 1;
+                    }
+                }
+                AudioMsg::End => break,
+            }
+        }
+        let _ = event_tx.send(TranscriptionEvent::Done {
+            text: full_text.join(" "),
+        });
+    });
+
+    (handle, event_rx)
+}
+
+/// Stand-in for `batch::transcribe_batch`: the script over `duration_secs`,
+/// alternating between two speakers.
+pub fn transcribe_batch(duration_secs: f64) -> TranscriptionResponse {
+    let count = ((duration_secs / SENTENCE_SECS).ceil() as usize).max(1);
+    let segments: Vec<TranscriptionSegment> = (0..count)
+        .map(|i| TranscriptionSegment {
+            text: sentence(i).to_string(),
+            start: i as f64 * SENTENCE_SECS,
+            end: ((i + 1) as f64 * SENTENCE_SECS).min(duration_secs.max(SENTENCE_SECS)),
+            speaker_id: Some(format!("speaker_{}", i % 2)),
+            language: Some("fr".to_string()),
+        })
+        .collect();
+    TranscriptionResponse {
+        text: segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" "),
+        language: Some("fr".to_string()),
+        segments,
+    }
+}
+
+pub fn title() -> String {
+    "Point planning projet (demo)".to_string()
+}
+
+pub fn summary() -> Summary {
+    Summary {
+        key_points: vec![
+            "La version beta est prete pour les tests internes".to_string(),
+            "Deux anomalies bloquantes restent sur l'export PDF".to_string(),
+        ],
+        decisions: vec!["La livraison est decalee d'une semaine".to_string()],
+        action_items: vec![ActionItem {
+            description: "Prevenir le client du nouveau calendrier".to_string(),
+            assignee: Some("speaker_1".to_string()),
+        }],
+        topics: vec!["planning".to_string(), "livraison".to_string()],
+    }
+}
+
+pub fn live_summary() -> String {
+    "La reunion porte sur le planning de la beta. L'equipe envisage de decaler la livraison.".to_string()
+}
+
+pub fn translate(text: &str, language: &str) -> String {
+    format!("[{}] {}", language, text)
+}
+
+/// Glossary terms written with their glossary spelling, whatever the case
+/// they were transcribed with.
+pub fn polish(text: &str, glossary: &[String]) -> String {
+    let terms: Vec<crate::db::Replacement> = glossary
+        .iter()
+        .map(|term| crate::db::Replacement {
+            id: 0,
+            pattern: term.clone(),
+            replacement: term.clone(),
+            created_at: String::new(),
+        })
+        .collect();
+    crate::cleanup::SegmentCleanup::new(&terms, None).replace(text)
+}
+
+pub fn digest() -> Digest {
+    let summary = summary();
+    Digest {
+        themes: summary.key_points,
+        decisions: summary.decisions,
+        outstanding_actions: summary.action_items,
+    }
+}
+
+pub fn answer(query: &str) -> String {
+    format!("Reponse de demonstration a la question \"{}\".", query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcribe_batch_covers_duration() {
+        let response = transcribe_batch(10.0);
+        assert_eq!(response.segments.len(), 3);
+        assert_eq!(response.segments[0].speaker_id.as_deref(), Some("speaker_0"));
+        assert_eq!(response.segments[1].speaker_id.as_deref(), Some("speaker_1"));
+        assert_eq!(response.segments[2].end, 10.0);
+    }
+
+    #[tokio::test]
+    async fn test_connect_realtime_emits_per_sentence() {
+        let (handle, mut events) = connect_realtime(1000);
+        assert!(handle.send_audio(vec![0; 9000]));
+        handle.end_audio().await;
+
+        let mut segments = 0;
+        let mut done = None;
+        while let Some(event) = events.recv().await {
+            match event {
+                TranscriptionEvent::Segment { .. } => segments += 1,
+                TranscriptionEvent::Done { text } => done = Some(text),
+                _ => {}
+            }
+        }
+        assert_eq!(segments, 2);
+        assert_eq!(done.as_deref(), Some(format!("{} {}", SCRIPT[0], SCRIPT[1]).as_str()));
+    }
+}
@@ -0,0 +1,343 @@
+//! Incremental batch transcription: during long recordings, each completed
+//! chunk of audio is sent to the batch endpoint while recording goes on,
+//! so that only the last chunk remains to transcribe after the stop.
+//!
+//! Chunks are diarized separately, so each one labels its speakers on its
+//! own. Every chunk after the first also covers the end of the previous
+//! one: the speakers are matched to the labels of the previous chunks by
+//! the time they share in that overlap, as in rolling diarization, then
+//! the segments are shifted to the session timeline and concatenated.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::batch::{TranscriptionResponse, TranscriptionSegment};
+use crate::audio::buffer::SampleBuffer;
+
+/// Setting holding the chunk length in minutes; unset or "0" disables
+/// incremental transcription.
+pub const CHUNK_MINUTES_SETTING: &str = "incremental_batch_minutes";
+
+/// Seconds of the previous chunk sent again at the start of a chunk, to
+/// match its speakers.
+const OVERLAP_SECS: u64 = 30;
+
+/// A segment of the previous chunk ending this close to its end may have
+/// been cut: it is taken from the next chunk instead.
+const CUT_TOLERANCE_SECS: f64 = 1.0;
+
+struct ChunkTask {
+    /// Session time at which the chunk audio starts.
+    offset: f64,
+    /// Session time from which the chunk audio is new, after the overlap.
+    boundary: f64,
+    task: tokio::task::JoinHandle<Result<TranscriptionResponse, String>>,
+}
+
+pub struct IncrementalBatch {
+    api_key: String,
+    language: Option<String>,
+    sample_rate: u32,
+    chunk_samples: usize,
+    overlap_samples: usize,
+    /// Where the chunk files are written, named after `session_id`.
+    dir: PathBuf,
+    session_id: String,
+    /// Samples already sent, from the start of the recording.
+    dispatched: usize,
+    chunks: Vec<ChunkTask>,
+}
+
+impl IncrementalBatch {
+    pub fn new(
+        api_key: &str,
+        language: Option<&str>,
+        sample_rate: u32,
+        chunk_secs: u64,
+        dir: &Path,
+        session_id: &str,
+    ) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            language: language.map(str::to_string),
+            sample_rate,
+            chunk_samples: (chunk_secs * sample_rate as u64).max(1) as usize,
+            overlap_samples: (OVERLAP_SECS * sample_rate as u64) as usize,
+            dir: dir.to_path_buf(),
+            session_id: session_id.to_string(),
+            dispatched: 0,
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Sample from which the audio left after the last chunk must be read
+    /// for `finish`, overlap included.
+    pub fn tail_start(&self) -> usize {
+        self.overlap_start()
+    }
+
+    fn overlap_start(&self) -> usize {
+        self.dispatched.saturating_sub(self.overlap_samples)
+    }
+
+    /// Sends the next chunk once the recording has gone past it. Called
+    /// regularly from the session loop.
+    pub fn poll(&mut self, buffer: &Mutex<SampleBuffer>) {
+        let Ok(mut buffer) = buffer.lock() else {
+            return;
+        };
+        if buffer.len() < self.dispatched + self.chunk_samples {
+            return;
+        }
+        let start = self.overlap_start();
+        let mut samples = match buffer.read_from(start) {
+            Ok(samples) => samples,
+            Err(e) => {
+                eprintln!("[incremental] Failed to read chunk: {}", e);
+                return;
+            }
+        };
+        drop(buffer);
+        samples.truncate(self.dispatched - start + self.chunk_samples);
+        self.dispatch(samples, start);
+    }
+
+    /// Sends `samples`, which start at sample `start` of the recording.
+    fn dispatch(&mut self, samples: Vec<i16>, start: usize) {
+        let index = self.chunks.len();
+        let offset = start as f64 / self.sample_rate as f64;
+        let boundary = self.dispatched as f64 / self.sample_rate as f64;
+        self.dispatched = start + samples.len();
+        let path = self.dir.join(format!("{}.chunk{}.wav", self.session_id, index));
+        eprintln!(
+            "[incremental] Sending chunk {} of {} ({:.0}s from {:.0}s)",
+            index,
+            self.session_id,
+            samples.len() as f64 / self.sample_rate as f64,
+            offset
+        );
+        let task = tokio::spawn(transcribe_chunk(
+            self.api_key.clone(),
+            self.language.clone(),
+            path,
+            samples,
+            self.sample_rate,
+            offset,
+        ));
+        self.chunks.push(ChunkTask { offset, boundary, task });
+    }
+
+    /// Transcribes the remaining `tail` samples, read from `tail_start`,
+    /// and returns the whole transcription, once every chunk is done. Fails
+    /// if any chunk failed.
+    pub async fn finish(mut self, tail: Vec<i16>) -> Result<TranscriptionResponse, String> {
+        if tail.len() > self.dispatched - self.overlap_start() {
+            let start = self.overlap_start();
+            self.dispatch(tail, start);
+        }
+        let mut chunks = Vec::with_capacity(self.chunks.len());
+        for (index, chunk) in self.chunks.into_iter().enumerate() {
+            let response = chunk
+                .task
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| format!("partie {}: {}", index + 1, e))?;
+            chunks.push((chunk.offset, chunk.boundary, response));
+        }
+        Ok(merge(chunks))
+    }
+}
+
+async fn transcribe_chunk(
+    api_key: String,
+    language: Option<String>,
+    path: PathBuf,
+    samples: Vec<i16>,
+    sample_rate: u32,
+    offset: f64,
+) -> Result<TranscriptionResponse, String> {
+    let wav_path = path.clone();
+    tokio::task::spawn_blocking(move || {
+        crate::audio::store::save_wav(&wav_path, &samples, sample_rate).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let result = super::batch::transcribe_batch(&api_key, &path, true, language.as_deref()).await;
+    let _ = std::fs::remove_file(&path);
+    let mut response = result.map_err(|e| e.to_string())?;
+    for segment in &mut response.segments {
+        segment.start += offset;
+        segment.end += offset;
+    }
+    Ok(response)
+}
+
+/// Concatenates chunk transcriptions, given with their `offset` and
+/// `boundary`, in order. Each chunk replaces the previous ones from its
+/// boundary on, and from the start of a segment cut by it. The language is
+/// the one of the first chunk that reports it.
+fn merge(chunks: Vec<(f64, f64, TranscriptionResponse)>) -> TranscriptionResponse {
+    let language = chunks.iter().find_map(|(_, _, r)| r.language.clone());
+    let mut segments: Vec<TranscriptionSegment> = Vec::new();
+    let mut next_label = 0;
+    for (offset, boundary, response) in chunks {
+        let labels = match_speakers(&segments, &response.segments, boundary, &mut next_label);
+        let cut = segments
+            .last()
+            .filter(|s| s.end >= boundary - CUT_TOLERANCE_SECS)
+            .map_or(boundary, |s| s.start.clamp(offset, boundary));
+        segments.retain(|s| s.start < cut);
+        segments.extend(
+            response
+                .segments
+                .into_iter()
+                .filter(|s| (s.start + s.end) / 2.0 >= cut)
+                .map(|mut s| {
+                    s.speaker_id = s.speaker_id.map(|id| labels[&id].clone());
+                    s
+                }),
+        );
+    }
+    let text = segments
+        .iter()
+        .map(|s| s.text.trim())
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    TranscriptionResponse {
+        text,
+        language,
+        segments,
+    }
+}
+
+/// Session-wide label of each speaker of a chunk: the one of the merged
+/// segments it shares the most time with before `boundary`, one-to-one,
+/// or a new label.
+fn match_speakers(
+    merged: &[TranscriptionSegment],
+    chunk: &[TranscriptionSegment],
+    boundary: f64,
+    next_label: &mut usize,
+) -> HashMap<String, String> {
+    let mut overlaps: HashMap<(&str, &str), f64> = HashMap::new();
+    for segment in chunk.iter().filter(|s| s.start < boundary) {
+        let Some(speaker) = segment.speaker_id.as_deref() else {
+            continue;
+        };
+        for known in merged {
+            let Some(label) = known.speaker_id.as_deref() else {
+                continue;
+            };
+            let end = segment.end.min(boundary);
+            let shared = super::rolling::overlap(segment.start, end, known.start, known.end);
+            if shared > 0.0 {
+                *overlaps.entry((speaker, label)).or_default() += shared;
+            }
+        }
+    }
+    let mut pairs: Vec<((&str, &str), f64)> = overlaps.into_iter().collect();
+    pairs.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    // One-to-one, best overlaps first
+    let mut labels: HashMap<String, String> = HashMap::new();
+    let mut taken: Vec<&str> = Vec::new();
+    for ((speaker, known), _) in pairs {
+        if labels.contains_key(speaker) || taken.contains(&known) {
+            continue;
+        }
+        labels.insert(speaker.to_string(), known.to_string());
+        taken.push(known);
+    }
+    for speaker in chunk.iter().filter_map(|s| s.speaker_id.as_deref()) {
+        if !labels.contains_key(speaker) {
+            labels.insert(speaker.to_string(), format!("speaker_{}", *next_label));
+            *next_label += 1;
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(text: &str, start: f64, end: f64, speaker: &str) -> TranscriptionSegment {
+        TranscriptionSegment {
+            text: text.to_string(),
+            start,
+            end,
+            speaker_id: Some(speaker.to_string()),
+            language: None,
+        }
+    }
+
+    fn response(segments: Vec<TranscriptionSegment>, language: Option<&str>) -> TranscriptionResponse {
+        TranscriptionResponse {
+            text: String::new(),
+            language: language.map(str::to_string),
+            segments,
+        }
+    }
+
+    #[test]
+    fn test_merge_chunks() {
+        let merged = merge(vec![
+            (0.0, 0.0, response(vec![segment("Bonjour", 0.0, 1.0, "speaker_0")], None)),
+            (570.0, 600.0, response(vec![segment("Au revoir", 600.0, 601.0, "speaker_0")], Some("fr"))),
+        ]);
+        assert_eq!(merged.text, "Bonjour Au revoir");
+        assert_eq!(merged.language.as_deref(), Some("fr"));
+        assert_eq!(merged.segments.len(), 2);
+        assert_eq!(merged.segments[1].start, 600.0);
+    }
+
+    #[test]
+    fn test_merge_matches_speakers_across_chunks() {
+        let merged = merge(vec![
+            (
+                0.0,
+                0.0,
+                response(
+                    vec![
+                        segment("Bonjour", 0.0, 20.0, "speaker_0"),
+                        segment("Salut", 20.0, 40.0, "speaker_1"),
+                        segment("Alors", 40.0, 59.5, "speaker_0"),
+                    ],
+                    None,
+                ),
+            ),
+            // Overlaps the first chunk from 30s; the provider swapped the
+            // labels, re-transcribed the cut segment and a third speaker joins
+            (
+                30.0,
+                60.0,
+                response(
+                    vec![
+                        segment("Salut", 30.0, 40.0, "speaker_0"),
+                        segment("Alors on commence", 40.0, 62.0, "speaker_1"),
+                        segment("Merci", 62.0, 70.0, "speaker_0"),
+                        segment("Pardon", 70.0, 75.0, "speaker_2"),
+                    ],
+                    None,
+                ),
+            ),
+        ]);
+        let turns: Vec<(&str, &str)> = merged
+            .segments
+            .iter()
+            .map(|s| (s.text.as_str(), s.speaker_id.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                ("Bonjour", "speaker_0"),
+                ("Salut", "speaker_1"),
+                ("Alors on commence", "speaker_0"),
+                ("Merci", "speaker_1"),
+                ("Pardon", "speaker_2"),
+            ]
+        );
+    }
+}
@@ -1,3 +1,36 @@
 pub mod realtime;
+pub mod rolling;
 pub mod batch;
 pub mod chat;
+pub mod demo;
+pub mod incremental;
+pub mod pricing;
+
+/// Outcome of probing the Mistral API with a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStatus {
+    Valid,
+    /// The API answered but refused the key (401/403).
+    Rejected,
+    /// The API answered with another error status.
+    Unexpected(u16),
+}
+
+/// Lists the models with `api_key`: a cheap authenticated request that tells
+/// apart a bad key (`Ok(Rejected)`) from an unreachable API (`Err`).
+pub async fn check_api_key(api_key: &str) -> Result<KeyStatus, reqwest::Error> {
+    let response = reqwest::Client::new()
+        .get("https://api.mistral.ai/v1/models")
+        .bearer_auth(api_key)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await?;
+    let status = response.status();
+    Ok(if status.is_success() {
+        KeyStatus::Valid
+    } else if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        KeyStatus::Rejected
+    } else {
+        KeyStatus::Unexpected(status.as_u16())
+    })
+}
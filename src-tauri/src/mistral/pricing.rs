@@ -0,0 +1,105 @@
+//! Estimated cost of the API calls made on a recording, from Mistral's
+//! public prices in USD. Token counts are derived from the speech duration
+//! or the text length, so the figures are orders of magnitude, not quotes.
+
+use serde::Serialize;
+
+/// Tokens spoken per minute in a typical meeting (~150 words).
+const TOKENS_PER_MINUTE: f64 = 200.0;
+/// Average characters per token for French and English text.
+const CHARS_PER_TOKEN: f64 = 4.0;
+/// System prompt and formatting sent with every chat call.
+const PROMPT_TOKENS: u64 = 200;
+/// Transcript excerpt sent to `generate_title`.
+const TITLE_CONTEXT_TOKENS: u64 = 500;
+const TITLE_OUTPUT_TOKENS: u64 = 20;
+const SUMMARY_OUTPUT_TOKENS: u64 = 800;
+pub const DIGEST_OUTPUT_TOKENS: u64 = 1500;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CostEstimate {
+    /// Audio sent to the transcription endpoint.
+    pub audio_minutes: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Price per minute of audio of a transcription model.
+fn transcription_price_per_minute(model: &str) -> f64 {
+    if model.starts_with("voxtral-small") {
+        0.004
+    } else {
+        0.001
+    }
+}
+
+/// Input and output prices per million tokens of a chat model.
+fn chat_price_per_million(model: &str) -> (f64, f64) {
+    if model.starts_with("mistral-large") {
+        (2.0, 6.0)
+    } else if model.starts_with("mistral-medium") {
+        (0.4, 2.0)
+    } else {
+        (0.1, 0.3)
+    }
+}
+
+pub fn tokens_for_chars(chars: usize) -> u64 {
+    (chars as f64 / CHARS_PER_TOKEN).ceil() as u64
+}
+
+fn chat_cost(model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+    let (input_price, output_price) = chat_price_per_million(model);
+    (input_tokens as f64 * input_price + output_tokens as f64 * output_price) / 1_000_000.0
+}
+
+/// Batch transcription of `duration_secs` of audio, then the title and
+/// summary generated from the transcript.
+pub fn estimate_transcription(duration_secs: f64, transcription_model: &str, chat_model: &str) -> CostEstimate {
+    let audio_minutes = duration_secs.max(0.0) / 60.0;
+    let transcript_tokens = (audio_minutes * TOKENS_PER_MINUTE).ceil() as u64;
+    let input_tokens = 2 * PROMPT_TOKENS + transcript_tokens + transcript_tokens.min(TITLE_CONTEXT_TOKENS);
+    let output_tokens = TITLE_OUTPUT_TOKENS + SUMMARY_OUTPUT_TOKENS;
+    CostEstimate {
+        audio_minutes,
+        input_tokens,
+        output_tokens,
+        cost_usd: audio_minutes * transcription_price_per_minute(transcription_model)
+            + chat_cost(chat_model, input_tokens, output_tokens),
+    }
+}
+
+/// A single chat call over `input_chars` of text.
+pub fn estimate_chat(input_chars: usize, output_tokens: u64, chat_model: &str) -> CostEstimate {
+    let input_tokens = PROMPT_TOKENS + tokens_for_chars(input_chars);
+    CostEstimate {
+        audio_minutes: 0.0,
+        input_tokens,
+        output_tokens,
+        cost_usd: chat_cost(chat_model, input_tokens, output_tokens),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_transcription_of_an_hour() {
+        let estimate = estimate_transcription(3600.0, "voxtral-mini-latest", "mistral-small-latest");
+        assert_eq!(estimate.audio_minutes, 60.0);
+        assert_eq!(estimate.input_tokens, 400 + 12_000 + 500);
+        assert_eq!(estimate.output_tokens, 820);
+        // 60 min of audio dominates the chat calls
+        assert!((estimate.cost_usd - 0.0615).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_larger_chat_models_cost_more() {
+        let small = estimate_chat(40_000, DIGEST_OUTPUT_TOKENS, "mistral-small-latest");
+        let large = estimate_chat(40_000, DIGEST_OUTPUT_TOKENS, "mistral-large-latest");
+        assert_eq!(small.input_tokens, 10_200);
+        assert!(large.cost_usd > 10.0 * small.cost_usd);
+    }
+}
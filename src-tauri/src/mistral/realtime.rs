@@ -7,6 +7,14 @@ use tokio_tungstenite::tungstenite;
 const REALTIME_MODEL: &str = "voxtral-mini-transcribe-realtime-2602";
 const WS_BASE: &str = "wss://api.mistral.ai/v1/audio/transcriptions/realtime";
 
+/// Default duration of audio grouped into one `input_audio.append` message.
+pub const DEFAULT_BATCH_MS: u32 = 250;
+
+/// Chunks waiting for the WebSocket, a few seconds of audio. Beyond that the
+/// network is lagging and new chunks are dropped from the live transcript;
+/// they remain in the recording used by the batch pass.
+const AUDIO_QUEUE_CAPACITY: usize = 512;
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type")]
 pub enum TranscriptionEvent {
@@ -44,28 +52,116 @@ enum WsIncoming {
 }
 
 /// Messages sent from the audio loop to the WebSocket sender task.
-enum AudioMsg {
+pub(super) enum AudioMsg {
     Chunk(Vec<i16>),
     End,
 }
 
 /// Handle for sending audio to an active real-time transcription session.
 pub struct RealtimeHandle {
-    tx: mpsc::UnboundedSender<AudioMsg>,
+    tx: mpsc::Sender<AudioMsg>,
 }
 
 impl RealtimeHandle {
-    /// Send a chunk of i16 PCM samples to the transcription service.
-    pub fn send_audio(&self, samples: Vec<i16>) {
-        let _ = self.tx.send(AudioMsg::Chunk(samples));
+    /// A handle and the bounded queue it feeds.
+    pub(super) fn channel() -> (Self, mpsc::Receiver<AudioMsg>) {
+        let (tx, rx) = mpsc::channel(AUDIO_QUEUE_CAPACITY);
+        (Self { tx }, rx)
+    }
+
+    /// Queue a chunk of i16 PCM samples for the transcription service without
+    /// waiting. Returns `false` when the queue is full and the chunk was
+    /// dropped.
+    pub fn send_audio(&self, samples: Vec<i16>) -> bool {
+        !matches!(
+            self.tx.try_send(AudioMsg::Chunk(samples)),
+            Err(mpsc::error::TrySendError::Full(_))
+        )
+    }
+
+    /// Signal end of audio input, waiting for room in the queue.
+    pub async fn end_audio(&self) {
+        let _ = self.tx.send(AudioMsg::End).await;
+    }
+}
+
+/// Groups the small buffers delivered by the audio callbacks into frames of
+/// a fixed number of samples, so that each WebSocket message carries a
+/// meaningful amount of audio.
+struct ChunkBatcher {
+    frame_len: usize,
+    pending: Vec<i16>,
+}
+
+impl ChunkBatcher {
+    /// `batch_ms` of 16kHz audio per frame; 0 forwards every chunk as is.
+    fn new(batch_ms: u32) -> Self {
+        let frame_len = 16 * batch_ms as usize;
+        Self {
+            frame_len,
+            pending: Vec::with_capacity(frame_len),
+        }
     }
 
-    /// Signal end of audio input.
-    pub fn end_audio(&self) {
-        let _ = self.tx.send(AudioMsg::End);
+    /// Adds samples and returns the frames that are now complete.
+    fn push(&mut self, samples: &[i16]) -> Vec<Vec<i16>> {
+        if self.frame_len == 0 {
+            return vec![samples.to_vec()];
+        }
+        self.pending.extend_from_slice(samples);
+        let mut frames = Vec::new();
+        while self.pending.len() >= self.frame_len {
+            let rest = self.pending.split_off(self.frame_len);
+            frames.push(std::mem::replace(&mut self.pending, rest));
+        }
+        frames
+    }
+
+    /// Returns the incomplete frame left, if any.
+    fn flush(&mut self) -> Option<Vec<i16>> {
+        (!self.pending.is_empty()).then(|| std::mem::take(&mut self.pending))
     }
 }
 
+/// Turns 16kHz PCM frames into WebSocket messages, reusing its buffers
+/// from one frame to the next.
+struct AudioEncoder {
+    /// Send raw little-endian PCM as binary frames instead of base64 JSON.
+    binary: bool,
+    bytes: Vec<u8>,
+    message: String,
+}
+
+impl AudioEncoder {
+    fn new(binary: bool) -> Self {
+        Self {
+            binary,
+            bytes: Vec::new(),
+            message: String::new(),
+        }
+    }
+
+    fn encode(&mut self, samples: &[i16]) -> tungstenite::Message {
+        self.bytes.clear();
+        self.bytes.reserve(samples.len() * 2);
+        for &s in samples {
+            self.bytes.extend_from_slice(&s.to_le_bytes());
+        }
+        if self.binary {
+            return tungstenite::Message::Binary(self.bytes.clone().into());
+        }
+        // Base64 needs no JSON escaping, so the message is assembled directly
+        self.message.clear();
+        self.message.push_str(r#"{"type":"input_audio.append","audio":""#);
+        base64::engine::general_purpose::STANDARD.encode_string(&self.bytes, &mut self.message);
+        self.message.push_str(r#""}"#);
+        text_msg(self.message.clone())
+    }
+}
+
+/// How long the server gets to reject the binary probe frame.
+const BINARY_PROBE_MS: u64 = 500;
+
 /// Helper: build a tungstenite Text message from a string.
 fn text_msg(s: String) -> tungstenite::Message {
     tungstenite::Message::Text(s.into())
@@ -105,12 +201,20 @@ pub fn resample(samples: &[i16], from_rate: u32, to_rate: u32) -> Vec<i16> {
 ///
 /// `source_sample_rate` is the rate of audio you will send via `send_audio()`.
 /// Audio is resampled to 16kHz internally before being sent to the API.
+/// `language` pins the transcription language instead of auto-detecting it.
+/// `batch_ms` is the duration of audio sent per message (0 = one message
+/// per captured chunk). `binary_audio` asks for raw PCM binary frames,
+/// about a third smaller than base64 JSON; it is only used if the server
+/// accepts an empty probe frame, and JSON is sent otherwise.
 ///
 /// Returns a `RealtimeHandle` for sending audio and a receiver for
 /// transcription events. The WebSocket I/O runs in spawned tasks.
 pub async fn connect_realtime(
     api_key: &str,
     source_sample_rate: u32,
+    language: Option<&str>,
+    batch_ms: u32,
+    binary_audio: bool,
 ) -> Result<
     (
         RealtimeHandle,
@@ -171,7 +275,7 @@ pub async fn connect_realtime(
 
     // Always send 16kHz to Mistral (API requirement)
     let api_sample_rate: u32 = 16000;
-    let update = serde_json::json!({
+    let mut update = serde_json::json!({
         "type": "session.update",
         "session": {
             "audio_format": {
@@ -180,6 +284,9 @@ pub async fn connect_realtime(
             }
         }
     });
+    if let Some(lang) = language {
+        update["session"]["language"] = serde_json::Value::from(lang);
+    }
     ws_write
         .send(text_msg(update.to_string()))
         .await
@@ -218,39 +325,64 @@ pub async fn connect_realtime(
         api_sample_rate, source_sample_rate
     );
 
+    // Capability check: an empty binary frame carries no audio, so a server
+    // that accepts binary input stays silent while one that does not
+    // answers with an error
+    let mut binary = false;
+    if binary_audio {
+        ws_write
+            .send(tungstenite::Message::Binary(Vec::new().into()))
+            .await
+            .map_err(|e| format!("Failed to send binary probe: {}", e))?;
+        let probe = tokio::time::timeout(
+            std::time::Duration::from_millis(BINARY_PROBE_MS),
+            ws_read.next(),
+        )
+        .await;
+        match probe {
+            Err(_) => binary = true,
+            Ok(Some(Ok(msg))) => {
+                if let Some(text) = msg_text(&msg) {
+                    eprintln!("[realtime] << {}", text);
+                }
+                if matches!(msg, tungstenite::Message::Close(_)) {
+                    return Err("WebSocket closed after binary probe".into());
+                }
+            }
+            Ok(Some(Err(e))) => return Err(format!("WebSocket read error: {}", e).into()),
+            Ok(None) => return Err("WebSocket closed after binary probe".into()),
+        }
+        eprintln!(
+            "[realtime] Binary audio frames {}",
+            if binary { "accepted" } else { "rejected, using base64 JSON" }
+        );
+    }
+
     // Channels
-    let (audio_tx, mut audio_rx) = mpsc::unbounded_channel::<AudioMsg>();
+    let (handle, mut audio_rx) = RealtimeHandle::channel();
     let (event_tx, event_rx) = mpsc::unbounded_channel::<TranscriptionEvent>();
 
     // Sender task: reads audio messages, resamples if needed, forwards to WebSocket
     let src_rate = source_sample_rate;
     tokio::spawn(async move {
-        let b64 = base64::engine::general_purpose::STANDARD;
-        while let Some(msg) = audio_rx.recv().await {
+        let mut batcher = ChunkBatcher::new(batch_ms);
+        let mut encoder = AudioEncoder::new(binary);
+        'send: while let Some(msg) = audio_rx.recv().await {
             match msg {
                 AudioMsg::Chunk(samples) => {
                     // Resample to 16kHz if source rate differs
                     let resampled = resample(&samples, src_rate, 16000);
-
-                    // Convert i16 samples to little-endian bytes
-                    let mut bytes = Vec::with_capacity(resampled.len() * 2);
-                    for &s in &resampled {
-                        bytes.extend_from_slice(&s.to_le_bytes());
-                    }
-                    let encoded = b64.encode(&bytes);
-                    let json = serde_json::json!({
-                        "type": "input_audio.append",
-                        "audio": encoded
-                    });
-                    if ws_write
-                        .send(text_msg(json.to_string()))
-                        .await
-                        .is_err()
-                    {
-                        break;
+                    for frame in batcher.push(&resampled) {
+                        if ws_write.send(encoder.encode(&frame)).await.is_err() {
+                            break 'send;
+                        }
                     }
                 }
                 AudioMsg::End => {
+                    // The tail of the recording is shorter than a frame
+                    if let Some(frame) = batcher.flush() {
+                        let _ = ws_write.send(encoder.encode(&frame)).await;
+                    }
                     let json = serde_json::json!({"type": "input_audio.end"});
                     let _ = ws_write
                         .send(text_msg(json.to_string()))
@@ -316,7 +448,7 @@ pub async fn connect_realtime(
         }
     });
 
-    Ok((RealtimeHandle { tx: audio_tx }, event_rx))
+    Ok((handle, event_rx))
 }
 
 /// Stream transcription events from Mistral HTTP API (file upload).
@@ -339,7 +471,7 @@ pub async fn transcribe_stream(
         .mime_str("audio/wav")?;
 
     let form = reqwest::multipart::Form::new()
-        .text("model", "voxtral-mini-latest")
+        .text("model", super::batch::BATCH_MODEL)
         .text("stream", "true")
         .text("timestamp_granularities", "segment")
         .part("file", file_part);
@@ -447,6 +579,45 @@ mod tests {
         assert!(matches!(event, WsIncoming::SessionCreated { .. }));
     }
 
+    #[test]
+    fn test_chunk_batcher() {
+        // 10 ms = 160 samples per frame
+        let mut batcher = ChunkBatcher::new(10);
+        assert!(batcher.push(&[1; 100]).is_empty());
+        let frames = batcher.push(&[2; 300]);
+        assert_eq!(frames.len(), 2);
+        assert!(frames.iter().all(|f| f.len() == 160));
+        assert_eq!(frames[0][99..101], [1, 2]);
+        assert_eq!(batcher.flush().map(|f| f.len()), Some(80));
+        assert_eq!(batcher.flush(), None);
+
+        let mut passthrough = ChunkBatcher::new(0);
+        assert_eq!(passthrough.push(&[3; 7]), vec![vec![3; 7]]);
+        assert_eq!(passthrough.flush(), None);
+    }
+
+    #[test]
+    fn test_audio_encoder() {
+        let mut encoder = AudioEncoder::new(false);
+        let msg = encoder.encode(&[1, -1]);
+        let json: serde_json::Value = serde_json::from_str(&msg_text(&msg).unwrap()).unwrap();
+        assert_eq!(json["type"], "input_audio.append");
+        // Buffers are reset between frames
+        let msg = encoder.encode(&[1, -1]);
+        let again: serde_json::Value = serde_json::from_str(&msg_text(&msg).unwrap()).unwrap();
+        assert_eq!(json, again);
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(json["audio"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(bytes, vec![0x01, 0x00, 0xff, 0xff]);
+
+        let mut binary = AudioEncoder::new(true);
+        match binary.encode(&[1, -1]) {
+            tungstenite::Message::Binary(data) => assert_eq!(data.to_vec(), bytes),
+            other => panic!("Expected a binary frame, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_parse_ws_error() {
         let data =
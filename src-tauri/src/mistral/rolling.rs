@@ -0,0 +1,240 @@
+//! Rolling diarization: during a session, the last minutes of audio are
+//! periodically re-transcribed with diarization, and the speakers found are
+//! attributed to the overlapping live segments.
+//!
+//! Each window labels its speakers on its own ("speaker_0" may be someone
+//! else from one window to the next), so they are matched to the labels of
+//! the previous windows by the time they share with them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::batch::TranscriptionSegment;
+use crate::audio::buffer::SampleBuffer;
+use crate::db::Segment;
+
+/// Setting holding the window length in minutes; unset or "0" disables
+/// rolling diarization.
+pub const WINDOW_MINUTES_SETTING: &str = "rolling_diarization_minutes";
+
+/// Seconds between two windows.
+pub const INTERVAL_SECS: u64 = 120;
+
+/// A speaker turn on the session timeline, with a session-wide label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    pub start: f64,
+    pub end: f64,
+    pub speaker: String,
+}
+
+pub struct RollingDiarization {
+    api_key: String,
+    language: Option<String>,
+    sample_rate: u32,
+    window_samples: usize,
+    /// Where the window file is written, named after `session_id`.
+    dir: PathBuf,
+    session_id: String,
+    /// Buffer length at the last window, to skip paused stretches.
+    last_len: usize,
+    turns: Vec<Turn>,
+    next_label: usize,
+}
+
+impl RollingDiarization {
+    pub fn new(
+        api_key: &str,
+        language: Option<&str>,
+        sample_rate: u32,
+        window_secs: u64,
+        dir: &Path,
+        session_id: &str,
+    ) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            language: language.map(str::to_string),
+            sample_rate,
+            window_samples: (window_secs * sample_rate as u64).max(1) as usize,
+            dir: dir.to_path_buf(),
+            session_id: session_id.to_string(),
+            last_len: 0,
+            turns: Vec::new(),
+            next_label: 0,
+        }
+    }
+
+    /// Diarizes the last window of the recording and returns the speaker
+    /// turns known so far. `None` when nothing was recorded since the last
+    /// window.
+    pub async fn run_window(&mut self, buffer: &Mutex<SampleBuffer>) -> Result<Option<&[Turn]>, String> {
+        let (samples, offset) = {
+            let mut buffer = buffer.lock().map_err(|e| e.to_string())?;
+            let len = buffer.len();
+            if len == self.last_len {
+                return Ok(None);
+            }
+            self.last_len = len;
+            let from = len.saturating_sub(self.window_samples);
+            let samples = buffer.read_from(from).map_err(|e| e.to_string())?;
+            (samples, from as f64 / self.sample_rate as f64)
+        };
+
+        let path = self.dir.join(format!("{}.rolling.wav", self.session_id));
+        let wav_path = path.clone();
+        let sample_rate = self.sample_rate;
+        tokio::task::spawn_blocking(move || {
+            crate::audio::store::save_wav(&wav_path, &samples, sample_rate).map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        let result =
+            super::batch::transcribe_batch(&self.api_key, &path, true, self.language.as_deref()).await;
+        let _ = std::fs::remove_file(&path);
+        let response = result.map_err(|e| e.to_string())?;
+
+        self.reconcile(offset, &response.segments);
+        Ok(Some(&self.turns))
+    }
+
+    /// Renames the window speakers after the known ones they overlap the
+    /// most, new labels going to the others, then replaces the turns from
+    /// `offset` on with those of the window.
+    fn reconcile(&mut self, offset: f64, segments: &[TranscriptionSegment]) {
+        let window: Vec<(f64, f64, &str)> = segments
+            .iter()
+            .filter_map(|s| Some((s.start + offset, s.end + offset, s.speaker_id.as_deref()?)))
+            .collect();
+
+        let mut overlaps: HashMap<(&str, &str), f64> = HashMap::new();
+        for &(start, end, speaker) in &window {
+            for turn in &self.turns {
+                let shared = overlap(start, end, turn.start, turn.end);
+                if shared > 0.0 {
+                    *overlaps.entry((speaker, turn.speaker.as_str())).or_default() += shared;
+                }
+            }
+        }
+        let mut pairs: Vec<((&str, &str), f64)> = overlaps.into_iter().collect();
+        pairs.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        // One-to-one, best overlaps first
+        let mut labels: HashMap<&str, String> = HashMap::new();
+        let mut taken: Vec<&str> = Vec::new();
+        for ((speaker, known), _) in pairs {
+            if labels.contains_key(speaker) || taken.contains(&known) {
+                continue;
+            }
+            labels.insert(speaker, known.to_string());
+            taken.push(known);
+        }
+        for &(_, _, speaker) in &window {
+            if !labels.contains_key(speaker) {
+                labels.insert(speaker, format!("speaker_{}", self.next_label));
+                self.next_label += 1;
+            }
+        }
+
+        self.turns.retain(|t| t.end <= offset);
+        self.turns.extend(window.iter().map(|&(start, end, speaker)| Turn {
+            start,
+            end,
+            speaker: labels[speaker].clone(),
+        }));
+    }
+}
+
+pub(super) fn overlap(start_a: f64, end_a: f64, start_b: f64, end_b: f64) -> f64 {
+    (end_a.min(end_b) - start_a.max(start_b)).max(0.0)
+}
+
+/// Speaker of each live segment that differs from the one it has: the
+/// label of the turns it overlaps the most.
+pub fn attribute(segments: &[Segment], turns: &[Turn]) -> Vec<(i64, String)> {
+    segments
+        .iter()
+        .filter(|s| !s.is_diarized)
+        .filter_map(|s| {
+            let mut shares: HashMap<&str, f64> = HashMap::new();
+            for turn in turns {
+                let shared = overlap(s.start_time, s.end_time, turn.start, turn.end);
+                if shared > 0.0 {
+                    *shares.entry(turn.speaker.as_str()).or_default() += shared;
+                }
+            }
+            let (speaker, _) = shares
+                .into_iter()
+                .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(a.0)))?;
+            (s.speaker.as_deref() != Some(speaker)).then(|| (s.id, speaker.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f64, end: f64, speaker: &str) -> TranscriptionSegment {
+        TranscriptionSegment {
+            text: String::new(),
+            start,
+            end,
+            speaker_id: Some(speaker.to_string()),
+            language: None,
+        }
+    }
+
+    fn rolling() -> RollingDiarization {
+        RollingDiarization::new("key", None, 16000, 60, Path::new("."), "session")
+    }
+
+    #[test]
+    fn test_reconcile_keeps_labels_across_windows() {
+        let mut rolling = rolling();
+        rolling.reconcile(0.0, &[segment(0.0, 10.0, "speaker_0"), segment(10.0, 20.0, "speaker_1")]);
+        assert_eq!(rolling.turns[0].speaker, "speaker_0");
+        assert_eq!(rolling.turns[1].speaker, "speaker_1");
+
+        // Next window from 5s: the provider swapped the labels and a third
+        // speaker joins
+        rolling.reconcile(
+            5.0,
+            &[
+                segment(0.0, 5.0, "speaker_1"),
+                segment(5.0, 15.0, "speaker_0"),
+                segment(15.0, 20.0, "speaker_2"),
+            ],
+        );
+        let speakers: Vec<&str> = rolling.turns.iter().map(|t| t.speaker.as_str()).collect();
+        assert_eq!(speakers, vec!["speaker_0", "speaker_1", "speaker_2"]);
+        assert_eq!(rolling.turns[0].start, 5.0);
+        assert_eq!(rolling.turns[2].end, 25.0);
+    }
+
+    #[test]
+    fn test_attribute_live_segments() {
+        let turn = |start: f64, end: f64, speaker: &str| Turn {
+            start,
+            end,
+            speaker: speaker.to_string(),
+        };
+        let turns = vec![turn(0.0, 4.0, "speaker_0"), turn(4.0, 10.0, "speaker_1")];
+        let live = |id: i64, start: f64, end: f64, speaker: Option<&str>| Segment {
+            id,
+            session_id: "s".to_string(),
+            text: String::new(),
+            start_time: start,
+            end_time: end,
+            speaker: speaker.map(str::to_string),
+            is_diarized: false,
+            language: None,
+        };
+        let segments = vec![
+            live(1, 0.0, 3.0, None),
+            live(2, 3.0, 8.0, Some("speaker_1")),
+            live(3, 12.0, 14.0, None),
+        ];
+        assert_eq!(attribute(&segments, &turns), vec![(1, "speaker_0".to_string())]);
+    }
+}
@@ -0,0 +1,50 @@
+//! App passcode guarding protected sessions. Only an argon2 hash (PHC
+//! string) is stored, in the `passcode_hash` setting.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+
+/// Settings key holding the passcode hash.
+pub const PASSCODE_SETTING: &str = "passcode_hash";
+
+/// Shortest accepted passcode.
+pub const MIN_PASSCODE_LEN: usize = 4;
+
+pub fn hash_passcode(passcode: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(passcode.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Checks `passcode` against a stored hash; a malformed hash never matches.
+pub fn verify_passcode(passcode: &str, hash: &str) -> bool {
+    match PasswordHash::new(hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(passcode.as_bytes(), &parsed)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify() {
+        let hash = hash_passcode("1234").unwrap();
+        assert!(hash.starts_with("$argon2"));
+        assert!(verify_passcode("1234", &hash));
+        assert!(!verify_passcode("4321", &hash));
+        // Salted: same passcode, different hash
+        assert_ne!(hash, hash_passcode("1234").unwrap());
+    }
+
+    #[test]
+    fn test_verify_malformed_hash() {
+        assert!(!verify_passcode("1234", "not-a-hash"));
+    }
+}
@@ -0,0 +1,287 @@
+//! Permissions needed for capture: the Apple privacy permissions (TCC), the
+//! microphone for both modes and, on macOS, screen recording for
+//! ScreenCaptureKit in visio mode, and RECORD_AUDIO on Android, asked
+//! through the `microphone` plugin. Other platforms have no such gate and
+//! report `NotRequired`.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    /// The user has not been asked yet
+    NotDetermined,
+    /// Blocked by a device policy, the user cannot change it
+    Restricted,
+    NotRequired,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Permissions {
+    pub microphone: PermissionState,
+    pub screen_recording: PermissionState,
+}
+
+/// Current state of the permissions, without prompting.
+pub fn check(app: &tauri::AppHandle) -> Permissions {
+    Permissions {
+        microphone: platform::microphone(app),
+        screen_recording: platform::screen_recording(),
+    }
+}
+
+/// Prompts for the permissions not decided yet and opens the System Settings
+/// pane of those already denied (macOS only asks once), then re-checks.
+pub async fn request(app: &tauri::AppHandle) -> Permissions {
+    let current = check(app);
+    match current.microphone {
+        PermissionState::NotDetermined => {
+            platform::request_microphone(app).await;
+        }
+        PermissionState::Denied => platform::open_settings(app, "Privacy_Microphone"),
+        _ => {}
+    }
+    if current.screen_recording == PermissionState::Denied && !platform::request_screen_recording()
+    {
+        platform::open_settings(app, "Privacy_ScreenCapture");
+    }
+    check(app)
+}
+
+/// Fails with an actionable message when a permission needed by `mode` is
+/// missing, rather than letting the capture fail later. On Android, where
+/// the capture would silently record nothing, asks for the microphone
+/// first when it was never asked.
+pub async fn preflight(app: &tauri::AppHandle, mode: &str) -> Result<(), String> {
+    let mut permissions = check(app);
+    if !platform::PROMPTS_ON_CAPTURE && permissions.microphone == PermissionState::NotDetermined {
+        if platform::request_microphone(app).await {
+            permissions = check(app);
+        } else {
+            permissions.microphone = PermissionState::Denied;
+        }
+    }
+    if matches!(
+        permissions.microphone,
+        PermissionState::Denied | PermissionState::Restricted
+    ) {
+        return Err(format!(
+            "Acces au microphone refuse. Autorisez PopTranscribe dans {}.",
+            platform::MICROPHONE_SETTINGS
+        ));
+    }
+    if mode == "visio" && permissions.screen_recording == PermissionState::Denied {
+        // Shows the system prompt the first time; later the user has to go
+        // through System Settings
+        if !platform::request_screen_recording() {
+            return Err(
+                "Autorisation d'enregistrement de l'ecran requise pour capturer le son de la visio. Autorisez PopTranscribe dans Reglages Systeme > Confidentialite > Enregistrement de l'ecran, puis relancez l'application.".to_string(),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod platform {
+    use super::PermissionState;
+    use block2::RcBlock;
+    use objc2::runtime::{AnyClass, AnyObject, Bool};
+    use objc2::{class, msg_send};
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeAudio: *const AnyObject;
+    }
+
+    #[cfg(target_os = "macos")]
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    /// The first capture shows the system prompt.
+    pub const PROMPTS_ON_CAPTURE: bool = true;
+    pub const MICROPHONE_SETTINGS: &str = "Reglages Systeme > Confidentialite > Microphone";
+
+    fn capture_device() -> &'static AnyClass {
+        class!(AVCaptureDevice)
+    }
+
+    pub fn microphone(_app: &tauri::AppHandle) -> PermissionState {
+        // AVAuthorizationStatus
+        let status: isize = unsafe {
+            msg_send![capture_device(), authorizationStatusForMediaType: AVMediaTypeAudio]
+        };
+        match status {
+            0 => PermissionState::NotDetermined,
+            1 => PermissionState::Restricted,
+            2 => PermissionState::Denied,
+            _ => PermissionState::Granted,
+        }
+    }
+
+    pub async fn request_microphone(_app: &tauri::AppHandle) -> bool {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = std::sync::Mutex::new(Some(tx));
+        let handler = RcBlock::new(move |granted: Bool| {
+            if let Some(tx) = tx.lock().ok().and_then(|mut tx| tx.take()) {
+                let _ = tx.send(granted.as_bool());
+            }
+        });
+        unsafe {
+            let _: () = msg_send![
+                capture_device(),
+                requestAccessForMediaType: AVMediaTypeAudio,
+                completionHandler: &*handler
+            ];
+        }
+        rx.await.unwrap_or(false)
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn screen_recording() -> PermissionState {
+        // The API cannot tell "never asked" from "refused"
+        if unsafe { CGPreflightScreenCaptureAccess() } {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn request_screen_recording() -> bool {
+        unsafe { CGRequestScreenCaptureAccess() }
+    }
+
+    #[cfg(target_os = "ios")]
+    pub fn screen_recording() -> PermissionState {
+        PermissionState::NotRequired
+    }
+
+    #[cfg(target_os = "ios")]
+    pub fn request_screen_recording() -> bool {
+        true
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn open_settings(_app: &tauri::AppHandle, pane: &str) {
+        let url = format!(
+            "x-apple.systempreferences:com.apple.preference.security?{}",
+            pane
+        );
+        if let Err(e) = std::process::Command::new("open").arg(url).spawn() {
+            eprintln!("[permissions] Failed to open System Settings: {}", e);
+        }
+    }
+
+    /// iOS has no deep link to a privacy pane; the app's own settings page
+    /// lists its permissions.
+    #[cfg(target_os = "ios")]
+    pub fn open_settings(_app: &tauri::AppHandle, _pane: &str) {
+        let url: *mut AnyObject = unsafe {
+            let string: *mut AnyObject = msg_send![
+                class!(NSString),
+                stringWithUTF8String: c"app-settings:".as_ptr()
+            ];
+            msg_send![class!(NSURL), URLWithString: string]
+        };
+        let application: *mut AnyObject = unsafe { msg_send![class!(UIApplication), sharedApplication] };
+        let options: *mut AnyObject = unsafe { msg_send![class!(NSDictionary), dictionary] };
+        unsafe {
+            let _: () = msg_send![
+                application,
+                openURL: url,
+                options: options,
+                completionHandler: std::ptr::null::<block2::Block<dyn Fn(Bool)>>()
+            ];
+        }
+    }
+}
+
+#[cfg(target_os = "android")]
+mod platform {
+    use super::PermissionState;
+    use tauri::plugin::PermissionState as AndroidState;
+    use tauri_plugin_microphone::MicrophoneExt;
+
+    /// Android never prompts for a runtime permission on its own.
+    pub const PROMPTS_ON_CAPTURE: bool = false;
+    pub const MICROPHONE_SETTINGS: &str = "Parametres > Applications > PopTranscribe > Autorisations";
+
+    fn to_state(state: AndroidState) -> PermissionState {
+        match state {
+            AndroidState::Granted => PermissionState::Granted,
+            // Refused for good: only the app settings can grant it
+            AndroidState::Denied => PermissionState::Denied,
+            _ => PermissionState::NotDetermined,
+        }
+    }
+
+    pub fn microphone(app: &tauri::AppHandle) -> PermissionState {
+        match app.microphone().state() {
+            Ok(state) => to_state(state),
+            Err(e) => {
+                eprintln!("[permissions] Microphone state unavailable: {}", e);
+                PermissionState::NotDetermined
+            }
+        }
+    }
+
+    pub async fn request_microphone(app: &tauri::AppHandle) -> bool {
+        // Waits for the user's answer
+        let app = app.clone();
+        let state = tokio::task::spawn_blocking(move || app.microphone().request()).await;
+        match state {
+            Ok(Ok(state)) => to_state(state) == PermissionState::Granted,
+            Ok(Err(e)) => {
+                eprintln!("[permissions] Microphone request failed: {}", e);
+                false
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn screen_recording() -> PermissionState {
+        PermissionState::NotRequired
+    }
+
+    pub fn request_screen_recording() -> bool {
+        true
+    }
+
+    pub fn open_settings(app: &tauri::AppHandle, _pane: &str) {
+        if let Err(e) = app.microphone().open_settings() {
+            eprintln!("[permissions] Failed to open the app settings: {}", e);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "ios", target_os = "android")))]
+mod platform {
+    use super::PermissionState;
+
+    pub const PROMPTS_ON_CAPTURE: bool = true;
+    pub const MICROPHONE_SETTINGS: &str = "les reglages du systeme";
+
+    pub fn microphone(_app: &tauri::AppHandle) -> PermissionState {
+        PermissionState::NotRequired
+    }
+
+    pub async fn request_microphone(_app: &tauri::AppHandle) -> bool {
+        true
+    }
+
+    pub fn screen_recording() -> PermissionState {
+        PermissionState::NotRequired
+    }
+
+    pub fn request_screen_recording() -> bool {
+        true
+    }
+
+    pub fn open_settings(_app: &tauri::AppHandle, _pane: &str) {}
+}
@@ -0,0 +1,66 @@
+//! System sleep detection. There is no portable suspend notification, so a
+//! sleep is inferred when the wall clock jumps ahead of the monotonic clock
+//! (which stops while suspended on macOS and Linux) or when a loop that
+//! normally ticks every few milliseconds was frozen for a long time.
+
+use std::time::{Duration, Instant, SystemTime};
+
+/// Gaps shorter than this are regular scheduling jitter.
+const SLEEP_GAP: Duration = Duration::from_secs(5);
+
+/// Polled from a frequently running loop; `check()` reports a sleep once
+/// the loop runs again after wake-up.
+pub struct SleepDetector {
+    last_instant: Instant,
+    last_wall: SystemTime,
+}
+
+impl Default for SleepDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SleepDetector {
+    pub fn new() -> Self {
+        Self {
+            last_instant: Instant::now(),
+            last_wall: SystemTime::now(),
+        }
+    }
+
+    /// Returns how long the system slept since the previous call, if it did.
+    pub fn check(&mut self) -> Option<Duration> {
+        let now_instant = Instant::now();
+        let now_wall = SystemTime::now();
+        let mono_delta = now_instant.duration_since(self.last_instant);
+        // A wall clock set backwards is not a sleep
+        let wall_delta = now_wall.duration_since(self.last_wall).unwrap_or_default();
+        self.last_instant = now_instant;
+        self.last_wall = now_wall;
+        slept_for(wall_delta, mono_delta)
+    }
+}
+
+fn slept_for(wall_delta: Duration, mono_delta: Duration) -> Option<Duration> {
+    let frozen = mono_delta.max(wall_delta.saturating_sub(mono_delta));
+    (wall_delta >= SLEEP_GAP && frozen >= SLEEP_GAP).then_some(wall_delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slept_for() {
+        let ms = Duration::from_millis;
+        // Regular tick
+        assert_eq!(slept_for(ms(10), ms(10)), None);
+        // Monotonic clock stopped during an hour of sleep
+        assert_eq!(slept_for(ms(3_600_000), ms(15)), Some(ms(3_600_000)));
+        // Loop frozen with both clocks running (Windows)
+        assert_eq!(slept_for(ms(60_000), ms(60_000)), Some(ms(60_000)));
+        // Wall clock moved backwards
+        assert_eq!(slept_for(Duration::ZERO, ms(10)), None);
+    }
+}
@@ -0,0 +1,93 @@
+//! Profanity filtering of stored transcripts and exports, enabled by the
+//! `profanity_filter` setting: "mask" keeps the first letter of a word
+//! ("m****"), "remove" drops it.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+pub const PROFANITY_FILTER_SETTING: &str = "profanity_filter";
+
+/// Filtered words, French and English, lowercase. Inflected forms are
+/// listed since matching is on whole words.
+const WORDS: &[&str] = &[
+    "merde", "merdes", "putain", "connard", "connards", "connasse", "salaud", "salope", "encule",
+    "enculé", "enculer", "chier", "con", "conne", "foutre", "bite", "couilles", "pute", "putes",
+    "batard", "bâtard", "nique", "niquer", "emmerde", "emmerder", "fuck", "fucking", "fucked",
+    "shit", "bullshit", "asshole", "bitch", "bastard", "dick", "cunt", "motherfucker",
+];
+
+fn words_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        let words: Vec<String> = WORDS.iter().map(|w| regex::escape(w)).collect();
+        Regex::new(&format!(r"(?i)\b(?:{})\b", words.join("|"))).expect("escaped words")
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfanityFilter {
+    Mask,
+    Remove,
+}
+
+impl ProfanityFilter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mask" => Some(Self::Mask),
+            "remove" => Some(Self::Remove),
+            _ => None,
+        }
+    }
+
+    /// The configured filter, `None` when disabled.
+    pub fn from_settings(db: &crate::db::Database) -> Option<Self> {
+        db.get_setting(PROFANITY_FILTER_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|name| Self::from_name(&name))
+    }
+
+    pub fn apply(self, text: &str) -> String {
+        let re = words_regex();
+        if !re.is_match(text) {
+            return text.to_string();
+        }
+        match self {
+            Self::Mask => re
+                .replace_all(text, |caps: &regex::Captures| {
+                    let mut chars = caps[0].chars();
+                    let first = chars.next().map(String::from).unwrap_or_default();
+                    first + &"*".repeat(chars.count())
+                })
+                .into_owned(),
+            Self::Remove => {
+                let removed = re.replace_all(text, "");
+                // Spaces left around the removed word
+                let mut result = String::with_capacity(removed.len());
+                for (i, line) in removed.split('\n').enumerate() {
+                    if i > 0 {
+                        result.push('\n');
+                    }
+                    result.push_str(&line.split_whitespace().collect::<Vec<_>>().join(" "));
+                }
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profanity_filter() {
+        assert_eq!(ProfanityFilter::Mask.apply("Oh merde, le build a casse"), "Oh m****, le build a casse");
+        assert_eq!(ProfanityFilter::Mask.apply("What the FUCK"), "What the F***");
+        assert_eq!(ProfanityFilter::Remove.apply("C'est  putain de  lent\nShit happens"), "C'est de lent\nhappens");
+        // Whole words only
+        assert_eq!(ProfanityFilter::Mask.apply("Le contrat est conclu"), "Le contrat est conclu");
+        assert_eq!(ProfanityFilter::from_name("off"), None);
+    }
+}
@@ -0,0 +1,107 @@
+//! Punctuation and casing restoration of live segments, which arrive with
+//! weaker punctuation than the batch output. Enabled by the
+//! `live_punctuation` setting: "rules" applies local rules, "ai" asks
+//! Mistral and falls back to the rules.
+
+pub const LIVE_PUNCTUATION_SETTING: &str = "live_punctuation";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PunctuationMode {
+    Rules,
+    Ai,
+}
+
+impl PunctuationMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rules" => Some(Self::Rules),
+            "ai" => Some(Self::Ai),
+            _ => None,
+        }
+    }
+
+    /// The configured mode, `None` when disabled.
+    pub fn from_settings(db: &crate::db::Database) -> Option<Self> {
+        db.get_setting(LIVE_PUNCTUATION_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|name| Self::from_name(&name))
+    }
+}
+
+fn ends_sentence(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…')
+}
+
+/// Rule-based restoration: single spaces, no space before "," and ".",
+/// capital letters at the start of sentences and a final period.
+pub fn restore(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 1);
+    let mut capitalize = true;
+    for word in text.split_whitespace() {
+        // Punctuation split from its word: " ,"
+        let attached = word.starts_with([',', '.']);
+        if !result.is_empty() && !attached {
+            result.push(' ');
+        }
+        let mut chars = word.chars();
+        if capitalize {
+            // Capitalized at the first letter, after any opening quote
+            for c in chars.by_ref() {
+                if c.is_alphabetic() {
+                    result.extend(c.to_uppercase());
+                    break;
+                }
+                result.push(c);
+            }
+        }
+        result.extend(chars);
+        let ended = result
+            .trim_end_matches(['"', '»', ')', ' '])
+            .chars()
+            .last()
+            .is_some_and(ends_sentence);
+        // A word without letters ("«", "-") leaves the next one to
+        // capitalize
+        capitalize = ended || (capitalize && !word.chars().any(char::is_alphanumeric));
+    }
+    if result.chars().last().is_some_and(|c| c.is_alphanumeric()) {
+        result.push('.');
+    }
+    result
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether two texts have the same words, whatever their punctuation and
+/// casing: an AI restoration that reworded the segment is discarded.
+pub fn same_words(a: &str, b: &str) -> bool {
+    words(a) == words(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_punctuation() {
+        assert_eq!(
+            restore("  bonjour a tous , on commence ? oui   allons-y"),
+            "Bonjour a tous, on commence ? Oui allons-y."
+        );
+        assert_eq!(restore("\"ok\" dit-il. « voila »"), "\"Ok\" dit-il. « Voila »");
+        assert_eq!(restore("Deja correct."), "Deja correct.");
+        assert_eq!(restore("   "), "");
+    }
+
+    #[test]
+    fn test_same_words() {
+        assert!(same_words("bonjour a tous on commence", "Bonjour a tous, on commence ?"));
+        assert!(!same_words("bonjour a tous", "Bonjour a toutes."));
+    }
+}
@@ -0,0 +1,119 @@
+//! Anonymization of transcripts for sharing: emails, phone numbers and the
+//! names of people are replaced with placeholders, in a copy of the text.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+pub const EMAIL_PLACEHOLDER: &str = "[EMAIL]";
+pub const PHONE_PLACEHOLDER: &str = "[TELEPHONE]";
+
+/// Digits in a phone number, with the country code (E.164 allows 15).
+const PHONE_DIGITS: std::ops::RangeInclusive<usize> = 8..=15;
+
+fn email_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+").expect("valid email regex"))
+}
+
+/// Candidate phone numbers: digits with the usual separators, checked for
+/// their digit count afterwards so that dates and amounts are left alone.
+fn phone_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\+?\(?\d[\d\s.()-]{6,}\d").expect("valid phone regex"))
+}
+
+/// Replaces emails and phone numbers.
+pub fn redact_contacts(text: &str) -> String {
+    let text = email_regex().replace_all(text, EMAIL_PLACEHOLDER);
+    phone_regex()
+        .replace_all(&text, |caps: &regex::Captures| {
+            let number = &caps[0];
+            let digits = number.chars().filter(char::is_ascii_digit).count();
+            if PHONE_DIGITS.contains(&digits) {
+                PHONE_PLACEHOLDER.to_string()
+            } else {
+                number.to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Replaces contacts and a set of names. Each distinct name gets its own
+/// "[PERSONNE n]" placeholder, numbered in the order the names were given,
+/// so that who said what stays readable.
+pub struct Redactor {
+    /// Lowercased name -> placeholder.
+    names: Vec<(String, String)>,
+    pattern: Option<Regex>,
+}
+
+impl Redactor {
+    pub fn new<I: IntoIterator<Item = String>>(names: I) -> Self {
+        let mut known: Vec<(String, String)> = Vec::new();
+        for name in names {
+            let name = name.trim().to_lowercase();
+            if name.chars().count() < 2 || known.iter().any(|(n, _)| *n == name) {
+                continue;
+            }
+            let placeholder = format!("[PERSONNE {}]", known.len() + 1);
+            known.push((name, placeholder));
+        }
+        // Longest first, so that "Marie Curie" wins over "Marie"
+        let mut alternatives: Vec<&str> = known.iter().map(|(n, _)| n.as_str()).collect();
+        alternatives.sort_by_key(|n| std::cmp::Reverse(n.chars().count()));
+        let pattern = (!alternatives.is_empty()).then(|| {
+            let escaped: Vec<String> = alternatives.iter().map(|n| regex::escape(n)).collect();
+            Regex::new(&format!(r"(?i)\b(?:{})\b", escaped.join("|"))).expect("escaped names")
+        });
+        Self { names: known, pattern }
+    }
+
+    /// Placeholder of a name, used for speaker labels.
+    pub fn placeholder(&self, name: &str) -> Option<&str> {
+        let name = name.trim().to_lowercase();
+        self.names
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, placeholder)| placeholder.as_str())
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let text = redact_contacts(text);
+        match self.pattern {
+            Some(ref pattern) => pattern
+                .replace_all(&text, |caps: &regex::Captures| {
+                    self.placeholder(&caps[0]).unwrap_or("[PERSONNE]").to_string()
+                })
+                .into_owned(),
+            None => text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_contacts() {
+        assert_eq!(
+            redact_contacts("Ecris a marie.curie@acme.fr ou appelle le 06 12 34 56 78."),
+            "Ecris a [EMAIL] ou appelle le [TELEPHONE]."
+        );
+        assert_eq!(redact_contacts("Rappel: +33 (1) 23 45 67 89"), "Rappel: [TELEPHONE]");
+        // Dates and amounts are not phone numbers
+        assert_eq!(redact_contacts("Le 12/03/2025, budget 15 000"), "Le 12/03/2025, budget 15 000");
+    }
+
+    #[test]
+    fn test_redactor_names() {
+        let redactor = Redactor::new(vec!["Marie".to_string(), "Paul Martin".to_string(), "marie".to_string()]);
+        assert_eq!(redactor.placeholder("marie"), Some("[PERSONNE 1]"));
+        assert_eq!(
+            redactor.redact("Paul Martin et MARIE arrivent, pas Mariette."),
+            "[PERSONNE 2] et [PERSONNE 1] arrivent, pas Mariette."
+        );
+        assert_eq!(Redactor::new(Vec::new()).redact("Bonjour Marie"), "Bonjour Marie");
+    }
+}
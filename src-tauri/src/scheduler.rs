@@ -0,0 +1,140 @@
+//! Runs scheduled recordings: starts a session at the scheduled time and
+//! stops it once its duration has elapsed, with system notifications.
+
+use std::time::Duration;
+
+use chrono::Local;
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::app_state::AppState;
+use crate::db::ScheduledRecording;
+
+/// How often schedules are checked.
+const CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Starts missed by more than this (app closed, machine asleep) are
+/// skipped rather than recorded late.
+const MISSED_GRACE_MINUTES: i64 = 10;
+
+/// Scheduler loop, spawned once at startup.
+pub async fn run(app: tauri::AppHandle) {
+    loop {
+        if let Err(e) = run_due(&app).await {
+            eprintln!("[scheduler] {}", e);
+        }
+        tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+    }
+}
+
+fn notify(app: &tauri::AppHandle, title: &str, body: &str) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("[scheduler] Notification failed: {}", e);
+    }
+}
+
+async fn run_due(app: &tauri::AppHandle) -> Result<(), String> {
+    let now = Local::now();
+    let state = app.state::<AppState>();
+    let due: Vec<ScheduledRecording> = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.list_scheduled_recordings()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|s| s.is_due(now))
+            .collect()
+    };
+
+    for schedule in due {
+        {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            db.complete_scheduled_run(&schedule, now).map_err(|e| e.to_string())?;
+        }
+
+        let missed = schedule
+            .start_time()
+            .map(|start| now - start > chrono::Duration::minutes(MISSED_GRACE_MINUTES))
+            .unwrap_or(true);
+        if missed {
+            notify(
+                app,
+                "Enregistrement programme manque",
+                &format!("\"{}\" n'a pas pu demarrer a l'heure prevue", schedule.title),
+            );
+            continue;
+        }
+
+        let busy = state
+            .active_session
+            .lock()
+            .map(|active| active.is_some())
+            .unwrap_or(true);
+        if busy {
+            notify(
+                app,
+                "Enregistrement programme ignore",
+                &format!("\"{}\": une session est deja en cours", schedule.title),
+            );
+            continue;
+        }
+
+        match start_scheduled(app, &schedule).await {
+            Ok(session_id) => {
+                notify(
+                    app,
+                    "Enregistrement demarre",
+                    &format!("\"{}\" pour {} min", schedule.title, schedule.duration_minutes),
+                );
+                let _ = app.emit(
+                    "scheduled-recording-started",
+                    serde_json::json!({ "schedule_id": schedule.id, "session_id": session_id }),
+                );
+                spawn_scheduled_stop(app, session_id, &schedule);
+            }
+            Err(e) => notify(
+                app,
+                "Enregistrement programme impossible",
+                &format!("\"{}\": {}", schedule.title, e),
+            ),
+        }
+    }
+    Ok(())
+}
+
+async fn start_scheduled(app: &tauri::AppHandle, schedule: &ScheduledRecording) -> Result<String, String> {
+    let session_id = crate::commands::start_session(
+        schedule.mode.clone(),
+        Some(schedule.title.clone()),
+        None,
+        None,
+        app.clone(),
+        app.state(),
+    )
+    .await?;
+    let state = app.state::<AppState>();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.update_session_title(&session_id, &schedule.title)
+        .map_err(|e| e.to_string())?;
+    Ok(session_id)
+}
+
+/// Stops the session after the scheduled duration, unless it was already
+/// stopped by hand.
+fn spawn_scheduled_stop(app: &tauri::AppHandle, session_id: String, schedule: &ScheduledRecording) {
+    let app = app.clone();
+    let duration = Duration::from_secs(schedule.duration_minutes as u64 * 60);
+    let title = schedule.title.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let still_active = app
+            .state::<AppState>()
+            .active_session
+            .lock()
+            .map(|active| active.as_ref().map(|s| s.id == session_id).unwrap_or(false))
+            .unwrap_or(false);
+        if still_active {
+            crate::commands::spawn_auto_stop(&app, &session_id, "scheduled");
+            notify(&app, "Enregistrement termine", &format!("\"{}\" est en cours de traitement", title));
+        }
+    });
+}
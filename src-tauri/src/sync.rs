@@ -0,0 +1,322 @@
+//! Sync of sessions between devices through a shared folder (Dropbox,
+//! iCloud Drive, a network share...) set in the `sync_folder` setting.
+//!
+//! Each session is a JSON file of the folder, a tombstone once deleted. A
+//! sync compares every session with its file and keeps the side changed
+//! last. Recordings stay on the device that made them; passcode-protected
+//! sessions are never written to the folder, and the copy of a session
+//! protected after it was synced is replaced with a tombstone.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::{Database, SessionStamp, SyncedSession};
+
+pub const SYNC_FOLDER_SETTING: &str = "sync_folder";
+
+/// Seconds between two background syncs.
+pub const SYNC_INTERVAL_SECS: u64 = 300;
+
+const SESSIONS_DIR: &str = "sessions";
+
+/// Version of the session files; newer files are left alone.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct SyncFile {
+    format: u32,
+    updated_at: String,
+    deleted: bool,
+    session: Option<SyncedSession>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Action {
+    Push,
+    Pull,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct SyncReport {
+    /// Local changes written to the folder.
+    pub pushed: usize,
+    /// Sessions created or updated from the folder.
+    pub pulled: usize,
+    /// Sessions deleted because they were deleted on another device.
+    pub deleted: usize,
+}
+
+/// The configured folder, `None` when sync is disabled.
+pub fn folder(db: &Database) -> Option<PathBuf> {
+    db.get_setting(SYNC_FOLDER_SETTING)
+        .ok()
+        .flatten()
+        .filter(|f| !f.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Orders change times, RFC 3339 with or without fractional seconds.
+fn is_newer(a: &str, b: &str) -> bool {
+    match (
+        chrono::DateTime::parse_from_rfc3339(a),
+        chrono::DateTime::parse_from_rfc3339(b),
+    ) {
+        (Ok(a), Ok(b)) => a > b,
+        _ => a > b,
+    }
+}
+
+/// What to do with each session known on either side.
+fn plan(local: &[SessionStamp], remote: &[SessionStamp]) -> Vec<(String, Action)> {
+    let remote: HashMap<&str, &SessionStamp> = remote.iter().map(|r| (r.session_id.as_str(), r)).collect();
+    let mut actions = Vec::new();
+    for stamp in local {
+        match remote.get(stamp.session_id.as_str()) {
+            None => actions.push((stamp.session_id.clone(), Action::Push)),
+            Some(r) if is_newer(&stamp.updated_at, &r.updated_at) => {
+                actions.push((stamp.session_id.clone(), Action::Push))
+            }
+            Some(r) if is_newer(&r.updated_at, &stamp.updated_at) => {
+                actions.push((stamp.session_id.clone(), Action::Pull))
+            }
+            Some(_) => {}
+        }
+    }
+    for stamp in remote.values() {
+        // Deleted before this device ever saw it
+        if !stamp.deleted && !local.iter().any(|l| l.session_id == stamp.session_id) {
+            actions.push((stamp.session_id.clone(), Action::Pull));
+        }
+    }
+    actions
+}
+
+fn read_file(path: &Path) -> Option<SyncFile> {
+    let file: SyncFile = serde_json::from_slice(&std::fs::read(path).ok()?)
+        .inspect_err(|e| eprintln!("[sync] Ignored {}: {}", path.display(), e))
+        .ok()?;
+    (file.format <= FORMAT_VERSION).then_some(file)
+}
+
+/// Written next to its destination then renamed, so that the folder's own
+/// sync client never uploads half a file.
+fn write_file(path: &Path, file: &SyncFile) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(file).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| format!("Erreur ecriture sync: {}", e))
+}
+
+/// Merges the database with the session files of `folder`. `skip` is a
+/// session left out, the one being recorded.
+pub fn sync(db: &Mutex<Database>, folder: &Path, skip: Option<&str>) -> Result<SyncReport, String> {
+    if !folder.is_dir() {
+        return Err(format!("Dossier de synchronisation introuvable: {}", folder.display()));
+    }
+    let dir = folder.join(SESSIONS_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Erreur creation dossier: {}", e))?;
+
+    let mut files: HashMap<String, SyncFile> = HashMap::new();
+    for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|s| s.to_str()).map(str::to_string) else {
+            continue;
+        };
+        match read_file(&path) {
+            Some(file) if file.session.as_ref().is_none_or(|s| s.session.id == id) => {
+                files.insert(id, file);
+            }
+            _ => {}
+        }
+    }
+    let remote: Vec<SessionStamp> = files
+        .iter()
+        .map(|(id, file)| SessionStamp {
+            session_id: id.clone(),
+            updated_at: file.updated_at.clone(),
+            deleted: file.deleted,
+        })
+        .collect();
+
+    let (local, protected) = {
+        let db = db.lock().map_err(|e| e.to_string())?;
+        let local = db.list_session_stamps().map_err(|e| e.to_string())?;
+        (local, db.protected_session_ids().map_err(|e| e.to_string())?)
+    };
+    let deleted: HashMap<&str, &str> = local
+        .iter()
+        .filter(|s| s.deleted)
+        .map(|s| (s.session_id.as_str(), s.updated_at.as_str()))
+        .collect();
+
+    let mut report = SyncReport::default();
+    // Sessions protected since they were written: their content must not
+    // stay in the folder
+    for stamp in local.iter().filter(|s| protected.contains(&s.session_id)) {
+        let shared = files.get(&stamp.session_id).is_some_and(|f| f.session.is_some());
+        if !shared || Some(stamp.session_id.as_str()) == skip {
+            continue;
+        }
+        let tombstone = SyncFile {
+            format: FORMAT_VERSION,
+            updated_at: stamp.updated_at.clone(),
+            deleted: true,
+            session: None,
+        };
+        write_file(&dir.join(format!("{}.json", stamp.session_id)), &tombstone)?;
+        report.pushed += 1;
+    }
+    for (id, action) in plan(&local, &remote) {
+        if Some(id.as_str()) == skip || protected.contains(&id) {
+            continue;
+        }
+        let path = dir.join(format!("{}.json", id));
+        match action {
+            Action::Push => {
+                let file = match deleted.get(id.as_str()) {
+                    Some(deleted_at) => SyncFile {
+                        format: FORMAT_VERSION,
+                        updated_at: deleted_at.to_string(),
+                        deleted: true,
+                        session: None,
+                    },
+                    None => {
+                        let db = db.lock().map_err(|e| e.to_string())?;
+                        let synced = db.get_synced_session(&id).map_err(|e| e.to_string())?;
+                        SyncFile {
+                            format: FORMAT_VERSION,
+                            updated_at: synced.updated_at.clone(),
+                            deleted: false,
+                            session: Some(synced),
+                        }
+                    }
+                };
+                write_file(&path, &file)?;
+                report.pushed += 1;
+            }
+            Action::Pull => {
+                let Some(file) = files.get(&id) else { continue };
+                let db = db.lock().map_err(|e| e.to_string())?;
+                match file.session {
+                    Some(ref synced) if !file.deleted => {
+                        db.apply_synced_session(synced).map_err(|e| e.to_string())?;
+                        report.pulled += 1;
+                    }
+                    _ => {
+                        if !deleted.contains_key(id.as_str()) {
+                            report.deleted += 1;
+                        }
+                        db.apply_session_deletion(&id, &file.updated_at)
+                            .map_err(|e| e.to_string())?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamp(id: &str, updated_at: &str, deleted: bool) -> SessionStamp {
+        SessionStamp {
+            session_id: id.to_string(),
+            updated_at: updated_at.to_string(),
+            deleted,
+        }
+    }
+
+    #[test]
+    fn test_plan_last_write_wins() {
+        let local = vec![
+            stamp("mine", "2025-03-01T10:00:00+00:00", false),
+            stamp("edited-here", "2025-03-02T10:00:00.500Z", false),
+            stamp("edited-there", "2025-03-01T10:00:00+00:00", false),
+            stamp("same", "2025-03-01T10:00:00Z", false),
+        ];
+        let remote = vec![
+            stamp("edited-here", "2025-03-02T10:00:00Z", false),
+            stamp("edited-there", "2025-03-01T11:00:00Z", true),
+            stamp("same", "2025-03-01T10:00:00+00:00", false),
+            stamp("theirs", "2025-03-01T10:00:00Z", false),
+            stamp("gone", "2025-03-01T10:00:00Z", true),
+        ];
+        let mut actions = plan(&local, &remote);
+        actions.sort();
+        assert_eq!(
+            actions,
+            vec![
+                ("edited-here".to_string(), Action::Push),
+                ("edited-there".to_string(), Action::Pull),
+                ("mine".to_string(), Action::Push),
+                ("theirs".to_string(), Action::Pull),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sync_between_two_databases() {
+        let folder = std::env::temp_dir().join(format!("poptranscribe_sync_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+
+        let laptop = Mutex::new(Database::new_in_memory().unwrap());
+        let id = {
+            let db = laptop.lock().unwrap();
+            let id = db.create_session("Revue", "visio").unwrap();
+            db.save_segment(&id, "Bonjour", 0.0, 1.0, None, true).unwrap();
+            id
+        };
+        let desktop = Mutex::new(Database::new_in_memory().unwrap());
+
+        assert_eq!(sync(&laptop, &folder, None).unwrap().pushed, 1);
+        assert_eq!(sync(&desktop, &folder, None).unwrap().pulled, 1);
+        assert_eq!(desktop.lock().unwrap().get_segments(&id).unwrap()[0].text, "Bonjour");
+        // Nothing changed since
+        assert_eq!(sync(&desktop, &folder, None).unwrap(), SyncReport::default());
+
+        // Change times have millisecond precision
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        desktop.lock().unwrap().delete_session(&id).unwrap();
+        assert_eq!(sync(&desktop, &folder, None).unwrap().pushed, 1);
+        assert_eq!(sync(&laptop, &folder, None).unwrap().deleted, 1);
+        assert!(laptop.lock().unwrap().get_session(&id).is_err());
+
+        std::fs::remove_dir_all(&folder).ok();
+    }
+
+    #[test]
+    fn test_sync_withdraws_protected_session() {
+        let folder = std::env::temp_dir().join(format!("poptranscribe_sync_protected_{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+
+        let laptop = Mutex::new(Database::new_in_memory().unwrap());
+        let id = {
+            let db = laptop.lock().unwrap();
+            let id = db.create_session("Entretien", "presentiel").unwrap();
+            db.save_segment(&id, "Confidentiel", 0.0, 1.0, None, true).unwrap();
+            id
+        };
+        assert_eq!(sync(&laptop, &folder, None).unwrap().pushed, 1);
+
+        laptop.lock().unwrap().set_session_protected(&id, true).unwrap();
+        assert_eq!(sync(&laptop, &folder, None).unwrap().pushed, 1);
+        let path = folder.join(SESSIONS_DIR).join(format!("{}.json", id));
+        let file = read_file(&path).unwrap();
+        assert!(file.deleted && file.session.is_none());
+        assert!(!String::from_utf8(std::fs::read(&path).unwrap()).unwrap().contains("Confidentiel"));
+        // Kept here, and nothing more to do
+        assert!(laptop.lock().unwrap().get_session(&id).is_ok());
+        assert_eq!(sync(&laptop, &folder, None).unwrap(), SyncReport::default());
+
+        std::fs::remove_dir_all(&folder).ok();
+    }
+}
@@ -0,0 +1,220 @@
+//! Upload of exported files to a remote storage, configured by the
+//! `upload_target` setting (`webdav`, `google_drive` or `dropbox`) and the
+//! `upload_url`, `upload_username`, `upload_token` and `upload_folder`
+//! settings.
+
+pub mod s3;
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::db::Database;
+
+/// Attempts before an upload is reported as failed.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// A remote storage exported files can be pushed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UploadTarget {
+    /// `PUT` into the collection at `url`, with basic authentication.
+    WebDav { url: reqwest::Url, username: String, password: String },
+    /// Google Drive, with an OAuth access token. `folder_id` is the parent
+    /// folder, the root of the drive when empty.
+    GoogleDrive { token: String, folder_id: String },
+    /// Dropbox, with an access token. `folder` is a path like `/Notes`.
+    Dropbox { token: String, folder: String },
+}
+
+impl UploadTarget {
+    /// The configured target, `None` when uploads are not set up.
+    pub fn from_settings(db: &Database) -> Option<Self> {
+        let setting = |key: &str| db.get_setting(key).ok().flatten().unwrap_or_default();
+        let token = setting("upload_token");
+        let folder = setting("upload_folder");
+        match setting("upload_target").as_str() {
+            "webdav" => {
+                let url = reqwest::Url::parse(&setting("upload_url"))
+                    .ok()
+                    .filter(|url| !url.cannot_be_a_base())?;
+                Some(Self::WebDav {
+                    url,
+                    username: setting("upload_username"),
+                    password: token,
+                })
+            }
+            "google_drive" if !token.is_empty() => Some(Self::GoogleDrive { token, folder_id: folder }),
+            "dropbox" if !token.is_empty() => Some(Self::Dropbox { token, folder }),
+            _ => None,
+        }
+    }
+
+    /// Uploads `path`, see `send_with_retry`. Errors from the request
+    /// itself (e.g. a rejected token) are not retried.
+    pub async fn upload(&self, path: &Path) -> Result<(), String> {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or("Nom de fichier invalide")?;
+        let bytes = tokio::fs::read(path)
+            .await
+            .map_err(|e| format!("Erreur lecture fichier: {}", e))?;
+        let client = reqwest::Client::new();
+        send_with_retry(|| self.send(&client, name, bytes.clone())).await
+    }
+
+    async fn send(
+        &self,
+        client: &reqwest::Client,
+        name: &str,
+        bytes: Vec<u8>,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let request = match self {
+            Self::WebDav { url, username, password } => {
+                let mut url = url.clone();
+                if let Ok(mut segments) = url.path_segments_mut() {
+                    segments.pop_if_empty().push(name);
+                }
+                client
+                    .put(url)
+                    .basic_auth(username, Some(password))
+                    .body(bytes)
+            }
+            Self::GoogleDrive { token, folder_id } => {
+                let (content_type, body) = drive_multipart(name, folder_id, bytes);
+                client
+                    .post("https://www.googleapis.com/upload/drive/v3/files?uploadType=multipart")
+                    .bearer_auth(token)
+                    .header(reqwest::header::CONTENT_TYPE, content_type)
+                    .body(body)
+            }
+            Self::Dropbox { token, folder } => {
+                let path = format!("{}/{}", folder.trim_end_matches('/'), name);
+                let arg = serde_json::json!({ "path": path, "mode": "overwrite" });
+                client
+                    .post("https://content.dropboxapi.com/2/files/upload")
+                    .bearer_auth(token)
+                    .header("Dropbox-API-Arg", ascii_json(&arg.to_string()))
+                    .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                    .body(bytes)
+            }
+        };
+        request.timeout(Duration::from_secs(300)).send().await
+    }
+}
+
+/// Sends the request built by `send` until it succeeds, retrying with a
+/// growing delay on network errors, server errors and rate limiting.
+pub(crate) async fn send_with_retry<F, Fut>(mut send: F) -> Result<(), String>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<reqwest::Response, reqwest::Error>>,
+{
+    let mut attempt = 1;
+    loop {
+        match send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                let retryable = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                if !retryable || attempt >= MAX_ATTEMPTS {
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(format!("Envoi refuse ({}): {}", status, body));
+                }
+            }
+            Err(e) if attempt >= MAX_ATTEMPTS => return Err(format!("Envoi impossible: {}", e)),
+            Err(e) => eprintln!("[upload] Attempt {} failed: {}", attempt, e),
+        }
+        tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+        attempt += 1;
+    }
+}
+
+/// `multipart/related` body of a Drive upload: the file metadata, then its
+/// content. Returns the content type and the body.
+fn drive_multipart(name: &str, folder_id: &str, bytes: Vec<u8>) -> (String, Vec<u8>) {
+    let boundary = format!("poptranscribe-{}", uuid::Uuid::new_v4().simple());
+    let mut metadata = serde_json::json!({ "name": name });
+    if !folder_id.is_empty() {
+        metadata["parents"] = serde_json::json!([folder_id]);
+    }
+    let mut body = format!(
+        "--{b}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{m}\r\n--{b}\r\nContent-Type: application/octet-stream\r\n\r\n",
+        b = boundary,
+        m = metadata
+    )
+    .into_bytes();
+    body.extend_from_slice(&bytes);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    (format!("multipart/related; boundary={}", boundary), body)
+}
+
+/// Escapes non-ASCII characters of a JSON text as `\uXXXX`, as HTTP header
+/// values must be ASCII.
+fn ascii_json(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            out.push(c);
+        } else {
+            let mut units = [0u16; 2];
+            for unit in c.encode_utf16(&mut units) {
+                out.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_settings() {
+        let db = Database::new_in_memory().unwrap();
+        assert_eq!(UploadTarget::from_settings(&db), None);
+
+        db.set_setting("upload_target", "dropbox").unwrap();
+        assert_eq!(UploadTarget::from_settings(&db), None);
+        db.set_setting("upload_token", "secret").unwrap();
+        db.set_setting("upload_folder", "/Notes").unwrap();
+        assert_eq!(
+            UploadTarget::from_settings(&db),
+            Some(UploadTarget::Dropbox { token: "secret".into(), folder: "/Notes".into() })
+        );
+    }
+
+    #[test]
+    fn test_webdav_requires_valid_url() {
+        let db = Database::new_in_memory().unwrap();
+        db.set_setting("upload_target", "webdav").unwrap();
+        db.set_setting("upload_url", "not a url").unwrap();
+        assert_eq!(UploadTarget::from_settings(&db), None);
+
+        db.set_setting("upload_url", "https://dav.example.com/notes/").unwrap();
+        db.set_setting("upload_username", "anna").unwrap();
+        db.set_setting("upload_token", "pw").unwrap();
+        match UploadTarget::from_settings(&db) {
+            Some(UploadTarget::WebDav { url, username, password }) => {
+                assert_eq!(url.as_str(), "https://dav.example.com/notes/");
+                assert_eq!((username.as_str(), password.as_str()), ("anna", "pw"));
+            }
+            other => panic!("unexpected target: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ascii_json() {
+        assert_eq!(ascii_json(r#"{"path":"/Réunion"}"#), r#"{"path":"/R\u00e9union"}"#);
+    }
+
+    #[test]
+    fn test_drive_multipart() {
+        let (content_type, body) = drive_multipart("notes.md", "folder", b"# Notes".to_vec());
+        let boundary = content_type.strip_prefix("multipart/related; boundary=").unwrap();
+        let body = String::from_utf8(body).unwrap();
+        assert!(body.starts_with(&format!("--{}\r\n", boundary)));
+        assert!(body.contains(r#""parents":["folder"]"#));
+        assert!(body.ends_with(&format!("# Notes\r\n--{}--\r\n", boundary)));
+    }
+}
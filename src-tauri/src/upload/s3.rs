@@ -0,0 +1,217 @@
+//! Minimal S3 client for archiving sessions to any S3-compatible storage
+//! (AWS, MinIO, Scaleway...), configured by the `s3_endpoint`, `s3_bucket`,
+//! `s3_region`, `s3_access_key` and `s3_secret_key` settings. Objects are
+//! addressed path-style, `<endpoint>/<bucket>/<key>`, and requests are
+//! signed with AWS Signature Version 4.
+
+use std::io::Read;
+use std::path::Path;
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::db::Database;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S3Config {
+    pub endpoint: reqwest::Url,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl S3Config {
+    /// The configured bucket, `None` while any setting is missing.
+    pub fn from_settings(db: &Database) -> Option<Self> {
+        let setting = |key: &str| db.get_setting(key).ok().flatten().filter(|v| !v.is_empty());
+        Some(Self {
+            endpoint: reqwest::Url::parse(&setting("s3_endpoint")?)
+                .ok()
+                .filter(|url| !url.cannot_be_a_base())?,
+            bucket: setting("s3_bucket")?,
+            region: setting("s3_region").unwrap_or_else(|| "us-east-1".to_string()),
+            access_key: setting("s3_access_key")?,
+            secret_key: setting("s3_secret_key")?,
+        })
+    }
+
+    /// Path of `key` in the bucket, URI-encoded as it is signed.
+    fn object_path(&self, key: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            uri_encode(&self.bucket, false),
+            uri_encode(key, true)
+        )
+    }
+
+    /// `Host` header value, with the port when it is not the default one.
+    fn host(&self) -> String {
+        let host = self.endpoint.host_str().unwrap_or_default();
+        match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        }
+    }
+
+    /// Stores the file at `path` under `key`, with the retries of
+    /// `send_with_retry`. The file is streamed, never held in memory.
+    pub async fn put_object(&self, key: &str, path: &Path) -> Result<(), String> {
+        let url_path = self.object_path(key);
+        let url = format!("{}://{}{}", self.endpoint.scheme(), self.host(), url_path);
+        let hashed_path = path.to_path_buf();
+        let (payload_hash, len) = tokio::task::spawn_blocking(move || hash_file(&hashed_path))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Erreur lecture fichier: {}", e))?;
+        let client = reqwest::Client::new();
+        let (client, url, url_path, payload_hash) = (&client, &url, &url_path, &payload_hash);
+        super::send_with_retry(move || async move {
+            // Signed again on each attempt: signatures expire after 15 minutes
+            let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+            let authorization = self.authorization("PUT", url_path, payload_hash, &amz_date);
+            let body = match tokio::fs::File::open(path).await {
+                Ok(file) => reqwest::Body::from(file),
+                // Fails the attempt like a network error
+                Err(e) => reqwest::Body::wrap_stream(futures_util::stream::once(async move {
+                    Err::<Vec<u8>, _>(e)
+                })),
+            };
+            client
+                .put(url)
+                .header("x-amz-date", &amz_date)
+                .header("x-amz-content-sha256", payload_hash.as_str())
+                .header(reqwest::header::AUTHORIZATION, authorization)
+                // Streamed bodies are sent chunked otherwise, which S3 refuses
+                .header(reqwest::header::CONTENT_LENGTH, len)
+                .body(body)
+                .timeout(std::time::Duration::from_secs(600))
+                .send()
+                .await
+        })
+        .await
+    }
+
+    /// `Authorization` header of a request without query string, signing
+    /// the `host`, `x-amz-content-sha256` and `x-amz-date` headers.
+    fn authorization(&self, method: &str, path: &str, payload_hash: &str, amz_date: &str) -> String {
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method,
+            path,
+            self.host(),
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = signing_key(&self.secret_key, date, &self.region, "s3");
+        let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, scope, signed_headers, signature
+        )
+    }
+}
+
+/// SHA-256 (hex) and length of a file, read by chunks.
+fn hash_file(path: &Path) -> std::io::Result<(String, u64)> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    let mut len = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        len += n as u64;
+    }
+    Ok((hex(&hasher.finalize()), len))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, region.as_bytes());
+    let key = hmac_sha256(&key, service.as_bytes());
+    hmac_sha256(&key, b"aws4_request")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Percent-encodes everything but the unreserved characters, and `/` when
+/// `keep_slash` is set, as Signature Version 4 requires.
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example from the AWS Signature Version 4 documentation
+    #[test]
+    fn test_signing_key() {
+        let key = signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+        assert_eq!(hex(&key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let path = std::env::temp_dir().join(format!("poptranscribe_s3_hash_{}", std::process::id()));
+        std::fs::write(&path, b"abc").unwrap();
+        let (hash, len) = hash_file(&path).unwrap();
+        assert_eq!(hash, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(len, 3);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(uri_encode("poptranscribe/réunion 1.zip", true), "poptranscribe/r%C3%A9union%201.zip");
+        assert_eq!(uri_encode("a/b", false), "a%2Fb");
+    }
+
+    #[test]
+    fn test_object_path_and_host() {
+        let config = S3Config {
+            endpoint: reqwest::Url::parse("http://localhost:9000/storage/").unwrap(),
+            bucket: "notes".into(),
+            region: "us-east-1".into(),
+            access_key: "key".into(),
+            secret_key: "secret".into(),
+        };
+        assert_eq!(config.object_path("a b.zip"), "/storage/notes/a%20b.zip");
+        assert_eq!(config.host(), "localhost:9000");
+
+        let auth = config.authorization("PUT", "/storage/notes/a%20b.zip", "UNSIGNED", "20240102T030405Z");
+        assert!(auth.starts_with("AWS4-HMAC-SHA256 Credential=key/20240102/us-east-1/s3/aws4_request, "));
+    }
+}
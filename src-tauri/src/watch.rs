@@ -0,0 +1,137 @@
+//! Watch folder: audio files dropped into the folder configured in the
+//! `watch_folder` setting are imported and transcribed automatically.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use tauri::{Emitter, Manager};
+
+use crate::app_state::AppState;
+use crate::commands::{import_audio_file, is_importable_audio, process_recording};
+
+pub const WATCH_FOLDER_SETTING: &str = "watch_folder";
+
+/// Sub-folder receiving processed files, so they are not imported twice.
+const IMPORTED_DIR: &str = "imported";
+
+/// A dropped file is considered complete once its size stops changing for
+/// this long (copies from a dictaphone can take a while).
+const SETTLE_SECS: u64 = 3;
+
+/// (Re)starts watching the folder from settings, replacing any previous
+/// watcher. An empty setting just stops watching.
+pub fn restart(app: &tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let folder = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_setting(WATCH_FOLDER_SETTING)
+            .ok()
+            .flatten()
+            .filter(|f| !f.trim().is_empty())
+    };
+
+    let mut slot = state.folder_watcher.lock().map_err(|e| e.to_string())?;
+    // Dropping the watcher closes its channel, which ends the queue task
+    *slot = None;
+    let Some(folder) = folder.map(PathBuf::from) else {
+        return Ok(());
+    };
+    if !folder.is_dir() {
+        return Err(format!("Dossier surveille introuvable: {}", folder.display()));
+    }
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<PathBuf>();
+
+    // Files dropped while the app was closed
+    if let Ok(entries) = std::fs::read_dir(&folder) {
+        for entry in entries.flatten() {
+            let _ = tx.send(entry.path());
+        }
+    }
+
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+        }
+    })
+    .map_err(|e| format!("Impossible de surveiller le dossier: {}", e))?;
+    watcher
+        .watch(&folder, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Impossible de surveiller le dossier: {}", e))?;
+    eprintln!("[watch] Watching {}", folder.display());
+
+    tauri::async_runtime::spawn(process_queue(app.clone(), folder, rx));
+    *slot = Some(watcher);
+    Ok(())
+}
+
+/// Imports queued files one at a time, so transcriptions do not run in
+/// parallel against the API.
+async fn process_queue(
+    app: tauri::AppHandle,
+    folder: PathBuf,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<PathBuf>,
+) {
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    while let Some(path) = rx.recv().await {
+        // Modify events repeat while a file is written
+        if path.parent() != Some(folder.as_path())
+            || !is_importable_audio(&path)
+            || !seen.insert(path.clone())
+        {
+            continue;
+        }
+        if !wait_until_settled(&path).await {
+            seen.remove(&path);
+            continue;
+        }
+        // Transcription failures are already reported by `process_recording`
+        if let Err(e) = import_dropped_file(&app, &folder, &path).await {
+            eprintln!("[watch] Import of {} failed: {}", path.display(), e);
+        }
+    }
+    eprintln!("[watch] Stopped watching {}", folder.display());
+}
+
+/// Waits until the file size is stable. False if the file disappeared.
+async fn wait_until_settled(path: &Path) -> bool {
+    let mut last_size = None;
+    loop {
+        let Ok(size) = std::fs::metadata(path).map(|m| m.len()) else {
+            return false;
+        };
+        if size > 0 && last_size == Some(size) {
+            return true;
+        }
+        last_size = Some(size);
+        tokio::time::sleep(std::time::Duration::from_secs(SETTLE_SECS)).await;
+    }
+}
+
+async fn import_dropped_file(app: &tauri::AppHandle, folder: &Path, path: &Path) -> Result<(), String> {
+    let (session_id, audio_path) = {
+        let state = app.state::<AppState>();
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        import_audio_file(&db, path).inspect_err(|e| {
+            let _ = app.emit("session-error", format!("Import automatique: {}", e));
+        })?
+    };
+
+    // The copy now lives in the recordings folder
+    let imported_dir = folder.join(IMPORTED_DIR);
+    if let Some(file_name) = path.file_name() {
+        if let Err(e) = std::fs::create_dir_all(&imported_dir)
+            .and_then(|_| std::fs::rename(path, imported_dir.join(file_name)))
+        {
+            eprintln!("[watch] Could not move {}: {}", path.display(), e);
+        }
+    }
+
+    let _ = app.emit("watch-folder-import", &session_id);
+    process_recording(app.clone(), session_id, audio_path).await
+}
@@ -1,6 +1,7 @@
 import { useState, useCallback, useEffect } from 'react';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { listen } from '@tauri-apps/api/event';
+import { invoke } from '@tauri-apps/api/core';
 import { check } from '@tauri-apps/plugin-updater';
 import { relaunch } from '@tauri-apps/plugin-process';
 import SessionList from './components/SessionList';
@@ -8,12 +9,15 @@ import SessionView from './views/SessionView';
 import DetailView from './views/DetailView';
 import ChatPanel from './components/ChatPanel';
 import SettingsModal from './views/SettingsView';
+import type { BulkImportProgress, DeepLink } from './types';
 
 type ActiveTab = 'session' | 'historique';
 
 function App() {
   const [activeTab, setActiveTab] = useState<ActiveTab>('session');
   const [selectedSessionId, setSelectedSessionId] = useState<string | null>(null);
+  // Moment of the selected session to scroll to, from a deep link
+  const [focusTime, setFocusTime] = useState<number | null>(null);
   const [showSettings, setShowSettings] = useState(false);
 
   const [liveSessionId, setLiveSessionId] = useState<string | null>(null);
@@ -24,6 +28,9 @@ function App() {
   const [updateAvailable, setUpdateAvailable] = useState<{ version: string; body: string | null } | null>(null);
   const [updating, setUpdating] = useState(false);
 
+  // Audio files dropped on the window, being transcribed
+  const [importProgress, setImportProgress] = useState<BulkImportProgress | null>(null);
+
   // Listen for native menu "Parametres..." (Cmd+,)
   useEffect(() => {
     const unlisten = listen('open-settings', () => {
@@ -40,6 +47,60 @@ function App() {
     return () => { unlisten.then(fn => fn()); };
   }, []);
 
+  // Sessions received from another device
+  useEffect(() => {
+    const unlisten = listen('sync-complete', () => {
+      setSessionListRefreshKey((prev) => prev + 1);
+    });
+    return () => { unlisten.then(fn => fn()); };
+  }, []);
+
+  // Recent session clicked in the tray menu
+  useEffect(() => {
+    const unlisten = listen<string>('open-session', (event) => {
+      setActiveTab('historique');
+      setSelectedSessionId(event.payload);
+    });
+    return () => { unlisten.then(fn => fn()); };
+  }, []);
+
+  // poptranscribe:// link opened, e.g. a timestamp of exported notes. A
+  // link that launched the app is waiting on the backend.
+  useEffect(() => {
+    const openLink = (link: DeepLink) => {
+      setActiveTab('historique');
+      setSelectedSessionId(link.session_id);
+      setFocusTime(link.t);
+    };
+    invoke<DeepLink | null>('take_pending_deep_link')
+      .then((link) => { if (link) openLink(link); })
+      .catch(() => {});
+    const unlisten = listen<DeepLink>('deep-link', (event) => openLink(event.payload));
+    return () => { unlisten.then(fn => fn()); };
+  }, []);
+
+  // Dropped audio files: their sessions appear at once, then get transcribed
+  useEffect(() => {
+    const unlistenImported = listen<string[]>('audio-files-imported', (event) => {
+      setSessionListRefreshKey((prev) => prev + 1);
+      setActiveTab('historique');
+      setSelectedSessionId(event.payload[0] ?? null);
+      setFocusTime(null);
+    });
+    const unlistenProgress = listen<BulkImportProgress>('bulk-import-progress', (event) => {
+      const progress = event.payload;
+      const finished = progress.status !== 'processing' && progress.index === progress.total;
+      setImportProgress(finished && progress.status === 'done' ? null : progress);
+      if (progress.status !== 'processing') {
+        setSessionListRefreshKey((prev) => prev + 1);
+      }
+    });
+    return () => {
+      unlistenImported.then(fn => fn());
+      unlistenProgress.then(fn => fn());
+    };
+  }, []);
+
   // Check for updates on startup
   useEffect(() => {
     check().then((update) => {
@@ -67,6 +128,7 @@ function App() {
 
   const handleSessionSelect = useCallback((id: string) => {
     setSelectedSessionId(id);
+    setFocusTime(null);
   }, []);
 
   const handleSessionStopped = useCallback((stoppedSessionId: string) => {
@@ -146,6 +208,7 @@ function App() {
           ) : selectedSessionId ? (
             <DetailView
               sessionId={selectedSessionId}
+              focusTime={focusTime}
               onBack={() => setSelectedSessionId(null)}
               onTitleChanged={() => setSessionListRefreshKey((prev) => prev + 1)}
             />
@@ -172,6 +235,28 @@ function App() {
         <SettingsModal onClose={() => setShowSettings(false)} />
       )}
 
+      {/* Import progress */}
+      {importProgress && (
+        <div className="fixed bottom-4 left-4 bg-white rounded-xl shadow-lg border border-gray-200 p-4 max-w-xs z-50">
+          <p className="text-sm font-medium text-gray-900 mb-1 truncate">
+            {importProgress.status === 'error' ? 'Echec de la transcription' : 'Transcription en cours'}
+            {importProgress.total > 1 && ` (${importProgress.index}/${importProgress.total})`}
+          </p>
+          <p className="text-xs text-gray-500 truncate">{importProgress.file_name}</p>
+          {importProgress.error && (
+            <p className="text-xs text-red-500 mt-1">{importProgress.error}</p>
+          )}
+          {importProgress.status === 'error' && (
+            <button
+              onClick={() => setImportProgress(null)}
+              className="mt-2 px-3 py-1.5 text-xs text-gray-500 hover:text-gray-700 transition-colors"
+            >
+              Fermer
+            </button>
+          )}
+        </div>
+      )}
+
       {/* Update banner */}
       {updateAvailable && (
         <div className="fixed bottom-4 right-4 bg-white rounded-xl shadow-lg border border-gray-200 p-4 max-w-xs z-50">
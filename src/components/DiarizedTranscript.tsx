@@ -1,4 +1,4 @@
-import { useState, useMemo } from 'react';
+import { useState, useMemo, useEffect, useRef } from 'react';
 import { Facehash } from 'facehash';
 import type { Segment } from '../types';
 import SpeakerEditor from './SpeakerEditor';
@@ -18,14 +18,36 @@ function formatTimestamp(seconds: number): string {
 interface DiarizedTranscriptProps {
   sessionId: string;
   segments: Segment[];
+  /** Seconds into the recording: the segment at that moment is scrolled to and highlighted */
+  focusTime?: number | null;
   onSpeakerRenamed: (oldName: string, newName: string) => void;
 }
 
 export default function DiarizedTranscript({
   sessionId,
   segments,
+  focusTime,
   onSpeakerRenamed,
 }: DiarizedTranscriptProps) {
+  const containerRef = useRef<HTMLDivElement>(null);
+
+  // Last segment started at or before the focused moment
+  const focusedId = useMemo(() => {
+    if (focusTime === null || focusTime === undefined) return null;
+    let found: number | null = null;
+    for (const seg of segments) {
+      if (seg.start_time > focusTime) break;
+      found = seg.id;
+    }
+    return found ?? segments[0]?.id ?? null;
+  }, [segments, focusTime]);
+
+  useEffect(() => {
+    if (focusedId === null) return;
+    const element = containerRef.current?.querySelector(`[data-segment-id="${focusedId}"]`);
+    element?.scrollIntoView({ behavior: 'smooth', block: 'center' });
+  }, [focusedId]);
+
   const [editingSpeaker, setEditingSpeaker] = useState<{
     name: string;
     segmentId: number;
@@ -59,7 +81,7 @@ export default function DiarizedTranscript({
   }
 
   return (
-    <div className="space-y-4 overflow-auto">
+    <div ref={containerRef} className="space-y-4 overflow-auto">
       {segments.map((seg) => {
         const speakerName = seg.speaker ?? 'Inconnu';
         const isUser = speakerName === userSpeaker;
@@ -70,7 +92,10 @@ export default function DiarizedTranscript({
         return (
           <div
             key={seg.id}
-            className={`flex gap-2.5 ${isUser ? 'flex-row-reverse' : ''}`}
+            data-segment-id={seg.id}
+            className={`flex gap-2.5 ${isUser ? 'flex-row-reverse' : ''} ${
+              seg.id === focusedId ? 'ring-2 ring-amber-200 rounded-2xl' : ''
+            }`}
           >
             {/* Avatar */}
             <div className="shrink-0 mt-5">
@@ -1,13 +1,25 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import MiniRecorderView from "./views/MiniRecorderView";
 import "./styles.css";
 
 // Disable browser context menu (removes "Inspect Element" in production feel)
 document.addEventListener("contextmenu", (e) => e.preventDefault());
 
+// The compact recorder window loads the same page with `?mini=<session id>`
+const params = new URLSearchParams(window.location.search);
+const miniSessionId = params.get("mini");
+
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {miniSessionId ? (
+      <MiniRecorderView
+        sessionId={miniSessionId}
+        initialElapsed={Number(params.get("elapsed") ?? 0)}
+      />
+    ) : (
+      <App />
+    )}
   </React.StrictMode>,
 );
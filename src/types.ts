@@ -6,6 +6,13 @@ export interface Session {
   created_at: string;
   duration_secs: number | null;
   summary_json: string | null;
+  protected: boolean;
+  archived: boolean;
+  language: string | null;
+  transcript_only: boolean;
+  summary_model: string | null;
+  speaker_count: number | null;
+  detected_language: string | null;
 }
 
 export interface Segment {
@@ -16,12 +23,19 @@ export interface Segment {
   end_time: number;
   speaker: string | null;
   is_diarized: boolean;
+  language: string | null;
+}
+
+// Segment with its stored translation in the requested language
+export interface TranslatedSegment extends Segment {
+  translation: string | null;
 }
 
 export interface Summary {
   key_points: string[];
   decisions: string[];
   action_items: ActionItem[];
+  topics: string[];
 }
 
 export interface ActionItem {
@@ -29,7 +43,197 @@ export interface ActionItem {
   assignee: string | null;
 }
 
+export interface TopicCount {
+  topic: string;
+  session_count: number;
+}
+
+// Entry of the replacement dictionary applied to incoming segments
+export interface Replacement {
+  id: number;
+  pattern: string;
+  replacement: string;
+  created_at: string;
+}
+
+// Alternative text of a transcript (e.g. glossary-corrected), adopted on demand
+export interface TranscriptVersion {
+  id: number;
+  session_id: string;
+  kind: string;
+  model: string | null;
+  created_at: string;
+  segments: { segment_id: number; text: string }[];
+}
+
+// poptranscribe://session/{id}?t=123 link, emitted as `deep-link`
+export interface DeepLink {
+  session_id: string;
+  t: number | null;
+}
+
+// Previous text of an edited segment
+export interface SegmentRevision {
+  id: number;
+  segment_id: number;
+  text: string;
+  created_at: string;
+}
+
+// Word-level diff of the live transcript against the diarized one
+export interface TranscriptChange {
+  kind: 'equal' | 'insert' | 'delete' | 'replace';
+  live: string;
+  diarized: string;
+  segment_id: number | null;
+  start_time: number | null;
+  speaker: string | null;
+}
+
+export interface TranscriptComparison {
+  live_words: number;
+  diarized_words: number;
+  similarity: number;
+  changes: TranscriptChange[];
+  unattributed_segments: number[];
+}
+
+// Action item caught in the live transcript, emitted as `action-item-detected`
+export interface LiveActionItem {
+  id: number;
+  session_id: string;
+  description: string;
+  assignee: string | null;
+  segment_id: number | null;
+  created_at: string;
+}
+
+export interface Attachment {
+  id: number;
+  session_id: string;
+  file_name: string;
+  path: string;
+  size_bytes: number;
+  created_at: string;
+}
+
 export interface SessionDetail extends Session {
   segments: Segment[];
   summary: Summary | null;
+  attachments: Attachment[];
+  live_transcript: string | null;
+}
+
+export interface SpeakerProfile {
+  name: string;
+  color: string | null;
+  role: string | null;
+  company: string | null;
+  email: string | null;
+}
+
+export interface SpeakerMapping {
+  speaker: string;
+  name: string;
+  evidence: string | null;
+}
+
+export interface Operation {
+  id: number;
+  session_id: string;
+  kind: "rename_speaker" | "set_segment_speaker" | "delete_segments";
+  created_at: string;
+}
+
+export interface MicrophoneTest {
+  device_name: string | null;
+  sample_rate: number;
+  duration_secs: number;
+  peak: number;
+  rms: number;
+  peak_dbfs: number;
+  rms_dbfs: number;
+}
+
+export interface BulkImportProgress {
+  index: number;
+  total: number;
+  session_id: string;
+  file_name: string;
+  status: "processing" | "done" | "error";
+  error: string | null;
+}
+
+export interface ScheduledRecording {
+  id: number;
+  title: string;
+  mode: string;
+  start_at: string;
+  duration_minutes: number;
+  recurrence: "daily" | "weekdays" | "weekly" | null;
+  last_run_at: string | null;
+}
+
+export interface HealthCheck {
+  status: "ok" | "error" | "skipped";
+  message: string;
+}
+
+export interface HealthReport {
+  network: HealthCheck;
+  api_key: HealthCheck;
+  input_device: HealthCheck;
+  screen_recording: HealthCheck;
+}
+
+export type PermissionState =
+  | "granted"
+  | "denied"
+  | "not_determined"
+  | "restricted"
+  | "not_required";
+
+export interface Permissions {
+  microphone: PermissionState;
+  screen_recording: PermissionState;
+}
+
+export interface MaintenanceReport {
+  size_before: number;
+  size_after: number;
+}
+
+export interface IntegrityReport {
+  integrity_errors: string[];
+  fts_consistent: boolean;
+  missing_audio: string[];
+  orphaned_audio: string[];
+  repaired: boolean;
+}
+
+export interface CostEstimate {
+  audio_minutes: number;
+  input_tokens: number;
+  output_tokens: number;
+  cost_usd: number;
+}
+
+export interface SyncReport {
+  pushed: number;
+  pulled: number;
+  deleted: number;
+}
+
+export interface EmailTemplate {
+  id: number;
+  name: string;
+  subject: string;
+  body: string;
+  created_at: string;
+}
+
+export interface RenderedEmail {
+  subject: string;
+  body: string;
+  recipients: string[];
 }
@@ -32,11 +32,13 @@ type DetailTab = 'transcript' | 'summary';
 
 interface DetailViewProps {
   sessionId: string;
+  /** Seconds into the recording to scroll the transcript to */
+  focusTime?: number | null;
   onBack: () => void;
   onTitleChanged?: () => void;
 }
 
-export default function DetailView({ sessionId, onBack, onTitleChanged }: DetailViewProps) {
+export default function DetailView({ sessionId, focusTime, onBack, onTitleChanged }: DetailViewProps) {
   const [detail, setDetail] = useState<SessionDetail | null>(null);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
@@ -219,6 +221,7 @@ export default function DetailView({ sessionId, onBack, onTitleChanged }: Detail
             <DiarizedTranscript
               sessionId={detail.id}
               segments={detail.segments}
+              focusTime={focusTime}
               onSpeakerRenamed={handleSpeakerRenamed}
             />
           </div>
@@ -0,0 +1,68 @@
+import { useState, useEffect, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import AudioLevel from '../components/AudioLevel';
+
+function formatElapsedTime(totalSeconds: number): string {
+  const hours = Math.floor(totalSeconds / 3600);
+  const minutes = Math.floor((totalSeconds % 3600) / 60);
+  const seconds = totalSeconds % 60;
+  return `${String(hours).padStart(2, '0')}:${String(minutes).padStart(2, '0')}:${String(seconds).padStart(2, '0')}`;
+}
+
+interface MiniRecorderViewProps {
+  sessionId: string;
+  initialElapsed: number;
+}
+
+// Compact always-on-top recorder, opened by `open_mini_recorder`. The
+// backend closes the window when the session stops.
+export default function MiniRecorderView({ sessionId, initialElapsed }: MiniRecorderViewProps) {
+  const [elapsedTime, setElapsedTime] = useState(initialElapsed);
+  const [audioLevel, setAudioLevel] = useState(0);
+  const [stopping, setStopping] = useState(false);
+
+  useEffect(() => {
+    const unlisten = listen<number>('audio-level', (event) => {
+      setAudioLevel(event.payload);
+    });
+    return () => { unlisten.then(fn => fn()); };
+  }, []);
+
+  useEffect(() => {
+    const interval = setInterval(() => {
+      setElapsedTime((prev) => prev + 1);
+    }, 1000);
+    return () => clearInterval(interval);
+  }, []);
+
+  const handleStop = useCallback(async () => {
+    setStopping(true);
+    try {
+      await invoke('stop_session', { sessionId });
+    } catch (err) {
+      console.error('Erreur a l\'arret de la session:', err);
+      setStopping(false);
+    }
+  }, [sessionId]);
+
+  return (
+    <div data-tauri-drag-region className="flex items-center gap-3 h-screen px-4 bg-white select-none">
+      <span className="relative flex h-2 w-2">
+        <span className="animate-ping absolute inline-flex h-full w-full rounded-full bg-red-400 opacity-75" />
+        <span className="relative inline-flex rounded-full h-2 w-2 bg-red-500" />
+      </span>
+      <span className="font-mono text-xs text-gray-900 tabular-nums">
+        {formatElapsedTime(elapsedTime)}
+      </span>
+      <AudioLevel level={audioLevel * 100} isActive={!stopping} />
+      <button
+        onClick={handleStop}
+        disabled={stopping}
+        className="ml-auto px-3 py-1 rounded-full text-xs font-medium bg-gray-900 text-white disabled:opacity-50"
+      >
+        {stopping ? 'Arret...' : 'Arreter'}
+      </button>
+    </div>
+  );
+}
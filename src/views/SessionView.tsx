@@ -1,7 +1,7 @@
 import { useState, useEffect, useRef, useCallback, useMemo } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import type { Segment } from '../types';
+import type { LiveActionItem, Segment } from '../types';
 import TranscriptLine from '../components/TranscriptLine';
 
 function formatElapsedTime(totalSeconds: number): string {
@@ -35,6 +35,7 @@ export default function SessionView({ onSessionStopped, onLiveSessionChange, onL
   const [audioLevel, setAudioLevel] = useState(0);
   const [toasts, setToasts] = useState<Toast[]>([]);
   const [liveText, setLiveText] = useState('');
+  const [liveSummary, setLiveSummary] = useState<string | null>(null);
 
   const transcriptEndRef = useRef<HTMLDivElement>(null);
   const barSeeds = useMemo(() => Array.from({ length: INLINE_BARS }, () => Math.random()), []);
@@ -79,6 +80,27 @@ export default function SessionView({ onSessionStopped, onLiveSessionChange, onL
       setLiveText('');
     });
 
+    // Speakers attributed during the recording by rolling diarization
+    const unlistenSpeakers = listen<{ session_id: string; segments: { id: number; speaker: string }[] }>(
+      'live-speakers',
+      (event) => {
+        const speakers = new Map(event.payload.segments.map((s) => [s.id, s.speaker]));
+        setSegments((prev) =>
+          prev.map((seg) => (speakers.has(seg.id) ? { ...seg, speaker: speakers.get(seg.id)! } : seg)),
+        );
+      },
+    );
+
+    // Periodic summary of the meeting so far ("live_summary_minutes")
+    const unlistenSummary = listen<{ session_id: string; summary: string }>('live-summary', (event) => {
+      setLiveSummary(event.payload.summary);
+    });
+
+    const unlistenActionItem = listen<LiveActionItem>('action-item-detected', (event) => {
+      const { description, assignee } = event.payload;
+      addToast(`Action detectee: ${description}${assignee ? ` (${assignee})` : ''}`, 'success');
+    });
+
     const unlistenDelta = listen<string>('transcription-delta', (event) => {
       setLiveText((prev) => prev + event.payload);
     });
@@ -103,8 +125,24 @@ export default function SessionView({ onSessionStopped, onLiveSessionChange, onL
       addToast(String(event.payload), 'error');
     });
 
+    // Stopped outside this view (mini recorder, tray)
+    const unlistenStopped = listen<string>('session-stopped', (event) => {
+      setSessionId((prev) => {
+        if (prev === event.payload) {
+          setIsRecording(false);
+          setAudioLevel(0);
+          return null;
+        }
+        return prev;
+      });
+    });
+
     return () => {
+      unlistenStopped.then((fn) => fn());
       unlistenSegment.then((fn) => fn());
+      unlistenSpeakers.then((fn) => fn());
+      unlistenSummary.then((fn) => fn());
+      unlistenActionItem.then((fn) => fn());
       unlistenDelta.then((fn) => fn());
       unlistenAudio.then((fn) => fn());
       unlistenComplete.then((fn) => fn());
@@ -127,6 +165,7 @@ export default function SessionView({ onSessionStopped, onLiveSessionChange, onL
       setSessionId(id);
       setIsRecording(true);
       setSegments([]);
+      setLiveSummary(null);
       setElapsedTime(0);
       setSearchQuery('');
     } catch (err) {
@@ -257,6 +296,15 @@ export default function SessionView({ onSessionStopped, onLiveSessionChange, onL
               </div>
             )}
 
+            {isRecording && (
+              <button
+                onClick={() => invoke('open_mini_recorder').catch((err) => addToast(String(err), 'error'))}
+                className="px-3 py-1.5 bg-white/80 hover:bg-white text-gray-500 rounded-full text-xs font-medium shadow-sm transition-all duration-150"
+              >
+                Mini
+              </button>
+            )}
+
             {isRecording ? (
               <button
                 onClick={handleStop}
@@ -282,6 +330,13 @@ export default function SessionView({ onSessionStopped, onLiveSessionChange, onL
         </div>
       </div>
 
+      {isRecording && liveSummary && (
+        <div className="mb-3 px-4 py-3 bg-gray-50 border border-gray-100 rounded-2xl">
+          <p className="text-[11px] font-medium text-gray-400 uppercase tracking-wide mb-1">Resume en direct</p>
+          <p className="text-sm text-gray-600 leading-relaxed">{liveSummary}</p>
+        </div>
+      )}
+
       {/* Transcript */}
       <div className="flex-1 overflow-auto min-h-0 pr-2">
         {filteredSegments.length === 0 && !liveText ? (
@@ -14,6 +14,7 @@ export default function SettingsModal({ onClose }: SettingsModalProps) {
   const [apiKey, setApiKey] = useState('');
   const [inputDevice, setInputDevice] = useState('');
   const [exportDir, setExportDir] = useState('');
+  const [removeDisfluencies, setRemoveDisfluencies] = useState(false);
   const [devices, setDevices] = useState<AudioDevice[]>([]);
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
@@ -24,15 +25,17 @@ export default function SettingsModal({ onClose }: SettingsModalProps) {
   useEffect(() => {
     (async () => {
       try {
-        const [key, deviceSetting, exportSetting, deviceList] = await Promise.all([
+        const [key, deviceSetting, exportSetting, disfluencySetting, deviceList] = await Promise.all([
           invoke<string>('get_api_key'),
           invoke<string | null>('get_setting', { key: 'input_device' }),
           invoke<string | null>('get_setting', { key: 'export_dir' }),
+          invoke<string | null>('get_setting', { key: 'remove_disfluencies' }),
           invoke<AudioDevice[]>('list_input_devices'),
         ]);
         setApiKey(key);
         setInputDevice(deviceSetting ?? '');
         setExportDir(exportSetting ?? '');
+        setRemoveDisfluencies(disfluencySetting === 'true');
         setDevices(deviceList);
       } catch (err) {
         console.error('Erreur chargement parametres:', err);
@@ -66,6 +69,7 @@ export default function SettingsModal({ onClose }: SettingsModalProps) {
         invoke('set_api_key', { key: apiKey }),
         invoke('set_setting', { key: 'input_device', value: inputDevice }),
         invoke('set_setting', { key: 'export_dir', value: exportDir }),
+        invoke('set_setting', { key: 'remove_disfluencies', value: removeDisfluencies ? 'true' : 'false' }),
       ]);
       setFeedback({ type: 'success', message: 'Parametres sauvegardes avec succes.' });
     } catch (err) {
@@ -74,7 +78,7 @@ export default function SettingsModal({ onClose }: SettingsModalProps) {
     } finally {
       setSaving(false);
     }
-  }, [apiKey, inputDevice, exportDir]);
+  }, [apiKey, inputDevice, exportDir, removeDisfluencies]);
 
   const handlePickFolder = useCallback(async () => {
     try {
@@ -245,6 +249,22 @@ export default function SettingsModal({ onClose }: SettingsModalProps) {
                     )}
                   </div>
                 </div>
+
+                {/* ── Disfluencies section ── */}
+                <div>
+                  <label className="flex items-center gap-3 text-sm font-medium text-gray-900">
+                    <input
+                      type="checkbox"
+                      checked={removeDisfluencies}
+                      onChange={(e) => setRemoveDisfluencies(e.target.checked)}
+                      className="w-4 h-4 rounded border-gray-300 text-gray-900 focus:ring-0"
+                    />
+                    Retirer les hesitations
+                  </label>
+                  <p className="text-xs text-gray-400 mt-1 ml-7">
+                    Supprime les "euh", "bah" et mots repetes des exports et des resumes. La transcription enregistree reste intacte.
+                  </p>
+                </div>
               </>
             )}
 